@@ -0,0 +1,83 @@
+use std::time::Duration;
+use std::time::Instant;
+
+fn main() {
+  v8::V8::initialize_platform(v8::new_default_platform(0, false).make_shared());
+  v8::V8::initialize();
+  let isolate = &mut v8::Isolate::new(v8::CreateParams::default());
+  let handle_scope = &mut v8::HandleScope::new(isolate);
+
+  fn noop(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  const TEMPLATE_COUNT: usize = 64;
+  let templates = (0..TEMPLATE_COUNT)
+    .map(|_| v8::FunctionTemplate::new(handle_scope, noop))
+    .collect::<Vec<_>>();
+
+  {
+    // Instantiating templates in a fresh context. This is what embedders pay
+    // for every context they create when they install their bindings eagerly.
+    const N: u32 = 1_000;
+    let mut total = Duration::ZERO;
+    for _ in 0..N {
+      let scope = &mut v8::HandleScope::new(handle_scope);
+      let context = v8::Context::new(scope);
+      let scope = &mut v8::ContextScope::new(scope, context);
+      let now = Instant::now();
+      for template in &templates {
+        template.get_function(scope).unwrap();
+      }
+      total += now.elapsed();
+    }
+    println!(
+      "get_function (new context): {:.1} ns per template",
+      total.as_nanos() as f64 / (N as f64 * TEMPLATE_COUNT as f64)
+    );
+  }
+
+  {
+    // Looking up an already instantiated function hits the per-context cache.
+    const N: u32 = 100_000;
+    let scope = &mut v8::HandleScope::new(handle_scope);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let template = templates[0];
+    template.get_function(scope).unwrap();
+    let now = Instant::now();
+    for _ in 0..N {
+      let scope = &mut v8::HandleScope::new(scope);
+      template.get_function(scope).unwrap();
+    }
+    println!(
+      "get_function (cached): {:.1} ns",
+      now.elapsed().as_nanos() as f64 / N as f64
+    );
+  }
+
+  {
+    // Type checking an embedder object against its template.
+    const N: u32 = 1_000_000;
+    let scope = &mut v8::HandleScope::new(handle_scope);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let template = templates[0];
+    let object = template
+      .get_function(scope)
+      .unwrap()
+      .new_instance(scope, &[])
+      .unwrap();
+    let now = Instant::now();
+    for _ in 0..N {
+      assert!(template.has_instance(object.into()));
+    }
+    println!(
+      "has_instance: {:.1} ns",
+      now.elapsed().as_nanos() as f64 / N as f64
+    );
+  }
+}
@@ -71,6 +71,13 @@ fn main() {
     return build_v8();
   }
 
+  if cfg!(feature = "handle_checks") {
+    println!(
+      "cargo:warning=The prebuilt V8 library is built without extra checks; \
+       set V8_FROM_SOURCE=1 to enable V8's handle checks."
+    );
+  }
+
   // utilize a lockfile to prevent linking of
   // only partially downloaded static library.
   let root = env::current_dir().unwrap();
@@ -121,6 +128,12 @@ fn build_v8() {
     gn_args.push("use_custom_libcxx=false".to_string());
   }
 
+  if cfg!(feature = "handle_checks") {
+    gn_args.push("v8_enable_v8_checks=true".to_string());
+    gn_args.push("v8_enable_verify_heap=true".to_string());
+    gn_args.push("v8_enable_handle_zapping=true".to_string());
+  }
+
   // Fix GN's host_cpu detection when using x86_64 bins on Apple Silicon
   if cfg!(target_os = "macos") && cfg!(target_arch = "aarch64") {
     gn_args.push("host_cpu=\"arm64\"".to_string())
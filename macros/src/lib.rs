@@ -0,0 +1,371 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+//! Procedural macros for the `v8` crate. Use them through the re-exports in
+//! `v8`, which are available with its `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse_macro_input;
+use syn::spanned::Spanned;
+use syn::Attribute;
+use syn::AttributeArgs;
+use syn::Error;
+use syn::Ident;
+use syn::ImplItem;
+use syn::ItemImpl;
+use syn::Lit;
+use syn::Meta;
+use syn::NestedMeta;
+
+/// Implements `v8::object_wrap::ObjectWrap` for the type of an `impl` block,
+/// exposing it to JavaScript as a class. The following attributes can be put
+/// on the functions in the block:
+///
+/// - `#[constructor]`: called for `new ClassName(...)`. Signature:
+///   `fn(&mut v8::HandleScope, v8::FunctionCallbackArguments) -> Option<Self>`.
+///   Without a constructor the class can only be instantiated from Rust, with
+///   `v8::object_wrap::wrap()`.
+/// - `#[method]`: a method on the class prototype. Signature:
+///   `fn(&self, &mut v8::HandleScope, v8::FunctionCallbackArguments,
+///   v8::ReturnValue)`.
+/// - `#[getter]`: a property getter. Signature:
+///   `fn(&self, &mut v8::HandleScope, v8::ReturnValue)`.
+/// - `#[setter]`: a property setter, which must be named `set_<property>` and
+///   requires a getter for the same property. Signature:
+///   `fn(&self, &mut v8::HandleScope, v8::Local<v8::Value>)`.
+///
+/// JavaScript names are the Rust names converted to camel case; they can be
+/// overridden with e.g. `#[method(name = "toString")]`. The class name
+/// defaults to the name of the type and can be overridden with
+/// `#[v8_class(name = "...")]`.
+///
+/// The generated code refers to the `v8` crate as `::v8`. If it is imported
+/// under a different name, pass its path with e.g.
+/// `#[v8_class(crate = "my_v8")]`.
+///
+/// Since methods only get a shared reference to `self`, mutable state must
+/// use interior mutability (e.g. `Cell` or `RefCell`).
+#[proc_macro_attribute]
+pub fn v8_class(attr: TokenStream, item: TokenStream) -> TokenStream {
+  let args = parse_macro_input!(attr as AttributeArgs);
+  let item = parse_macro_input!(item as ItemImpl);
+  match expand_v8_class(args, item) {
+    Ok(tokens) => tokens.into(),
+    Err(err) => err.to_compile_error().into(),
+  }
+}
+
+struct Accessor {
+  js_name: String,
+  getter: Option<Ident>,
+  setter: Option<Ident>,
+  span: Span,
+}
+
+fn expand_v8_class(
+  args: AttributeArgs,
+  mut item: ItemImpl,
+) -> Result<TokenStream2, Error> {
+  if !item.generics.params.is_empty() {
+    return Err(Error::new_spanned(
+      &item.generics,
+      "#[v8_class] does not support generic types",
+    ));
+  }
+  if let Some((_, path, _)) = &item.trait_ {
+    return Err(Error::new_spanned(
+      path,
+      "#[v8_class] must be put on an inherent impl block",
+    ));
+  }
+
+  let self_ty = item.self_ty.clone();
+  let (name, krate) = class_args(&args)?;
+  let class_name = match name {
+    Some(name) => name,
+    None => match &*self_ty {
+      syn::Type::Path(path) => {
+        path.path.segments.last().unwrap().ident.to_string()
+      }
+      _ => {
+        return Err(Error::new_spanned(
+          &self_ty,
+          "#[v8_class] requires a `name` argument for this type",
+        ))
+      }
+    },
+  };
+
+  let mut constructor = None;
+  let mut methods = Vec::new();
+  let mut accessors: Vec<Accessor> = Vec::new();
+
+  for impl_item in &mut item.items {
+    let method = match impl_item {
+      ImplItem::Method(method) => method,
+      _ => continue,
+    };
+    let ident = method.sig.ident.clone();
+    let mut kept_attrs = Vec::new();
+    for attr in method.attrs.drain(..) {
+      let kind = match attr.path.get_ident().map(|i| i.to_string()) {
+        Some(kind)
+          if matches!(
+            kind.as_str(),
+            "constructor" | "method" | "getter" | "setter"
+          ) =>
+        {
+          kind
+        }
+        _ => {
+          kept_attrs.push(attr);
+          continue;
+        }
+      };
+      let js_name = attr_name(&attr)?;
+      match kind.as_str() {
+        "constructor" => {
+          if constructor.is_some() {
+            return Err(Error::new_spanned(
+              &attr,
+              "a class can only have one #[constructor]",
+            ));
+          }
+          constructor = Some(ident.clone());
+        }
+        "method" => {
+          let js_name =
+            js_name.unwrap_or_else(|| to_camel_case(&ident.to_string()));
+          methods.push((js_name, ident.clone()));
+        }
+        "getter" | "setter" => {
+          let is_setter = kind == "setter";
+          let rust_name = ident.to_string();
+          let property = if is_setter {
+            match rust_name.strip_prefix("set_") {
+              Some(property) => property.to_owned(),
+              None if js_name.is_some() => rust_name.clone(),
+              None => {
+                return Err(Error::new_spanned(
+                  &ident,
+                  "#[setter] functions must be named `set_<property>`",
+                ))
+              }
+            }
+          } else {
+            rust_name.clone()
+          };
+          let js_name = js_name.unwrap_or_else(|| to_camel_case(&property));
+          let index = match accessors.iter().position(|a| a.js_name == js_name)
+          {
+            Some(index) => index,
+            None => {
+              accessors.push(Accessor {
+                js_name,
+                getter: None,
+                setter: None,
+                span: ident.span(),
+              });
+              accessors.len() - 1
+            }
+          };
+          let accessor = &mut accessors[index];
+          let slot = if is_setter {
+            &mut accessor.setter
+          } else {
+            &mut accessor.getter
+          };
+          if slot.is_some() {
+            return Err(Error::new_spanned(
+              &attr,
+              format!(
+                "duplicate #[{}] for property `{}`",
+                kind, accessor.js_name
+              ),
+            ));
+          }
+          *slot = Some(ident.clone());
+        }
+        _ => unreachable!(),
+      }
+    }
+    method.attrs = kept_attrs;
+  }
+
+  let construct = constructor.map(|ident| {
+    quote! {
+      fn construct(
+        scope: &mut #krate::HandleScope,
+        args: #krate::FunctionCallbackArguments,
+      ) -> Option<Self> {
+        Self::#ident(scope, args)
+      }
+    }
+  });
+
+  let methods = methods.iter().map(|(js_name, ident)| {
+    quote! {
+      {
+        fn callback(
+          scope: &mut #krate::HandleScope,
+          args: #krate::FunctionCallbackArguments,
+          rv: #krate::ReturnValue,
+        ) {
+          let this = args.this();
+          if let Some(this) =
+            #krate::object_wrap::unwrap_receiver::<#self_ty>(scope, this)
+          {
+            this.#ident(scope, args, rv);
+          }
+        }
+        let name = #krate::String::new(scope, #js_name).unwrap();
+        let function = #krate::FunctionTemplate::new(scope, callback);
+        prototype.set(name.into(), function.into());
+      }
+    }
+  });
+
+  let mut accessor_tokens = Vec::new();
+  for accessor in &accessors {
+    let js_name = &accessor.js_name;
+    let getter = match &accessor.getter {
+      Some(getter) => getter,
+      None => {
+        return Err(Error::new(
+          accessor.span,
+          format!("#[setter] for property `{}` requires a #[getter]", js_name),
+        ))
+      }
+    };
+    let set_accessor = match &accessor.setter {
+      Some(setter) => quote! {
+        fn setter(
+          scope: &mut #krate::HandleScope,
+          _key: #krate::Local<#krate::Name>,
+          value: #krate::Local<#krate::Value>,
+          args: #krate::PropertyCallbackArguments,
+        ) {
+          let this = args.this();
+          if let Some(this) =
+            #krate::object_wrap::unwrap_receiver::<#self_ty>(scope, this)
+          {
+            this.#setter(scope, value);
+          }
+        }
+        instance.set_accessor_with_setter(name.into(), getter, setter);
+      },
+      None => quote! {
+        instance.set_accessor(name.into(), getter);
+      },
+    };
+    accessor_tokens.push(quote! {
+      {
+        fn getter(
+          scope: &mut #krate::HandleScope,
+          _key: #krate::Local<#krate::Name>,
+          args: #krate::PropertyCallbackArguments,
+          rv: #krate::ReturnValue,
+        ) {
+          let this = args.this();
+          if let Some(this) =
+            #krate::object_wrap::unwrap_receiver::<#self_ty>(scope, this)
+          {
+            this.#getter(scope, rv);
+          }
+        }
+        let name = #krate::String::new(scope, #js_name).unwrap();
+        #set_accessor
+      }
+    });
+  }
+
+  Ok(quote! {
+    #item
+
+    impl #krate::object_wrap::ObjectWrap for #self_ty {
+      const CLASS_NAME: &'static str = #class_name;
+
+      #construct
+
+      #[allow(unused_variables)]
+      fn init_template(
+        scope: &mut #krate::HandleScope<()>,
+        template: #krate::Local<#krate::FunctionTemplate>,
+      ) {
+        let prototype = template.prototype_template(scope);
+        let instance = template.instance_template(scope);
+        #(#methods)*
+        #(#accessor_tokens)*
+      }
+    }
+  })
+}
+
+/// Parses the `name = "..."` and `crate = "..."` arguments of `#[v8_class]`.
+/// The crate path defaults to `::v8`.
+fn class_args(
+  args: &[NestedMeta],
+) -> Result<(Option<String>, syn::Path), Error> {
+  let mut name_args = Vec::new();
+  let mut krate = None;
+  for arg in args {
+    match arg {
+      NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("crate") => {
+        match &nv.lit {
+          Lit::Str(s) => krate = Some(s.parse()?),
+          lit => return Err(Error::new_spanned(lit, "expected a string")),
+        }
+      }
+      arg => name_args.push(arg.clone()),
+    }
+  }
+  let krate = krate.unwrap_or_else(|| syn::parse_quote!(::v8));
+  Ok((name_arg(&name_args)?, krate))
+}
+
+/// Parses the `name = "..."` argument of an attribute.
+fn name_arg(args: &[NestedMeta]) -> Result<Option<String>, Error> {
+  let mut name = None;
+  for arg in args {
+    match arg {
+      NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+        match &nv.lit {
+          Lit::Str(s) => name = Some(s.value()),
+          lit => return Err(Error::new_spanned(lit, "expected a string")),
+        }
+      }
+      arg => return Err(Error::new_spanned(arg, "unknown argument")),
+    }
+  }
+  Ok(name)
+}
+
+/// Parses the optional `(name = "...")` argument of a function attribute.
+fn attr_name(attr: &Attribute) -> Result<Option<String>, Error> {
+  match attr.parse_meta()? {
+    Meta::Path(_) => Ok(None),
+    Meta::List(list) => {
+      let args = list.nested.into_iter().collect::<Vec<_>>();
+      name_arg(&args)
+    }
+    meta => Err(Error::new(meta.span(), "expected `name = \"...\"`")),
+  }
+}
+
+fn to_camel_case(name: &str) -> String {
+  let mut result = String::with_capacity(name.len());
+  let mut upper = false;
+  for c in name.trim_start_matches('_').chars() {
+    if c == '_' {
+      upper = true;
+    } else if upper {
+      result.extend(c.to_uppercase());
+      upper = false;
+    } else {
+      result.push(c);
+    }
+  }
+  result
+}
@@ -1,6 +1,7 @@
 // Copyright 2019-2021 the Deno authors. All rights reserved. MIT license.
 use libc::c_char;
 use libc::c_int;
+use std::ffi::c_void;
 use std::ffi::CStr;
 use std::ffi::CString;
 use std::sync::Mutex;
@@ -19,6 +20,13 @@ extern "C" {
   fn v8__V8__SetFlagsFromString(flags: *const u8, length: usize);
   fn v8__V8__SetEntropySource(callback: EntropySource);
   fn v8__V8__GetVersion() -> *const c_char;
+  fn v8__V8__GetBuildConfig(config: *mut BuildConfig);
+  fn v8__V8__ForEachJsFeature(callback: FeatureCallback, arg: *mut c_void);
+  fn v8__V8__ForEachWasmFeature(callback: FeatureCallback, arg: *mut c_void);
+  fn v8__V8__IsWasmExposed() -> bool;
+  fn v8__V8__IsAsmValidationEnabled() -> bool;
+  fn v8__V8__HasMemoryProtectionKeySupport() -> bool;
+  fn v8__V8__IsMemoryProtectionKeysEnabled() -> bool;
   fn v8__V8__InitializePlatform(platform: *mut Platform);
   fn v8__V8__Initialize();
   fn v8__V8__Dispose() -> bool;
@@ -160,13 +168,21 @@ pub fn set_flags_from_string(flags: &str) {
 }
 
 /// Allows the host application to provide a callback which can be used
-/// as a source of entropy for random number generators.
+/// as a source of entropy for random number generators. A callback that
+/// always produces the same bytes makes `Math.random()` deterministic, see
+/// also `Platform::new_with_clock()`.
 pub fn set_entropy_source(
   callback: impl UnitType + Into<EntropySource> + FnOnce(&mut [u8]) -> bool,
 ) {
   unsafe { v8__V8__SetEntropySource(callback.into()) };
 }
 
+/// The major, minor and build number of the V8 version these bindings are
+/// built against, e.g. `(10, 5, 218)` for V8 10.5.218.1. The patch level is
+/// left out, since patches don't change the API. Use `get_version()` for the
+/// full version string of the linked V8 library.
+pub const VERSION: (u32, u32, u32) = (10, 5, 218);
+
 /// Get the version string.
 pub fn get_version() -> &'static str {
   let version = unsafe { v8__V8__GetVersion() };
@@ -174,6 +190,157 @@ pub fn get_version() -> &'static str {
   c_str.to_str().unwrap()
 }
 
+/// The options the linked V8 library was built with. See
+/// `get_build_config()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildConfig {
+  /// Whether pointers on the V8 heap are compressed to 32 bits, which limits
+  /// the heap of an isolate to 4GB (`v8_enable_pointer_compression`).
+  pub pointer_compression: bool,
+  /// Whether the V8 sandbox is enabled (`v8_enable_sandbox`). If so, the
+  /// backing stores of array buffers have to be allocated inside of it.
+  pub sandbox: bool,
+  /// Whether V8 was built with ICU, i.e. supports the `Intl` API
+  /// (`v8_enable_i18n_support`).
+  pub i18n: bool,
+  /// Whether V8 runs without generating executable code at runtime. This is
+  /// a flag (`--jitless`) rather than a build option in this V8 version, so
+  /// it reflects the flags that are currently set.
+  pub jitless: bool,
+}
+
+/// Returns the options the linked V8 library was built with.
+pub fn get_build_config() -> BuildConfig {
+  let mut config = BuildConfig {
+    pointer_compression: false,
+    sandbox: false,
+    i18n: false,
+    jitless: false,
+  };
+  unsafe { v8__V8__GetBuildConfig(&mut config) };
+  config
+}
+
+/// How far along the implementation of a language feature is.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureStatus {
+  /// The feature is still being implemented, and is disabled by default.
+  InProgress,
+  /// The feature is complete, but is disabled by default. It is enabled by
+  /// `--harmony` (for JavaScript features) or `--wasm-staging` (for
+  /// WebAssembly features).
+  Staged,
+  /// The feature is enabled by default.
+  Shipped,
+}
+
+/// A JavaScript or WebAssembly feature that can be toggled with a V8 flag.
+/// See `get_js_features()` and `get_wasm_features()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+  /// The name of the flag that toggles the feature, with underscores, e.g.
+  /// `harmony_shadow_realm`.
+  pub flag: &'static str,
+  pub description: &'static str,
+  pub status: FeatureStatus,
+  /// Whether the feature is enabled, according to the current flags.
+  pub enabled: bool,
+}
+
+type FeatureCallback = extern "C" fn(
+  arg: *mut c_void,
+  flag: *const c_char,
+  description: *const c_char,
+  status: FeatureStatus,
+  enabled: bool,
+);
+
+extern "C" fn push_feature(
+  arg: *mut c_void,
+  flag: *const c_char,
+  description: *const c_char,
+  status: FeatureStatus,
+  enabled: bool,
+) {
+  // The strings are literals in the V8 binary.
+  let to_static_str =
+    |s: *const c_char| unsafe { CStr::from_ptr(s) }.to_str().unwrap();
+  let features = unsafe { &mut *(arg as *mut Vec<Feature>) };
+  features.push(Feature {
+    flag: to_static_str(flag),
+    description: to_static_str(description),
+    status,
+    enabled,
+  });
+}
+
+/// Returns the JavaScript language features that the linked V8 build knows
+/// about, and whether they are enabled. Features that have been shipped for
+/// long enough to no longer have a flag aren't included.
+///
+/// Flag implications are only applied by `initialize()`, so this should be
+/// called after that to get accurate results.
+pub fn get_js_features() -> Vec<Feature> {
+  let mut features = Vec::new();
+  unsafe {
+    v8__V8__ForEachJsFeature(
+      push_feature,
+      &mut features as *mut Vec<Feature> as *mut c_void,
+    )
+  };
+  features
+}
+
+/// Returns the WebAssembly proposals that the linked V8 build knows about,
+/// and whether they are enabled by the current flags. Some features can also
+/// be enabled per isolate by the embedder (e.g. for origin trials), which
+/// isn't reflected here.
+///
+/// Flag implications are only applied by `initialize()`, so this should be
+/// called after that to get accurate results.
+pub fn get_wasm_features() -> Vec<Feature> {
+  let mut features = Vec::new();
+  unsafe {
+    v8__V8__ForEachWasmFeature(
+      push_feature,
+      &mut features as *mut Vec<Feature> as *mut c_void,
+    )
+  };
+  features
+}
+
+/// Returns true if the `WebAssembly` global object is installed in new
+/// contexts. This is false e.g. when V8 runs with `--jitless`.
+pub fn is_wasm_exposed() -> bool {
+  unsafe { v8__V8__IsWasmExposed() }
+}
+
+/// Returns true if asm.js code is validated and compiled to WebAssembly.
+/// Otherwise asm.js code runs as regular JavaScript.
+pub fn is_asm_validation_enabled() -> bool {
+  unsafe { v8__V8__IsAsmValidationEnabled() }
+}
+
+/// Returns true if the CPU and operating system support memory protection
+/// keys (e.g. Intel PKU on Linux), which V8 can use to make the code space of
+/// WebAssembly modules writable only while V8 is writing code to it.
+///
+/// Must be called after `initialize()`.
+pub fn has_memory_protection_key_support() -> bool {
+  unsafe { v8__V8__HasMemoryProtectionKeySupport() }
+}
+
+/// Returns true if V8 actually uses memory protection keys to write-protect
+/// WebAssembly code, which requires `has_memory_protection_key_support()` and
+/// the `--wasm-memory-protection-keys` flag.
+///
+/// Must be called after `initialize()`.
+pub fn is_memory_protection_keys_enabled() -> bool {
+  unsafe { v8__V8__IsMemoryProtectionKeysEnabled() }
+}
+
 /// Sets the v8::Platform to use. This should be invoked before V8 is
 /// initialized.
 pub fn initialize_platform(platform: SharedRef<Platform>) {
@@ -0,0 +1,255 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use crate::support::int;
+use crate::support::Opaque;
+use crate::HandleScope;
+use crate::Isolate;
+use crate::Local;
+use crate::String;
+
+extern "C" {
+  fn v8__HeapProfiler__GetAllocationProfile(
+    isolate: *mut Isolate,
+  ) -> *mut RawAllocationProfile;
+  fn v8__AllocationProfile__DELETE(this: *mut RawAllocationProfile);
+  fn v8__AllocationProfile__GetRootNode(
+    this: *const RawAllocationProfile,
+  ) -> *const RawAllocationProfileNode;
+  fn v8__AllocationProfile__GetSamplesCount(
+    this: *const RawAllocationProfile,
+  ) -> usize;
+  fn v8__AllocationProfile__GetSample(
+    this: *const RawAllocationProfile,
+    index: usize,
+    node_id: *mut u32,
+    size: *mut usize,
+    count: *mut u32,
+    sample_id: *mut u64,
+  );
+
+  fn v8__AllocationProfile__Node__GetName(
+    this: *const RawAllocationProfileNode,
+  ) -> *const String;
+  fn v8__AllocationProfile__Node__GetScriptName(
+    this: *const RawAllocationProfileNode,
+  ) -> *const String;
+  fn v8__AllocationProfile__Node__GetScriptId(
+    this: *const RawAllocationProfileNode,
+  ) -> int;
+  fn v8__AllocationProfile__Node__GetStartPosition(
+    this: *const RawAllocationProfileNode,
+  ) -> int;
+  fn v8__AllocationProfile__Node__GetLineNumber(
+    this: *const RawAllocationProfileNode,
+  ) -> int;
+  fn v8__AllocationProfile__Node__GetColumnNumber(
+    this: *const RawAllocationProfileNode,
+  ) -> int;
+  fn v8__AllocationProfile__Node__GetNodeId(
+    this: *const RawAllocationProfileNode,
+  ) -> u32;
+  fn v8__AllocationProfile__Node__GetChildrenCount(
+    this: *const RawAllocationProfileNode,
+  ) -> usize;
+  fn v8__AllocationProfile__Node__GetChild(
+    this: *const RawAllocationProfileNode,
+    index: usize,
+  ) -> *const RawAllocationProfileNode;
+  fn v8__AllocationProfile__Node__GetAllocationsCount(
+    this: *const RawAllocationProfileNode,
+  ) -> usize;
+  fn v8__AllocationProfile__Node__GetAllocation(
+    this: *const RawAllocationProfileNode,
+    index: usize,
+    size: *mut usize,
+    count: *mut u32,
+  );
+}
+
+bitflags! {
+  /// Flags for `Isolate::start_sampling_heap_profiler()`.
+  #[derive(Default)]
+  #[repr(transparent)]
+  pub struct SamplingHeapProfilerFlags: int {
+    const NONE = 0;
+    /// Forces a garbage collection before the profiler is started.
+    const FORCE_GC = 1 << 0;
+    /// Keeps reporting samples for objects that have been collected by a
+    /// major (mark-compact) garbage collection.
+    const INCLUDE_OBJECTS_COLLECTED_BY_MAJOR_GC = 1 << 1;
+    /// Keeps reporting samples for objects that have been collected by a
+    /// minor (scavenge) garbage collection.
+    const INCLUDE_OBJECTS_COLLECTED_BY_MINOR_GC = 1 << 2;
+  }
+}
+
+#[repr(C)]
+struct RawAllocationProfile(Opaque);
+
+#[repr(C)]
+struct RawAllocationProfileNode(Opaque);
+
+/// The allocations sampled by the sampling heap profiler. See
+/// `Isolate::start_sampling_heap_profiler()`.
+///
+/// Like `CpuProfile`, this is a plain Rust structure, which makes it easy to
+/// convert to other formats (e.g. pprof).
+#[derive(Debug, Clone)]
+pub struct AllocationProfile {
+  /// The nodes of the profile's call tree, in depth-first order. The first
+  /// node is the root of the tree.
+  pub nodes: Vec<AllocationProfileNode>,
+  /// The individual samples that are still alive (unless the profiler was
+  /// started with flags that include collected objects).
+  pub samples: Vec<AllocationProfileSample>,
+}
+
+/// A node in the call tree of an `AllocationProfile`, i.e. a function
+/// together with the call stack it was called from.
+#[derive(Debug, Clone)]
+pub struct AllocationProfileNode {
+  /// The id of the node, which is unique within the profile.
+  pub id: u32,
+  /// The name of the function, which is empty for anonymous functions.
+  pub name: std::string::String,
+  /// The resource name of the script the function belongs to.
+  pub script_name: std::string::String,
+  /// The id of the script the function belongs to.
+  pub script_id: i32,
+  /// The offset of the function in the script source.
+  pub start_position: i32,
+  /// The 1-based line number of the function, or 0 if unknown.
+  pub line_number: i32,
+  /// The 1-based column number of the function, or 0 if unknown.
+  pub column_number: i32,
+  /// The ids of the child nodes.
+  pub children: Vec<u32>,
+  /// The allocations made directly by this function, grouped by size.
+  pub allocations: Vec<AllocationProfileAllocation>,
+}
+
+/// The number of sampled allocations of a particular size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationProfileAllocation {
+  /// The size of each allocation, in bytes.
+  pub size: usize,
+  /// The number of allocations of this size.
+  pub count: u32,
+}
+
+/// A sampled allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationProfileSample {
+  /// The id of the node that made the allocation.
+  pub node_id: u32,
+  /// The size of the allocation, in bytes.
+  pub size: usize,
+  /// The number of allocations this sample represents.
+  pub count: u32,
+  /// An id that is unique for every sample, and increases over time.
+  pub sample_id: u64,
+}
+
+impl AllocationProfile {
+  /// See `HandleScope::get_allocation_profile()`.
+  pub(crate) fn get(scope: &mut HandleScope<()>) -> Option<Self> {
+    // V8 creates handles for the names of the nodes in the current handle
+    // scope, so use one that doesn't outlive this call.
+    let scope = &mut HandleScope::new(scope);
+    let isolate: &mut Isolate = scope;
+    let raw = unsafe { v8__HeapProfiler__GetAllocationProfile(isolate) };
+    if raw.is_null() {
+      return None;
+    }
+    let profile = unsafe { Self::from_raw(scope, &*raw) };
+    unsafe { v8__AllocationProfile__DELETE(raw) };
+    Some(profile)
+  }
+
+  unsafe fn from_raw(
+    scope: &mut HandleScope<()>,
+    raw: &RawAllocationProfile,
+  ) -> Self {
+    let mut nodes = Vec::new();
+    let mut stack = vec![v8__AllocationProfile__GetRootNode(raw)];
+    while let Some(node) = stack.pop() {
+      let children = (0..v8__AllocationProfile__Node__GetChildrenCount(node))
+        .map(|index| v8__AllocationProfile__Node__GetChild(node, index))
+        .collect::<Vec<_>>();
+      nodes.push(AllocationProfileNode::from_raw(scope, node, &children));
+      // Push the children in reverse, so that they're visited in order.
+      stack.extend(children.into_iter().rev());
+    }
+
+    let samples = (0..v8__AllocationProfile__GetSamplesCount(raw))
+      .map(|index| {
+        let mut sample = AllocationProfileSample {
+          node_id: 0,
+          size: 0,
+          count: 0,
+          sample_id: 0,
+        };
+        v8__AllocationProfile__GetSample(
+          raw,
+          index,
+          &mut sample.node_id,
+          &mut sample.size,
+          &mut sample.count,
+          &mut sample.sample_id,
+        );
+        sample
+      })
+      .collect();
+
+    Self { nodes, samples }
+  }
+
+  /// Returns the node with the given id.
+  pub fn get_node(&self, id: u32) -> Option<&AllocationProfileNode> {
+    self.nodes.iter().find(|node| node.id == id)
+  }
+}
+
+impl AllocationProfileNode {
+  unsafe fn from_raw(
+    scope: &mut HandleScope<()>,
+    node: *const RawAllocationProfileNode,
+    children: &[*const RawAllocationProfileNode],
+  ) -> Self {
+    let mut to_string = |s: *const String| match Local::from_raw(s) {
+      Some(s) => s.to_rust_string_lossy(scope),
+      None => Default::default(),
+    };
+    let name = to_string(v8__AllocationProfile__Node__GetName(node));
+    let script_name =
+      to_string(v8__AllocationProfile__Node__GetScriptName(node));
+    let allocations =
+      (0..v8__AllocationProfile__Node__GetAllocationsCount(node))
+        .map(|index| {
+          let mut allocation =
+            AllocationProfileAllocation { size: 0, count: 0 };
+          v8__AllocationProfile__Node__GetAllocation(
+            node,
+            index,
+            &mut allocation.size,
+            &mut allocation.count,
+          );
+          allocation
+        })
+        .collect();
+    Self {
+      id: v8__AllocationProfile__Node__GetNodeId(node),
+      name,
+      script_name,
+      script_id: v8__AllocationProfile__Node__GetScriptId(node),
+      start_position: v8__AllocationProfile__Node__GetStartPosition(node),
+      line_number: v8__AllocationProfile__Node__GetLineNumber(node),
+      column_number: v8__AllocationProfile__Node__GetColumnNumber(node),
+      children: children
+        .iter()
+        .map(|&child| v8__AllocationProfile__Node__GetNodeId(child))
+        .collect(),
+      allocations,
+    }
+  }
+}
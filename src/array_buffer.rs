@@ -7,6 +7,9 @@ use std::ptr;
 use std::ptr::null_mut;
 use std::ptr::NonNull;
 use std::slice;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 use crate::support::long;
 use crate::support::Opaque;
@@ -51,10 +54,19 @@ extern "C" {
     deleter: BackingStoreDeleterCallback,
     deleter_data: *mut c_void,
   ) -> *mut BackingStore;
+  fn v8__ArrayBuffer__TryNewBackingStore(
+    isolate: *mut Isolate,
+    byte_length: usize,
+  ) -> *mut BackingStore;
 
   fn v8__BackingStore__Data(this: *const BackingStore) -> *mut c_void;
   fn v8__BackingStore__ByteLength(this: *const BackingStore) -> usize;
   fn v8__BackingStore__IsShared(this: *const BackingStore) -> bool;
+  fn v8__BackingStore__Reallocate(
+    isolate: *mut Isolate,
+    backing_store: *mut BackingStore,
+    byte_length: usize,
+  ) -> *mut BackingStore;
   fn v8__BackingStore__DELETE(this: *mut BackingStore);
 
   fn std__shared_ptr__v8__BackingStore__COPY(
@@ -111,6 +123,11 @@ extern "C" {
 #[derive(Debug)]
 pub struct Allocator(Opaque);
 
+// V8 requires array buffer allocators to be thread-safe, since it allocates
+// and frees backing stores from background threads as well.
+unsafe impl Send for Allocator {}
+unsafe impl Sync for Allocator {}
+
 /// A wrapper around the V8 Allocator class.
 #[repr(C)]
 pub struct RustAllocatorVtable<T> {
@@ -170,6 +187,154 @@ pub unsafe fn new_rust_allocator<T: Sized + Send + Sync + 'static>(
   ))
 }
 
+/// Keeps track of the memory used by an allocator created with
+/// `new_counting_allocator()`. Since every isolate can be given its own
+/// allocator, this allows accounting the memory used by `ArrayBuffer`s per
+/// isolate, e.g. to enforce a memory quota.
+#[derive(Debug, Default)]
+pub struct AllocatorStats {
+  allocated_bytes: AtomicUsize,
+  peak_allocated_bytes: AtomicUsize,
+  limit: Option<usize>,
+}
+
+impl AllocatorStats {
+  /// Returns the number of bytes that are currently allocated.
+  pub fn allocated_bytes(&self) -> usize {
+    self.allocated_bytes.load(Ordering::Relaxed)
+  }
+
+  /// Returns the largest number of bytes that were allocated at any time.
+  pub fn peak_allocated_bytes(&self) -> usize {
+    self.peak_allocated_bytes.load(Ordering::Relaxed)
+  }
+
+  /// Returns the maximum number of bytes that can be allocated, if any.
+  pub fn limit(&self) -> Option<usize> {
+    self.limit
+  }
+
+  /// Accounts for `len` more bytes. Returns false if that would exceed the
+  /// limit.
+  fn reserve(&self, len: usize) -> bool {
+    let limit = self.limit.unwrap_or(usize::MAX);
+    let reserved = self.allocated_bytes.fetch_update(
+      Ordering::Relaxed,
+      Ordering::Relaxed,
+      |allocated| allocated.checked_add(len).filter(|&n| n <= limit),
+    );
+    match reserved {
+      Ok(allocated) => {
+        self
+          .peak_allocated_bytes
+          .fetch_max(allocated + len, Ordering::Relaxed);
+        true
+      }
+      Err(_) => false,
+    }
+  }
+
+  fn release(&self, len: usize) {
+    self.allocated_bytes.fetch_sub(len, Ordering::Relaxed);
+  }
+}
+
+/// Creates a malloc/free based allocator, like `new_default_allocator()`, that
+/// keeps track of the number of bytes it has allocated in the returned
+/// `AllocatorStats`.
+///
+/// If `limit` is set, allocations that would make the allocator exceed it
+/// fail. How V8 handles the failure depends on the caller: the JavaScript
+/// `ArrayBuffer` constructor throws a `RangeError`, and
+/// `ArrayBuffer::try_new()` and `ArrayBuffer::try_new_backing_store()` return
+/// `None`, but `ArrayBuffer::new()` and `ArrayBuffer::new_backing_store()`
+/// treat it as an out-of-memory condition and abort the process. Use the
+/// `try_` variants for allocations that are subject to the limit.
+pub fn new_counting_allocator(
+  limit: Option<usize>,
+) -> (UniqueRef<Allocator>, Arc<AllocatorStats>) {
+  unsafe extern "C" fn allocate(
+    stats: &AllocatorStats,
+    len: usize,
+  ) -> *mut c_void {
+    if !stats.reserve(len) {
+      return null_mut();
+    }
+    let data = libc::calloc(len.max(1), 1);
+    if data.is_null() {
+      stats.release(len);
+    }
+    data
+  }
+  unsafe extern "C" fn allocate_uninitialized(
+    stats: &AllocatorStats,
+    len: usize,
+  ) -> *mut c_void {
+    if !stats.reserve(len) {
+      return null_mut();
+    }
+    let data = libc::malloc(len.max(1));
+    if data.is_null() {
+      stats.release(len);
+    }
+    data
+  }
+  unsafe extern "C" fn free(
+    stats: &AllocatorStats,
+    data: *mut c_void,
+    len: usize,
+  ) {
+    libc::free(data);
+    stats.release(len);
+  }
+  unsafe extern "C" fn reallocate(
+    stats: &AllocatorStats,
+    data: *mut c_void,
+    old_length: usize,
+    new_length: usize,
+  ) -> *mut c_void {
+    if new_length > old_length && !stats.reserve(new_length - old_length) {
+      return null_mut();
+    }
+    let new_data = libc::realloc(data, new_length.max(1));
+    if new_data.is_null() {
+      if new_length > old_length {
+        stats.release(new_length - old_length);
+      }
+      return null_mut();
+    }
+    if new_length > old_length {
+      ptr::write_bytes(
+        (new_data as *mut u8).add(old_length),
+        0,
+        new_length - old_length,
+      );
+    } else {
+      stats.release(old_length - new_length);
+    }
+    new_data
+  }
+  unsafe extern "C" fn drop(stats: *const AllocatorStats) {
+    let _ = Arc::from_raw(stats);
+  }
+
+  static VTABLE: RustAllocatorVtable<AllocatorStats> = RustAllocatorVtable {
+    allocate,
+    allocate_uninitialized,
+    free,
+    reallocate,
+    drop,
+  };
+
+  let stats = Arc::new(AllocatorStats {
+    limit,
+    ..Default::default()
+  });
+  let allocator =
+    unsafe { new_rust_allocator(Arc::into_raw(stats.clone()), &VTABLE) };
+  (allocator, stats)
+}
+
 #[test]
 fn test_rust_allocator() {
   use std::sync::atomic::{AtomicUsize, Ordering};
@@ -290,6 +455,31 @@ impl BackingStore {
   pub fn is_shared(&self) -> bool {
     unsafe { v8__BackingStore__IsShared(self) }
   }
+
+  /// Wrapper around ArrayBuffer::Allocator::Reallocate that preserves the
+  /// contents of the backing store (up to the smaller of the old and new
+  /// byte lengths) while growing or shrinking it.
+  ///
+  /// The backing store must have been allocated with the array buffer
+  /// allocator of the given isolate, e.g. with
+  /// `ArrayBuffer::new_backing_store()`, and must not be shared.
+  ///
+  /// If the allocator returns nullptr, then the function may cause GCs in the
+  /// given isolate and re-try the allocation. If GCs do not help, then the
+  /// function will crash with an out-of-memory error.
+  pub fn reallocate(
+    scope: &mut Isolate,
+    backing_store: UniqueRef<BackingStore>,
+    byte_length: usize,
+  ) -> UniqueRef<BackingStore> {
+    unsafe {
+      UniqueRef::from_raw(v8__BackingStore__Reallocate(
+        scope,
+        backing_store.into_raw(),
+        byte_length,
+      ))
+    }
+  }
 }
 
 impl Deref for BackingStore {
@@ -354,6 +544,23 @@ impl ArrayBuffer {
     .unwrap()
   }
 
+  /// Like `new()`, but returns `None` instead of crashing the process if the
+  /// memory can't be allocated, e.g. because `byte_length` is too large, or
+  /// because the isolate's allocator has reached its limit (see
+  /// `new_counting_allocator()`).
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn try_new<'s>(
+    scope: &mut HandleScope<'s>,
+    byte_length: usize,
+  ) -> Option<Local<'s, ArrayBuffer>> {
+    let backing_store = Self::try_new_backing_store(scope, byte_length)?;
+    Some(Self::with_backing_store(
+      scope,
+      &backing_store.make_shared(),
+    ))
+  }
+
   pub fn with_backing_store<'s>(
     scope: &mut HandleScope<'s>,
     backing_store: &SharedRef<BackingStore>,
@@ -369,6 +576,20 @@ impl ArrayBuffer {
     .unwrap()
   }
 
+  /// Create a new ArrayBuffer that takes over ownership of the given vector
+  /// without copying its contents. The vector's memory is released with the
+  /// Rust allocator once the ArrayBuffer (and any other users of its backing
+  /// store) are gone.
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn from_vec<'s>(
+    scope: &mut HandleScope<'s>,
+    data: Vec<u8>,
+  ) -> Local<'s, ArrayBuffer> {
+    let backing_store = Self::new_backing_store_from_vec(data).make_shared();
+    Self::with_backing_store(scope, &backing_store)
+  }
+
   /// Data length in bytes.
   pub fn byte_length(&self) -> usize {
     unsafe { v8__ArrayBuffer__ByteLength(self) }
@@ -418,6 +639,26 @@ impl ArrayBuffer {
     }
   }
 
+  /// Like `new_backing_store()`, but returns `None` instead of crashing the
+  /// process if the array buffer allocator of the isolate fails to allocate
+  /// the memory. Unlike `new_backing_store()`, no garbage collection is
+  /// triggered to retry the allocation, and the memory is not reported to the
+  /// isolate as externally allocated memory, so it doesn't count towards the
+  /// heuristics that schedule garbage collections.
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn try_new_backing_store(
+    scope: &mut Isolate,
+    byte_length: usize,
+  ) -> Option<UniqueRef<BackingStore>> {
+    unsafe {
+      UniqueRef::try_from_raw(v8__ArrayBuffer__TryNewBackingStore(
+        scope,
+        byte_length,
+      ))
+    }
+  }
+
   /// Returns a new standalone BackingStore that takes over the ownership of
   /// the given buffer.
   ///
@@ -1,9 +1,13 @@
 // Copyright 2019-2021 the Deno authors. All rights reserved. MIT license.
+#include <algorithm>
 #include <cassert>
 #include <cstdint>
 #include <iostream>
+#include <string>
+#include <thread>
 
 #include "support.h"
+#include "unicode/locid.h"
 #include "v8-callbacks.h"
 #include "v8/include/libplatform/libplatform.h"
 #include "v8/include/v8-fast-api-calls.h"
@@ -13,12 +17,22 @@
 #include "v8/include/v8.h"
 #include "v8/src/api/api-inl.h"
 #include "v8/src/api/api.h"
+#include "v8/src/base/platform/time.h"
+#include "v8/src/debug/debug-interface.h"
 #include "v8/src/execution/isolate-utils-inl.h"
 #include "v8/src/execution/isolate-utils.h"
 #include "v8/src/flags/flags.h"
+#include "v8/src/libplatform/default-platform.h"
+#include "v8/src/objects/lookup.h"
 #include "v8/src/objects/objects-inl.h"
 #include "v8/src/objects/objects.h"
+#include "v8/src/objects/property-descriptor.h"
 #include "v8/src/objects/smi.h"
+#include "v8/src/wasm/wasm-code-manager.h"
+#include "v8/src/wasm/wasm-feature-flags.h"
+#include "v8/src/wasm/wasm-objects.h"
+#include "v8/third_party/inspector_protocol/crdtp/cbor.h"
+#include "v8/third_party/inspector_protocol/crdtp/json.h"
 
 using namespace support;
 
@@ -40,6 +54,9 @@ static_assert(sizeof(v8::PromiseRejectMessage) == sizeof(size_t) * 3,
 
 static_assert(sizeof(v8::Locker) == sizeof(size_t) * 2, "Locker size mismatch");
 
+static_assert(sizeof(v8::Unlocker) == sizeof(size_t) * 1,
+              "Unlocker size mismatch");
+
 static_assert(sizeof(v8::ScriptCompiler::Source) <= sizeof(size_t) * 8,
               "Source size mismatch");
 
@@ -64,6 +81,11 @@ static_assert(sizeof(v8::SnapshotCreator) == sizeof(size_t) * 1,
 static_assert(sizeof(v8::CFunction) == sizeof(size_t) * 2,
               "CFunction size mismatch");
 
+static_assert(sizeof(v8::CFunctionInfo) == sizeof(size_t) * 2,
+              "CFunctionInfo size mismatch");
+
+static_assert(sizeof(v8::CTypeInfo) == 3, "CTypeInfo size mismatch");
+
 static_assert(sizeof(three_pointers_t) == sizeof(v8_inspector::StringView),
               "StringView size mismatch");
 
@@ -91,6 +113,18 @@ static_assert(offsetof(v8::ScriptCompiler::CachedData, buffer_policy) == 12,
               "CachedData.buffer_policy offset mismatch");
 #endif
 
+// Rust's `JitCodeEvent` mirrors the layout of `v8::JitCodeEvent`.
+static_assert(offsetof(v8::JitCodeEvent, code_start) == 8,
+              "JitCodeEvent.code_start offset mismatch");
+static_assert(offsetof(v8::JitCodeEvent, user_data) == 8 + 3 * sizeof(void*),
+              "JitCodeEvent.user_data offset mismatch");
+static_assert(offsetof(v8::JitCodeEvent, name) == 8 + 5 * sizeof(void*),
+              "JitCodeEvent.name offset mismatch");
+static_assert(offsetof(v8::JitCodeEvent, isolate) ==
+                  8 + 5 * sizeof(void*) +
+                      sizeof(v8::JitCodeEvent::line_info_t),
+              "JitCodeEvent.isolate offset mismatch");
+
 enum InternalSlots {
   kSlotDynamicImport = 0,
   kNumInternalSlots,
@@ -143,6 +177,128 @@ void v8__V8__SetEntropySource(v8::EntropySource callback) {
 
 const char* v8__V8__GetVersion() { return v8::V8::GetVersion(); }
 
+struct BuildConfig {
+  bool pointer_compression;
+  bool sandbox;
+  bool i18n;
+  bool jitless;
+};
+
+void v8__V8__GetBuildConfig(BuildConfig* config) {
+#ifdef V8_COMPRESS_POINTERS
+  config->pointer_compression = true;
+#else
+  config->pointer_compression = false;
+#endif
+#ifdef V8_SANDBOX
+  config->sandbox = true;
+#else
+  config->sandbox = false;
+#endif
+#ifdef V8_INTL_SUPPORT
+  config->i18n = true;
+#else
+  config->i18n = false;
+#endif
+  config->jitless = v8::internal::FLAG_jitless;
+}
+
+enum class FeatureStatus { kInProgress, kStaged, kShipped };
+
+using FeatureCallback = void (*)(void* arg, const char* flag,
+                                 const char* description, FeatureStatus status,
+                                 bool enabled);
+
+typedef void (*LocaleCallback)(void* arg, const char* data, size_t length);
+
+int icu__Locale__SetDefault(const char* data, size_t length) {
+  UErrorCode status = U_ZERO_ERROR;
+  icu::Locale locale =
+      icu::Locale::forLanguageTag(icu::StringPiece(data, length), status);
+  if (U_SUCCESS(status)) {
+    icu::Locale::setDefault(locale, status);
+  }
+  return status;
+}
+
+void icu__Locale__GetDefault(LocaleCallback callback, void* arg) {
+  UErrorCode status = U_ZERO_ERROR;
+  std::string tag = icu::Locale::getDefault().toLanguageTag<std::string>(status);
+  if (U_SUCCESS(status)) {
+    callback(arg, tag.data(), tag.size());
+  }
+}
+
+void icu__Locale__ForEachAvailableLocale(LocaleCallback callback, void* arg) {
+  int32_t count = 0;
+  const icu::Locale* locales = icu::Locale::getAvailableLocales(count);
+  for (int32_t i = 0; i < count; i++) {
+    UErrorCode status = U_ZERO_ERROR;
+    std::string tag = locales[i].toLanguageTag<std::string>(status);
+    if (U_SUCCESS(status)) {
+      callback(arg, tag.data(), tag.size());
+    }
+  }
+}
+
+void v8__V8__ForEachJsFeature(FeatureCallback callback, void* arg) {
+  namespace i = v8::internal;
+#define V(flag, description)                                    \
+  callback(arg, #flag, description, FeatureStatus::kInProgress, \
+           i::FLAG_##flag);
+  HARMONY_INPROGRESS(V)
+#undef V
+#define V(flag, description) \
+  callback(arg, #flag, description, FeatureStatus::kStaged, i::FLAG_##flag);
+  HARMONY_STAGED(V)
+#undef V
+#define V(flag, description) \
+  callback(arg, #flag, description, FeatureStatus::kShipped, i::FLAG_##flag);
+  HARMONY_SHIPPING(V)
+#undef V
+}
+
+void v8__V8__ForEachWasmFeature(FeatureCallback callback, void* arg) {
+  namespace i = v8::internal;
+#define V(feature, description, default_value)                     \
+  callback(arg, "experimental_wasm_" #feature, description, STATUS, \
+           i::FLAG_experimental_wasm_##feature);
+#define STATUS FeatureStatus::kInProgress
+  FOREACH_WASM_EXPERIMENTAL_FEATURE_FLAG(V)
+#undef STATUS
+#define STATUS FeatureStatus::kStaged
+  FOREACH_WASM_STAGING_FEATURE_FLAG(V)
+#undef STATUS
+#define STATUS FeatureStatus::kShipped
+  FOREACH_WASM_SHIPPED_FEATURE_FLAG(V)
+#undef STATUS
+#undef V
+}
+
+bool v8__V8__IsWasmExposed() { return v8::internal::FLAG_expose_wasm; }
+
+bool v8__V8__IsAsmValidationEnabled() {
+  return v8::internal::FLAG_validate_asm;
+}
+
+bool v8__V8__HasMemoryProtectionKeySupport() {
+#if V8_ENABLE_WEBASSEMBLY
+  return v8::internal::wasm::GetWasmCodeManager()
+      ->HasMemoryProtectionKeySupport();
+#else
+  return false;
+#endif
+}
+
+bool v8__V8__IsMemoryProtectionKeysEnabled() {
+#if V8_ENABLE_WEBASSEMBLY
+  return v8::internal::wasm::GetWasmCodeManager()
+      ->MemoryProtectionKeysEnabled();
+#else
+  return false;
+#endif
+}
+
 void v8__V8__InitializePlatform(v8::Platform* platform) {
   v8::V8::InitializePlatform(platform);
 }
@@ -163,6 +319,10 @@ void v8__Isolate__Enter(v8::Isolate* isolate) { isolate->Enter(); }
 
 void v8__Isolate__Exit(v8::Isolate* isolate) { isolate->Exit(); }
 
+v8::Isolate* v8__Isolate__TryGetCurrent() {
+  return v8::Isolate::TryGetCurrent();
+}
+
 void v8__Isolate__ClearKeptObjects(v8::Isolate* isolate) {
   isolate->ClearKeptObjects();
 }
@@ -171,11 +331,128 @@ void v8__Isolate__LowMemoryNotification(v8::Isolate* isolate) {
   isolate->LowMemoryNotification();
 }
 
+static_assert(static_cast<int>(v8::MemoryPressureLevel::kNone) == 0,
+              "MemoryPressureLevel::kNone mismatch");
+static_assert(static_cast<int>(v8::MemoryPressureLevel::kModerate) == 1,
+              "MemoryPressureLevel::kModerate mismatch");
+static_assert(static_cast<int>(v8::MemoryPressureLevel::kCritical) == 2,
+              "MemoryPressureLevel::kCritical mismatch");
+
+void v8__Isolate__MemoryPressureNotification(v8::Isolate* isolate,
+                                             v8::MemoryPressureLevel level) {
+  isolate->MemoryPressureNotification(level);
+}
+
+static_assert(static_cast<int>(v8::MeasureMemoryExecution::kDefault) == 0,
+              "MeasureMemoryExecution::kDefault mismatch");
+static_assert(static_cast<int>(v8::MeasureMemoryExecution::kEager) == 1,
+              "MeasureMemoryExecution::kEager mismatch");
+static_assert(static_cast<int>(v8::MeasureMemoryExecution::kLazy) == 2,
+              "MeasureMemoryExecution::kLazy mismatch");
+
+bool v8__MeasureMemoryDelegate__BASE__ShouldMeasure(void* self,
+                                                     const v8::Context* context);
+void v8__MeasureMemoryDelegate__BASE__MeasurementComplete(
+    void* self, v8::Isolate* isolate, const v8::Context* const* contexts,
+    const size_t* sizes, size_t len, size_t unattributed_size_in_bytes);
+void v8__MeasureMemoryDelegate__BASE__DROP(void* self);
+
+// Forwards to a `Box<dyn MeasureMemoryDelegate>`, which is dropped together
+// with this delegate.
+class RustMeasureMemoryDelegate : public v8::MeasureMemoryDelegate {
+ public:
+  RustMeasureMemoryDelegate(v8::Isolate* isolate, void* handle)
+      : isolate_(isolate), handle_(handle) {}
+
+  RustMeasureMemoryDelegate(const RustMeasureMemoryDelegate& that) = delete;
+  void operator=(const RustMeasureMemoryDelegate& that) = delete;
+
+  ~RustMeasureMemoryDelegate() override {
+    v8__MeasureMemoryDelegate__BASE__DROP(handle_);
+  }
+
+  bool ShouldMeasure(v8::Local<v8::Context> context) override {
+    return v8__MeasureMemoryDelegate__BASE__ShouldMeasure(
+        handle_, local_to_ptr(context));
+  }
+
+  void MeasurementComplete(
+      const std::vector<std::pair<v8::Local<v8::Context>, size_t>>&
+          context_sizes_in_bytes,
+      size_t unattributed_size_in_bytes) override {
+    std::vector<const v8::Context*> contexts;
+    std::vector<size_t> sizes;
+    for (const auto& entry : context_sizes_in_bytes) {
+      contexts.push_back(local_to_ptr(entry.first));
+      sizes.push_back(entry.second);
+    }
+    v8__MeasureMemoryDelegate__BASE__MeasurementComplete(
+        handle_, isolate_, contexts.data(), sizes.data(), contexts.size(),
+        unattributed_size_in_bytes);
+  }
+
+ private:
+  v8::Isolate* isolate_;
+  void* handle_;
+};
+
+bool v8__Isolate__MeasureMemory(v8::Isolate* isolate, void* delegate,
+                                v8::MeasureMemoryExecution execution) {
+  return isolate->MeasureMemory(
+      std::make_unique<RustMeasureMemoryDelegate>(isolate, delegate),
+      execution);
+}
+
+void v8__Isolate__LocaleConfigurationChangeNotification(v8::Isolate* isolate) {
+  isolate->LocaleConfigurationChangeNotification();
+}
+
+int v8__Isolate__SetDefaultLocale(v8::Isolate* isolate, const char* data,
+                                  size_t length) {
+  namespace i = v8::internal;
+  UErrorCode status = U_ZERO_ERROR;
+  icu::Locale locale =
+      icu::Locale::forLanguageTag(icu::StringPiece(data, length), status);
+  if (U_FAILURE(status)) {
+    return status;
+  }
+  std::string tag = locale.toLanguageTag<std::string>(status);
+  if (U_FAILURE(status)) {
+    return status;
+  }
+  i::Isolate* i_isolate = reinterpret_cast<i::Isolate*>(isolate);
+  i_isolate->ResetDefaultLocale();
+  i_isolate->set_default_locale(tag);
+  // Cached formatters and collators were created for the old locale.
+  i_isolate->clear_cached_icu_objects();
+  return status;
+}
+
+bool v8__Isolate__HasPendingException(v8::Isolate* isolate) {
+  namespace i = v8::internal;
+  i::Isolate* i_isolate = reinterpret_cast<i::Isolate*>(isolate);
+  // Exceptions thrown from API callbacks are scheduled, and only become
+  // pending once the callback returns to JavaScript.
+  return i_isolate->has_pending_exception() ||
+         i_isolate->has_scheduled_exception();
+}
+
+size_t v8__Isolate__GetHeapLimit(v8::Isolate* isolate) {
+  namespace i = v8::internal;
+  i::Isolate* i_isolate = reinterpret_cast<i::Isolate*>(isolate);
+  return i_isolate->heap()->max_old_generation_size();
+}
+
 void v8__Isolate__GetHeapStatistics(v8::Isolate* isolate,
                                     v8::HeapStatistics* s) {
   isolate->GetHeapStatistics(s);
 }
 
+void v8__Isolate__GetCodeRange(v8::Isolate* isolate, void** start,
+                               size_t* length_in_bytes) {
+  isolate->GetCodeRange(start, length_in_bytes);
+}
+
 const v8::Context* v8__Isolate__GetCurrentContext(v8::Isolate* isolate) {
   return local_to_ptr(isolate->GetCurrentContext());
 }
@@ -185,6 +462,14 @@ const v8::Context* v8__Isolate__GetEnteredOrMicrotaskContext(
   return local_to_ptr(isolate->GetEnteredOrMicrotaskContext());
 }
 
+const v8::Context* v8__Isolate__GetIncumbentContext(v8::Isolate* isolate) {
+  return local_to_ptr(isolate->GetIncumbentContext());
+}
+
+bool v8__Isolate__InContext(v8::Isolate* isolate) {
+  return isolate->InContext();
+}
+
 void v8__Isolate__SetData(v8::Isolate* isolate, uint32_t slot, void* data) {
   isolate->SetData(slot, data);
 }
@@ -227,11 +512,93 @@ void v8__Isolate__EnqueueMicrotask(v8::Isolate* isolate,
   isolate->EnqueueMicrotask(ptr_to_local(&function));
 }
 
+void v8__Isolate__EnqueueMicrotaskCallback(v8::Isolate* isolate,
+                                           v8::MicrotaskCallback callback,
+                                           void* data) {
+  isolate->EnqueueMicrotask(callback, data);
+}
+
+void v8__Isolate__AddMicrotasksCompletedCallback(
+    v8::Isolate* isolate, v8::MicrotasksCompletedCallbackWithData callback,
+    void* data) {
+  isolate->AddMicrotasksCompletedCallback(callback, data);
+}
+
+void v8__Isolate__RemoveMicrotasksCompletedCallback(
+    v8::Isolate* isolate, v8::MicrotasksCompletedCallbackWithData callback,
+    void* data) {
+  isolate->RemoveMicrotasksCompletedCallback(callback, data);
+}
+
+void v8__Isolate__AddGCPrologueCallback(
+    v8::Isolate* isolate, v8::Isolate::GCCallbackWithData callback, void* data,
+    v8::GCType gc_type_filter) {
+  isolate->AddGCPrologueCallback(callback, data, gc_type_filter);
+}
+
+void v8__Isolate__RemoveGCPrologueCallback(
+    v8::Isolate* isolate, v8::Isolate::GCCallbackWithData callback,
+    void* data) {
+  isolate->RemoveGCPrologueCallback(callback, data);
+}
+
+void v8__Isolate__AddGCEpilogueCallback(
+    v8::Isolate* isolate, v8::Isolate::GCCallbackWithData callback, void* data,
+    v8::GCType gc_type_filter) {
+  isolate->AddGCEpilogueCallback(callback, data, gc_type_filter);
+}
+
+void v8__Isolate__RemoveGCEpilogueCallback(
+    v8::Isolate* isolate, v8::Isolate::GCCallbackWithData callback,
+    void* data) {
+  isolate->RemoveGCEpilogueCallback(callback, data);
+}
+
+bool v8__MicrotasksScope__IsRunningMicrotasks(v8::Isolate* isolate) {
+  return v8::MicrotasksScope::IsRunningMicrotasks(isolate);
+}
+
+int v8__MicrotasksScope__GetCurrentDepth(v8::Isolate* isolate) {
+  return v8::MicrotasksScope::GetCurrentDepth(isolate);
+}
+
 void v8__Isolate__RequestInterrupt(v8::Isolate* isolate,
                                    v8::InterruptCallback callback, void* data) {
   isolate->RequestInterrupt(callback, data);
 }
 
+static_assert(static_cast<int>(v8::debug::EvaluateGlobalMode::kDefault) == 0,
+              "EvaluateGlobalMode::kDefault mismatch");
+static_assert(static_cast<int>(v8::debug::EvaluateGlobalMode::
+                                   kDisableBreaksAndThrowOnSideEffect) == 2,
+              "EvaluateGlobalMode::kDisableBreaksAndThrowOnSideEffect mismatch");
+
+const v8::Value* v8__debug__EvaluateGlobal(v8::Isolate* isolate,
+                                           const v8::String& source,
+                                           v8::debug::EvaluateGlobalMode mode,
+                                           bool repl_mode) {
+  return maybe_local_to_ptr(v8::debug::EvaluateGlobal(
+      isolate, ptr_to_local(&source), mode, repl_mode));
+}
+
+using ExternalStringVisitorCallback = void (*)(void*, const v8::String*);
+
+void v8__Isolate__VisitExternalResources(
+    v8::Isolate* isolate, ExternalStringVisitorCallback callback, void* arg) {
+  struct Visitor : public v8::ExternalResourceVisitor {
+    Visitor(ExternalStringVisitorCallback callback, void* arg)
+        : callback_(callback), arg_(arg) {}
+    void VisitExternalString(v8::Local<v8::String> string) override {
+      callback_(arg_, local_to_ptr(string));
+    }
+    ExternalStringVisitorCallback const callback_;
+    void* const arg_;
+  };
+
+  Visitor visitor(callback, arg);
+  isolate->VisitExternalResources(&visitor);
+}
+
 void v8__Isolate__SetPrepareStackTraceCallback(
     v8::Isolate* isolate, v8::PrepareStackTraceCallback callback) {
   isolate->SetPrepareStackTraceCallback(callback);
@@ -296,6 +663,33 @@ int64_t v8__Isolate__AdjustAmountOfExternalAllocatedMemory(
   return isolate->AdjustAmountOfExternalAllocatedMemory(change_in_bytes);
 }
 
+v8::Isolate::SafeForTerminationScope*
+v8__Isolate__SafeForTerminationScope__NEW(v8::Isolate* isolate) {
+  return new v8::Isolate::SafeForTerminationScope(isolate);
+}
+
+void v8__Isolate__SafeForTerminationScope__DELETE(
+    v8::Isolate::SafeForTerminationScope* self) {
+  delete self;
+}
+
+void v8__Isolate__SetEventLogger(v8::Isolate* isolate,
+                                 v8::LogEventCallback callback) {
+  isolate->SetEventLogger(callback);
+}
+
+void v8__Isolate__SetJitCodeEventHandler(v8::Isolate* isolate,
+                                         v8::JitCodeEventOptions options,
+                                         v8::JitCodeEventHandler handler) {
+  isolate->SetJitCodeEventHandler(options, handler);
+}
+
+void v8__Isolate__SetAbortOnUncaughtExceptionCallback(
+    v8::Isolate* isolate,
+    v8::Isolate::AbortOnUncaughtExceptionCallback callback) {
+  isolate->SetAbortOnUncaughtExceptionCallback(callback);
+}
+
 void v8__Isolate__SetOOMErrorHandler(v8::Isolate* isolate,
                                      v8::OOMErrorCallback callback) {
   isolate->SetOOMErrorHandler(callback);
@@ -354,6 +748,23 @@ void v8__HandleScope__CONSTRUCT(uninit_t<v8::HandleScope>* buf,
 
 void v8__HandleScope__DESTRUCT(v8::HandleScope* self) { self->~HandleScope(); }
 
+void v8__Locker__CONSTRUCT(uninit_t<v8::Locker>* buf, v8::Isolate* isolate) {
+  construct_in_place<v8::Locker>(buf, isolate);
+}
+
+void v8__Locker__DESTRUCT(v8::Locker* self) { self->~Locker(); }
+
+bool v8__Locker__IsLocked(v8::Isolate* isolate) {
+  return v8::Locker::IsLocked(isolate);
+}
+
+void v8__Unlocker__CONSTRUCT(uninit_t<v8::Unlocker>* buf,
+                             v8::Isolate* isolate) {
+  construct_in_place<v8::Unlocker>(buf, isolate);
+}
+
+void v8__Unlocker__DESTRUCT(v8::Unlocker* self) { self->~Unlocker(); }
+
 const v8::Data* v8__Local__New(v8::Isolate* isolate, const v8::Data& other) {
   return local_to_ptr(v8::Local<v8::Data>::New(isolate, ptr_to_local(&other)));
 }
@@ -397,13 +808,15 @@ void v8__WeakCallbackInfo__SetSecondPassCallback(
 void v8__ScriptCompiler__Source__CONSTRUCT(
     uninit_t<v8::ScriptCompiler::Source>* buf, const v8::String& source_string,
     const v8::ScriptOrigin* origin,
-    v8::ScriptCompiler::CachedData* cached_data) {
+    v8::ScriptCompiler::CachedData* cached_data,
+    v8::ScriptCompiler::ConsumeCodeCacheTask* consume_cache_task) {
   if (origin) {
     construct_in_place<v8::ScriptCompiler::Source>(
-        buf, ptr_to_local(&source_string), *origin, cached_data);
+        buf, ptr_to_local(&source_string), *origin, cached_data,
+        consume_cache_task);
   } else {
     construct_in_place<v8::ScriptCompiler::Source>(
-        buf, ptr_to_local(&source_string), cached_data);
+        buf, ptr_to_local(&source_string), cached_data, consume_cache_task);
   }
 }
 
@@ -422,6 +835,25 @@ void v8__ScriptCompiler__CachedData__DELETE(
   delete self;
 }
 
+v8::ScriptCompiler::ConsumeCodeCacheTask*
+v8__ScriptCompiler__StartConsumingCodeCache(
+    v8::Isolate* isolate, v8::ScriptCompiler::CachedData* cached_data) {
+  return v8::ScriptCompiler::StartConsumingCodeCache(
+             isolate,
+             std::unique_ptr<v8::ScriptCompiler::CachedData>(cached_data))
+      .release();
+}
+
+void v8__ScriptCompiler__ConsumeCodeCacheTask__Run(
+    v8::ScriptCompiler::ConsumeCodeCacheTask* self) {
+  self->Run();
+}
+
+void v8__ScriptCompiler__ConsumeCodeCacheTask__DELETE(
+    v8::ScriptCompiler::ConsumeCodeCacheTask* self) {
+  delete self;
+}
+
 const v8::ScriptCompiler::CachedData* v8__ScriptCompiler__Source__GetCachedData(
     const v8::ScriptCompiler::Source* source) {
   return source->GetCachedData();
@@ -703,6 +1135,17 @@ bool v8__Value__IsWasmMemoryObject(const v8::Value& self) {
   return self.IsWasmMemoryObject();
 }
 
+bool v8__Value__IsWasmFunction(const v8::Value& self) {
+  namespace i = v8::internal;
+  return i::WasmExternalFunction::IsWasmExternalFunction(
+      *v8::Utils::OpenHandle(&self));
+}
+
+bool v8__Value__IsWasmObject(const v8::Value& self) {
+  auto object = v8::Utils::OpenHandle(&self);
+  return object->IsWasmStruct() || object->IsWasmArray();
+}
+
 bool v8__Value__IsModuleNamespaceObject(const v8::Value& self) {
   return self.IsModuleNamespaceObject();
 }
@@ -720,6 +1163,11 @@ const v8::Uint32* v8__Value__ToUint32(const v8::Value& self,
   return maybe_local_to_ptr(self.ToUint32(ptr_to_local(&context)));
 }
 
+const v8::Uint32* v8__Value__ToArrayIndex(const v8::Value& self,
+                                          const v8::Context& context) {
+  return maybe_local_to_ptr(self.ToArrayIndex(ptr_to_local(&context)));
+}
+
 const v8::Int32* v8__Value__ToInt32(const v8::Value& self,
                                     const v8::Context& context) {
   return maybe_local_to_ptr(self.ToInt32(ptr_to_local(&context)));
@@ -845,6 +1293,51 @@ v8::BackingStore* v8__ArrayBuffer__NewBackingStore__with_data(
   return u.release();
 }
 
+// The deleter data of backing stores allocated by try_allocate_backing_store.
+// It keeps the allocator alive, because the backing store may outlive the
+// isolate (e.g. when it is shared with another isolate).
+static void array_buffer_allocator_deleter(void* data, size_t byte_length,
+                                           void* deleter_data) {
+  auto allocator =
+      static_cast<std::shared_ptr<v8::ArrayBuffer::Allocator>*>(deleter_data);
+  (*allocator)->Free(data, byte_length);
+  delete allocator;
+}
+
+// V8 crashes when it fails to allocate a backing store, so the memory is
+// allocated with the isolate's array buffer allocator here instead, and
+// nullptr is returned if that fails. Unlike the memory of backing stores
+// created by V8, it is not reported to the isolate as external memory.
+static v8::BackingStore* try_allocate_backing_store(
+    v8::Isolate* isolate, size_t byte_length,
+    std::unique_ptr<v8::BackingStore> (*new_backing_store)(
+        void*, size_t, v8::BackingStore::DeleterCallback, void*)) {
+  if (byte_length > v8::ArrayBuffer::kMaxByteLength) {
+    return nullptr;
+  }
+  std::shared_ptr<v8::ArrayBuffer::Allocator> allocator =
+      reinterpret_cast<v8::internal::Isolate*>(isolate)
+          ->array_buffer_allocator_shared();
+  assert(allocator != nullptr);
+  void* data = allocator->Allocate(byte_length);
+  if (data == nullptr) {
+    return nullptr;
+  }
+  return new_backing_store(
+             data, byte_length, array_buffer_allocator_deleter,
+             new std::shared_ptr<v8::ArrayBuffer::Allocator>(allocator))
+      .release();
+}
+
+v8::BackingStore* v8__ArrayBuffer__TryNewBackingStore(v8::Isolate* isolate,
+                                                      size_t byte_length) {
+  if (byte_length == 0) {
+    return v8::ArrayBuffer::NewBackingStore(isolate, 0).release();
+  }
+  return try_allocate_backing_store(isolate, byte_length,
+                                    v8::ArrayBuffer::NewBackingStore);
+}
+
 two_pointers_t v8__ArrayBuffer__GetBackingStore(const v8::ArrayBuffer& self) {
   return make_pod<two_pointers_t>(ptr_to_local(&self)->GetBackingStore());
 }
@@ -869,6 +1362,15 @@ bool v8__BackingStore__IsShared(const v8::BackingStore& self) {
   return self.IsShared();
 }
 
+v8::BackingStore* v8__BackingStore__Reallocate(v8::Isolate* isolate,
+                                              v8::BackingStore* backing_store,
+                                              size_t byte_length) {
+  return v8::BackingStore::Reallocate(
+             isolate, std::unique_ptr<v8::BackingStore>(backing_store),
+             byte_length)
+      .release();
+}
+
 void v8__BackingStore__DELETE(v8::BackingStore* self) { delete self; }
 
 two_pointers_t std__shared_ptr__v8__BackingStore__COPY(
@@ -1001,6 +1503,36 @@ const v8::String* v8__String__NewExternalOneByteStatic(v8::Isolate* isolate,
       isolate, new ExternalStaticOneByteStringResource(data, length)));
 }
 
+class ExternalRustOneByteStringResource
+    : public v8::String::ExternalOneByteStringResource {
+ public:
+  typedef void (*Deleter)(char* data, size_t length);
+
+  ExternalRustOneByteStringResource(char* data, size_t length,
+                                    Deleter deleter)
+      : _data(data), _length(length), _deleter(deleter) {}
+  ~ExternalRustOneByteStringResource() override { _deleter(_data, _length); }
+  const char* data() const override { return _data; }
+  size_t length() const override { return _length; }
+
+ private:
+  char* const _data;
+  const size_t _length;
+  const Deleter _deleter;
+};
+
+const v8::String* v8__String__NewExternalOneByte(
+    v8::Isolate* isolate, char* data, int length,
+    ExternalRustOneByteStringResource::Deleter deleter) {
+  auto resource = new ExternalRustOneByteStringResource(data, length, deleter);
+  auto maybe_str = v8::String::NewExternalOneByte(isolate, resource);
+  if (maybe_str.IsEmpty()) {
+    // The resource (and thereby the buffer) is only owned by V8 on success.
+    delete resource;
+  }
+  return maybe_local_to_ptr(maybe_str);
+}
+
 class ExternalStaticStringResource : public v8::String::ExternalStringResource {
  public:
   ExternalStaticStringResource(const uint16_t* data, int length)
@@ -1020,6 +1552,45 @@ const v8::String* v8__String__NewExternalTwoByteStatic(v8::Isolate* isolate,
       isolate, new ExternalStaticStringResource(data, length)));
 }
 
+class ExternalRustStringResource : public v8::String::ExternalStringResource {
+ public:
+  typedef void (*Deleter)(uint16_t* data, size_t length);
+
+  ExternalRustStringResource(uint16_t* data, size_t length, Deleter deleter)
+      : _data(data), _length(length), _deleter(deleter) {}
+  ~ExternalRustStringResource() override { _deleter(_data, _length); }
+  const uint16_t* data() const override { return _data; }
+  size_t length() const override { return _length; }
+
+ private:
+  uint16_t* const _data;
+  const size_t _length;
+  const Deleter _deleter;
+};
+
+bool v8__String__MakeExternalOneByte(
+    const v8::String& self, char* data, size_t length,
+    ExternalRustOneByteStringResource::Deleter deleter) {
+  auto resource = new ExternalRustOneByteStringResource(data, length, deleter);
+  if (ptr_to_local(&self)->MakeExternal(resource)) {
+    return true;
+  }
+  // The resource (and thereby the buffer) is only owned by V8 on success.
+  delete resource;
+  return false;
+}
+
+bool v8__String__MakeExternalTwoByte(
+    const v8::String& self, uint16_t* data, size_t length,
+    ExternalRustStringResource::Deleter deleter) {
+  auto resource = new ExternalRustStringResource(data, length, deleter);
+  if (ptr_to_local(&self)->MakeExternal(resource)) {
+    return true;
+  }
+  delete resource;
+  return false;
+}
+
 bool v8__String__IsExternal(const v8::String& self) {
   return self.IsExternal();
 }
@@ -1275,6 +1846,87 @@ MaybeBool v8__Object__HasOwnProperty(const v8::Object& self,
       ptr_to_local(&context), ptr_to_local(&key)));
 }
 
+const v8::Value* v8__Object__GetOwnPropertyDescriptor(
+    const v8::Object& self, const v8::Context& context, const v8::Name& key) {
+  return maybe_local_to_ptr(ptr_to_local(&self)->GetOwnPropertyDescriptor(
+      ptr_to_local(&context), ptr_to_local(&key)));
+}
+
+MaybeBool v8__Object__HasRealNamedProperty(const v8::Object& self,
+                                           const v8::Context& context,
+                                           const v8::Name& key) {
+  return maybe_to_maybe_bool(ptr_to_local(&self)->HasRealNamedProperty(
+      ptr_to_local(&context), ptr_to_local(&key)));
+}
+
+MaybeBool v8__Object__HasRealIndexedProperty(const v8::Object& self,
+                                             const v8::Context& context,
+                                             uint32_t index) {
+  return maybe_to_maybe_bool(ptr_to_local(&self)->HasRealIndexedProperty(
+      ptr_to_local(&context), index));
+}
+
+MaybeBool v8__Object__HasRealNamedCallbackProperty(const v8::Object& self,
+                                                   const v8::Context& context,
+                                                   const v8::Name& key) {
+  return maybe_to_maybe_bool(ptr_to_local(&self)->HasRealNamedCallbackProperty(
+      ptr_to_local(&context), ptr_to_local(&key)));
+}
+
+const v8::Value* v8__Object__GetRealNamedProperty(const v8::Object& self,
+                                                  const v8::Context& context,
+                                                  const v8::Name& key) {
+  return maybe_local_to_ptr(ptr_to_local(&self)->GetRealNamedProperty(
+      ptr_to_local(&context), ptr_to_local(&key)));
+}
+
+const v8::Value* v8__Object__GetRealNamedPropertyInPrototypeChain(
+    const v8::Object& self, const v8::Context& context, const v8::Name& key) {
+  return maybe_local_to_ptr(
+      ptr_to_local(&self)->GetRealNamedPropertyInPrototypeChain(
+          ptr_to_local(&context), ptr_to_local(&key)));
+}
+
+bool v8__Object__GetRealNamedPropertyAttributes(const v8::Object& self,
+                                                const v8::Context& context,
+                                                const v8::Name& key,
+                                                v8::PropertyAttribute* out) {
+  return ptr_to_local(&self)
+      ->GetRealNamedPropertyAttributes(ptr_to_local(&context),
+                                       ptr_to_local(&key))
+      .To(out);
+}
+
+// Like v8::Object::DefineOwnProperty(), but the property is looked up without
+// invoking the interceptors of the object, so that it can be used to
+// implement the default behavior from inside an interceptor callback.
+MaybeBool v8__Object__DefineRealOwnProperty(const v8::Object& self,
+                                            const v8::Context& context,
+                                            const v8::Name& key,
+                                            const v8::Value& value,
+                                            v8::PropertyAttribute attr) {
+  namespace i = v8::internal;
+  auto self_obj = v8::Utils::OpenHandle(&self);
+  // Only API objects and global objects can have interceptors. Other objects
+  // may be exotic (e.g. arrays and typed arrays), and must go through their
+  // own [[DefineOwnProperty]].
+  if (!self_obj->IsJSApiObject() && !self_obj->IsJSSpecialApiObject() &&
+      !self_obj->IsJSGlobalObject() && !self_obj->IsJSGlobalProxy()) {
+    return v8__Object__DefineOwnProperty(self, context, key, value, attr);
+  }
+  i::Isolate* i_isolate = reinterpret_cast<i::Isolate*>(context.GetIsolate());
+  i::PropertyDescriptor desc;
+  desc.set_writable(!(attr & v8::ReadOnly));
+  desc.set_enumerable(!(attr & v8::DontEnum));
+  desc.set_configurable(!(attr & v8::DontDelete));
+  desc.set_value(v8::Utils::OpenHandle(&value));
+  i::PropertyKey lookup_key(i_isolate, v8::Utils::OpenHandle(&key));
+  i::LookupIterator it(i_isolate, i::Handle<i::JSObject>::cast(self_obj),
+                       lookup_key, i::LookupIterator::OWN_SKIP_INTERCEPTOR);
+  return maybe_to_maybe_bool(i::JSReceiver::OrdinaryDefineOwnProperty(
+      &it, &desc, v8::Just(i::kDontThrow)));
+}
+
 MaybeBool v8__Object__Delete(const v8::Object& self, const v8::Context& context,
                              const v8::Value& key) {
   return maybe_to_maybe_bool(
@@ -1291,6 +1943,10 @@ int v8__Object__InternalFieldCount(const v8::Object& self) {
   return ptr_to_local(&self)->InternalFieldCount();
 }
 
+bool v8__Object__IsApiWrapper(const v8::Object& self) {
+  return ptr_to_local(&self)->IsApiWrapper();
+}
+
 const v8::Value* v8__Object__GetInternalField(const v8::Object& self,
                                               int index) {
   return local_to_ptr(ptr_to_local(&self)->GetInternalField(index));
@@ -1581,12 +2237,14 @@ void DeserializeInternalFields(v8::Local<v8::Object> holder, int index,
 
 const v8::Context* v8__Context__New(v8::Isolate* isolate,
                                     const v8::ObjectTemplate* templ,
-                                    const v8::Value* global_object) {
+                                    const v8::Value* global_object,
+                                    v8::MicrotaskQueue* microtask_queue) {
   return local_to_ptr(
       v8::Context::New(isolate, nullptr, ptr_to_maybe_local(templ),
                        ptr_to_maybe_local(global_object),
                        v8::DeserializeInternalFieldsCallback(
-                           DeserializeInternalFields, nullptr)));
+                           DeserializeInternalFields, nullptr),
+                       microtask_queue));
 }
 
 bool v8__Context__EQ(const v8::Context& self, const v8::Context& other) {
@@ -1607,6 +2265,65 @@ const v8::Object* v8__Context__Global(const v8::Context& self) {
   return local_to_ptr(ptr_to_local(&self)->Global());
 }
 
+void v8__Context__DetachGlobal(const v8::Context& self) {
+  ptr_to_local(&self)->DetachGlobal();
+}
+
+v8::MicrotaskQueue* v8__Context__GetMicrotaskQueue(const v8::Context& self) {
+  return ptr_to_local(&self)->GetMicrotaskQueue();
+}
+
+const v8::Value* v8__Context__GetContinuationPreservedEmbedderData(
+    const v8::Context& self) {
+  return local_to_ptr(
+      ptr_to_local(&self)->GetContinuationPreservedEmbedderData());
+}
+
+void v8__Context__SetContinuationPreservedEmbedderData(
+    const v8::Context& self, const v8::Value& data) {
+  ptr_to_local(&self)->SetContinuationPreservedEmbedderData(
+      ptr_to_local(&data));
+}
+
+v8::MicrotaskQueue* v8__MicrotaskQueue__New(v8::Isolate* isolate,
+                                            v8::MicrotasksPolicy policy) {
+  return v8::MicrotaskQueue::New(isolate, policy).release();
+}
+
+void v8__MicrotaskQueue__DELETE(v8::MicrotaskQueue* self) { delete self; }
+
+void v8__MicrotaskQueue__EnqueueMicrotask(v8::MicrotaskQueue* self,
+                                          v8::Isolate* isolate,
+                                          const v8::Function& microtask) {
+  self->EnqueueMicrotask(isolate, ptr_to_local(&microtask));
+}
+
+void v8__MicrotaskQueue__PerformCheckpoint(v8::MicrotaskQueue* self,
+                                           v8::Isolate* isolate) {
+  self->PerformCheckpoint(isolate);
+}
+
+bool v8__MicrotaskQueue__IsRunningMicrotasks(const v8::MicrotaskQueue& self) {
+  return self.IsRunningMicrotasks();
+}
+
+int v8__MicrotaskQueue__GetMicrotasksScopeDepth(
+    const v8::MicrotaskQueue& self) {
+  return self.GetMicrotasksScopeDepth();
+}
+
+void v8__MicrotaskQueue__AddMicrotasksCompletedCallback(
+    v8::MicrotaskQueue* self, v8::MicrotasksCompletedCallbackWithData callback,
+    void* data) {
+  self->AddMicrotasksCompletedCallback(callback, data);
+}
+
+void v8__MicrotaskQueue__RemoveMicrotasksCompletedCallback(
+    v8::MicrotaskQueue* self, v8::MicrotasksCompletedCallbackWithData callback,
+    void* data) {
+  self->RemoveMicrotasksCompletedCallback(callback, data);
+}
+
 uint32_t v8__Context__GetNumberOfEmbedderDataFields(const v8::Context& self) {
   return ptr_to_local(&self)->GetNumberOfEmbedderDataFields();
 }
@@ -1779,58 +2496,18 @@ const v8::Signature* v8__Signature__New(v8::Isolate* isolate,
   return local_to_ptr(v8::Signature::New(isolate, ptr_to_local(templ)));
 }
 
-v8::CTypeInfo* v8__CTypeInfo__New(v8::CTypeInfo::Type ty) {
-  std::unique_ptr<v8::CTypeInfo> u =
-      std::make_unique<v8::CTypeInfo>(v8::CTypeInfo(ty));
-  return u.release();
-}
-
-struct CTypeSequenceType {
-  v8::CTypeInfo::Type c_type;
-  v8::CTypeInfo::SequenceType sequence_type;
-};
-
-v8::CTypeInfo* v8__CTypeInfo__New__From__Slice(unsigned int len,
-                                               CTypeSequenceType* ty) {
-  v8::CTypeInfo* v = (v8::CTypeInfo*)malloc(sizeof(v8::CTypeInfo) * len);
-  for (size_t i = 0; i < len; i += 1) {
-    v[i] = v8::CTypeInfo(ty[i].c_type, ty[i].sequence_type);
-  }
-  return v;
-}
-
-v8::CFunctionInfo* v8__CFunctionInfo__New(const v8::CTypeInfo& return_info,
-                                          unsigned int args_len,
-                                          v8::CTypeInfo* args_info) {
-  std::unique_ptr<v8::CFunctionInfo> info = std::make_unique<v8::CFunctionInfo>(
-      v8::CFunctionInfo(return_info, args_len, args_info));
-  return info.release();
-}
-
 const v8::FunctionTemplate* v8__FunctionTemplate__New(
     v8::Isolate* isolate, v8::FunctionCallback callback,
     const v8::Value* data_or_null, const v8::Signature* signature_or_null,
     int length, v8::ConstructorBehavior constructor_behavior,
-    v8::SideEffectType side_effect_type, void* func_ptr1,
-    const v8::CFunctionInfo* c_function_info1, void* func_ptr2,
-    const v8::CFunctionInfo* c_function_info2) {
-  auto overload = v8::MemorySpan<const v8::CFunction>{};
-  // Support upto 2 overloads. V8 requires TypedArray to have a
-  // v8::Array overload.
-  if (func_ptr1) {
-    if (func_ptr2 == nullptr) {
-      const v8::CFunction o[] = {v8::CFunction(func_ptr1, c_function_info1)};
-      overload = v8::MemorySpan<const v8::CFunction>{o, 1};
-    } else {
-      const v8::CFunction o[] = {v8::CFunction(func_ptr1, c_function_info1),
-                                 v8::CFunction(func_ptr2, c_function_info2)};
-      overload = v8::MemorySpan<const v8::CFunction>{o, 2};
-    }
-  }
+    v8::SideEffectType side_effect_type, const v8::CFunction* c_functions,
+    size_t c_functions_len) {
+  auto overloads =
+      v8::MemorySpan<const v8::CFunction>{c_functions, c_functions_len};
   return local_to_ptr(v8::FunctionTemplate::NewWithCFunctionOverloads(
       isolate, callback, ptr_to_local(data_or_null),
       ptr_to_local(signature_or_null), length, constructor_behavior,
-      side_effect_type, overload));
+      side_effect_type, overloads));
 }
 
 const v8::Function* v8__FunctionTemplate__GetFunction(
@@ -1839,6 +2516,11 @@ const v8::Function* v8__FunctionTemplate__GetFunction(
       ptr_to_local(&self)->GetFunction(ptr_to_local(&context)));
 }
 
+bool v8__FunctionTemplate__HasInstance(const v8::FunctionTemplate& self,
+                                       const v8::Value& object) {
+  return ptr_to_local(&self)->HasInstance(ptr_to_local(&object));
+}
+
 void v8__FunctionTemplate__SetClassName(const v8::FunctionTemplate& self,
                                         const v8::String& name) {
   ptr_to_local(&self)->SetClassName(ptr_to_local(&name));
@@ -2163,6 +2845,15 @@ v8::BackingStore* v8__SharedArrayBuffer__NewBackingStore__with_data(
   return u.release();
 }
 
+v8::BackingStore* v8__SharedArrayBuffer__TryNewBackingStore(
+    v8::Isolate* isolate, size_t byte_length) {
+  if (byte_length == 0) {
+    return v8::SharedArrayBuffer::NewBackingStore(isolate, 0).release();
+  }
+  return try_allocate_backing_store(isolate, byte_length,
+                                    v8::SharedArrayBuffer::NewBackingStore);
+}
+
 const v8::Value* v8__JSON__Parse(const v8::Context& context,
                                  const v8::String& json_string) {
   return maybe_local_to_ptr(
@@ -2348,6 +3039,139 @@ v8::Platform* v8__Platform__NewDefaultPlatform(int thread_pool_size,
       .release();
 }
 
+typedef void (*v8__Platform__ForegroundTaskPostedCallback)(
+    v8::Isolate* isolate, double delay_in_seconds);
+
+// Forwards tasks to the task runner of the default platform, and notifies the
+// embedder after every task that is posted.
+class NotifyingTaskRunner final : public v8::TaskRunner {
+ public:
+  NotifyingTaskRunner(std::shared_ptr<v8::TaskRunner> runner,
+                      v8::Isolate* isolate,
+                      v8__Platform__ForegroundTaskPostedCallback callback)
+      : runner_(std::move(runner)), isolate_(isolate), callback_(callback) {}
+
+  void PostTask(std::unique_ptr<v8::Task> task) override {
+    runner_->PostTask(std::move(task));
+    callback_(isolate_, 0);
+  }
+
+  void PostNonNestableTask(std::unique_ptr<v8::Task> task) override {
+    runner_->PostNonNestableTask(std::move(task));
+    callback_(isolate_, 0);
+  }
+
+  void PostDelayedTask(std::unique_ptr<v8::Task> task,
+                       double delay_in_seconds) override {
+    runner_->PostDelayedTask(std::move(task), delay_in_seconds);
+    callback_(isolate_, delay_in_seconds);
+  }
+
+  void PostNonNestableDelayedTask(std::unique_ptr<v8::Task> task,
+                                  double delay_in_seconds) override {
+    runner_->PostNonNestableDelayedTask(std::move(task), delay_in_seconds);
+    callback_(isolate_, delay_in_seconds);
+  }
+
+  void PostIdleTask(std::unique_ptr<v8::IdleTask> task) override {
+    runner_->PostIdleTask(std::move(task));
+    callback_(isolate_, 0);
+  }
+
+  bool IdleTasksEnabled() override { return runner_->IdleTasksEnabled(); }
+
+  bool NonNestableTasksEnabled() const override {
+    return runner_->NonNestableTasksEnabled();
+  }
+
+  bool NonNestableDelayedTasksEnabled() const override {
+    return runner_->NonNestableDelayedTasksEnabled();
+  }
+
+ private:
+  std::shared_ptr<v8::TaskRunner> runner_;
+  v8::Isolate* isolate_;
+  v8__Platform__ForegroundTaskPostedCallback callback_;
+};
+
+// A default platform that notifies the embedder whenever a foreground task is
+// posted. Since it is a `DefaultPlatform`, `v8::platform::PumpMessageLoop()`
+// works as usual.
+class NotifyingPlatform final : public v8::platform::DefaultPlatform {
+ public:
+  NotifyingPlatform(int thread_pool_size,
+                    v8::platform::IdleTaskSupport idle_task_support,
+                    v8__Platform__ForegroundTaskPostedCallback callback)
+      : v8::platform::DefaultPlatform(thread_pool_size, idle_task_support),
+        callback_(callback) {}
+
+  std::shared_ptr<v8::TaskRunner> GetForegroundTaskRunner(
+      v8::Isolate* isolate) override {
+    return std::make_shared<NotifyingTaskRunner>(
+        v8::platform::DefaultPlatform::GetForegroundTaskRunner(isolate),
+        isolate, callback_);
+  }
+
+ private:
+  v8__Platform__ForegroundTaskPostedCallback callback_;
+};
+
+v8::Platform* v8__Platform__NewNotifyingPlatform(
+    int thread_pool_size, bool idle_task_support,
+    v8__Platform__ForegroundTaskPostedCallback callback) {
+  if (thread_pool_size < 1) {
+    // Mirrors the default that `v8::platform::NewDefaultPlatform()` uses.
+    int processors = static_cast<int>(std::thread::hardware_concurrency());
+    thread_pool_size = std::max(std::min(processors - 1, 16), 1);
+  }
+  return new NotifyingPlatform(
+      thread_pool_size,
+      idle_task_support ? v8::platform::IdleTaskSupport::kEnabled
+                        : v8::platform::IdleTaskSupport::kDisabled,
+      callback);
+}
+
+typedef double v8__Platform__ClockCallback();
+
+// A `DefaultPlatform` whose clocks are provided by the embedder, e.g. to make
+// `Date.now()` and timers deterministic.
+class ClockPlatform final : public v8::platform::DefaultPlatform {
+ public:
+  ClockPlatform(int thread_pool_size,
+                v8::platform::IdleTaskSupport idle_task_support,
+                v8__Platform__ClockCallback* monotonic_time,
+                v8__Platform__ClockCallback* current_clock_time_millis)
+      : v8::platform::DefaultPlatform(thread_pool_size, idle_task_support),
+        monotonic_time_(monotonic_time),
+        current_clock_time_millis_(current_clock_time_millis) {}
+
+  double MonotonicallyIncreasingTime() override { return monotonic_time_(); }
+
+  double CurrentClockTimeMillis() override {
+    return current_clock_time_millis_();
+  }
+
+ private:
+  v8__Platform__ClockCallback* monotonic_time_;
+  v8__Platform__ClockCallback* current_clock_time_millis_;
+};
+
+v8::Platform* v8__Platform__NewClockPlatform(
+    int thread_pool_size, bool idle_task_support,
+    v8__Platform__ClockCallback* monotonic_time,
+    v8__Platform__ClockCallback* current_clock_time_millis) {
+  if (thread_pool_size < 1) {
+    // Mirrors the default that `v8::platform::NewDefaultPlatform()` uses.
+    int processors = static_cast<int>(std::thread::hardware_concurrency());
+    thread_pool_size = std::max(std::min(processors - 1, 16), 1);
+  }
+  return new ClockPlatform(
+      thread_pool_size,
+      idle_task_support ? v8::platform::IdleTaskSupport::kEnabled
+                        : v8::platform::IdleTaskSupport::kDisabled,
+      monotonic_time, current_clock_time_millis);
+}
+
 v8::Platform* v8__Platform__NewSingleThreadedDefaultPlatform(
     bool idle_task_support) {
   return v8::platform::NewSingleThreadedDefaultPlatform(
@@ -2427,9 +3251,36 @@ v8_inspector::V8InspectorSession* v8_inspector__V8Inspector__connect(
 
 void v8_inspector__V8Inspector__contextCreated(
     v8_inspector::V8Inspector* self, const v8::Context& context,
-    int contextGroupId, v8_inspector::StringView humanReadableName) {
-  self->contextCreated(v8_inspector::V8ContextInfo(
-      ptr_to_local(&context), contextGroupId, humanReadableName));
+    int contextGroupId, v8_inspector::StringView humanReadableName,
+    v8_inspector::StringView origin, v8_inspector::StringView auxData,
+    bool hasMemoryOnConsole) {
+  v8_inspector::V8ContextInfo info(ptr_to_local(&context), contextGroupId,
+                                   humanReadableName);
+  info.origin = origin;
+  info.auxData = auxData;
+  info.hasMemoryOnConsole = hasMemoryOnConsole;
+  self->contextCreated(info);
+}
+
+void v8_inspector__V8Inspector__contextDestroyed(
+    v8_inspector::V8Inspector* self, const v8::Context& context) {
+  self->contextDestroyed(ptr_to_local(&context));
+}
+
+void v8_inspector__V8Inspector__resetContextGroup(
+    v8_inspector::V8Inspector* self, int contextGroupId) {
+  self->resetContextGroup(contextGroupId);
+}
+
+const v8::Context* v8_inspector__V8Inspector__contextById(
+    v8_inspector::V8Inspector* self, int contextId) {
+  return maybe_local_to_ptr(self->contextById(contextId));
+}
+
+int v8_inspector__V8ContextInfo__executionContextId(
+    const v8::Context& context) {
+  return v8_inspector::V8ContextInfo::executionContextId(
+      ptr_to_local(&context));
 }
 
 bool v8_inspector__V8InspectorSession__canDispatchMethod(
@@ -2544,6 +3395,22 @@ struct v8_inspector__V8InspectorClient__BASE
   }
 };
 
+// A StringBuffer that borrows the contents of a Rust-owned buffer, instead of
+// copying them like StringBuffer::create() does.
+class RustStringBuffer : public v8_inspector::StringBuffer {
+ public:
+  RustStringBuffer(v8_inspector::StringView view, void* owner,
+                   void (*drop_owner)(void*))
+      : view_(view), owner_(owner), drop_owner_(drop_owner) {}
+  ~RustStringBuffer() override { drop_owner_(owner_); }
+  v8_inspector::StringView string() const override { return view_; }
+
+ private:
+  v8_inspector::StringView const view_;
+  void* const owner_;
+  void (*const drop_owner_)(void*);
+};
+
 extern "C" {
 void v8_inspector__V8InspectorClient__BASE__CONSTRUCT(
     uninit_t<v8_inspector__V8InspectorClient__BASE>* buf) {
@@ -2592,6 +3459,57 @@ v8_inspector::StringBuffer* v8_inspector__StringBuffer__create(
   return v8_inspector::StringBuffer::create(source).release();
 }
 
+v8_inspector::StringBuffer* v8_inspector__StringBuffer__NEW(
+    v8_inspector::StringView source, void* owner, void (*drop_owner)(void*)) {
+  return new RustStringBuffer(source, owner, drop_owner);
+}
+
+bool v8_inspector__IsCBORMessage(const uint8_t* data, size_t len) {
+  return v8_crdtp::cbor::IsCBORMessage(v8_crdtp::span<uint8_t>(data, len));
+}
+
+typedef void (*ExtendBytesCallback)(void* vec, const uint8_t* data,
+                                    size_t len);
+
+static bool crdtp_result(const v8_crdtp::Status& status,
+                         const std::vector<uint8_t>& output,
+                         ExtendBytesCallback extend, void* out,
+                         void* error_message, size_t* error_pos) {
+  if (!status.ok()) {
+    std::string message = status.Message();
+    extend(error_message, reinterpret_cast<const uint8_t*>(message.data()),
+           message.size());
+    *error_pos = status.pos;
+    return false;
+  }
+  extend(out, output.data(), output.size());
+  return true;
+}
+
+bool v8_inspector__ConvertJSONToCBOR(v8_inspector::StringView json,
+                                     ExtendBytesCallback extend, void* cbor,
+                                     void* error_message, size_t* error_pos) {
+  std::vector<uint8_t> output;
+  v8_crdtp::Status status =
+      json.is8Bit()
+          ? v8_crdtp::json::ConvertJSONToCBOR(
+                v8_crdtp::span<uint8_t>(json.characters8(), json.length()),
+                &output)
+          : v8_crdtp::json::ConvertJSONToCBOR(
+                v8_crdtp::span<uint16_t>(json.characters16(), json.length()),
+                &output);
+  return crdtp_result(status, output, extend, cbor, error_message, error_pos);
+}
+
+bool v8_inspector__ConvertCBORToJSON(const uint8_t* data, size_t len,
+                                     ExtendBytesCallback extend, void* json,
+                                     void* error_message, size_t* error_pos) {
+  std::vector<uint8_t> output;
+  v8_crdtp::Status status = v8_crdtp::json::ConvertCBORToJSON(
+      v8_crdtp::span<uint8_t>(data, len), &output);
+  return crdtp_result(status, output, extend, json, error_message, error_pos);
+}
+
 int v8__Location__GetLineNumber(v8::Location* self) {
   return self->GetLineNumber();
 }
@@ -2621,6 +3539,16 @@ const v8::Value* v8__Module__GetModuleNamespace(const v8::Module& self) {
   return local_to_ptr(ptr_to_local(&self)->GetModuleNamespace());
 }
 
+const v8::Array* v8__Module__GetExportNames(const v8::Module& self,
+                                            const v8::Context* context) {
+  // Don't filter out non-enumerable properties, since checking whether an
+  // export is enumerable throws if its binding is uninitialized.
+  v8::Local<v8::Object> ns =
+      ptr_to_local(&self)->GetModuleNamespace().As<v8::Object>();
+  return maybe_local_to_ptr(ns->GetOwnPropertyNames(
+      ptr_to_local(context), v8::PropertyFilter::SKIP_SYMBOLS));
+}
+
 int v8__Module__GetIdentityHash(const v8::Module& self) {
   return self.GetIdentityHash();
 }
@@ -2730,37 +3658,456 @@ void v8__WasmStreaming__SetUrl(WasmStreamingSharedPtr* self, const char* url,
 }
 
 using HeapSnapshotCallback = bool (*)(void*, const char*, size_t);
+using HeapSnapshotProgressCallback = bool (*)(void*, uint32_t, uint32_t);
+
+struct HeapSnapshotOutputStream : public v8::OutputStream {
+  HeapSnapshotOutputStream(HeapSnapshotCallback callback, void* arg,
+                           int chunk_size)
+      : callback_(callback), arg_(arg), chunk_size_(chunk_size) {}
+  void EndOfStream() override {
+    static_cast<void>(callback_(arg_, nullptr, 0));
+  }
+  int GetChunkSize() override {
+    return chunk_size_ > 0 ? chunk_size_ : v8::OutputStream::GetChunkSize();
+  }
+  v8::OutputStream::WriteResult WriteAsciiChunk(char* data,
+                                                int size) override {
+    assert(size >= 0);  // Can never be < 0 barring bugs in V8.
+    if (callback_(arg_, data, static_cast<size_t>(size)))
+      return v8::OutputStream::kContinue;
+    aborted_ = true;
+    return v8::OutputStream::kAbort;
+  }
+  HeapSnapshotCallback const callback_;
+  void* const arg_;
+  int const chunk_size_;
+  bool aborted_ = false;
+};
+
+bool v8__HeapSnapshot__Serialize(const v8::HeapSnapshot& self,
+                                 HeapSnapshotCallback callback, void* arg,
+                                 int chunk_size) {
+  HeapSnapshotOutputStream stream(callback, arg, chunk_size);
+  self.Serialize(&stream);
+  return !stream.aborted_;
+}
+
+unsigned v8__HeapSnapshot__GetUid(const v8::HeapSnapshot& self) {
+  return self.GetUid();
+}
 
-void v8__HeapProfiler__TakeHeapSnapshot(v8::Isolate* isolate,
-                                        HeapSnapshotCallback callback,
-                                        void* arg) {
-  struct OutputStream : public v8::OutputStream {
-    OutputStream(HeapSnapshotCallback callback, void* arg)
+int v8__HeapSnapshot__GetNodesCount(const v8::HeapSnapshot& self) {
+  return self.GetNodesCount();
+}
+
+bool v8__HeapProfiler__TakeHeapSnapshot(
+    v8::Isolate* isolate, HeapSnapshotCallback callback, void* arg,
+    int chunk_size, HeapSnapshotProgressCallback progress_callback,
+    void* progress_arg) {
+  struct ActivityControl : public v8::ActivityControl {
+    ActivityControl(HeapSnapshotProgressCallback callback, void* arg)
         : callback_(callback), arg_(arg) {}
-    void EndOfStream() override {
-      static_cast<void>(callback_(arg_, nullptr, 0));
-    }
-    v8::OutputStream::WriteResult WriteAsciiChunk(char* data,
-                                                  int size) override {
-      assert(size >= 0);  // Can never be < 0 barring bugs in V8.
-      if (callback_(arg_, data, static_cast<size_t>(size)))
-        return v8::OutputStream::kContinue;
-      return v8::OutputStream::kAbort;
+    v8::ActivityControl::ControlOption ReportProgressValue(
+        uint32_t done, uint32_t total) override {
+      if (callback_(arg_, done, total)) return v8::ActivityControl::kContinue;
+      return v8::ActivityControl::kAbort;
     }
-    HeapSnapshotCallback const callback_;
+    HeapSnapshotProgressCallback const callback_;
     void* const arg_;
   };
 
+  ActivityControl control(progress_callback, progress_arg);
   const v8::HeapSnapshot* snapshot =
-      isolate->GetHeapProfiler()->TakeHeapSnapshot();
-  if (snapshot == nullptr) return;  // Snapshotting failed, probably OOM.
-  OutputStream stream(callback, arg);
-  snapshot->Serialize(&stream);
+      isolate->GetHeapProfiler()->TakeHeapSnapshot(
+          progress_callback != nullptr ? &control : nullptr);
+  // Snapshotting failed (probably OOM) or was aborted.
+  if (snapshot == nullptr) return false;
+  bool completed =
+      v8__HeapSnapshot__Serialize(*snapshot, callback, arg, chunk_size);
   // We don't want to call HeapProfiler::DeleteAllHeapSnapshots() because that
   // invalidates snapshots we don't own. The const_cast hack has been in use
   // in node-heapdump for the last 8 years and I think there is a pretty
   // good chance it'll keep working for 8 more.
   const_cast<v8::HeapSnapshot*>(snapshot)->Delete();
+  return completed;
+}
+
+const v8::HeapSnapshot* v8__HeapProfiler__TakeRetainedHeapSnapshot(
+    v8::Isolate* isolate) {
+  return isolate->GetHeapProfiler()->TakeHeapSnapshot();
+}
+
+int v8__HeapProfiler__GetSnapshotCount(v8::Isolate* isolate) {
+  return isolate->GetHeapProfiler()->GetSnapshotCount();
+}
+
+const v8::HeapSnapshot* v8__HeapProfiler__GetHeapSnapshot(v8::Isolate* isolate,
+                                                          int index) {
+  return isolate->GetHeapProfiler()->GetHeapSnapshot(index);
+}
+
+bool v8__HeapProfiler__DeleteHeapSnapshot(v8::Isolate* isolate,
+                                          unsigned uid) {
+  v8::HeapProfiler* profiler = isolate->GetHeapProfiler();
+  for (int i = 0; i < profiler->GetSnapshotCount(); i++) {
+    const v8::HeapSnapshot* snapshot = profiler->GetHeapSnapshot(i);
+    if (snapshot->GetUid() == uid) {
+      const_cast<v8::HeapSnapshot*>(snapshot)->Delete();
+      return true;
+    }
+  }
+  return false;
+}
+
+void v8__HeapProfiler__DeleteAllHeapSnapshots(v8::Isolate* isolate) {
+  isolate->GetHeapProfiler()->DeleteAllHeapSnapshots();
+}
+
+v8::SnapshotObjectId v8__HeapSnapshot__GetMaxSnapshotJSObjectId(
+    const v8::HeapSnapshot& self) {
+  return self.GetMaxSnapshotJSObjectId();
+}
+
+v8::SnapshotObjectId v8__HeapProfiler__GetObjectId(v8::Isolate* isolate,
+                                                   const v8::Value& value) {
+  return isolate->GetHeapProfiler()->GetObjectId(ptr_to_local(&value));
+}
+
+const v8::Value* v8__HeapProfiler__FindObjectById(v8::Isolate* isolate,
+                                                  v8::SnapshotObjectId id) {
+  return local_to_ptr(isolate->GetHeapProfiler()->FindObjectById(id));
+}
+
+void v8__HeapProfiler__StartTrackingHeapObjects(v8::Isolate* isolate,
+                                                bool track_allocations) {
+  isolate->GetHeapProfiler()->StartTrackingHeapObjects(track_allocations);
+}
+
+void v8__HeapProfiler__StopTrackingHeapObjects(v8::Isolate* isolate) {
+  isolate->GetHeapProfiler()->StopTrackingHeapObjects();
+}
+
+static_assert(sizeof(v8::HeapStatsUpdate) == 3 * sizeof(uint32_t),
+              "HeapStatsUpdate size mismatch");
+
+v8::SnapshotObjectId v8__HeapProfiler__GetHeapStats(
+    v8::Isolate* isolate,
+    bool (*callback)(void*, const v8::HeapStatsUpdate*, size_t), void* arg) {
+  struct HeapStatsOutputStream : public v8::OutputStream {
+    HeapStatsOutputStream(bool (*callback)(void*, const v8::HeapStatsUpdate*,
+                                           size_t),
+                          void* arg)
+        : callback_(callback), arg_(arg) {}
+    void EndOfStream() override {}
+    v8::OutputStream::WriteResult WriteAsciiChunk(char*, int) override {
+      return v8::OutputStream::kAbort;
+    }
+    v8::OutputStream::WriteResult WriteHeapStatsChunk(v8::HeapStatsUpdate* data,
+                                                      int count) override {
+      assert(count >= 0);
+      return callback_(arg_, data, static_cast<size_t>(count))
+                 ? v8::OutputStream::kContinue
+                 : v8::OutputStream::kAbort;
+    }
+    bool (*const callback_)(void*, const v8::HeapStatsUpdate*, size_t);
+    void* const arg_;
+  };
+  HeapStatsOutputStream stream(callback, arg);
+  return isolate->GetHeapProfiler()->GetHeapStats(&stream);
+}
+
+void v8__HeapProfiler__ClearObjectIds(v8::Isolate* isolate) {
+  isolate->GetHeapProfiler()->ClearObjectIds();
+}
+
+void v8__HeapProfiler__AddBuildEmbedderGraphCallback(
+    v8::Isolate* isolate, v8::HeapProfiler::BuildEmbedderGraphCallback callback,
+    void* data) {
+  isolate->GetHeapProfiler()->AddBuildEmbedderGraphCallback(callback, data);
+}
+
+void v8__HeapProfiler__RemoveBuildEmbedderGraphCallback(
+    v8::Isolate* isolate, v8::HeapProfiler::BuildEmbedderGraphCallback callback,
+    void* data) {
+  isolate->GetHeapProfiler()->RemoveBuildEmbedderGraphCallback(callback, data);
+}
+
+class RustEmbedderGraphNode : public v8::EmbedderGraph::Node {
+ public:
+  RustEmbedderGraphNode(const char* name, size_t name_len, size_t size_in_bytes,
+                        bool is_root)
+      : name_(name, name_len),
+        size_in_bytes_(size_in_bytes),
+        is_root_(is_root) {}
+  const char* Name() override { return name_.c_str(); }
+  size_t SizeInBytes() override { return size_in_bytes_; }
+  bool IsRootNode() override { return is_root_; }
+
+ private:
+  std::string const name_;
+  size_t const size_in_bytes_;
+  bool const is_root_;
+};
+
+v8::EmbedderGraph::Node* v8__EmbedderGraph__V8Node(v8::EmbedderGraph* self,
+                                                   const v8::Value& value) {
+  return self->V8Node(ptr_to_local(&value));
+}
+
+v8::EmbedderGraph::Node* v8__EmbedderGraph__AddNode(v8::EmbedderGraph* self,
+                                                    const char* name,
+                                                    size_t name_len,
+                                                    size_t size_in_bytes,
+                                                    bool is_root) {
+  return self->AddNode(std::make_unique<RustEmbedderGraphNode>(
+      name, name_len, size_in_bytes, is_root));
+}
+
+void v8__EmbedderGraph__AddEdge(v8::EmbedderGraph* self,
+                                v8::EmbedderGraph::Node* from,
+                                v8::EmbedderGraph::Node* to,
+                                const char* name) {
+  self->AddEdge(from, to, name);
+}
+
+bool v8__HeapProfiler__StartSamplingHeapProfiler(v8::Isolate* isolate,
+                                                 uint64_t sample_interval,
+                                                 int stack_depth, int flags) {
+  return isolate->GetHeapProfiler()->StartSamplingHeapProfiler(
+      sample_interval, stack_depth,
+      static_cast<v8::HeapProfiler::SamplingFlags>(flags));
+}
+
+void v8__HeapProfiler__StopSamplingHeapProfiler(v8::Isolate* isolate) {
+  isolate->GetHeapProfiler()->StopSamplingHeapProfiler();
+}
+
+v8::AllocationProfile* v8__HeapProfiler__GetAllocationProfile(
+    v8::Isolate* isolate) {
+  return isolate->GetHeapProfiler()->GetAllocationProfile();
+}
+
+void v8__AllocationProfile__DELETE(v8::AllocationProfile* self) {
+  delete self;
+}
+
+const v8::AllocationProfile::Node* v8__AllocationProfile__GetRootNode(
+    v8::AllocationProfile& self) {
+  return self.GetRootNode();
+}
+
+size_t v8__AllocationProfile__GetSamplesCount(v8::AllocationProfile& self) {
+  return self.GetSamples().size();
+}
+
+void v8__AllocationProfile__GetSample(v8::AllocationProfile& self,
+                                      size_t index, uint32_t* node_id,
+                                      size_t* size, unsigned* count,
+                                      uint64_t* sample_id) {
+  const v8::AllocationProfile::Sample& sample = self.GetSamples()[index];
+  *node_id = sample.node_id;
+  *size = sample.size;
+  *count = sample.count;
+  *sample_id = sample.sample_id;
+}
+
+const v8::String* v8__AllocationProfile__Node__GetName(
+    const v8::AllocationProfile::Node& self) {
+  return local_to_ptr(self.name);
+}
+
+const v8::String* v8__AllocationProfile__Node__GetScriptName(
+    const v8::AllocationProfile::Node& self) {
+  return local_to_ptr(self.script_name);
+}
+
+int v8__AllocationProfile__Node__GetScriptId(
+    const v8::AllocationProfile::Node& self) {
+  return self.script_id;
+}
+
+int v8__AllocationProfile__Node__GetStartPosition(
+    const v8::AllocationProfile::Node& self) {
+  return self.start_position;
+}
+
+int v8__AllocationProfile__Node__GetLineNumber(
+    const v8::AllocationProfile::Node& self) {
+  return self.line_number;
+}
+
+int v8__AllocationProfile__Node__GetColumnNumber(
+    const v8::AllocationProfile::Node& self) {
+  return self.column_number;
+}
+
+uint32_t v8__AllocationProfile__Node__GetNodeId(
+    const v8::AllocationProfile::Node& self) {
+  return self.node_id;
+}
+
+size_t v8__AllocationProfile__Node__GetChildrenCount(
+    const v8::AllocationProfile::Node& self) {
+  return self.children.size();
+}
+
+const v8::AllocationProfile::Node* v8__AllocationProfile__Node__GetChild(
+    const v8::AllocationProfile::Node& self, size_t index) {
+  return self.children[index];
+}
+
+size_t v8__AllocationProfile__Node__GetAllocationsCount(
+    const v8::AllocationProfile::Node& self) {
+  return self.allocations.size();
+}
+
+void v8__AllocationProfile__Node__GetAllocation(
+    const v8::AllocationProfile::Node& self, size_t index, size_t* size,
+    unsigned* count) {
+  const v8::AllocationProfile::Allocation& allocation =
+      self.allocations[index];
+  *size = allocation.size;
+  *count = allocation.count;
+}
+
+v8::CpuProfiler* v8__CpuProfiler__New(v8::Isolate* isolate) {
+  return v8::CpuProfiler::New(isolate);
+}
+
+void v8__CpuProfiler__Dispose(v8::CpuProfiler* self) { self->Dispose(); }
+
+void v8__CpuProfiler__SetSamplingInterval(v8::CpuProfiler* self, int us) {
+  self->SetSamplingInterval(us);
+}
+
+void v8__CpuProfiler__SetUsePreciseSampling(v8::CpuProfiler* self,
+                                            bool use_precise_sampling) {
+  self->SetUsePreciseSampling(use_precise_sampling);
+}
+
+v8::CpuProfilingStatus v8__CpuProfiler__StartProfiling(
+    v8::CpuProfiler* self, const v8::String& title, bool record_samples) {
+  return self->StartProfiling(ptr_to_local(&title), record_samples);
+}
+
+v8::CpuProfile* v8__CpuProfiler__StopProfiling(v8::CpuProfiler* self,
+                                               const v8::String& title) {
+  return self->StopProfiling(ptr_to_local(&title));
+}
+
+void v8__CpuProfile__Delete(v8::CpuProfile* self) { self->Delete(); }
+
+const v8::CpuProfileNode* v8__CpuProfile__GetTopDownRoot(
+    const v8::CpuProfile& self) {
+  return self.GetTopDownRoot();
+}
+
+int v8__CpuProfile__GetSamplesCount(const v8::CpuProfile& self) {
+  return self.GetSamplesCount();
+}
+
+const v8::CpuProfileNode* v8__CpuProfile__GetSample(
+    const v8::CpuProfile& self, int index) {
+  return self.GetSample(index);
+}
+
+int64_t v8__CpuProfile__GetSampleTimestamp(const v8::CpuProfile& self,
+                                           int index) {
+  return self.GetSampleTimestamp(index);
+}
+
+int64_t v8__CpuProfile__GetStartTime(const v8::CpuProfile& self) {
+  return self.GetStartTime();
+}
+
+int64_t v8__CpuProfile__GetEndTime(const v8::CpuProfile& self) {
+  return self.GetEndTime();
+}
+
+unsigned v8__CpuProfileNode__GetNodeId(const v8::CpuProfileNode& self) {
+  return self.GetNodeId();
+}
+
+const char* v8__CpuProfileNode__GetFunctionNameStr(
+    const v8::CpuProfileNode& self) {
+  return self.GetFunctionNameStr();
+}
+
+int v8__CpuProfileNode__GetScriptId(const v8::CpuProfileNode& self) {
+  return self.GetScriptId();
+}
+
+const char* v8__CpuProfileNode__GetScriptResourceNameStr(
+    const v8::CpuProfileNode& self) {
+  return self.GetScriptResourceNameStr();
+}
+
+int v8__CpuProfileNode__GetLineNumber(const v8::CpuProfileNode& self) {
+  return self.GetLineNumber();
+}
+
+int v8__CpuProfileNode__GetColumnNumber(const v8::CpuProfileNode& self) {
+  return self.GetColumnNumber();
+}
+
+unsigned v8__CpuProfileNode__GetHitCount(const v8::CpuProfileNode& self) {
+  return self.GetHitCount();
+}
+
+const char* v8__CpuProfileNode__GetBailoutReason(
+    const v8::CpuProfileNode& self) {
+  return self.GetBailoutReason();
+}
+
+unsigned v8__CpuProfileNode__GetHitLineCount(const v8::CpuProfileNode& self) {
+  return self.GetHitLineCount();
+}
+
+bool v8__CpuProfileNode__GetLineTicks(const v8::CpuProfileNode& self,
+                                      int* lines, unsigned* hit_counts,
+                                      unsigned length) {
+  std::vector<v8::CpuProfileNode::LineTick> entries(length);
+  if (!self.GetLineTicks(entries.data(), length)) {
+    return false;
+  }
+  for (unsigned i = 0; i < length; i++) {
+    lines[i] = entries[i].line;
+    hit_counts[i] = entries[i].hit_count;
+  }
+  return true;
+}
+
+int v8__CpuProfileNode__GetChildrenCount(const v8::CpuProfileNode& self) {
+  return self.GetChildrenCount();
+}
+
+const v8::CpuProfileNode* v8__CpuProfileNode__GetChild(
+    const v8::CpuProfileNode& self, int index) {
+  return self.GetChild(index);
+}
+
+size_t v8__CpuProfileNode__GetDeoptInfosCount(
+    const v8::CpuProfileNode& self) {
+  return self.GetDeoptInfos().size();
+}
+
+const char* v8__CpuProfileNode__GetDeoptInfoReason(
+    const v8::CpuProfileNode& self, size_t index) {
+  return self.GetDeoptInfos()[index].deopt_reason;
+}
+
+size_t v8__CpuProfileNode__GetDeoptInfoStackSize(
+    const v8::CpuProfileNode& self, size_t index) {
+  return self.GetDeoptInfos()[index].stack.size();
+}
+
+void v8__CpuProfileNode__GetDeoptInfoFrame(const v8::CpuProfileNode& self,
+                                           size_t index, size_t frame_index,
+                                           int* script_id, size_t* position) {
+  const v8::CpuProfileDeoptFrame& frame =
+      self.GetDeoptInfos()[index].stack[frame_index];
+  *script_id = frame.script_id;
+  *position = frame.position;
 }
 
 // This is necessary for v8__internal__GetIsolateFromHeapObject() to be
@@ -2823,6 +4170,17 @@ V(number_of_detached_contexts)
 V(does_zap_garbage)  // Returns size_t, not bool like you'd expect.
 
 #undef V
+
+// Returns the CPU time of the current thread in microseconds, or the time
+// since an arbitrary point if thread ticks aren't supported on this platform.
+int64_t v8__base__ThreadTicks__Now() {
+  if (v8::base::ThreadTicks::IsSupported()) {
+    v8::base::ThreadTicks::WaitUntilInitialized();
+    return (v8::base::ThreadTicks::Now() - v8::base::ThreadTicks())
+        .InMicroseconds();
+  }
+  return (v8::base::TimeTicks::Now() - v8::base::TimeTicks()).InMicroseconds();
+}
 }  // extern "C"
 
 // v8::ValueSerializer::Delegate
@@ -2835,6 +4193,13 @@ MaybeBool v8__ValueSerializer__Delegate__WriteHostObject(
     v8::ValueSerializer::Delegate* self, v8::Isolate* isolate,
     v8::Local<v8::Object> object);
 
+bool v8__ValueSerializer__Delegate__HasCustomHostObject(
+    v8::ValueSerializer::Delegate* self, v8::Isolate* isolate);
+
+MaybeBool v8__ValueSerializer__Delegate__IsHostObject(
+    v8::ValueSerializer::Delegate* self, v8::Isolate* isolate,
+    v8::Local<v8::Object> object);
+
 bool v8__ValueSerializer__Delegate__GetSharedArrayBufferId(
     v8::ValueSerializer::Delegate* self, v8::Isolate* isolate,
     v8::Local<v8::SharedArrayBuffer> shared_array_buffer, uint32_t* result);
@@ -2862,6 +4227,16 @@ struct v8__ValueSerializer__Delegate : public v8::ValueSerializer::Delegate {
         v8__ValueSerializer__Delegate__WriteHostObject(this, isolate, object));
   }
 
+  bool HasCustomHostObject(v8::Isolate* isolate) override {
+    return v8__ValueSerializer__Delegate__HasCustomHostObject(this, isolate);
+  }
+
+  v8::Maybe<bool> IsHostObject(v8::Isolate* isolate,
+                               v8::Local<v8::Object> object) override {
+    return maybe_bool_to_maybe(
+        v8__ValueSerializer__Delegate__IsHostObject(this, isolate, object));
+  }
+
   v8::Maybe<uint32_t> GetSharedArrayBufferId(
       v8::Isolate* isolate,
       v8::Local<v8::SharedArrayBuffer> shared_array_buffer) override {
@@ -2930,6 +4305,11 @@ void v8__ValueSerializer__WriteHeader(v8::ValueSerializer* self) {
   self->WriteHeader();
 }
 
+void v8__ValueSerializer__SetTreatArrayBufferViewsAsHostObjects(
+    v8::ValueSerializer* self, bool mode) {
+  self->SetTreatArrayBufferViewsAsHostObjects(mode);
+}
+
 MaybeBool v8__ValueSerializer__WriteValue(v8::ValueSerializer* self,
                                           v8::Local<v8::Context> context,
                                           v8::Local<v8::Value> value) {
@@ -3046,6 +4426,11 @@ void v8__ValueDeserializer__SetSupportsLegacyWireFormat(
   self->SetSupportsLegacyWireFormat(supports_legacy_wire_format);
 }
 
+uint32_t v8__ValueDeserializer__GetWireFormatVersion(
+    const v8::ValueDeserializer& self) {
+  return self.GetWireFormatVersion();
+}
+
 bool v8__ValueDeserializer__ReadUint32(v8::ValueDeserializer* self,
                                        uint32_t* value) {
   return self->ReadUint32(value);
@@ -3076,6 +4461,11 @@ const v8::WasmModuleObject* v8__WasmModuleObject__FromCompiledModule(
       v8::WasmModuleObject::FromCompiledModule(isolate, *compiled_module));
 }
 
+const v8::ArrayBuffer* v8__WasmMemoryObject__Buffer(
+    const v8::WasmMemoryObject& self) {
+  return local_to_ptr(ptr_to_local(&self)->Buffer());
+}
+
 v8::CompiledWasmModule* v8__WasmModuleObject__GetCompiledModule(
     const v8::WasmModuleObject* self) {
   v8::CompiledWasmModule cwm = ptr_to_local(self)->GetCompiledModule();
@@ -3102,6 +4492,11 @@ const char* v8__CompiledWasmModule__SourceUrl(v8::CompiledWasmModule* self,
   return source_url.data();
 }
 
+v8::CompiledWasmModule* v8__CompiledWasmModule__COPY(
+    const v8::CompiledWasmModule* self) {
+  return new v8::CompiledWasmModule(*self);
+}
+
 void v8__CompiledWasmModule__DELETE(v8::CompiledWasmModule* self) {
   delete self;
 }
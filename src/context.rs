@@ -7,8 +7,10 @@ use crate::Context;
 use crate::Function;
 use crate::HandleScope;
 use crate::Local;
+use crate::MicrotaskQueue;
 use crate::Object;
 use crate::ObjectTemplate;
+use crate::UniqueRef;
 use crate::Value;
 use crate::Weak;
 use libc::c_int;
@@ -16,17 +18,30 @@ use std::any::TypeId;
 use std::collections::HashMap;
 use std::ffi::c_void;
 use std::ptr::{null, null_mut};
+use std::time::Duration;
 
 extern "C" {
   fn v8__Context__New(
     isolate: *mut Isolate,
     templ: *const ObjectTemplate,
     global_object: *const Value,
+    microtask_queue: *const MicrotaskQueue,
   ) -> *const Context;
   fn v8__Context__GetIsolate(this: *const Context) -> *mut Isolate;
   fn v8__Context__Global(this: *const Context) -> *const Object;
+  fn v8__Context__DetachGlobal(this: *const Context);
+  fn v8__Context__GetMicrotaskQueue(
+    this: *const Context,
+  ) -> *const MicrotaskQueue;
   fn v8__Context__GetExtrasBindingObject(this: *const Context)
     -> *const Object;
+  fn v8__Context__GetContinuationPreservedEmbedderData(
+    this: *const Context,
+  ) -> *const Value;
+  fn v8__Context__SetContinuationPreservedEmbedderData(
+    this: *const Context,
+    data: *const Value,
+  );
   fn v8__Context__SetPromiseHooks(
     this: *const Context,
     init_hook: *const Function,
@@ -53,8 +68,9 @@ impl Context {
   pub fn new<'s>(scope: &mut HandleScope<'s, ()>) -> Local<'s, Context> {
     // TODO: optional arguments;
     unsafe {
-      scope
-        .cast_local(|sd| v8__Context__New(sd.get_isolate_ptr(), null(), null()))
+      scope.cast_local(|sd| {
+        v8__Context__New(sd.get_isolate_ptr(), null(), null(), null())
+      })
     }
     .unwrap()
   }
@@ -67,12 +83,61 @@ impl Context {
   ) -> Local<'s, Context> {
     unsafe {
       scope.cast_local(|sd| {
-        v8__Context__New(sd.get_isolate_ptr(), &*templ, null())
+        v8__Context__New(sd.get_isolate_ptr(), &*templ, null(), null())
+      })
+    }
+    .unwrap()
+  }
+
+  /// Creates a new context that reuses the global proxy of another context,
+  /// which must have been detached with `detach_global()` first. This allows
+  /// implementing navigation, where references to the global proxy that are
+  /// held by other contexts (like `window` in a browser) stay valid, but
+  /// refer to the new context's global object from now on.
+  ///
+  /// If the detached context was created from a template, the same template
+  /// should be passed as `templ`.
+  pub fn new_with_global_proxy<'s>(
+    scope: &mut HandleScope<'s, ()>,
+    templ: Option<Local<ObjectTemplate>>,
+    global_proxy: Local<Object>,
+  ) -> Local<'s, Context> {
+    let templ = templ.map_or_else(null, |templ| &*templ as *const _);
+    let global_proxy: Local<Value> = global_proxy.into();
+    unsafe {
+      scope.cast_local(|sd| {
+        v8__Context__New(sd.get_isolate_ptr(), templ, &*global_proxy, null())
       })
     }
     .unwrap()
   }
 
+  /// Creates a new context whose microtasks (e.g. promise reactions) are
+  /// enqueued to `microtask_queue` instead of the isolate's default queue.
+  /// The queue is owned by the context and dropped when the context is
+  /// garbage collected; use `get_microtask_queue()` to run it.
+  pub fn new_with_microtask_queue<'s>(
+    scope: &mut HandleScope<'s, ()>,
+    microtask_queue: UniqueRef<MicrotaskQueue>,
+  ) -> Local<'s, Context> {
+    let context = unsafe {
+      scope.cast_local(|sd| {
+        v8__Context__New(
+          sd.get_isolate_ptr(),
+          null(),
+          null(),
+          &*microtask_queue,
+        )
+      })
+    }
+    .unwrap();
+    // The queue must outlive the context. The slot's type is private, so the
+    // queue can't be removed from the context by other code, and
+    // `clear_all_slots()` hands it over to the isolate.
+    context.set_slot(scope, OwnedMicrotaskQueue(microtask_queue));
+    context
+  }
+
   pub fn get_extras_binding_object<'s>(
     &self,
     scope: &mut HandleScope<'s, ()>,
@@ -98,6 +163,53 @@ impl Context {
     unsafe { scope.cast_local(|_| v8__Context__Global(self)) }.unwrap()
   }
 
+  /// Detaches the global proxy from this context, so that it no longer
+  /// refers to the context's global object, and can be reused by a new
+  /// context created with `new_with_global_proxy()`. Code that still runs in
+  /// this context can use its global object, but not through the global
+  /// proxy.
+  pub fn detach_global(&self) {
+    unsafe { v8__Context__DetachGlobal(self) }
+  }
+
+  /// Returns the microtask queue of this context, which is the isolate's
+  /// default queue unless the context was created with
+  /// `new_with_microtask_queue()`.
+  pub fn get_microtask_queue(&self) -> &MicrotaskQueue {
+    unsafe { &*v8__Context__GetMicrotaskQueue(self) }
+  }
+
+  /// Returns the value set with `set_continuation_preserved_embedder_data()`
+  /// that is current for the code running in this context, or undefined.
+  pub fn get_continuation_preserved_embedder_data<'s>(
+    &self,
+    scope: &mut HandleScope<'s, ()>,
+  ) -> Local<'s, Value> {
+    unsafe {
+      scope
+        .cast_local(|_| v8__Context__GetContinuationPreservedEmbedderData(self))
+    }
+    .unwrap()
+  }
+
+  /// Sets a value that V8 carries along with promise continuations: when a
+  /// reaction is added to a promise (e.g. with `then()`, or by `await`), the
+  /// current value is captured, and it is restored while the reaction runs.
+  /// This allows implementing `AsyncLocalStorage`-style context propagation
+  /// across await points without using promise hooks.
+  pub fn set_continuation_preserved_embedder_data(&self, data: Local<Value>) {
+    unsafe { v8__Context__SetContinuationPreservedEmbedderData(self, &*data) }
+  }
+
+  /// Returns the time spent executing in this context while execution time
+  /// tracking was enabled with `Isolate::set_context_execution_time_tracking()`,
+  /// including the time since it was entered if it's executing now.
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn get_execution_time(&self, isolate: &mut Isolate) -> Duration {
+    isolate.get_context_execution_time(self)
+  }
+
   pub fn set_promise_hooks(
     &self,
     init_hook: Local<Function>,
@@ -278,7 +390,12 @@ impl Context {
   /// [`SnapshotCreator`](crate::SnapshotCreator), since the internal embedder
   /// state uses [`Weak`] handles, which cannot be alive at the time of
   /// snapshotting.
+  ///
+  /// The microtask queue of a context created with
+  /// `new_with_microtask_queue()` is not dropped, since V8 keeps using it for
+  /// as long as the context exists. It is kept alive by the isolate instead.
   pub fn clear_all_slots<'a>(&'a self, isolate: &'a mut Isolate) {
+    let microtask_queue = self.remove_slot::<OwnedMicrotaskQueue>(isolate);
     if let Some(annex_mut) = self.get_annex_mut(isolate, false) {
       let annex_ptr = annex_mut as *mut ContextAnnex;
       let _ = unsafe { Box::from_raw(annex_ptr) };
@@ -290,9 +407,25 @@ impl Context {
         )
       };
     }
+    if let Some(microtask_queue) = microtask_queue {
+      match isolate.get_slot_mut::<DetachedMicrotaskQueues>() {
+        Some(queues) => queues.0.push(microtask_queue),
+        None => {
+          isolate.set_slot(DetachedMicrotaskQueues(vec![microtask_queue]));
+        }
+      }
+    }
   }
 }
 
+/// Keeps the microtask queue of a context created with
+/// `Context::new_with_microtask_queue()` alive.
+struct OwnedMicrotaskQueue(#[allow(dead_code)] UniqueRef<MicrotaskQueue>);
+
+/// Keeps the microtask queues of contexts whose slots were cleared with
+/// `Context::clear_all_slots()` alive, until the isolate is disposed.
+struct DetachedMicrotaskQueues(Vec<OwnedMicrotaskQueue>);
+
 struct ContextAnnex {
   slots: HashMap<TypeId, RawSlot, BuildTypeIdHasher>,
   // In order to run the finalizer that drops the ContextAnnex when the Context
@@ -0,0 +1,513 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::os::raw::c_char;
+use std::ptr::NonNull;
+
+use crate::support::int;
+use crate::support::Opaque;
+use crate::HandleScope;
+use crate::Isolate;
+use crate::String;
+
+extern "C" {
+  fn v8__CpuProfiler__New(isolate: *mut Isolate) -> *mut RawCpuProfiler;
+  fn v8__CpuProfiler__Dispose(this: *mut RawCpuProfiler);
+  fn v8__CpuProfiler__SetSamplingInterval(this: *mut RawCpuProfiler, us: int);
+  fn v8__CpuProfiler__SetUsePreciseSampling(
+    this: *mut RawCpuProfiler,
+    use_precise_sampling: bool,
+  );
+  fn v8__CpuProfiler__StartProfiling(
+    this: *mut RawCpuProfiler,
+    title: *const String,
+    record_samples: bool,
+  ) -> CpuProfilingStatus;
+  fn v8__CpuProfiler__StopProfiling(
+    this: *mut RawCpuProfiler,
+    title: *const String,
+  ) -> *mut RawCpuProfile;
+
+  fn v8__CpuProfile__Delete(this: *mut RawCpuProfile);
+  fn v8__CpuProfile__GetTopDownRoot(
+    this: *const RawCpuProfile,
+  ) -> *const RawCpuProfileNode;
+  fn v8__CpuProfile__GetSamplesCount(this: *const RawCpuProfile) -> int;
+  fn v8__CpuProfile__GetSample(
+    this: *const RawCpuProfile,
+    index: int,
+  ) -> *const RawCpuProfileNode;
+  fn v8__CpuProfile__GetSampleTimestamp(
+    this: *const RawCpuProfile,
+    index: int,
+  ) -> i64;
+  fn v8__CpuProfile__GetStartTime(this: *const RawCpuProfile) -> i64;
+  fn v8__CpuProfile__GetEndTime(this: *const RawCpuProfile) -> i64;
+
+  fn v8__CpuProfileNode__GetNodeId(this: *const RawCpuProfileNode) -> u32;
+  fn v8__CpuProfileNode__GetFunctionNameStr(
+    this: *const RawCpuProfileNode,
+  ) -> *const c_char;
+  fn v8__CpuProfileNode__GetScriptId(this: *const RawCpuProfileNode) -> int;
+  fn v8__CpuProfileNode__GetScriptResourceNameStr(
+    this: *const RawCpuProfileNode,
+  ) -> *const c_char;
+  fn v8__CpuProfileNode__GetLineNumber(this: *const RawCpuProfileNode) -> int;
+  fn v8__CpuProfileNode__GetColumnNumber(this: *const RawCpuProfileNode)
+    -> int;
+  fn v8__CpuProfileNode__GetHitCount(this: *const RawCpuProfileNode) -> u32;
+  fn v8__CpuProfileNode__GetBailoutReason(
+    this: *const RawCpuProfileNode,
+  ) -> *const c_char;
+  fn v8__CpuProfileNode__GetHitLineCount(this: *const RawCpuProfileNode)
+    -> u32;
+  fn v8__CpuProfileNode__GetLineTicks(
+    this: *const RawCpuProfileNode,
+    lines: *mut int,
+    hit_counts: *mut u32,
+    length: u32,
+  ) -> bool;
+  fn v8__CpuProfileNode__GetChildrenCount(
+    this: *const RawCpuProfileNode,
+  ) -> int;
+  fn v8__CpuProfileNode__GetChild(
+    this: *const RawCpuProfileNode,
+    index: int,
+  ) -> *const RawCpuProfileNode;
+  fn v8__CpuProfileNode__GetDeoptInfosCount(
+    this: *const RawCpuProfileNode,
+  ) -> usize;
+  fn v8__CpuProfileNode__GetDeoptInfoReason(
+    this: *const RawCpuProfileNode,
+    index: usize,
+  ) -> *const c_char;
+  fn v8__CpuProfileNode__GetDeoptInfoStackSize(
+    this: *const RawCpuProfileNode,
+    index: usize,
+  ) -> usize;
+  fn v8__CpuProfileNode__GetDeoptInfoFrame(
+    this: *const RawCpuProfileNode,
+    index: usize,
+    frame_index: usize,
+    script_id: *mut int,
+    position: *mut usize,
+  );
+}
+
+/// The result of `CpuProfiler::start_profiling()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuProfilingStatus {
+  Started,
+  AlreadyStarted,
+  ErrorTooManyProfilers,
+}
+
+#[repr(C)]
+struct RawCpuProfiler(Opaque);
+
+/// Samples the JavaScript stack of an isolate at regular intervals, and
+/// collects the samples into CPU profiles.
+///
+/// The profiler borrows the isolate it was created for, so it can't outlive
+/// it. To run code while profiling, create scopes from the profiler, which
+/// dereferences to the isolate.
+#[derive(Debug)]
+pub struct CpuProfiler<'i> {
+  raw: NonNull<RawCpuProfiler>,
+  isolate: &'i mut Isolate,
+}
+
+impl<'i> CpuProfiler<'i> {
+  /// Creates a new CPU profiler for `isolate`.
+  pub fn new(isolate: &'i mut Isolate) -> Self {
+    let raw = unsafe { v8__CpuProfiler__New(isolate) };
+    Self {
+      raw: NonNull::new(raw).unwrap(),
+      isolate,
+    }
+  }
+
+  /// Changes the default CPU profiler sampling interval to the specified
+  /// number of microseconds. The default interval is 1ms. Must be called
+  /// before profiling is started.
+  pub fn set_sampling_interval(&mut self, us: i32) {
+    unsafe { v8__CpuProfiler__SetSamplingInterval(self.raw.as_ptr(), us) }
+  }
+
+  /// Sets whether or not the profiler should prioritize consistency of sample
+  /// periodicity on Windows. Disabling this can greatly reduce CPU usage, but
+  /// may result in greater variance in sample timings from the platform's
+  /// scheduler. Defaults to enabled. Must be called before profiling is
+  /// started.
+  pub fn set_use_precise_sampling(&mut self, use_precise_sampling: bool) {
+    unsafe {
+      v8__CpuProfiler__SetUsePreciseSampling(
+        self.raw.as_ptr(),
+        use_precise_sampling,
+      )
+    }
+  }
+
+  /// Starts collecting a CPU profile. Several profiles may be collected at
+  /// once; they are identified by their titles. If `record_samples` is true,
+  /// the profile will contain the individual samples and their timestamps, in
+  /// addition to the aggregated call tree.
+  pub fn start_profiling(
+    &mut self,
+    title: &str,
+    record_samples: bool,
+  ) -> CpuProfilingStatus {
+    let raw = self.raw.as_ptr();
+    let scope = &mut HandleScope::new(&mut *self.isolate);
+    let title = String::new(scope, title).unwrap();
+    unsafe { v8__CpuProfiler__StartProfiling(raw, &*title, record_samples) }
+  }
+
+  /// Stops collecting the CPU profile with the given title and returns it, or
+  /// `None` if no profile with that title was being collected.
+  pub fn stop_profiling(&mut self, title: &str) -> Option<CpuProfile> {
+    let raw = self.raw.as_ptr();
+    let scope = &mut HandleScope::new(&mut *self.isolate);
+    let title = String::new(scope, title).unwrap();
+    let raw = unsafe { v8__CpuProfiler__StopProfiling(raw, &*title) };
+    if raw.is_null() {
+      return None;
+    }
+    let profile = unsafe { CpuProfile::from_raw(&*raw) };
+    unsafe { v8__CpuProfile__Delete(raw) };
+    Some(profile)
+  }
+}
+
+impl<'i> Deref for CpuProfiler<'i> {
+  type Target = Isolate;
+  fn deref(&self) -> &Isolate {
+    self.isolate
+  }
+}
+
+impl<'i> DerefMut for CpuProfiler<'i> {
+  fn deref_mut(&mut self) -> &mut Isolate {
+    self.isolate
+  }
+}
+
+impl<'i> Drop for CpuProfiler<'i> {
+  fn drop(&mut self) {
+    unsafe { v8__CpuProfiler__Dispose(self.raw.as_ptr()) }
+  }
+}
+
+#[repr(C)]
+struct RawCpuProfile(Opaque);
+
+#[repr(C)]
+struct RawCpuProfileNode(Opaque);
+
+/// A collected CPU profile. Unlike the JSON profiles produced by the inspector
+/// protocol, this is a plain Rust structure, which makes it easy to convert to
+/// other formats (e.g. pprof).
+///
+/// All times are in microseconds, measured from an unspecified starting point.
+#[derive(Debug, Clone)]
+pub struct CpuProfile {
+  /// The nodes of the profile's top-down call tree, in depth-first order. The
+  /// first node is the root of the tree.
+  pub nodes: Vec<CpuProfileNode>,
+  /// The ids of the nodes that were on top of the stack when each sample was
+  /// taken. Empty unless samples were recorded.
+  pub samples: Vec<u32>,
+  /// The time at which each sample was taken.
+  pub timestamps: Vec<i64>,
+  /// The time at which profiling was started.
+  pub start_time: i64,
+  /// The time at which profiling was stopped.
+  pub end_time: i64,
+}
+
+/// A node in the call tree of a `CpuProfile`, i.e. a function together with
+/// the call stack it was called from.
+#[derive(Debug, Clone)]
+pub struct CpuProfileNode {
+  /// The id of the node, which is unique within the profile.
+  pub id: u32,
+  /// The name of the function, which is empty for anonymous functions.
+  pub function_name: std::string::String,
+  /// The id of the script the function belongs to.
+  pub script_id: i32,
+  /// The resource name of the script the function belongs to.
+  pub url: std::string::String,
+  /// The 1-based line number of the function, or 0 if unknown.
+  pub line_number: i32,
+  /// The 1-based column number of the function, or 0 if unknown.
+  pub column_number: i32,
+  /// The number of samples in which this node was on top of the stack.
+  pub hit_count: u32,
+  /// The number of samples in which this node was on top of the stack, per
+  /// source line. Only lines with at least one sample are included.
+  pub line_ticks: Vec<CpuProfileLineTick>,
+  /// The reason why the function could not be optimized, if any.
+  pub bailout_reason: Option<std::string::String>,
+  /// The ids of the child nodes.
+  pub children: Vec<u32>,
+  /// The deoptimizations that happened in this function.
+  pub deopt_infos: Vec<CpuProfileDeoptInfo>,
+}
+
+/// The number of samples taken at a source line of a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuProfileLineTick {
+  /// The 1-based line number.
+  pub line: i32,
+  pub hit_count: u32,
+}
+
+/// Information about a deoptimization of a function.
+#[derive(Debug, Clone)]
+pub struct CpuProfileDeoptInfo {
+  /// A human readable reason for the deoptimization.
+  pub reason: std::string::String,
+  /// The location of the deoptimization, with inlined frames first.
+  pub stack: Vec<CpuProfileDeoptFrame>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuProfileDeoptFrame {
+  pub script_id: i32,
+  /// The offset of the deoptimization in the script source.
+  pub position: usize,
+}
+
+impl CpuProfile {
+  unsafe fn from_raw(raw: &RawCpuProfile) -> Self {
+    let mut nodes = Vec::new();
+    let mut stack = vec![v8__CpuProfile__GetTopDownRoot(raw)];
+    while let Some(node) = stack.pop() {
+      let children = (0..v8__CpuProfileNode__GetChildrenCount(node))
+        .map(|index| v8__CpuProfileNode__GetChild(node, index))
+        .collect::<Vec<_>>();
+      nodes.push(CpuProfileNode::from_raw(node, &children));
+      // Push the children in reverse, so that they're visited in order.
+      stack.extend(children.into_iter().rev());
+    }
+
+    let samples_count = v8__CpuProfile__GetSamplesCount(raw);
+    let samples = (0..samples_count)
+      .map(|index| {
+        v8__CpuProfileNode__GetNodeId(v8__CpuProfile__GetSample(raw, index))
+      })
+      .collect();
+    let timestamps = (0..samples_count)
+      .map(|index| v8__CpuProfile__GetSampleTimestamp(raw, index))
+      .collect();
+
+    Self {
+      nodes,
+      samples,
+      timestamps,
+      start_time: v8__CpuProfile__GetStartTime(raw),
+      end_time: v8__CpuProfile__GetEndTime(raw),
+    }
+  }
+
+  /// Returns the node with the given id.
+  pub fn get_node(&self, id: u32) -> Option<&CpuProfileNode> {
+    self.nodes.iter().find(|node| node.id == id)
+  }
+
+  /// Serializes the profile in the JSON format of the `Profile` type of the
+  /// Chrome DevTools protocol, which is what `.cpuprofile` files contain.
+  /// These can be loaded by Chrome DevTools and by most flamegraph viewers.
+  pub fn to_json(&self) -> std::string::String {
+    let mut out = std::string::String::new();
+    out.push_str("{\"nodes\":[");
+    for (i, node) in self.nodes.iter().enumerate() {
+      if i > 0 {
+        out.push(',');
+      }
+      // Line and column numbers are 0-based in the DevTools protocol.
+      write!(
+        out,
+        "{{\"id\":{},\"callFrame\":{{\"functionName\":",
+        node.id
+      )
+      .unwrap();
+      write_json_string(&mut out, &node.function_name);
+      write!(out, ",\"scriptId\":\"{}\",\"url\":", node.script_id).unwrap();
+      write_json_string(&mut out, &node.url);
+      write!(
+        out,
+        ",\"lineNumber\":{},\"columnNumber\":{}}},\"hitCount\":{}",
+        node.line_number - 1,
+        node.column_number - 1,
+        node.hit_count
+      )
+      .unwrap();
+      if !node.children.is_empty() {
+        out.push_str(",\"children\":");
+        write_json_array(&mut out, &node.children);
+      }
+      if let Some(reason) = &node.bailout_reason {
+        out.push_str(",\"deoptReason\":");
+        write_json_string(&mut out, reason);
+      }
+      if !node.line_ticks.is_empty() {
+        out.push_str(",\"positionTicks\":[");
+        for (j, tick) in node.line_ticks.iter().enumerate() {
+          if j > 0 {
+            out.push(',');
+          }
+          write!(
+            out,
+            "{{\"line\":{},\"ticks\":{}}}",
+            tick.line, tick.hit_count
+          )
+          .unwrap();
+        }
+        out.push(']');
+      }
+      out.push('}');
+    }
+    write!(
+      out,
+      "],\"startTime\":{},\"endTime\":{},\"samples\":",
+      self.start_time, self.end_time
+    )
+    .unwrap();
+    write_json_array(&mut out, &self.samples);
+    out.push_str(",\"timeDeltas\":");
+    write_json_array(&mut out, &self.time_deltas());
+    out.push('}');
+    out
+  }
+
+  /// Returns the time elapsed before each sample, starting from the start
+  /// time of the profile, as used by the `timeDeltas` field of the Chrome
+  /// DevTools profile format.
+  pub fn time_deltas(&self) -> Vec<i64> {
+    let mut previous = self.start_time;
+    self
+      .timestamps
+      .iter()
+      .map(|&timestamp| {
+        let delta = timestamp - previous;
+        previous = timestamp;
+        delta
+      })
+      .collect()
+  }
+}
+
+impl CpuProfileNode {
+  unsafe fn from_raw(
+    node: *const RawCpuProfileNode,
+    children: &[*const RawCpuProfileNode],
+  ) -> Self {
+    let bailout_reason = c_string(v8__CpuProfileNode__GetBailoutReason(node));
+    let deopt_infos = (0..v8__CpuProfileNode__GetDeoptInfosCount(node))
+      .map(|index| CpuProfileDeoptInfo {
+        reason: c_string(v8__CpuProfileNode__GetDeoptInfoReason(node, index)),
+        stack: (0..v8__CpuProfileNode__GetDeoptInfoStackSize(node, index))
+          .map(|frame_index| {
+            let mut script_id = 0;
+            let mut position = 0;
+            v8__CpuProfileNode__GetDeoptInfoFrame(
+              node,
+              index,
+              frame_index,
+              &mut script_id,
+              &mut position,
+            );
+            CpuProfileDeoptFrame {
+              script_id,
+              position,
+            }
+          })
+          .collect(),
+      })
+      .collect();
+    Self {
+      id: v8__CpuProfileNode__GetNodeId(node),
+      function_name: c_string(v8__CpuProfileNode__GetFunctionNameStr(node)),
+      script_id: v8__CpuProfileNode__GetScriptId(node),
+      url: c_string(v8__CpuProfileNode__GetScriptResourceNameStr(node)),
+      line_number: v8__CpuProfileNode__GetLineNumber(node),
+      column_number: v8__CpuProfileNode__GetColumnNumber(node),
+      hit_count: v8__CpuProfileNode__GetHitCount(node),
+      line_ticks: line_ticks(node),
+      bailout_reason: Some(bailout_reason).filter(|reason| !reason.is_empty()),
+      children: children
+        .iter()
+        .map(|&child| v8__CpuProfileNode__GetNodeId(child))
+        .collect(),
+      deopt_infos,
+    }
+  }
+}
+
+unsafe fn line_ticks(
+  node: *const RawCpuProfileNode,
+) -> Vec<CpuProfileLineTick> {
+  let length = v8__CpuProfileNode__GetHitLineCount(node);
+  let mut lines = vec![0; length as usize];
+  let mut hit_counts = vec![0; length as usize];
+  if length == 0
+    || !v8__CpuProfileNode__GetLineTicks(
+      node,
+      lines.as_mut_ptr(),
+      hit_counts.as_mut_ptr(),
+      length,
+    )
+  {
+    return Vec::new();
+  }
+  lines
+    .into_iter()
+    .zip(hit_counts)
+    .map(|(line, hit_count)| CpuProfileLineTick { line, hit_count })
+    .collect()
+}
+
+fn write_json_array<T: std::fmt::Display>(
+  out: &mut std::string::String,
+  values: &[T],
+) {
+  out.push('[');
+  for (i, value) in values.iter().enumerate() {
+    if i > 0 {
+      out.push(',');
+    }
+    write!(out, "{}", value).unwrap();
+  }
+  out.push(']');
+}
+
+/// Appends `s` to `out` as a JSON string literal.
+fn write_json_string(out: &mut std::string::String, s: &str) {
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => {
+        write!(out, "\\u{:04x}", c as u32).unwrap();
+      }
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+}
+
+unsafe fn c_string(ptr: *const c_char) -> std::string::String {
+  if ptr.is_null() {
+    return Default::default();
+  }
+  CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
@@ -0,0 +1,50 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+//! Bindings to parts of V8's debug interface, which is used to implement the
+//! inspector and other debugging tools.
+use crate::HandleScope;
+use crate::Isolate;
+use crate::Local;
+use crate::String;
+use crate::Value;
+
+extern "C" {
+  fn v8__debug__EvaluateGlobal(
+    isolate: *mut Isolate,
+    source: *const String,
+    mode: EvaluateGlobalMode,
+    repl_mode: bool,
+  ) -> *const Value;
+}
+
+/// Controls how `evaluate_global()` evaluates code.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvaluateGlobalMode {
+  Default,
+  /// Breakpoints and `debugger` statements are ignored.
+  DisableBreaks,
+  /// Like `DisableBreaks`, and in addition an `EvalError` is thrown when the
+  /// code would have a side effect, such as assigning to a global variable or
+  /// calling a function that isn't known to be side-effect free.
+  DisableBreaksAndThrowOnSideEffect,
+}
+
+/// Evaluates `source` in the global scope of the current context, like the
+/// inspector's `Runtime.evaluate` does. This is useful to evaluate watch
+/// expressions or configuration without letting them change the state of the
+/// program, by passing `EvaluateGlobalMode::DisableBreaksAndThrowOnSideEffect`.
+///
+/// In REPL mode, top-level `let` and `const` declarations can be redeclared,
+/// and top-level `await` is allowed, in which case a promise is returned.
+pub fn evaluate_global<'s>(
+  scope: &mut HandleScope<'s>,
+  source: Local<String>,
+  mode: EvaluateGlobalMode,
+  repl_mode: bool,
+) -> Option<Local<'s, Value>> {
+  unsafe {
+    scope.cast_local(|sd| {
+      v8__debug__EvaluateGlobal(sd.get_isolate_ptr(), &*source, mode, repl_mode)
+    })
+  }
+}
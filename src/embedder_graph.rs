@@ -0,0 +1,112 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use std::ffi::c_void;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr::null;
+
+use crate::support::Opaque;
+use crate::Isolate;
+use crate::Value;
+
+extern "C" {
+  fn v8__EmbedderGraph__V8Node(
+    this: *const EmbedderGraph,
+    value: *const Value,
+  ) -> *const EmbedderGraphNode;
+  fn v8__EmbedderGraph__AddNode(
+    this: *const EmbedderGraph,
+    name: *const u8,
+    name_len: usize,
+    size_in_bytes: usize,
+    is_root: bool,
+  ) -> *const EmbedderGraphNode;
+  fn v8__EmbedderGraph__AddEdge(
+    this: *const EmbedderGraph,
+    from: *const EmbedderGraphNode,
+    to: *const EmbedderGraphNode,
+    name: *const c_char,
+  );
+}
+
+/// Called while a heap snapshot is taken, to let the embedder add the objects
+/// it owns to the snapshot. See `Isolate::add_build_embedder_graph_callback()`.
+pub type BuildEmbedderGraphCallback = extern "C" fn(
+  isolate: &mut Isolate,
+  graph: &mut EmbedderGraph,
+  data: *mut c_void,
+);
+
+/// The graph of embedder objects that is merged into a heap snapshot. Nodes
+/// are either embedder nodes, which show up in the snapshot with the given
+/// name and size, or V8 nodes, which represent objects on the V8 heap.
+///
+/// An edge from an embedder node to a V8 node makes the embedder node show
+/// up as a retainer of the V8 object, e.g. for a `Global<Object>` held by a
+/// Rust resource.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EmbedderGraph(Opaque);
+
+/// A node in an `EmbedderGraph`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct EmbedderGraphNode(Opaque);
+
+impl EmbedderGraph {
+  /// Returns a node corresponding to the given V8 value. Nodes for primitive
+  /// values that aren't allocated on the heap (i.e. small integers) are
+  /// dropped from the snapshot.
+  pub fn v8_node(&self, value: &Value) -> &EmbedderGraphNode {
+    unsafe { &*v8__EmbedderGraph__V8Node(self, value) }
+  }
+
+  /// Adds an embedder node with the given name and self size to the graph.
+  pub fn add_node(
+    &self,
+    name: &str,
+    size_in_bytes: usize,
+  ) -> &EmbedderGraphNode {
+    unsafe {
+      &*v8__EmbedderGraph__AddNode(
+        self,
+        name.as_ptr(),
+        name.len(),
+        size_in_bytes,
+        false,
+      )
+    }
+  }
+
+  /// Like `add_node()`, but the node is a GC root, so it shows up at the top
+  /// level of the retainer tree in DevTools.
+  pub fn add_root_node(
+    &self,
+    name: &str,
+    size_in_bytes: usize,
+  ) -> &EmbedderGraphNode {
+    unsafe {
+      &*v8__EmbedderGraph__AddNode(
+        self,
+        name.as_ptr(),
+        name.len(),
+        size_in_bytes,
+        true,
+      )
+    }
+  }
+
+  /// Adds an edge that represents a strong reference from `from` to `to`.
+  /// Unnamed edges show up as indexed elements in the snapshot. V8 doesn't
+  /// copy the name, and only reads it once all callbacks have returned, so it
+  /// must be `'static`.
+  pub fn add_edge(
+    &self,
+    from: &EmbedderGraphNode,
+    to: &EmbedderGraphNode,
+    name: Option<&'static CStr>,
+  ) {
+    let name = name.map_or(null(), CStr::as_ptr);
+    unsafe { v8__EmbedderGraph__AddEdge(self, from, to, name) }
+  }
+}
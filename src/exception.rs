@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 
 use std::convert::TryInto;
+use std::fmt;
 
 use crate::isolate::Isolate;
 use crate::support::int;
@@ -356,3 +357,46 @@ impl Exception {
     unsafe { scope.cast_local(|_| v8__Exception__GetStackTrace(&*exception)) }
   }
 }
+
+/// A summary of a caught exception, as returned by
+/// `TryCatch::exception_report()`. This is not part of the V8 API; it bundles
+/// the information embedders commonly need to print an uncaught error.
+#[derive(Debug)]
+pub struct ExceptionReport<'s> {
+  /// The exception value that was thrown.
+  pub exception: Local<'s, Value>,
+  /// The message text, e.g. "Uncaught TypeError: x is not a function". Falls
+  /// back to the string conversion of the exception value if V8 did not
+  /// create a message.
+  pub message: std::string::String,
+  /// The resource name of the script where the exception was thrown.
+  pub resource_name: Option<std::string::String>,
+  /// The 1-based line number where the exception was thrown.
+  pub line_number: Option<usize>,
+  /// The 0-based column where the exception was thrown.
+  pub start_column: Option<usize>,
+  /// The `.stack` property of the exception, if it is a string.
+  pub stack_trace: Option<std::string::String>,
+}
+
+impl<'s> fmt::Display for ExceptionReport<'s> {
+  /// Formats the report as `resource:line:column: ` followed by the stack
+  /// trace, or by the message text if there is no stack trace. Location
+  /// components that are unknown are left out.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(resource_name) = &self.resource_name {
+      write!(f, "{}:", resource_name)?;
+      if let Some(line_number) = self.line_number {
+        write!(f, "{}:", line_number)?;
+        if let Some(start_column) = self.start_column {
+          write!(f, "{}:", start_column)?;
+        }
+      }
+      write!(f, " ")?;
+    }
+    match &self.stack_trace {
+      Some(stack_trace) => write!(f, "{}", stack_trace),
+      None => write!(f, "{}", self.message),
+    }
+  }
+}
@@ -1,66 +1,120 @@
-use crate::support::Opaque;
+use crate::Array;
+use crate::Local;
+use crate::Object;
+use crate::Value;
 use libc::c_void;
 use std::{
-  mem::align_of,
-  ptr::{self, NonNull},
+  mem::{align_of, size_of_val},
+  ptr,
 };
 
-extern "C" {
-  fn v8__CTypeInfo__New(ty: CType) -> *mut CTypeInfo;
-  fn v8__CTypeInfo__New__From__Slice(
-    len: usize,
-    tys: *const CTypeSequenceInfo,
-  ) -> *mut CTypeInfo;
-  fn v8__CFunctionInfo__New(
-    return_info: *const CTypeInfo,
-    args_len: usize,
-    args_info: *const CTypeInfo,
-  ) -> *mut CFunctionInfo;
-}
-
+/// The signature of a fast function. Mirrors the layout of
+/// `v8::CFunctionInfo`.
 #[repr(C)]
-#[derive(Default)]
-pub struct CFunctionInfo(Opaque);
+#[derive(Debug)]
+pub struct CFunctionInfo {
+  return_info: CTypeInfo,
+  arg_count: u32,
+  arg_info: *const CTypeInfo,
+}
 
+/// A fast function together with its signature. Mirrors the layout of
+/// `v8::CFunction`.
 #[repr(C)]
-#[derive(Default)]
-pub struct CFunction(Opaque);
+#[derive(Debug, Clone, Copy)]
+pub struct CFunction {
+  address: *const c_void,
+  type_info: *const CFunctionInfo,
+}
 
-impl CFunctionInfo {
-  pub(crate) unsafe fn new(
-    args: *const CTypeInfo,
-    args_len: usize,
-    return_type: *const CTypeInfo,
-  ) -> NonNull<CFunctionInfo> {
-    NonNull::new_unchecked(v8__CFunctionInfo__New(return_type, args_len, args))
+impl CFunction {
+  pub(crate) fn new(
+    address: *const c_void,
+    type_info: *const CFunctionInfo,
+  ) -> Self {
+    Self { address, type_info }
   }
 }
 
+/// The type of a fast function argument or return value. Mirrors the layout
+/// of `v8::CTypeInfo`.
 #[repr(C)]
-#[derive(Debug)]
-pub struct CTypeInfo(Opaque);
+#[derive(Debug, Clone, Copy)]
+pub struct CTypeInfo {
+  c_type: CType,
+  sequence_type: SequenceType,
+  flags: Flags,
+}
 
-impl CTypeInfo {
-  pub(crate) fn new(ty: CType) -> NonNull<CTypeInfo> {
-    unsafe { NonNull::new_unchecked(v8__CTypeInfo__New(ty)) }
+impl From<&Type> for CTypeInfo {
+  fn from(ty: &Type) -> CTypeInfo {
+    CTypeInfo {
+      c_type: ty.into(),
+      sequence_type: ty.into(),
+      flags: ty.flags(),
+    }
   }
+}
 
-  pub(crate) fn new_from_slice(types: &[Type]) -> NonNull<CTypeInfo> {
-    let mut structs = vec![];
+/// A `CFunctionInfo` together with the argument types that it points to.
+/// V8 keeps a pointer to the `CFunctionInfo` of every overload passed to
+/// `FunctionTemplate::build_fast()` for as long as the template exists, so
+/// these are owned by the isolate and dropped when it is disposed.
+pub(crate) struct OwnedCFunctionInfo {
+  info: Box<CFunctionInfo>,
+  _args: Box<[CTypeInfo]>,
+}
 
-    for type_ in types.iter() {
-      structs.push(type_.into())
-    }
+impl OwnedCFunctionInfo {
+  pub(crate) fn new(fast_function: &dyn FastFunction) -> Self {
+    let args = fast_function
+      .args()
+      .iter()
+      .map(CTypeInfo::from)
+      .collect::<Box<[_]>>();
+    let info = Box::new(CFunctionInfo {
+      return_info: CTypeInfo {
+        c_type: fast_function.return_type(),
+        sequence_type: SequenceType::Scalar,
+        flags: Flags::NONE,
+      },
+      arg_count: args.len() as u32,
+      arg_info: args.as_ptr(),
+    });
+    Self { info, _args: args }
+  }
 
-    unsafe {
-      NonNull::new_unchecked(v8__CTypeInfo__New__From__Slice(
-        structs.len(),
-        structs.as_ptr(),
-      ))
-    }
+  pub(crate) fn as_ptr(&self) -> *const CFunctionInfo {
+    &*self.info
+  }
+
+  /// Identifies `fast_function` by its address together with its signature.
+  /// Functions with different signatures can share an address, e.g. when the
+  /// linker folds identical generic instantiations, and each of them needs a
+  /// `CFunctionInfo` of its own.
+  pub(crate) fn key(fast_function: &dyn FastFunction) -> CFunctionInfoKey {
+    let return_info = CTypeInfo {
+      c_type: fast_function.return_type(),
+      sequence_type: SequenceType::Scalar,
+      flags: Flags::NONE,
+    };
+    let signature = std::iter::once(return_info)
+      .chain(fast_function.args().iter().map(CTypeInfo::from))
+      .map(|info| {
+        (
+          info.c_type as u8,
+          info.sequence_type as u8,
+          info.flags.bits(),
+        )
+      })
+      .collect();
+    (fast_function.function() as usize, signature)
   }
 }
 
+/// See `OwnedCFunctionInfo::key()`.
+pub(crate) type CFunctionInfoKey = (usize, Box<[(u8, u8, u8)]>);
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(u8)]
 pub enum SequenceType {
@@ -73,7 +127,19 @@ pub enum SequenceType {
   IsArrayBuffer,
 }
 
-#[derive(Clone, Copy)]
+// TODO: V8 10.5 has neither `CTypeInfo::Type::kPointer` nor the
+// `CFunctionInfo::Int64Representation` option (number vs. BigInt), both of
+// which were added in later versions of v8-fast-api-calls.h. Add
+// `CType::Pointer` and an int64 representation field to `CFunctionInfo`
+// once V8 is upgraded. The discriminants below must keep matching
+// `CTypeInfo::Type`, so `Pointer` can't be added before then.
+//
+// TODO: the same goes for `CTypeInfo::Type::kSeqOneByteString`, which lets
+// fast functions receive one-byte strings as a `const FastOneByteString&`.
+// Once it is available, add `CType::SeqOneByteString` (right after
+// `V8Value`), a matching `Type` variant and a `#[repr(C)]
+// FastApiOneByteString { data: *const u8, length: u32 }` struct.
+#[derive(Clone, Copy, Debug)]
 #[repr(u8)]
 #[non_exhaustive]
 pub enum CType {
@@ -94,7 +160,7 @@ pub enum CType {
   CallbackOptions = 255,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 #[non_exhaustive]
 pub enum Type {
   Void,
@@ -110,11 +176,49 @@ pub enum Type {
   Sequence(CType),
   TypedArray(CType),
   ArrayBuffer(CType),
+  /// Another type with additional flags, e.g.
+  /// `Type::WithFlags(&Type::Int32, Flags::CLAMP)`.
+  WithFlags(&'static Type, Flags),
+}
+
+bitflags! {
+  /// Flags that modify how V8 converts an argument of a fast function.
+  #[derive(Default)]
+  #[repr(transparent)]
+  pub struct Flags: u8 {
+    const NONE = 0;
+    /// Accept typed arrays that are backed by a `SharedArrayBuffer`.
+    const ALLOW_SHARED = 1 << 0;
+    /// Implements WebIDL's `[EnforceRange]` for integer arguments: numbers
+    /// that are out of range of the integer type fall back to the slow
+    /// callback.
+    const ENFORCE_RANGE = 1 << 1;
+    /// Implements WebIDL's `[Clamp]` for integer arguments: numbers that are
+    /// out of range of the integer type are clamped to it.
+    const CLAMP = 1 << 2;
+  }
+}
+
+impl Type {
+  /// Returns the type without any flags added by `Type::WithFlags`.
+  fn without_flags(&self) -> &Type {
+    match self {
+      Type::WithFlags(ty, _) => ty.without_flags(),
+      ty => ty,
+    }
+  }
+
+  fn flags(&self) -> Flags {
+    match self {
+      Type::WithFlags(ty, flags) => *flags | ty.flags(),
+      _ => Flags::NONE,
+    }
+  }
 }
 
 impl From<&Type> for CType {
   fn from(ty: &Type) -> CType {
-    match ty {
+    match ty.without_flags() {
       Type::Void => CType::Void,
       Type::Bool => CType::Bool,
       Type::Int32 => CType::Int32,
@@ -128,13 +232,14 @@ impl From<&Type> for CType {
       Type::Sequence(ty) => *ty,
       Type::TypedArray(ty) => *ty,
       Type::ArrayBuffer(ty) => *ty,
+      Type::WithFlags(..) => unreachable!(),
     }
   }
 }
 
 impl From<&Type> for SequenceType {
   fn from(ty: &Type) -> SequenceType {
-    match ty {
+    match ty.without_flags() {
       Type::Sequence(_) => SequenceType::IsSequence,
       Type::TypedArray(_) => SequenceType::IsTypedArray,
       Type::ArrayBuffer(_) => SequenceType::IsArrayBuffer,
@@ -143,21 +248,6 @@ impl From<&Type> for SequenceType {
   }
 }
 
-impl From<&Type> for CTypeSequenceInfo {
-  fn from(ty: &Type) -> CTypeSequenceInfo {
-    CTypeSequenceInfo {
-      c_type: ty.into(),
-      sequence_type: ty.into(),
-    }
-  }
-}
-
-#[repr(C)]
-struct CTypeSequenceInfo {
-  c_type: CType,
-  sequence_type: SequenceType,
-}
-
 #[repr(C)]
 pub union FastApiCallbackData {
   /// `data_ptr` allows for default constructing FastApiCallbackOptions.
@@ -166,6 +256,12 @@ pub union FastApiCallbackData {
   pub data: crate::Value,
 }
 
+// TODO: later versions of V8 add an `isolate` field to the start of
+// `v8::FastApiCallbackOptions`, which lets fast callbacks e.g. adjust the
+// external memory or request an interrupt without falling back. V8 10.5
+// doesn't have it, so an `isolate()` accessor can't be provided yet. Once V8
+// is upgraded, add `isolate: *mut Isolate` as the first field here (keeping
+// the layout in sync) and a `pub fn isolate(&mut self) -> &mut Isolate`.
 /// A struct which may be passed to a fast call callback, like so
 /// ```c
 /// void FastMethodWithOptions(int param, FastApiCallbackOptions& options);
@@ -190,6 +286,8 @@ pub struct FastApiCallbackOptions {
 // https://source.chromium.org/chromium/chromium/src/+/main:v8/include/v8-fast-api-calls.h;l=336
 #[repr(C)]
 pub struct FastApiTypedArray<T: Default> {
+  /// The number of elements (not bytes) in the typed array, despite the
+  /// name. Prefer `len()`.
   pub byte_length: usize,
   // This pointer should include the typed array offset applied.
   // It's not guaranteed that it's aligned to sizeof(T), it's only
@@ -200,30 +298,98 @@ pub struct FastApiTypedArray<T: Default> {
 }
 
 impl<T: Default> FastApiTypedArray<T> {
+  /// Returns the number of elements in the typed array.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.byte_length
+  }
+
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.byte_length == 0
+  }
+
+  /// Returns the element at `index`, which doesn't have to be aligned.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `index` is out of bounds.
   #[inline]
   pub fn get(&self, index: usize) -> T {
-    debug_assert!(index < self.byte_length);
-    let mut t: T = Default::default();
-    unsafe {
-      ptr::copy_nonoverlapping(self.data.add(index), &mut t, 1);
+    assert!(index < self.byte_length, "index out of bounds");
+    unsafe { ptr::read_unaligned(self.data.add(index)) }
+  }
+
+  /// Returns the elements as a slice, or `None` if the data is not aligned to
+  /// `T`, which can happen for 8-byte element types. Use `get()` or
+  /// `copy_into()` to read the elements in that case.
+  #[inline]
+  pub fn get_storage(&self) -> Option<&[T]> {
+    if self.byte_length == 0 {
+      return Some(&[]);
     }
-    t
+    if (self.data as usize) % align_of::<T>() != 0 {
+      return None;
+    }
+    Some(unsafe { std::slice::from_raw_parts(self.data, self.byte_length) })
   }
 
+  /// Like `get_storage()`, but returns a mutable slice.
   #[inline]
-  pub fn get_storage_if_aligned(&self) -> Option<&mut [T]> {
+  pub fn get_storage_if_aligned(&mut self) -> Option<&mut [T]> {
+    if self.byte_length == 0 {
+      return Some(&mut []);
+    }
     if (self.data as usize) % align_of::<T>() != 0 {
       return None;
     }
-    Some(unsafe {
-      std::slice::from_raw_parts_mut(
-        self.data,
-        self.byte_length / align_of::<T>(),
-      )
-    })
+    Some(unsafe { std::slice::from_raw_parts_mut(self.data, self.byte_length) })
+  }
+
+  /// Copies the first `dest.len()` elements into `dest`, regardless of
+  /// whether the data is aligned.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `dest` is longer than the typed array.
+  #[inline]
+  pub fn copy_into(&self, dest: &mut [T]) {
+    assert!(dest.len() <= self.byte_length, "destination is too long");
+    if dest.is_empty() {
+      return;
+    }
+    unsafe {
+      ptr::copy_nonoverlapping(
+        self.data as *const u8,
+        dest.as_mut_ptr() as *mut u8,
+        size_of_val(dest),
+      );
+    }
   }
 }
 
+/// A function that optimized code can call directly, bypassing the slow
+/// callback of a `FunctionTemplate`. See `FunctionBuilder::build_fast()`.
+///
+/// The first argument of a fast function is always the receiver (`this`),
+/// declared as `Type::V8Value` and received as a `Local<Object>`. To make a
+/// fast method of a class whose instances wrap native data:
+///
+/// - Give the class's instance template an internal field, and store a
+///   pointer to the native data in it with
+///   `Object::set_aligned_pointer_in_internal_field()`.
+/// - Build the method's template with a `Signature` for the class's
+///   template. V8 then only calls the fast function if the receiver is an
+///   instance of the class, and falls back to the slow callback (which
+///   throws a `TypeError`) otherwise.
+/// - In the fast function, read the pointer back with
+///   `Object::get_aligned_pointer_from_internal_field()`. Without a
+///   signature, check `Object::is_api_wrapper()` and
+///   `Object::internal_field_count()` first, and set
+///   `FastApiCallbackOptions::fallback` if the receiver doesn't match.
+///
+/// Fast functions must not allocate on the V8 heap or call into JavaScript,
+/// so they can't create a `HandleScope`.
 pub trait FastFunction {
   fn args(&self) -> &'static [Type] {
     &[]
@@ -233,3 +399,213 @@ pub trait FastFunction {
   }
   fn function(&self) -> *const c_void;
 }
+
+/// Checks that the signature declared by a `FastFunction` is one that V8 can
+/// actually dispatch to, and that it matches the slow callback it is an
+/// overload of: `length` is the number of arguments the slow callback is
+/// declared to take (0 if unspecified), and `has_signature` is set if the
+/// template checks the receiver. A malformed signature is not rejected by V8,
+/// instead the optimized code ends up reading arguments from the wrong
+/// registers or stack slots, so this is checked (in debug builds) when the
+/// template is built.
+#[cfg(debug_assertions)]
+pub(crate) fn validate_fast_function(
+  fast_function: &dyn FastFunction,
+  length: i32,
+  has_signature: bool,
+) {
+  let args = fast_function.args();
+  let receiver = args.first().map(Type::without_flags);
+  if has_signature && !matches!(receiver, Some(Type::V8Value)) {
+    panic!(
+      "FastFunction of a template with a signature must take the receiver \
+       as its first argument, declared as V8Value"
+    );
+  }
+  let js_args = args
+    .iter()
+    .skip(1)
+    .filter(|arg| !matches!(arg.without_flags(), Type::CallbackOptions))
+    .count();
+  if length > 0 && js_args != length as usize {
+    panic!(
+      "FastFunction takes {} arguments besides the receiver, but the slow \
+       callback is declared to take {}",
+      js_args, length
+    );
+  }
+
+  for (i, arg) in args.iter().enumerate() {
+    match arg.without_flags() {
+      Type::Void => panic!(
+        "FastFunction argument {} is Void, which is only valid as a return \
+         type or as the element type of a Sequence or TypedArray",
+        i
+      ),
+      Type::CallbackOptions if i != args.len() - 1 => panic!(
+        "FastFunction argument {} is CallbackOptions, which must be the last \
+         argument",
+        i
+      ),
+      Type::Sequence(CType::CallbackOptions)
+      | Type::TypedArray(
+        CType::Bool | CType::V8Value | CType::CallbackOptions,
+      )
+      | Type::ArrayBuffer(CType::CallbackOptions) => panic!(
+        "FastFunction argument {} has an invalid element type: {:?}",
+        i, arg
+      ),
+      _ => {}
+    }
+  }
+
+  let return_type = fast_function.return_type();
+  if matches!(return_type, CType::V8Value | CType::CallbackOptions) {
+    panic!("FastFunction has an invalid return type: {:?}", return_type);
+  }
+}
+
+/// A Rust type that V8 can pass as an argument to a fast function. See
+/// [`fast_fn!`](crate::fast_fn).
+pub trait FastArg {
+  const TYPE: Type;
+}
+
+/// A Rust type that a fast function can return. See
+/// [`fast_fn!`](crate::fast_fn).
+pub trait FastReturn {
+  const CTYPE: CType;
+}
+
+/// A Rust type that can be the element type of a [`FastApiTypedArray`].
+pub trait FastTypedArrayElement: Default {
+  const CTYPE: CType;
+}
+
+macro_rules! impl_fast_scalar {
+  ($($ty:ty => $c_type:ident),*) => {
+    $(
+      impl FastArg for $ty {
+        const TYPE: Type = Type::$c_type;
+      }
+
+      impl FastReturn for $ty {
+        const CTYPE: CType = CType::$c_type;
+      }
+    )*
+  };
+}
+
+impl_fast_scalar!(
+  bool => Bool,
+  i32 => Int32,
+  u32 => Uint32,
+  i64 => Int64,
+  u64 => Uint64,
+  f32 => Float32,
+  f64 => Float64
+);
+
+macro_rules! impl_fast_typed_array_element {
+  ($($ty:ty => $c_type:ident),*) => {
+    $(
+      impl FastTypedArrayElement for $ty {
+        const CTYPE: CType = CType::$c_type;
+      }
+    )*
+  };
+}
+
+impl_fast_typed_array_element!(
+  u8 => Uint8,
+  i32 => Int32,
+  u32 => Uint32,
+  i64 => Int64,
+  u64 => Uint64,
+  f32 => Float32,
+  f64 => Float64
+);
+
+impl FastReturn for () {
+  const CTYPE: CType = CType::Void;
+}
+
+impl<'s> FastArg for Local<'s, Value> {
+  const TYPE: Type = Type::V8Value;
+}
+
+impl<'s> FastArg for Local<'s, Object> {
+  const TYPE: Type = Type::V8Value;
+}
+
+impl<'s> FastArg for Local<'s, Array> {
+  const TYPE: Type = Type::Sequence(CType::Void);
+}
+
+impl<T: FastTypedArrayElement> FastArg for *const FastApiTypedArray<T> {
+  const TYPE: Type = Type::TypedArray(T::CTYPE);
+}
+
+impl FastArg for *mut FastApiCallbackOptions {
+  const TYPE: Type = Type::CallbackOptions;
+}
+
+/// Defines an `extern "C"` fast function together with a unit struct that
+/// implements [`FastFunction`] for it. The signature that is reported to V8 is
+/// derived from the Rust types of the arguments and the return value, so the
+/// two can't get out of sync:
+///
+/// ```
+/// v8::fast_fn! {
+///   pub struct FastAdd;
+///   extern "C" fn fast_add(
+///     _recv: v8::Local<v8::Object>,
+///     a: u32,
+///     b: u32,
+///   ) -> u32 {
+///     a + b
+///   }
+/// }
+/// ```
+///
+/// `FastAdd` can then be passed to `FunctionBuilder::build_fast()`. Argument
+/// types must implement [`FastArg`], and the return type must implement
+/// [`FastReturn`].
+#[macro_export]
+macro_rules! fast_fn {
+  (@return_type) => {
+    $crate::fast_api::CType::Void
+  };
+  (@return_type $ret_ty:ty) => {
+    <$ret_ty as $crate::fast_api::FastReturn>::CTYPE
+  };
+  (
+    $(#[$struct_attr:meta])*
+    $struct_vis:vis struct $struct_name:ident;
+    $(#[$fn_attr:meta])*
+    $fn_vis:vis extern "C" fn $fn_name:ident(
+      $($arg_name:tt: $arg_ty:ty),* $(,)?
+    ) $(-> $ret_ty:ty)? $body:block
+  ) => {
+    $(#[$fn_attr])*
+    $fn_vis extern "C" fn $fn_name($($arg_name: $arg_ty),*) $(-> $ret_ty)?
+      $body
+
+    $(#[$struct_attr])*
+    $struct_vis struct $struct_name;
+
+    impl $crate::fast_api::FastFunction for $struct_name {
+      fn args(&self) -> &'static [$crate::fast_api::Type] {
+        &[$(<$arg_ty as $crate::fast_api::FastArg>::TYPE),*]
+      }
+
+      fn return_type(&self) -> $crate::fast_api::CType {
+        $crate::fast_fn!(@return_type $($ret_ty)?)
+      }
+
+      fn function(&self) -> *const ::std::ffi::c_void {
+        $fn_name as _
+      }
+    }
+  };
+}
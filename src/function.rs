@@ -1,4 +1,7 @@
+use std::any::Any;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::ffi::c_void;
 use std::marker::PhantomData;
 use std::ptr::null;
 
@@ -10,6 +13,7 @@ use crate::support::ToCFn;
 use crate::support::UnitType;
 use crate::support::{int, Opaque};
 use crate::Context;
+use crate::External;
 use crate::Function;
 use crate::HandleScope;
 use crate::Local;
@@ -19,6 +23,7 @@ use crate::Signature;
 use crate::String;
 use crate::UniqueRef;
 use crate::Value;
+use crate::Weak;
 
 extern "C" {
   fn v8__Function__New(
@@ -381,6 +386,28 @@ where
   }
 }
 
+/// Owns the closures passed to `Function::new_closure()`, keyed by an id, along
+/// with a weak handle to the function each of them belongs to. An entry is
+/// removed by the weak handle's finalizer once the function has been garbage
+/// collected; the remaining entries are dropped when the isolate is disposed.
+#[derive(Default)]
+struct ClosureRegistry {
+  closures: HashMap<usize, (Box<dyn Any>, Weak<Function>)>,
+  next_id: usize,
+}
+
+fn closure_trampoline<F>(
+  scope: &mut HandleScope,
+  args: FunctionCallbackArguments,
+  rv: ReturnValue,
+) where
+  F: Fn(&mut HandleScope, FunctionCallbackArguments, ReturnValue) + 'static,
+{
+  let data = Local::<External>::try_from(args.data().unwrap()).unwrap();
+  let callback = unsafe { &*(data.value() as *const F) };
+  callback(scope, args, rv)
+}
+
 /// A builder to construct the properties of a Function or FunctionTemplate.
 pub struct FunctionBuilder<'s, T> {
   pub(crate) callback: FunctionCallback,
@@ -398,6 +425,15 @@ impl<'s, T> FunctionBuilder<'s, T> {
     Self::new_raw(callback.map_fn_to())
   }
 
+  /// Create a new FunctionBuilder from a raw `extern "C"` callback.
+  ///
+  /// The callback is registered with V8 as-is, without the adapter that
+  /// `new()` generates to construct a `CallbackScope`,
+  /// `FunctionCallbackArguments` and `ReturnValue` on every call. The callback
+  /// can opt into those wrappers itself via `CallbackScope::new()`,
+  /// `FunctionCallbackArguments::from_function_callback_info()` and
+  /// `ReturnValue::from_function_callback_info()`, or skip the ones it doesn't
+  /// need, which is useful for very frequently called functions.
   pub fn new_raw(callback: FunctionCallback) -> Self {
     Self {
       callback,
@@ -468,6 +504,8 @@ impl Function {
     FunctionBuilder::new(callback)
   }
 
+  /// Create a FunctionBuilder to configure a Function with a raw callback.
+  /// This is the same as FunctionBuilder::<Function>::new_raw().
   pub fn builder_raw<'s>(
     callback: FunctionCallback,
   ) -> FunctionBuilder<'s, Self> {
@@ -483,6 +521,8 @@ impl Function {
     Self::builder(callback).build(scope)
   }
 
+  /// Create a function in the current execution context
+  /// for a given raw FunctionCallback. See `FunctionBuilder::new_raw()`.
   pub fn new_raw<'s>(
     scope: &mut HandleScope<'s>,
     callback: FunctionCallback,
@@ -490,6 +530,56 @@ impl Function {
     Self::builder_raw(callback).build(scope)
   }
 
+  /// Create a function in the current execution context from a Rust closure.
+  ///
+  /// Unlike the callbacks accepted by `Function::new()`, the closure may
+  /// capture state. It is dropped once the function has been garbage
+  /// collected (or when the isolate is disposed), and is passed to the
+  /// function as its associated data, so `FunctionCallbackArguments::data()`
+  /// is not available to the closure.
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn new_closure<'s, F>(
+    scope: &mut HandleScope<'s>,
+    callback: F,
+  ) -> Option<Local<'s, Function>>
+  where
+    F: Fn(&mut HandleScope, FunctionCallbackArguments, ReturnValue) + 'static,
+  {
+    let callback = Box::new(callback);
+    let data = External::new(scope, &*callback as *const F as *mut c_void);
+    let function = Self::builder(closure_trampoline::<F>)
+      .data(data.into())
+      .build(scope)?;
+
+    if scope.get_slot::<ClosureRegistry>().is_none() {
+      scope.set_slot(ClosureRegistry::default());
+    }
+    let id = {
+      let registry = scope.get_slot_mut::<ClosureRegistry>().unwrap();
+      let id = registry.next_id;
+      registry.next_id += 1;
+      id
+    };
+    let weak = Weak::with_finalizer(
+      scope,
+      function,
+      Box::new(move |isolate| {
+        // Move the entry out of the slot before dropping it, since dropping
+        // the `Weak` accesses the isolate again.
+        let _entry = isolate
+          .get_slot_mut::<ClosureRegistry>()
+          .and_then(|registry| registry.closures.remove(&id));
+      }),
+    );
+    scope
+      .get_slot_mut::<ClosureRegistry>()
+      .unwrap()
+      .closures
+      .insert(id, (callback, weak));
+    Some(function)
+  }
+
   pub fn call<'s>(
     &self,
     scope: &mut HandleScope<'s>,
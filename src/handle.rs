@@ -1,17 +1,21 @@
 use std::borrow::Borrow;
 use std::cell::Cell;
+use std::convert::TryFrom;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::marker::PhantomData;
 use std::mem::forget;
 use std::mem::transmute;
+use std::mem::ManuallyDrop;
 use std::ops::Deref;
+use std::ptr;
 use std::ptr::NonNull;
 
 use libc::c_void;
 
 use crate::support::Opaque;
 use crate::Data;
+use crate::DataError;
 use crate::HandleScope;
 use crate::Isolate;
 use crate::IsolateHandle;
@@ -100,6 +104,27 @@ impl<'s, T> Local<'s, T> {
     transmute(other)
   }
 
+  /// Converts this handle into a handle of one of its super types. This is
+  /// the same as `Into::into()`, but names the target type, which helps
+  /// type inference, e.g. `object.upcast::<Value>()`.
+  pub fn upcast<B>(self) -> Local<'s, B>
+  where
+    Local<'s, B>: From<Self>,
+  {
+    self.into()
+  }
+
+  /// Tries to convert this handle into a handle of one of its sub types,
+  /// checking the type of the underlying value. This is the same as
+  /// `TryFrom::try_from()`, but names the target type, e.g.
+  /// `value.try_cast::<Object>()`.
+  pub fn try_cast<B>(self) -> Result<Local<'s, B>, DataError>
+  where
+    Local<'s, B>: TryFrom<Self, Error = DataError>,
+  {
+    Local::<B>::try_from(self)
+  }
+
   pub(crate) unsafe fn from_raw(ptr: *const T) -> Option<Self> {
     NonNull::new(ptr as *mut _).map(|nn| Self::from_non_null(nn))
   }
@@ -192,8 +217,53 @@ impl<T> Global<T> {
   pub fn open<'a>(&'a self, scope: &mut Isolate) -> &'a T {
     Handle::open(self, scope)
   }
+
+  /// Like `open()`, but only requires a shared reference to the isolate. This
+  /// allows reading from the handle without entering a `HandleScope` (or even
+  /// having mutable access to the isolate), e.g. to compute an identity hash
+  /// or compare handles in bookkeeping code:
+  ///
+  /// ```ignore
+  /// let hash = global.get(isolate).get_identity_hash();
+  /// ```
+  ///
+  /// Methods that create new handles take a `HandleScope`, so they can't be
+  /// called by accident this way.
+  ///
+  /// # Panics
+  ///
+  /// Panics if this handle is not hosted by `isolate`, or if the isolate that
+  /// hosts it has been disposed.
+  pub fn get<'a>(&'a self, isolate: &Isolate) -> &'a T {
+    assert!(
+      self.is_hosted_by(isolate),
+      "attempt to use Handle in an Isolate that is not its host"
+    );
+    unsafe { self.data.as_ref() }
+  }
+
+  /// Returns true if this handle is hosted by `isolate`. Returns false if the
+  /// handle belongs to another isolate, including when its isolate has been
+  /// disposed.
+  pub fn is_hosted_by(&self, isolate: &Isolate) -> bool {
+    // SAFETY: `Global` is not `Send`, so we're on the isolate's thread.
+    let host = unsafe { self.isolate_handle.get_isolate_ptr() };
+    ptr::eq(host, isolate)
+  }
+
+  /// Converts this `Global` into a `SendableGlobal`, which can be moved to,
+  /// and dropped on, other threads.
+  pub fn into_sendable(self) -> SendableGlobal<T> {
+    let this = ManuallyDrop::new(self);
+    SendableGlobal {
+      data: this.data,
+      isolate_handle: unsafe { ptr::read(&this.isolate_handle) },
+    }
+  }
 }
 
+/// Cloning a `Global` does not require a `HandleScope`; it only creates a new
+/// global handle pointing at the same value.
 impl<T> Clone for Global<T> {
   fn clone(&self) -> Self {
     let HandleInfo { data, host } = self.get_handle_info();
@@ -215,6 +285,58 @@ impl<T> Drop for Global<T> {
   }
 }
 
+/// A `Global` that can be sent to other threads, e.g. when it is captured by
+/// a future that may be polled on any thread. The value it refers to can't be
+/// accessed until it is converted back with `SendableGlobal::into_global()` on
+/// the isolate's thread.
+///
+/// V8 requires global handles to be released on the thread that owns the
+/// isolate. When a `SendableGlobal` is dropped, releasing the handle is
+/// therefore deferred until the isolate next runs JavaScript code (it's done
+/// from an interrupt), or until the isolate is disposed.
+#[derive(Debug)]
+pub struct SendableGlobal<T> {
+  data: NonNull<T>,
+  isolate_handle: IsolateHandle,
+}
+
+unsafe impl<T> Send for SendableGlobal<T> {}
+unsafe impl<T> Sync for SendableGlobal<T> {}
+
+impl<T> SendableGlobal<T> {
+  /// Converts this handle back into a `Global`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the handle is not hosted by `isolate`, or if the isolate that
+  /// hosts it has been disposed.
+  pub fn into_global(self, isolate: &mut Isolate) -> Global<T> {
+    let host = HandleHost::from(&self.isolate_handle);
+    host.assert_match_isolate(isolate);
+    let this = ManuallyDrop::new(self);
+    Global {
+      data: this.data,
+      isolate_handle: unsafe { ptr::read(&this.isolate_handle) },
+    }
+  }
+
+  /// Returns a handle to the isolate that hosts this global handle.
+  pub fn isolate_handle(&self) -> &IsolateHandle {
+    &self.isolate_handle
+  }
+}
+
+impl<T> Drop for SendableGlobal<T> {
+  fn drop(&mut self) {
+    self.isolate_handle.defer_global_reset(self.data.cast());
+  }
+}
+
+/// Releases a global handle. Must be called on the isolate's thread.
+pub(crate) unsafe fn reset_global(data: NonNull<Data>) {
+  v8__Global__Reset(data.as_ptr())
+}
+
 /// An implementation of [`Handle`] that can be constructed unsafely from a
 /// reference.
 pub(crate) struct UnsafeRefHandle<'a, T> {
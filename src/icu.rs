@@ -1,5 +1,27 @@
+use std::ffi::c_void;
+use std::os::raw::c_char;
+
 extern "C" {
   fn udata_setCommonData_71(this: *const u8, error_code: *mut i32);
+  fn icu__Locale__SetDefault(data: *const c_char, length: usize) -> i32;
+  fn icu__Locale__GetDefault(callback: LocaleCallback, arg: *mut c_void);
+  fn icu__Locale__ForEachAvailableLocale(
+    callback: LocaleCallback,
+    arg: *mut c_void,
+  );
+}
+
+type LocaleCallback =
+  extern "C" fn(arg: *mut c_void, data: *const c_char, length: usize);
+
+extern "C" fn push_locale(
+  arg: *mut c_void,
+  data: *const c_char,
+  length: usize,
+) {
+  let locales = unsafe { &mut *(arg as *mut Vec<String>) };
+  let tag = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
+  locales.push(String::from_utf8_lossy(tag).into_owned());
 }
 
 /// This function bypasses the normal ICU data loading process and allows you to force ICU's system
@@ -46,3 +68,58 @@ pub fn set_common_data_71(data: &'static [u8]) -> Result<(), i32> {
     Err(error_code)
   }
 }
+
+/// Sets the default locale of ICU, which is what V8 uses as the default
+/// locale of `Intl` APIs (e.g. `new Intl.DateTimeFormat().resolvedOptions()`)
+/// and methods like `toLocaleString()`. `locale` is a BCP 47 language tag,
+/// like `"de-CH"`.
+///
+/// The default locale is process-wide. Isolates that already exist only pick
+/// it up after `Isolate::locale_configuration_change_notification()` has been
+/// called. To override the default locale of a single isolate, use
+/// `Isolate::set_default_locale()` instead.
+///
+/// # Safety
+///
+/// ICU doesn't synchronize access to its default locale. This must not be
+/// called while another thread may be using ICU, which includes running
+/// JavaScript in any isolate. It should be called before any isolate is
+/// created.
+pub unsafe fn set_default_locale(locale: &str) -> Result<(), i32> {
+  let error_code =
+    icu__Locale__SetDefault(locale.as_ptr() as *const c_char, locale.len());
+  // ICU uses negative error codes for warnings.
+  if error_code <= 0 {
+    Ok(())
+  } else {
+    Err(error_code)
+  }
+}
+
+/// Returns the default locale of ICU as a BCP 47 language tag, e.g.
+/// `"en-US"`. See `set_default_locale()`.
+pub fn get_default_locale() -> String {
+  let mut locales = Vec::new();
+  unsafe {
+    icu__Locale__GetDefault(
+      push_locale,
+      &mut locales as *mut Vec<String> as *mut c_void,
+    )
+  };
+  locales.pop().unwrap_or_else(|| "und".to_owned())
+}
+
+/// Returns the locales for which the loaded ICU data has locale data, as BCP
+/// 47 language tags. This is the set of locales that `Intl` APIs like
+/// `Intl.DateTimeFormat.supportedLocalesOf()` can support, so it can be used
+/// to negotiate e.g. `navigator.languages` against the user's preferences.
+pub fn get_available_locales() -> Vec<String> {
+  let mut locales = Vec::new();
+  unsafe {
+    icu__Locale__ForEachAvailableLocale(
+      push_locale,
+      &mut locales as *mut Vec<String> as *mut c_void,
+    )
+  };
+  locales
+}
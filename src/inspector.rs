@@ -21,8 +21,10 @@ use crate::support::RustVTable;
 use crate::support::UniquePtr;
 use crate::support::UniqueRef;
 use crate::Context;
+use crate::HandleScope;
 use crate::Isolate;
 use crate::Local;
+use std::ffi::c_void;
 use std::fmt::{self, Debug, Formatter};
 
 extern "C" {
@@ -91,6 +93,28 @@ extern "C" {
   fn v8_inspector__StringBuffer__create(
     source: StringView,
   ) -> UniquePtr<StringBuffer>;
+  fn v8_inspector__StringBuffer__NEW(
+    source: StringView,
+    owner: *mut c_void,
+    drop_owner: unsafe extern "C" fn(*mut c_void),
+  ) -> UniquePtr<StringBuffer>;
+
+  fn v8_inspector__IsCBORMessage(data: *const u8, len: usize) -> bool;
+  fn v8_inspector__ConvertJSONToCBOR(
+    json: StringView,
+    extend: ExtendBytesCallback,
+    cbor: *mut c_void,
+    error_message: *mut c_void,
+    error_pos: *mut usize,
+  ) -> bool;
+  fn v8_inspector__ConvertCBORToJSON(
+    data: *const u8,
+    len: usize,
+    extend: ExtendBytesCallback,
+    json: *mut c_void,
+    error_message: *mut c_void,
+    error_pos: *mut usize,
+  ) -> bool;
 
   fn v8_inspector__V8Inspector__DELETE(this: &mut V8Inspector);
   fn v8_inspector__V8Inspector__create(
@@ -109,7 +133,25 @@ extern "C" {
     context: *const Context,
     contextGroupId: int,
     humanReadableName: StringView,
+    origin: StringView,
+    auxData: StringView,
+    hasMemoryOnConsole: bool,
+  );
+  fn v8_inspector__V8Inspector__contextDestroyed(
+    this: *mut V8Inspector,
+    context: *const Context,
+  );
+  fn v8_inspector__V8Inspector__resetContextGroup(
+    this: *mut V8Inspector,
+    contextGroupId: int,
   );
+  fn v8_inspector__V8Inspector__contextById(
+    this: *mut V8Inspector,
+    contextId: int,
+  ) -> *const Context;
+  fn v8_inspector__V8ContextInfo__executionContextId(
+    context: *const Context,
+  ) -> int;
 }
 
 #[no_mangle]
@@ -637,16 +679,15 @@ impl Drop for V8InspectorSession {
 
 // TODO: in C++, this class is intended to be user-extensible, just like
 // like `Task`, `Client`, `Channel`. In Rust this would ideally also be the
-// case, but currently to obtain a `UniquePtr<StringBuffer>` is by making a
-// copy using `StringBuffer::create()`.
+// case, but currently a `UniquePtr<StringBuffer>` can only be obtained by
+// making a copy using `StringBuffer::create()`, or by handing over an owned
+// buffer with `StringBuffer::from_vec8()` or `StringBuffer::from_vec16()`.
 #[repr(C)]
 #[derive(Debug)]
 pub struct StringBuffer {
   _cxx_vtable: CxxVTable,
 }
 
-// TODO: make it possible to obtain a `UniquePtr<StringBuffer>` directly from
-// an owned `Vec<u8>` or `Vec<u16>`,
 impl StringBuffer {
   // The C++ class definition does not declare `string()` to be a const method,
   // therefore we declare self as mutable here.
@@ -660,6 +701,39 @@ impl StringBuffer {
   pub fn create(source: StringView) -> UniquePtr<StringBuffer> {
     unsafe { v8_inspector__StringBuffer__create(source) }
   }
+
+  /// Creates a `StringBuffer` that takes ownership of `buffer` rather than
+  /// copying it, which avoids copying large protocol messages. The buffer
+  /// holds Latin-1 text or, if it's a binary protocol message, CBOR.
+  pub fn from_vec8(buffer: Vec<u8>) -> UniquePtr<StringBuffer> {
+    Self::from_owned(buffer, |buffer| StringView::from(&buffer[..]))
+  }
+
+  /// Like `from_vec8()`, but the buffer holds UTF-16 text.
+  pub fn from_vec16(buffer: Vec<u16>) -> UniquePtr<StringBuffer> {
+    Self::from_owned(buffer, |buffer| StringView::from(&buffer[..]))
+  }
+
+  fn from_owned<T: Send + 'static>(
+    owner: T,
+    view: impl FnOnce(&T) -> StringView,
+  ) -> UniquePtr<StringBuffer> {
+    unsafe extern "C" fn drop_owner<T>(owner: *mut c_void) {
+      drop(Box::from_raw(owner as *mut T));
+    }
+
+    // The owner is dropped when the `StringBuffer` is deleted, so the view
+    // stays valid for as long as the `StringBuffer` is alive.
+    let owner = Box::into_raw(Box::new(owner));
+    let view = view(unsafe { &*owner });
+    unsafe {
+      v8_inspector__StringBuffer__NEW(
+        view,
+        owner as *mut c_void,
+        drop_owner::<T>,
+      )
+    }
+  }
 }
 
 impl Drop for StringBuffer {
@@ -669,6 +743,108 @@ impl Drop for StringBuffer {
 }
 
 unsafe impl Send for StringBuffer {}
+
+type ExtendBytesCallback =
+  unsafe extern "C" fn(vec: *mut c_void, data: *const u8, len: usize);
+
+unsafe extern "C" fn extend_bytes(
+  vec: *mut c_void,
+  data: *const u8,
+  len: usize,
+) {
+  let vec = &mut *(vec as *mut Vec<u8>);
+  if len > 0 {
+    vec.extend_from_slice(slice::from_raw_parts(data, len));
+  }
+}
+
+/// An error that occurred while converting a protocol message between JSON
+/// and CBOR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolMessageError {
+  message: string::String,
+  position: usize,
+}
+
+impl ProtocolMessageError {
+  fn new(message: Vec<u8>, position: usize) -> Self {
+    Self {
+      message: string::String::from_utf8_lossy(&message).into_owned(),
+      position,
+    }
+  }
+
+  /// A description of the error.
+  pub fn message(&self) -> &str {
+    &self.message
+  }
+
+  /// The offset in the input at which the error occurred.
+  pub fn position(&self) -> usize {
+    self.position
+  }
+}
+
+impl fmt::Display for ProtocolMessageError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} at position {}", self.message, self.position)
+  }
+}
+
+impl std::error::Error for ProtocolMessageError {}
+
+/// Returns true if `message` is a Chrome DevTools Protocol message in the
+/// binary (CBOR) format, rather than JSON.
+///
+/// `V8InspectorSession::dispatch_protocol_message()` accepts both formats.
+/// Once a session has received a binary message, it also sends its responses
+/// and notifications in the binary format.
+pub fn is_cbor_message(message: &[u8]) -> bool {
+  unsafe { v8_inspector__IsCBORMessage(message.as_ptr(), message.len()) }
+}
+
+/// Converts a Chrome DevTools Protocol message from JSON to the binary
+/// (CBOR) format that V8 uses internally.
+pub fn json_to_cbor(json: StringView) -> Result<Vec<u8>, ProtocolMessageError> {
+  let mut cbor = Vec::new();
+  let mut error_message = Vec::new();
+  let mut error_pos = 0;
+  unsafe {
+    if v8_inspector__ConvertJSONToCBOR(
+      json,
+      extend_bytes,
+      &mut cbor as *mut Vec<u8> as *mut c_void,
+      &mut error_message as *mut Vec<u8> as *mut c_void,
+      &mut error_pos,
+    ) {
+      Ok(cbor)
+    } else {
+      Err(ProtocolMessageError::new(error_message, error_pos))
+    }
+  }
+}
+
+/// Converts a Chrome DevTools Protocol message from the binary (CBOR) format
+/// to UTF-8 encoded JSON.
+pub fn cbor_to_json(cbor: &[u8]) -> Result<Vec<u8>, ProtocolMessageError> {
+  let mut json = Vec::new();
+  let mut error_message = Vec::new();
+  let mut error_pos = 0;
+  unsafe {
+    if v8_inspector__ConvertCBORToJSON(
+      cbor.as_ptr(),
+      cbor.len(),
+      extend_bytes,
+      &mut json as *mut Vec<u8> as *mut c_void,
+      &mut error_message as *mut Vec<u8> as *mut c_void,
+      &mut error_pos,
+    ) {
+      Ok(json)
+    } else {
+      Err(ProtocolMessageError::new(error_message, error_pos))
+    }
+  }
+}
 use std::iter::ExactSizeIterator;
 use std::iter::IntoIterator;
 use std::marker::PhantomData;
@@ -930,23 +1106,123 @@ impl V8Inspector {
     }
   }
 
-  /// Note: this method deviates from the C++ API here because it's a lot of
-  /// work to bind the V8ContextInfo, which is not used elsewhere.
+  /// Registers a context with the inspector, so that it shows up in the
+  /// debugger. Shorthand for `context_created_with_info()` with a
+  /// `V8ContextInfo` that only has a name.
   pub fn context_created(
     &mut self,
     context: Local<Context>,
     context_group_id: i32,
     human_readable_name: StringView,
   ) {
+    self.context_created_with_info(&V8ContextInfo::new(
+      context,
+      context_group_id,
+      human_readable_name,
+    ))
+  }
+
+  /// Registers a context with the inspector, so that it shows up in the
+  /// debugger. Sessions that are connected to the context's group are sent a
+  /// `Runtime.executionContextCreated` notification.
+  ///
+  /// The name of a registered context can't be changed; to rename it, call
+  /// `context_destroyed()` and register it again. Note that the context gets
+  /// a new execution context id when that happens.
+  pub fn context_created_with_info(&mut self, info: &V8ContextInfo) {
     unsafe {
       v8_inspector__V8Inspector__contextCreated(
         self,
-        &*context,
-        context_group_id,
-        human_readable_name,
+        &*info.context,
+        info.context_group_id,
+        info.human_readable_name,
+        info.origin,
+        info.aux_data,
+        info.has_memory_on_console,
       )
     }
   }
+
+  /// Unregisters a context that was registered with `context_created()`.
+  /// Should be called before the context is disposed of by the embedder.
+  pub fn context_destroyed(&mut self, context: Local<Context>) {
+    unsafe { v8_inspector__V8Inspector__contextDestroyed(self, &*context) }
+  }
+
+  /// Unregisters all contexts in a context group, and discards the state that
+  /// the inspector keeps for the group, e.g. when a page is navigated.
+  pub fn reset_context_group(&mut self, context_group_id: i32) {
+    unsafe {
+      v8_inspector__V8Inspector__resetContextGroup(self, context_group_id)
+    }
+  }
+
+  /// Returns the registered context with the given execution context id, or
+  /// `None` if there is no such context.
+  pub fn context_by_id<'s>(
+    &mut self,
+    scope: &mut HandleScope<'s, ()>,
+    context_id: i32,
+  ) -> Option<Local<'s, Context>> {
+    unsafe {
+      scope.cast_local(|_| {
+        v8_inspector__V8Inspector__contextById(self, context_id)
+      })
+    }
+  }
+
+  /// Returns the execution context id of a context, which identifies it in
+  /// the inspector protocol. Contexts that haven't been registered with
+  /// `context_created()` have an id too, but it isn't known to the debugger.
+  pub fn context_id(context: Local<Context>) -> i32 {
+    unsafe { v8_inspector__V8ContextInfo__executionContextId(&*context) }
+  }
+}
+
+/// Describes a context that is registered with the inspector. See
+/// `V8Inspector::context_created_with_info()`.
+#[derive(Clone, Copy, Debug)]
+pub struct V8ContextInfo<'s, 'a> {
+  pub context: Local<'s, Context>,
+  pub context_group_id: i32,
+  pub human_readable_name: StringView<'a>,
+  /// The origin of the context, e.g. the URL of the page it belongs to.
+  pub origin: StringView<'a>,
+  /// JSON that is passed to the debugger as the `auxData` of the execution
+  /// context. DevTools uses it to tell which context is the main context of
+  /// a page, see `is_default()`.
+  pub aux_data: StringView<'a>,
+  /// Whether the context has a `console.memory` property.
+  pub has_memory_on_console: bool,
+}
+
+impl<'s, 'a> V8ContextInfo<'s, 'a> {
+  pub fn new(
+    context: Local<'s, Context>,
+    context_group_id: i32,
+    human_readable_name: StringView<'a>,
+  ) -> Self {
+    Self {
+      context,
+      context_group_id,
+      human_readable_name,
+      origin: StringView::empty(),
+      aux_data: StringView::empty(),
+      has_memory_on_console: false,
+    }
+  }
+
+  /// Sets `aux_data` to mark the context as the default (main) context of
+  /// its group, or as an additional context, e.g. for a `ShadowRealm`.
+  pub fn is_default(mut self, is_default: bool) -> Self {
+    let aux_data: &'static [u8] = if is_default {
+      br#"{"isDefault":true}"#
+    } else {
+      br#"{"isDefault":false}"#
+    };
+    self.aux_data = StringView::from(aux_data);
+    self
+  }
 }
 
 impl Drop for V8Inspector {
@@ -1,11 +1,24 @@
 use crate::PromiseResolver;
+use crate::SamplingHeapProfilerFlags;
 // Copyright 2019-2021 the Deno authors. All rights reserved. MIT license.
+use crate::embedder_graph::BuildEmbedderGraphCallback;
+use crate::fast_api::CFunctionInfo;
+use crate::fast_api::CFunctionInfoKey;
+use crate::fast_api::FastFunction;
+use crate::fast_api::OwnedCFunctionInfo;
 use crate::function::FunctionCallbackInfo;
+use crate::handle::reset_global;
 use crate::handle::FinalizerMap;
 use crate::isolate_create_params::raw;
 use crate::isolate_create_params::CreateParams;
+use crate::jit_code_event::JitCodeEventHandler;
+use crate::jit_code_event::JitCodeEventOptions;
+use crate::measure_memory::into_raw_delegate;
+use crate::measure_memory::MeasureMemoryDelegate;
+use crate::measure_memory::MeasureMemoryExecution;
 use crate::promise::PromiseRejectMessage;
 use crate::scope::data::ScopeData;
+use crate::support::int;
 use crate::support::MapFnFrom;
 use crate::support::MapFnTo;
 use crate::support::Opaque;
@@ -21,20 +34,26 @@ use crate::FixedArray;
 use crate::Function;
 use crate::HandleScope;
 use crate::Local;
+use crate::Locker;
 use crate::Message;
 use crate::Module;
 use crate::Object;
 use crate::Promise;
+use crate::Referrer;
 use crate::String;
 use crate::Value;
 
 use std::any::Any;
 use std::any::TypeId;
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::convert::TryInto;
 use std::ffi::c_void;
 use std::fmt::{self, Debug, Formatter};
 use std::hash::BuildHasher;
 use std::hash::Hasher;
+use std::io;
+use std::io::Write;
 use std::mem::align_of;
 use std::mem::forget;
 use std::mem::needs_drop;
@@ -42,13 +61,16 @@ use std::mem::size_of;
 use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::ops::Range;
 use std::os::raw::c_char;
 use std::ptr;
 use std::ptr::drop_in_place;
 use std::ptr::null_mut;
 use std::ptr::NonNull;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 /// Policy for running microtasks:
 ///   - explicit: microtasks are invoked with the
@@ -63,6 +85,20 @@ pub enum MicrotasksPolicy {
   Auto = 2,
 }
 
+/// Memory pressure level for `Isolate::memory_pressure_notification()`.
+///   - none: no memory pressure.
+///   - moderate: the embedder is running low on memory, and V8 should reduce
+///               its memory usage if it can do so cheaply.
+///   - critical: the embedder is about to run out of memory, and V8 should
+///               free as much memory as possible, even at the cost of latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum MemoryPressureLevel {
+  None = 0,
+  Moderate = 1,
+  Critical = 2,
+}
+
 /// PromiseHook with type Init is called when a new promise is
 /// created. When a new promise is created as part of the chain in the
 /// case of Promise.then or in the intermediate promises created by
@@ -91,6 +127,17 @@ pub type MessageCallback = extern "C" fn(Local<Message>, Local<Value>);
 pub type PromiseHook =
   extern "C" fn(PromiseHookType, Local<Promise>, Local<Value>);
 
+/// A closure passed to `Isolate::set_promise_hook_closure()`. It receives the
+/// promise, and for `PromiseHookType::Init` the parent promise (or undefined).
+pub type PromiseHookClosure = Box<
+  dyn for<'s> FnMut(
+    &mut HandleScope<'s>,
+    PromiseHookType,
+    Local<'s, Promise>,
+    Local<'s, Value>,
+  ),
+>;
+
 pub type PromiseRejectCallback = extern "C" fn(PromiseRejectMessage);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -136,6 +183,19 @@ pub type HostInitializeImportMetaObjectCallback =
 /// this promise with the exception. If the promise creation itself
 /// fails (e.g. due to stack overflow), the embedder must propagate
 /// that exception by returning an empty MaybeLocal.
+///
+/// `Isolate::set_host_import_module_dynamically_referrer_callback()` takes a
+/// Rust function with the following signature instead, which receives the
+/// referrer as a typed [`Referrer`]:
+///
+/// ```ignore
+/// fn callback<'s>(
+///   scope: &mut HandleScope<'s>,
+///   referrer: Referrer<'s>,
+///   specifier: Local<'s, String>,
+///   import_assertions: Local<'s, FixedArray>,
+/// ) -> Option<Local<'s, Promise>>;
+/// ```
 pub type HostImportModuleDynamicallyCallback = extern "C" fn(
   Local<Context>,
   Local<Data>,
@@ -160,6 +220,64 @@ pub type HostCreateShadowRealmContextCallback =
 pub type InterruptCallback =
   extern "C" fn(isolate: &mut Isolate, data: *mut c_void);
 
+/// A closure passed to `IsolateHandle::request_interrupt_closure()`.
+pub type InterruptClosure = Box<dyn FnOnce(&mut Isolate) + Send>;
+
+/// A closure passed to `Isolate::enqueue_microtask_closure()`.
+pub type MicrotaskClosure = Box<dyn FnOnce(&mut Isolate)>;
+
+/// A callback that is invoked after the microtask queue of an isolate has been
+/// run. See `Isolate::add_microtasks_completed_callback()`.
+pub type MicrotasksCompletedCallback =
+  extern "C" fn(isolate: &mut Isolate, data: *mut c_void);
+
+bitflags! {
+  /// The types of garbage collection. Used to filter the collections that a
+  /// GC callback is invoked for, and to tell the callback which kind of
+  /// collection is happening.
+  #[repr(transparent)]
+  pub struct GCType: int {
+    const SCAVENGE = 1 << 0;
+    const MINOR_MARK_COMPACT = 1 << 1;
+    const MARK_SWEEP_COMPACT = 1 << 2;
+    const INCREMENTAL_MARKING = 1 << 3;
+    const PROCESS_WEAK_CALLBACKS = 1 << 4;
+    const ALL = Self::SCAVENGE.bits
+      | Self::MINOR_MARK_COMPACT.bits
+      | Self::MARK_SWEEP_COMPACT.bits
+      | Self::INCREMENTAL_MARKING.bits
+      | Self::PROCESS_WEAK_CALLBACKS.bits;
+  }
+}
+
+bitflags! {
+  /// Additional information about a garbage collection, passed to GC
+  /// callbacks.
+  #[derive(Default)]
+  #[repr(transparent)]
+  pub struct GCCallbackFlags: int {
+    const NONE = 0;
+    const CONSTRUCT_RETAINED_OBJECT_INFOS = 1 << 1;
+    /// The collection was forced, e.g. by `low_memory_notification()` or
+    /// `gc()` with the `--expose-gc` flag.
+    const FORCED = 1 << 2;
+    const SYNCHRONOUS_PHANTOM_CALLBACK_PROCESSING = 1 << 3;
+    const COLLECT_ALL_AVAILABLE_GARBAGE = 1 << 4;
+    const COLLECT_ALL_EXTERNAL_MEMORY = 1 << 5;
+    const SCHEDULE_IDLE_GARBAGE_COLLECTION = 1 << 6;
+  }
+}
+
+/// Identifies a GC callback that was added with
+/// `Isolate::add_gc_prologue_callback()` or
+/// `Isolate::add_gc_epilogue_callback()`, so that it can be removed again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GCCallbackId(usize);
+
+/// A closure passed to `Isolate::add_near_heap_limit_closure()`. It receives
+/// the current and the initial heap limit, and returns the new heap limit.
+pub type NearHeapLimitClosure = Box<dyn FnMut(usize, usize) -> usize>;
+
 pub type NearHeapLimitCallback = extern "C" fn(
   data: *mut c_void,
   current_heap_limit: usize,
@@ -175,10 +293,160 @@ pub struct OomDetails {
 pub type OomErrorCallback =
   extern "C" fn(location: *const c_char, details: &OomDetails);
 
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogEventStatus {
+  Start = 0,
+  End = 1,
+  Stamp = 2,
+}
+
+/// Receives V8's internal log events, such as timer events (enabled with the
+/// `--log-timer-events` flag). See `Isolate::set_event_logger()`.
+pub type LogEventCallback =
+  extern "C" fn(name: *const c_char, status: LogEventStatus);
+
+/// Called when an exception is thrown that isn't going to be caught, if V8 was
+/// started with the `--abort-on-uncaught-exception` flag. V8 aborts the
+/// process if the callback returns true.
+pub type AbortOnUncaughtExceptionCallback =
+  extern "C" fn(isolate: &mut Isolate) -> bool;
+
+/// Options for `Isolate::take_heap_snapshot_with_options()`.
+#[derive(Default)]
+pub struct HeapSnapshotOptions<'a> {
+  /// The preferred size in bytes of the chunks that the serialized snapshot is
+  /// passed to the callback in. V8 uses 1 KB chunks by default; larger chunks
+  /// reduce the per-chunk overhead when streaming very large snapshots.
+  pub chunk_size: Option<usize>,
+  /// Called periodically while the snapshot is being taken, with the number of
+  /// objects processed so far and the total number of objects. Taking the
+  /// snapshot is aborted if the callback returns false.
+  pub progress: Option<&'a mut dyn FnMut(u32, u32) -> bool>,
+}
+
+/// The id that the heap profiler assigns to a heap object. Ids are stable for
+/// the lifetime of an object, even when it is moved by the garbage collector,
+/// so the same object has the same id in all heap snapshots of an isolate.
+pub type SnapshotObjectId = u32;
+
+/// A sample of the heap stats, reported by `Isolate::get_heap_stats()` for
+/// each time interval in which objects were allocated or freed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStatsUpdate {
+  /// The index of the time interval that was updated.
+  pub index: u32,
+  /// The number of objects that are still alive in the time interval.
+  pub count: u32,
+  /// The total size in bytes of those objects.
+  pub size: u32,
+}
+
+/// A heap snapshot that is retained by the heap profiler of an isolate. See
+/// `Isolate::take_retained_heap_snapshot()`.
+///
+/// Retained snapshots use memory until they are deleted with
+/// `Isolate::delete_heap_snapshot()` or `Isolate::delete_all_heap_snapshots()`,
+/// or until the isolate is disposed.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HeapSnapshot(Opaque);
+
+impl HeapSnapshot {
+  /// Returns the unique id of the snapshot.
+  pub fn get_uid(&self) -> u32 {
+    unsafe { v8__HeapSnapshot__GetUid(self) }
+  }
+
+  /// Returns the number of nodes (heap objects) in the snapshot.
+  pub fn get_nodes_count(&self) -> usize {
+    unsafe { v8__HeapSnapshot__GetNodesCount(self) as usize }
+  }
+
+  /// Returns the highest id of any JavaScript object in the snapshot. Objects
+  /// with a higher id were allocated after the snapshot was taken.
+  pub fn get_max_snapshot_js_object_id(&self) -> SnapshotObjectId {
+    unsafe { v8__HeapSnapshot__GetMaxSnapshotJSObjectId(self) }
+  }
+
+  /// Serializes the snapshot as JSON. The callback is invoked one or more
+  /// times with chunks of the serialized snapshot, like the callback of
+  /// `Isolate::take_heap_snapshot()`, and can return false to abort. Returns
+  /// false if serialization was aborted.
+  pub fn serialize<F>(&self, chunk_size: Option<usize>, mut callback: F) -> bool
+  where
+    F: FnMut(&[u8]) -> bool,
+  {
+    let arg = &mut callback as *mut F as *mut c_void;
+    unsafe {
+      v8__HeapSnapshot__Serialize(
+        self,
+        heap_snapshot_trampoline::<F>,
+        arg,
+        heap_snapshot_chunk_size(chunk_size),
+      )
+    }
+  }
+
+  /// Serializes the snapshot as JSON into `writer`, e.g. a `.heapsnapshot`
+  /// file.
+  pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+    write_heap_snapshot_chunks(writer, |callback| {
+      self.serialize(None, callback)
+    })
+  }
+}
+
+/// Streams a heap snapshot into `writer`. `serialize` is called with a
+/// callback that writes each chunk, and returns false if serialization
+/// failed or was aborted.
+fn write_heap_snapshot_chunks(
+  writer: &mut impl Write,
+  serialize: impl FnOnce(&mut dyn FnMut(&[u8]) -> bool) -> bool,
+) -> io::Result<()> {
+  let mut result = Ok(());
+  let completed = serialize(&mut |chunk| match writer.write_all(chunk) {
+    Ok(()) => true,
+    Err(err) => {
+      result = Err(err);
+      false
+    }
+  });
+  result?;
+  if !completed {
+    return Err(io::Error::other("failed to serialize heap snapshot"));
+  }
+  Ok(())
+}
+
+extern "C" fn heap_snapshot_trampoline<F>(
+  arg: *mut c_void,
+  data: *const u8,
+  size: usize,
+) -> bool
+where
+  F: FnMut(&[u8]) -> bool,
+{
+  let p = arg as *mut F;
+  let callback = unsafe { &mut *p };
+  let slice = unsafe { std::slice::from_raw_parts(data, size) };
+  callback(slice)
+}
+
+fn heap_snapshot_chunk_size(chunk_size: Option<usize>) -> int {
+  chunk_size.map_or(0, |size| size.try_into().unwrap_or(int::MAX))
+}
+
 /// Collection of V8 heap information.
 ///
 /// Instances of this class can be passed to v8::Isolate::GetHeapStatistics to
 /// get heap statistics from V8.
+///
+/// Note that `heap_size_limit()` is the maximum size of the whole heap,
+/// including the young generation, whereas `total_available_size()` is V8's
+/// estimate of how many more bytes can be allocated before that limit is
+/// reached. See also `Isolate::get_current_heap_limit()`.
 // Must be >= sizeof(v8::HeapStatistics), see v8__HeapStatistics__CONSTRUCT().
 #[repr(C)]
 #[derive(Debug)]
@@ -209,9 +477,32 @@ extern "C" {
   fn v8__Isolate__GetNumberOfDataSlots(this: *const Isolate) -> u32;
   fn v8__Isolate__Enter(this: *mut Isolate);
   fn v8__Isolate__Exit(this: *mut Isolate);
+  fn v8__Isolate__TryGetCurrent() -> *mut Isolate;
   fn v8__Isolate__ClearKeptObjects(isolate: *mut Isolate);
   fn v8__Isolate__LowMemoryNotification(isolate: *mut Isolate);
+  fn v8__Isolate__MemoryPressureNotification(
+    isolate: *mut Isolate,
+    level: MemoryPressureLevel,
+  );
+  fn v8__Isolate__MeasureMemory(
+    isolate: *mut Isolate,
+    delegate: *mut c_void,
+    execution: MeasureMemoryExecution,
+  ) -> bool;
+  fn v8__Isolate__HasPendingException(isolate: *const Isolate) -> bool;
+  fn v8__Isolate__LocaleConfigurationChangeNotification(isolate: *mut Isolate);
+  fn v8__Isolate__SetDefaultLocale(
+    isolate: *mut Isolate,
+    data: *const c_char,
+    length: usize,
+  ) -> i32;
   fn v8__Isolate__GetHeapStatistics(this: *mut Isolate, s: *mut HeapStatistics);
+  fn v8__Isolate__GetHeapLimit(this: *mut Isolate) -> usize;
+  fn v8__Isolate__GetCodeRange(
+    this: *mut Isolate,
+    start: *mut *mut c_void,
+    length_in_bytes: *mut usize,
+  );
   fn v8__Isolate__SetCaptureStackTraceForUncaughtExceptions(
     this: *mut Isolate,
     caputre: bool,
@@ -221,6 +512,28 @@ extern "C" {
     isolate: *mut Isolate,
     callback: MessageCallback,
   ) -> bool;
+  fn v8__Isolate__AddGCPrologueCallback(
+    isolate: *mut Isolate,
+    callback: RawGCCallback,
+    data: *mut c_void,
+    gc_type_filter: GCType,
+  );
+  fn v8__Isolate__RemoveGCPrologueCallback(
+    isolate: *mut Isolate,
+    callback: RawGCCallback,
+    data: *mut c_void,
+  );
+  fn v8__Isolate__AddGCEpilogueCallback(
+    isolate: *mut Isolate,
+    callback: RawGCCallback,
+    data: *mut c_void,
+    gc_type_filter: GCType,
+  );
+  fn v8__Isolate__RemoveGCEpilogueCallback(
+    isolate: *mut Isolate,
+    callback: RawGCCallback,
+    data: *mut c_void,
+  );
   fn v8__Isolate__AddNearHeapLimitCallback(
     isolate: *mut Isolate,
     callback: NearHeapLimitCallback,
@@ -235,6 +548,25 @@ extern "C" {
     isolate: *mut Isolate,
     callback: OomErrorCallback,
   );
+  fn v8__Isolate__SafeForTerminationScope__NEW(
+    isolate: *mut Isolate,
+  ) -> *mut RawSafeForTerminationScope;
+  fn v8__Isolate__SafeForTerminationScope__DELETE(
+    this: *mut RawSafeForTerminationScope,
+  );
+  fn v8__Isolate__SetEventLogger(
+    isolate: *mut Isolate,
+    callback: LogEventCallback,
+  );
+  fn v8__Isolate__SetJitCodeEventHandler(
+    isolate: *mut Isolate,
+    options: JitCodeEventOptions,
+    handler: Option<JitCodeEventHandler>,
+  );
+  fn v8__Isolate__SetAbortOnUncaughtExceptionCallback(
+    isolate: *mut Isolate,
+    callback: AbortOnUncaughtExceptionCallback,
+  );
   fn v8__Isolate__AdjustAmountOfExternalAllocatedMemory(
     isolate: *mut Isolate,
     change_in_bytes: i64,
@@ -280,6 +612,7 @@ extern "C" {
   );
   fn v8__Isolate__TerminateExecution(isolate: *const Isolate);
   fn v8__Isolate__IsExecutionTerminating(isolate: *const Isolate) -> bool;
+  fn v8__Isolate__InContext(isolate: *const Isolate) -> bool;
   fn v8__Isolate__CancelTerminateExecution(isolate: *const Isolate);
   fn v8__Isolate__GetMicrotasksPolicy(
     isolate: *const Isolate,
@@ -293,6 +626,21 @@ extern "C" {
     isolate: *mut Isolate,
     function: *const Function,
   );
+  fn v8__Isolate__EnqueueMicrotaskCallback(
+    isolate: *mut Isolate,
+    callback: extern "C" fn(data: *mut c_void),
+    data: *mut c_void,
+  );
+  fn v8__Isolate__AddMicrotasksCompletedCallback(
+    isolate: *mut Isolate,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  );
+  fn v8__Isolate__RemoveMicrotasksCompletedCallback(
+    isolate: *mut Isolate,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  );
   fn v8__Isolate__SetAllowAtomicsWait(isolate: *mut Isolate, allow: bool);
   fn v8__Isolate__SetWasmStreamingCallback(
     isolate: *mut Isolate,
@@ -304,6 +652,65 @@ extern "C" {
     isolate: *mut Isolate,
     callback: extern "C" fn(*mut c_void, *const u8, usize) -> bool,
     arg: *mut c_void,
+    chunk_size: int,
+    progress_callback: Option<extern "C" fn(*mut c_void, u32, u32) -> bool>,
+    progress_arg: *mut c_void,
+  ) -> bool;
+  fn v8__HeapProfiler__TakeRetainedHeapSnapshot(
+    isolate: *mut Isolate,
+  ) -> *const HeapSnapshot;
+  fn v8__HeapProfiler__GetSnapshotCount(isolate: *mut Isolate) -> int;
+  fn v8__HeapProfiler__GetHeapSnapshot(
+    isolate: *mut Isolate,
+    index: int,
+  ) -> *const HeapSnapshot;
+  fn v8__HeapProfiler__DeleteHeapSnapshot(
+    isolate: *mut Isolate,
+    uid: u32,
+  ) -> bool;
+  fn v8__HeapProfiler__DeleteAllHeapSnapshots(isolate: *mut Isolate);
+  fn v8__HeapSnapshot__Serialize(
+    this: *const HeapSnapshot,
+    callback: extern "C" fn(*mut c_void, *const u8, usize) -> bool,
+    arg: *mut c_void,
+    chunk_size: int,
+  ) -> bool;
+  fn v8__HeapSnapshot__GetUid(this: *const HeapSnapshot) -> u32;
+  fn v8__HeapSnapshot__GetNodesCount(this: *const HeapSnapshot) -> int;
+  fn v8__HeapSnapshot__GetMaxSnapshotJSObjectId(
+    this: *const HeapSnapshot,
+  ) -> SnapshotObjectId;
+  fn v8__HeapProfiler__GetObjectId(
+    isolate: *mut Isolate,
+    value: *const Value,
+  ) -> SnapshotObjectId;
+  fn v8__HeapProfiler__StartTrackingHeapObjects(
+    isolate: *mut Isolate,
+    track_allocations: bool,
+  );
+  fn v8__HeapProfiler__StopTrackingHeapObjects(isolate: *mut Isolate);
+  fn v8__HeapProfiler__GetHeapStats(
+    isolate: *mut Isolate,
+    callback: extern "C" fn(*mut c_void, *const HeapStatsUpdate, usize) -> bool,
+    arg: *mut c_void,
+  ) -> SnapshotObjectId;
+  fn v8__HeapProfiler__ClearObjectIds(isolate: *mut Isolate);
+  fn v8__HeapProfiler__StartSamplingHeapProfiler(
+    isolate: *mut Isolate,
+    sample_interval: u64,
+    stack_depth: int,
+    flags: SamplingHeapProfilerFlags,
+  ) -> bool;
+  fn v8__HeapProfiler__StopSamplingHeapProfiler(isolate: *mut Isolate);
+  fn v8__HeapProfiler__AddBuildEmbedderGraphCallback(
+    isolate: *mut Isolate,
+    callback: BuildEmbedderGraphCallback,
+    data: *mut c_void,
+  );
+  fn v8__HeapProfiler__RemoveBuildEmbedderGraphCallback(
+    isolate: *mut Isolate,
+    callback: BuildEmbedderGraphCallback,
+    data: *mut c_void,
   );
 
   fn v8__HeapStatistics__CONSTRUCT(s: *mut MaybeUninit<HeapStatistics>);
@@ -336,6 +743,8 @@ extern "C" {
     s: *const HeapStatistics,
   ) -> usize;
   fn v8__HeapStatistics__does_zap_garbage(s: *const HeapStatistics) -> usize;
+
+  fn v8__base__ThreadTicks__Now() -> i64;
 }
 
 /// Isolate represents an isolated instance of the V8 engine.  V8 isolates have
@@ -365,18 +774,41 @@ impl Isolate {
   /// V8::initialize() must have run prior to this.
   #[allow(clippy::new_ret_no_self)]
   pub fn new(params: CreateParams) -> OwnedIsolate {
-    crate::V8::assert_initialized();
-    let (raw_create_params, create_param_allocations) = params.finalize();
-    let cxx_isolate = unsafe { v8__Isolate__New(&raw_create_params) };
-    let mut owned_isolate = OwnedIsolate::new(cxx_isolate);
-    ScopeData::new_root(&mut owned_isolate);
-    owned_isolate.create_annex(create_param_allocations);
+    let mut owned_isolate = OwnedIsolate::new(Self::new_impl(params));
     unsafe {
       owned_isolate.enter();
     }
     owned_isolate
   }
 
+  /// Like `new()`, but the isolate isn't entered by the current thread.
+  /// Instead, it has to be locked with a `Locker` every time it is used,
+  /// which makes it possible to move the isolate between threads, e.g. to
+  /// share a pool of isolates between worker threads.
+  ///
+  /// # Safety
+  ///
+  /// The isolate and everything it owns may be used and dropped on any thread
+  /// that locks it. The caller must make sure that all of that is `Send`:
+  /// values stored with `set_slot()`, closures registered with the isolate
+  /// (e.g. promise hooks, GC callbacks, near-heap-limit callbacks and
+  /// `Function::new_closure()` closures), and `ContextExecutionTime` slots,
+  /// which are not. Handles such as `Global`s must only be used and dropped by
+  /// a thread that holds the isolate's lock.
+  pub unsafe fn new_unentered(params: CreateParams) -> UnenteredIsolate {
+    UnenteredIsolate::new(Self::new_impl(params))
+  }
+
+  fn new_impl(params: CreateParams) -> *mut Isolate {
+    crate::V8::assert_initialized();
+    let (raw_create_params, create_param_allocations) = params.finalize();
+    let cxx_isolate = unsafe { v8__Isolate__New(&raw_create_params) };
+    let isolate = unsafe { &mut *cxx_isolate };
+    ScopeData::new_root(isolate);
+    isolate.create_annex(create_param_allocations);
+    cxx_isolate
+  }
+
   /// Initial configuration parameters for a new Isolate.
   pub fn create_params() -> CreateParams {
     CreateParams::default()
@@ -401,6 +833,141 @@ impl Isolate {
     self.thread_safe_handle().is_execution_terminating()
   }
 
+  /// Returns true if an exception has been thrown (e.g. with
+  /// `HandleScope::throw_error()`) that hasn't been caught or propagated to
+  /// JavaScript yet. Native code can use this to check whether a call it
+  /// made has left an exception behind, without setting up a `TryCatch`.
+  ///
+  /// Exceptions caught by a `TryCatch` are not pending. Note that the
+  /// termination exception counts as pending too; use
+  /// `is_execution_terminating()` to tell it apart.
+  pub fn has_pending_exception(&self) -> bool {
+    unsafe { v8__Isolate__HasPendingException(self) }
+  }
+
+  /// Returns true if this isolate has a current context.
+  pub fn in_context(&self) -> bool {
+    unsafe { v8__Isolate__InContext(self) }
+  }
+
+  /// Returns the number of contexts that are currently entered through a
+  /// `ContextScope` or `HandleScope::with_context()`. Scopes that have been
+  /// dropped but not exited yet are included.
+  ///
+  /// This is intended to help diagnose bugs where code runs in the wrong
+  /// context; use `HandleScope::try_get_current_context()` to get the
+  /// current context itself.
+  pub fn get_entered_context_count(&self) -> usize {
+    ScopeData::count_entered_contexts(self)
+  }
+
+  /// Returns a description of the scopes that are currently open in this
+  /// isolate, starting with the innermost one, e.g.
+  /// `["TryCatch", "ContextScope", "HandleScope"]`. Scopes that have been
+  /// dropped but not exited yet are marked as "(zombie)".
+  ///
+  /// For a trace of every scope that is entered or exited, set the
+  /// `RUSTY_V8_TRACE_SCOPES` environment variable in a debug build.
+  pub fn describe_scope_stack(&self) -> Vec<std::string::String> {
+    ScopeData::describe_stack(self)
+  }
+
+  /// Enables or disables tracking the time spent executing in each context,
+  /// which is reported by `Context::get_execution_time()`, e.g. to bill or
+  /// limit the CPU usage of each tenant of a multi-tenant isolate.
+  ///
+  /// While tracking is enabled, the CPU time of the current thread is
+  /// attributed to the context that was most recently entered with a
+  /// `ContextScope` (or `HandleScope::with_context()`) and hasn't been exited
+  /// yet. This includes time spent in Rust code while the context is entered,
+  /// but not time spent waiting, e.g. for I/O. JavaScript functions of other
+  /// contexts that are called from the entered context are attributed to the
+  /// entered context, since V8 doesn't enter contexts for such calls. On
+  /// platforms that don't support measuring the CPU time of a thread, the
+  /// elapsed wall-clock time is used instead.
+  ///
+  /// Contexts that are already entered when tracking is enabled are only
+  /// tracked once they're entered again.
+  pub fn set_context_execution_time_tracking(&mut self, enabled: bool) {
+    let entered_contexts = &mut self.get_annex_mut().entered_contexts;
+    if !enabled {
+      *entered_contexts = None;
+    } else if entered_contexts.is_none() {
+      *entered_contexts = Some(Vec::new());
+    }
+  }
+
+  /// Called when a `ContextScope` enters `context`. Returns whether the
+  /// context is tracked, in which case `context_exited()` must be called when
+  /// it's exited.
+  pub(crate) fn context_entered(&mut self, context: &Context) -> bool {
+    if self.get_annex().entered_contexts.is_none() {
+      return false;
+    }
+    let execution_time =
+      match context.get_slot::<ContextExecutionTime>(self).cloned() {
+        Some(execution_time) => execution_time,
+        None => {
+          let execution_time = ContextExecutionTime::default();
+          context.set_slot(self, execution_time.clone());
+          execution_time
+        }
+      };
+    let now = thread_cpu_time();
+    let entered_contexts =
+      self.get_annex_mut().entered_contexts.as_mut().unwrap();
+    // Stop the clock of the outer context until this one is exited.
+    if let Some(outer) = entered_contexts.last() {
+      outer.execution_time.add(now.saturating_sub(outer.started));
+    }
+    entered_contexts.push(EnteredContext {
+      execution_time,
+      started: now,
+    });
+    true
+  }
+
+  /// Called when a context for which `context_entered()` returned true is
+  /// exited.
+  pub(crate) fn context_exited(&mut self) {
+    let now = thread_cpu_time();
+    if let Some(entered_contexts) = &mut self.get_annex_mut().entered_contexts {
+      if let Some(entered) = entered_contexts.pop() {
+        entered
+          .execution_time
+          .add(now.saturating_sub(entered.started));
+      }
+      if let Some(outer) = entered_contexts.last_mut() {
+        outer.started = now;
+      }
+    }
+  }
+
+  /// See `Context::get_execution_time()`.
+  pub(crate) fn get_context_execution_time(
+    &mut self,
+    context: &Context,
+  ) -> Duration {
+    let execution_time =
+      match context.get_slot::<ContextExecutionTime>(self).cloned() {
+        Some(execution_time) => execution_time,
+        None => return Duration::ZERO,
+      };
+    let mut total = execution_time.0.get();
+    // Include the time since the context was entered if it's executing now.
+    if let Some(current) = self
+      .get_annex()
+      .entered_contexts
+      .as_ref()
+      .and_then(|entered_contexts| entered_contexts.last())
+    {
+      if Rc::ptr_eq(&current.execution_time.0, &execution_time.0) {
+        total += thread_cpu_time().saturating_sub(current.started);
+      }
+    }
+    total
+  }
+
   pub(crate) fn create_annex(
     &mut self,
     create_param_allocations: Box<dyn Any>,
@@ -434,6 +1001,21 @@ impl Isolate {
     &mut self.get_annex_mut().finalizer_map
   }
 
+  /// Returns the signature of `fast_function`, which is allocated the first
+  /// time it is needed and lives as long as the isolate, since V8 doesn't
+  /// copy it when a function template is created.
+  pub(crate) fn get_c_function_info(
+    &mut self,
+    fast_function: &dyn FastFunction,
+  ) -> *const CFunctionInfo {
+    self
+      .get_annex_mut()
+      .c_function_infos
+      .entry(OwnedCFunctionInfo::key(fast_function))
+      .or_insert_with(|| OwnedCFunctionInfo::new(fast_function))
+      .as_ptr()
+  }
+
   fn get_annex_arc(&self) -> Arc<IsolateAnnex> {
     let annex_ptr = self.get_annex();
     let annex_arc = unsafe { Arc::from_raw(annex_ptr) };
@@ -535,7 +1117,8 @@ impl Isolate {
   /// restored when exiting.  Re-entering an isolate is allowed.
   ///
   /// rusty_v8 note: Unlike in the C++ API, the isolate is entered when it is
-  /// constructed and exited when dropped.
+  /// constructed and exited when dropped. Use `IsolateScope` to temporarily
+  /// enter another isolate.
   pub unsafe fn enter(&mut self) {
     v8__Isolate__Enter(self)
   }
@@ -552,6 +1135,12 @@ impl Isolate {
     v8__Isolate__Exit(self)
   }
 
+  /// Returns true if this isolate is the one that is entered in the current
+  /// thread, i.e. the isolate that was created or entered last.
+  pub fn is_current(&self) -> bool {
+    unsafe { v8__Isolate__TryGetCurrent() as *const Isolate == self }
+  }
+
   /// Clears the set of objects held strongly by the heap. This set of
   /// objects are originally built when a WeakRef is created or
   /// successfully dereferenced.
@@ -573,11 +1162,111 @@ impl Isolate {
     unsafe { v8__Isolate__LowMemoryNotification(self) }
   }
 
+  /// Notifies V8 that the memory pressure level of the system has changed,
+  /// e.g. because the OS signaled it. V8 uses this to decide how aggressively
+  /// to shrink its heap: on critical pressure it performs a full garbage
+  /// collection right away, if it's safe to do so.
+  pub fn memory_pressure_notification(&mut self, level: MemoryPressureLevel) {
+    unsafe { v8__Isolate__MemoryPressureNotification(self, level) }
+  }
+
+  /// Starts an asynchronous measurement of the memory used by the contexts
+  /// of this isolate. The measurement is performed by a garbage collection,
+  /// after which V8 posts a foreground task that reports the results to
+  /// `delegate`, so the embedder has to keep pumping the message loop.
+  ///
+  /// Returns false if the measurement could not be started, e.g. because
+  /// the isolate is being torn down.
+  pub fn measure_memory(
+    &mut self,
+    delegate: Box<dyn MeasureMemoryDelegate>,
+    execution: MeasureMemoryExecution,
+  ) -> bool {
+    // The delegate is owned by V8 from here on, and dropped once the
+    // measurement is complete.
+    let delegate = into_raw_delegate(delegate);
+    unsafe { v8__Isolate__MeasureMemory(self, delegate, execution) }
+  }
+
+  /// Notifies V8 that the default locale has changed, e.g. with
+  /// `icu::set_default_locale()`, so that the `Intl` APIs of this isolate
+  /// pick up the new default locale. V8 caches the default locale per
+  /// isolate, so this has to be called for every isolate that already exists.
+  pub fn locale_configuration_change_notification(&mut self) {
+    unsafe { v8__Isolate__LocaleConfigurationChangeNotification(self) }
+  }
+
+  /// Overrides the default locale of the `Intl` APIs of this isolate only,
+  /// without changing the process-wide default locale of ICU. `locale` is a
+  /// BCP 47 language tag, like `"de-CH"`; an ICU error code is returned if it
+  /// is malformed.
+  ///
+  /// The override is discarded by `locale_configuration_change_notification()`,
+  /// which makes the isolate use the default locale of ICU again.
+  pub fn set_default_locale(&mut self, locale: &str) -> Result<(), i32> {
+    let error_code = unsafe {
+      v8__Isolate__SetDefaultLocale(
+        self,
+        locale.as_ptr() as *const c_char,
+        locale.len(),
+      )
+    };
+    // ICU uses negative error codes for warnings.
+    if error_code <= 0 {
+      Ok(())
+    } else {
+      Err(error_code)
+    }
+  }
+
   /// Get statistics about the heap memory usage.
   pub fn get_heap_statistics(&mut self, s: &mut HeapStatistics) {
     unsafe { v8__Isolate__GetHeapStatistics(self, s) }
   }
 
+  /// Returns the address range of the virtual memory that V8 reserved for
+  /// generated code (the code range), or `None` if the isolate doesn't use
+  /// one, e.g. on 32-bit platforms, or on platforms that don't allow
+  /// allocating executable memory when running with `--jitless`.
+  ///
+  /// The size of the code range can be configured with
+  /// `CreateParams::code_range_size()`. When V8 is built with pointer
+  /// compression in a shared cage, all isolates share a single code range,
+  /// which is sized by the first isolate that is created.
+  pub fn get_code_range(&mut self) -> Option<Range<usize>> {
+    let mut start = null_mut();
+    let mut length_in_bytes = 0;
+    unsafe {
+      v8__Isolate__GetCodeRange(self, &mut start, &mut length_in_bytes)
+    };
+    if start.is_null() || length_in_bytes == 0 {
+      return None;
+    }
+    let start = start as usize;
+    Some(start..start + length_in_bytes)
+  }
+
+  /// Returns the current limit of the old generation of the heap, in bytes.
+  /// This is the `current_heap_limit` that is passed to a
+  /// `NearHeapLimitCallback`, and changes when such a callback raises it.
+  ///
+  /// Unlike `HeapStatistics::heap_size_limit()`, this doesn't include the
+  /// young generation, so `get_current_heap_limit()` minus the size of the
+  /// old generation is the headroom before V8 performs last-resort garbage
+  /// collections and invokes the near-heap-limit callback.
+  pub fn get_current_heap_limit(&mut self) -> usize {
+    unsafe { v8__Isolate__GetHeapLimit(self) }
+  }
+
+  /// Returns the limit of the old generation of the heap that the isolate
+  /// was created with, in bytes, i.e. the `initial_heap_limit` that is passed
+  /// to a `NearHeapLimitCallback`. It is derived from
+  /// `CreateParams::heap_limits()`, or from the amount of physical memory if
+  /// no limits were configured.
+  pub fn get_initial_heap_limit(&self) -> usize {
+    self.get_annex().initial_heap_limit
+  }
+
   /// Tells V8 to capture current stack trace when uncaught exception occurs
   /// and report it to the message listeners. The option is off by default.
   pub fn set_capture_stack_trace_for_uncaught_exceptions(
@@ -604,6 +1293,11 @@ impl Isolate {
     unsafe { v8__Isolate__AddMessageListener(self, callback) }
   }
 
+  // TODO: bind Isolate::SetExceptionPropagationCallback(), which reports the
+  // interface and property name an API exception propagated through. It was
+  // introduced in V8 12.x and is not available in the V8 version this crate
+  // currently builds against.
+
   /// This specifies the callback called when the stack property of Error
   /// is accessed.
   ///
@@ -627,9 +1321,23 @@ impl Isolate {
   /// Set the PromiseHook callback for various promise lifecycle
   /// events.
   pub fn set_promise_hook(&mut self, hook: PromiseHook) {
+    self.get_annex_mut().promise_hook_closure = None;
     unsafe { v8__Isolate__SetPromiseHook(self, hook) }
   }
 
+  /// Like `set_promise_hook()`, but takes a Rust closure, which is called
+  /// inside a `HandleScope` for the current context. This can be used to
+  /// track async resources, e.g. to implement `async_hooks` or to stitch
+  /// together async stack traces.
+  ///
+  /// Promise events that are triggered by the closure itself (e.g. because it
+  /// creates a promise) are not reported to it. The closure is owned by the
+  /// isolate until it is replaced, or the isolate is disposed.
+  pub fn set_promise_hook_closure(&mut self, hook: PromiseHookClosure) {
+    self.get_annex_mut().promise_hook_closure = Some(hook);
+    unsafe { v8__Isolate__SetPromiseHook(self, promise_hook_trampoline) }
+  }
+
   /// Set callback to notify about promise reject with no handler, or
   /// revocation of such a previous notification once the handler is added.
   pub fn set_promise_reject_callback(
@@ -668,6 +1376,21 @@ impl Isolate {
     }
   }
 
+  /// Like `set_host_import_module_dynamically_callback()`, but takes a Rust
+  /// function that is called inside a `HandleScope` and receives the script
+  /// or module that called import() as a typed `Referrer`.
+  pub fn set_host_import_module_dynamically_referrer_callback(
+    &mut self,
+    callback: impl MapFnTo<HostImportModuleDynamicallyCallback>,
+  ) {
+    unsafe {
+      v8__Isolate__SetHostImportModuleDynamicallyCallback(
+        self,
+        callback.map_fn_to(),
+      )
+    }
+  }
+
   /// This specifies the callback called by the upcoming `ShadowRealm`
   /// construction language feature to retrieve host created globals.
   pub fn set_host_create_shadow_realm_context_callback(
@@ -744,6 +1467,72 @@ impl Isolate {
     };
   }
 
+  /// Like `add_near_heap_limit_callback()`, but takes a Rust closure, which
+  /// receives the current and the initial heap limit and returns the new heap
+  /// limit. To enforce a memory cap gracefully, the closure can terminate
+  /// execution (using an `IsolateHandle` obtained beforehand) and return a
+  /// somewhat raised limit, so that V8 has the headroom to unwind:
+  ///
+  /// ```ignore
+  /// let handle = isolate.thread_safe_handle();
+  /// isolate.add_near_heap_limit_closure(Box::new(move |current, _| {
+  ///   handle.terminate_execution();
+  ///   current * 2
+  /// }));
+  /// ```
+  ///
+  /// The closure is owned by the isolate until it is removed with
+  /// `remove_near_heap_limit_closure()`, or the isolate is disposed.
+  pub fn add_near_heap_limit_closure(
+    &mut self,
+    callback: NearHeapLimitClosure,
+  ) {
+    let closures = &mut self.get_annex_mut().near_heap_limit_closures;
+    closures.push(callback);
+    // All closures share a single V8 callback, which invokes the most recently
+    // added one.
+    if closures.len() == 1 {
+      let data = closures as *mut Vec<NearHeapLimitClosure> as *mut c_void;
+      unsafe {
+        v8__Isolate__AddNearHeapLimitCallback(
+          self,
+          near_heap_limit_trampoline,
+          data,
+        )
+      };
+    }
+  }
+
+  /// Removes and drops the most recently added near-heap-limit closure, and
+  /// restores the heap limit to `heap_limit`, like
+  /// `remove_near_heap_limit_callback()`. Returns false if there is no
+  /// closure to remove.
+  pub fn remove_near_heap_limit_closure(&mut self, heap_limit: usize) -> bool {
+    let closures = &mut self.get_annex_mut().near_heap_limit_closures;
+    if closures.pop().is_none() {
+      return false;
+    }
+    let data = closures as *mut Vec<NearHeapLimitClosure> as *mut c_void;
+    let remaining = closures.len();
+    unsafe {
+      // V8 only restores the heap limit when a callback is removed, so the
+      // shared callback is re-added if other closures remain.
+      v8__Isolate__RemoveNearHeapLimitCallback(
+        self,
+        near_heap_limit_trampoline,
+        heap_limit,
+      );
+      if remaining > 0 {
+        v8__Isolate__AddNearHeapLimitCallback(
+          self,
+          near_heap_limit_trampoline,
+          data,
+        );
+      }
+    }
+    true
+  }
+
   /// Adjusts the amount of registered external memory. Used to give V8 an
   /// indication of the amount of externally allocated memory that is kept
   /// alive by JavaScript objects. V8 uses this to decide when to perform
@@ -764,6 +1553,41 @@ impl Isolate {
     unsafe { v8__Isolate__SetOOMErrorHandler(self, callback) };
   }
 
+  /// Sets the callback that receives V8's internal log events. Which events
+  /// are logged is controlled with V8 flags, e.g. `--log-timer-events`; other
+  /// logging, such as `--log-maps` or `--log-deopt`, can be enabled with
+  /// `V8::set_flags_from_string()` and is written to V8's log file.
+  pub fn set_event_logger(&mut self, callback: LogEventCallback) {
+    unsafe { v8__Isolate__SetEventLogger(self, callback) }
+  }
+
+  /// Sets the handler that is notified when code is added, moved or removed,
+  /// so that external profilers and tracing systems can map code addresses
+  /// to JavaScript functions. Pass `None` to remove the handler.
+  ///
+  /// With `JitCodeEventOptions::EnumExisting`, the handler is also called for
+  /// all code that already exists, before this function returns.
+  pub fn set_jit_code_event_handler(
+    &mut self,
+    options: JitCodeEventOptions,
+    handler: Option<JitCodeEventHandler>,
+  ) {
+    unsafe { v8__Isolate__SetJitCodeEventHandler(self, options, handler) }
+  }
+
+  /// Sets the callback that decides whether to abort the process when an
+  /// uncaught exception is thrown. Only has an effect if V8 was started with
+  /// the `--abort-on-uncaught-exception` flag; without a callback, V8 always
+  /// aborts in that case. This makes it possible to replicate Node's
+  /// `--abort-on-uncaught-exception`, which e.g. doesn't abort for exceptions
+  /// that are handled by a domain.
+  pub fn set_abort_on_uncaught_exception_callback(
+    &mut self,
+    callback: AbortOnUncaughtExceptionCallback,
+  ) {
+    unsafe { v8__Isolate__SetAbortOnUncaughtExceptionCallback(self, callback) };
+  }
+
   /// Returns the policy controlling how Microtasks are invoked.
   pub fn get_microtasks_policy(&self) -> MicrotasksPolicy {
     unsafe { v8__Isolate__GetMicrotasksPolicy(self) }
@@ -779,9 +1603,21 @@ impl Isolate {
   /// the MicrotasksPolicy is not kScoped. Any exceptions thrown by microtask
   /// callbacks are swallowed.
   pub fn perform_microtask_checkpoint(&mut self) {
+    self.enqueue_pending_resolutions();
     unsafe { v8__Isolate__PerformMicrotaskCheckpoint(self) }
   }
 
+  /// Moves the promise resolutions that were sent from other threads with a
+  /// `ResolverHandle` to the microtask queue.
+  fn enqueue_pending_resolutions(&mut self) {
+    let pending = std::mem::take(
+      &mut *self.get_annex().pending_resolutions.lock().unwrap(),
+    );
+    for resolution in pending {
+      self.enqueue_microtask_closure(resolution);
+    }
+  }
+
   /// An alias for PerformMicrotaskCheckpoint.
   #[deprecated(note = "Use Isolate::perform_microtask_checkpoint() instead")]
   pub fn run_microtasks(&mut self) {
@@ -793,19 +1629,175 @@ impl Isolate {
     unsafe { v8__Isolate__EnqueueMicrotask(self, &*microtask) }
   }
 
-  /// Set whether calling Atomics.wait (a function that may block) is allowed in
-  /// this isolate. This can also be configured via
-  /// CreateParams::allow_atomics_wait.
-  pub fn set_allow_atomics_wait(&mut self, allow: bool) {
-    unsafe { v8__Isolate__SetAllowAtomicsWait(self, allow) }
+  /// Enqueues a Rust closure to the default MicrotaskQueue. The closure is
+  /// owned by the isolate until it is run; if the isolate is disposed before
+  /// that, the closure is dropped without being called.
+  pub fn enqueue_microtask_closure(&mut self, microtask: MicrotaskClosure) {
+    extern "C" fn trampoline(data: *mut c_void) {
+      let pending = data as *const PendingMicrotask;
+      let isolate = unsafe { &mut *(*pending).isolate };
+      let pending = isolate
+        .get_annex_mut()
+        .pending_microtasks
+        .remove(&(pending as usize));
+      if let Some(pending) = pending {
+        (pending.callback)(isolate);
+      }
+    }
+
+    let pending = Box::new(PendingMicrotask {
+      isolate: self,
+      callback: microtask,
+    });
+    let data = &*pending as *const PendingMicrotask;
+    self
+      .get_annex_mut()
+      .pending_microtasks
+      .insert(data as usize, pending);
+    unsafe {
+      v8__Isolate__EnqueueMicrotaskCallback(self, trampoline, data as *mut _)
+    }
   }
 
-  /// Embedder injection point for `WebAssembly.compileStreaming(source)`.
-  /// The expectation is that the embedder sets it at most once.
+  /// Adds a callback that is invoked every time the default MicrotaskQueue
+  /// has been run, e.g. after a microtask checkpoint. This lets an event loop
+  /// know when it is safe to continue with the next macrotask.
   ///
-  /// The callback receives the source argument (string, Promise, etc.)
-  /// and an instance of [WasmStreaming]. The [WasmStreaming] instance
-  /// can outlive the callback and is used to feed data chunks to V8
+  /// The callback is only added once for each `(callback, data)` pair.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // False positive.
+  pub fn add_microtasks_completed_callback(
+    &mut self,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  ) {
+    unsafe { v8__Isolate__AddMicrotasksCompletedCallback(self, callback, data) }
+  }
+
+  /// Removes a callback that was added with
+  /// `add_microtasks_completed_callback()` with the same `data` argument.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // False positive.
+  pub fn remove_microtasks_completed_callback(
+    &mut self,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  ) {
+    unsafe {
+      v8__Isolate__RemoveMicrotasksCompletedCallback(self, callback, data)
+    }
+  }
+
+  /// Adds a closure that is invoked before every garbage collection whose
+  /// type matches `gc_type_filter`, e.g. to time GC pauses together with a
+  /// closure added with `add_gc_epilogue_callback()`.
+  ///
+  /// The closure is owned by the isolate until it is removed with
+  /// `remove_gc_callback()`, or the isolate is disposed. It must not allocate
+  /// on the V8 heap, nor add GC callbacks. It may remove GC callbacks,
+  /// including itself.
+  pub fn add_gc_prologue_callback(
+    &mut self,
+    callback: impl FnMut(&mut Isolate, GCType, GCCallbackFlags) + 'static,
+    gc_type_filter: GCType,
+  ) -> GCCallbackId {
+    self.add_gc_callback(GCCallbackKind::Prologue, callback, gc_type_filter)
+  }
+
+  /// Like `add_gc_prologue_callback()`, but the closure is invoked after every
+  /// garbage collection whose type matches `gc_type_filter`.
+  pub fn add_gc_epilogue_callback(
+    &mut self,
+    callback: impl FnMut(&mut Isolate, GCType, GCCallbackFlags) + 'static,
+    gc_type_filter: GCType,
+  ) -> GCCallbackId {
+    self.add_gc_callback(GCCallbackKind::Epilogue, callback, gc_type_filter)
+  }
+
+  fn add_gc_callback(
+    &mut self,
+    kind: GCCallbackKind,
+    callback: impl FnMut(&mut Isolate, GCType, GCCallbackFlags) + 'static,
+    gc_type_filter: GCType,
+  ) -> GCCallbackId {
+    self.remove_deferred_gc_callbacks();
+    let entry = NonNull::from(Box::leak(Box::new(GCCallbackEntry {
+      kind,
+      callback: Box::new(callback),
+      removed: Cell::new(false),
+    })));
+    let data = entry.as_ptr() as *mut c_void;
+    self
+      .get_annex_mut()
+      .gc_callbacks
+      .insert(data as usize, entry);
+    unsafe {
+      match kind {
+        GCCallbackKind::Prologue => v8__Isolate__AddGCPrologueCallback(
+          self,
+          gc_callback_trampoline,
+          data,
+          gc_type_filter,
+        ),
+        GCCallbackKind::Epilogue => v8__Isolate__AddGCEpilogueCallback(
+          self,
+          gc_callback_trampoline,
+          data,
+          gc_type_filter,
+        ),
+      }
+    }
+    GCCallbackId(data as usize)
+  }
+
+  /// Removes a GC callback that was added with `add_gc_prologue_callback()`
+  /// or `add_gc_epilogue_callback()`, and drops its closure. Returns false if
+  /// the callback had already been removed.
+  ///
+  /// When this is called from inside a GC callback, the closure isn't called
+  /// anymore, but it is only dropped later, since it may be the one that is
+  /// running.
+  pub fn remove_gc_callback(&mut self, id: GCCallbackId) -> bool {
+    self.remove_deferred_gc_callbacks();
+    let entry = match self.get_annex_mut().gc_callbacks.remove(&id.0) {
+      Some(entry) => entry,
+      None => return false,
+    };
+    if self.get_annex().gc_callback_depth > 0 {
+      // V8 is iterating over its GC callbacks, which must not be modified
+      // until it is done.
+      unsafe { (*entry.as_ptr()).removed.set(true) };
+      self.get_annex_mut().removed_gc_callbacks.push(entry);
+    } else {
+      unsafe { GCCallbackEntry::remove(entry, self) };
+    }
+    true
+  }
+
+  /// Unregisters and drops the GC callbacks that were removed from inside a
+  /// GC callback.
+  fn remove_deferred_gc_callbacks(&mut self) {
+    if self.get_annex().gc_callback_depth > 0 {
+      return;
+    }
+    let removed =
+      std::mem::take(&mut self.get_annex_mut().removed_gc_callbacks);
+    for entry in removed {
+      unsafe { GCCallbackEntry::remove(entry, self) };
+    }
+  }
+
+  /// Set whether calling Atomics.wait (a function that may block) is allowed in
+  /// this isolate. This can also be configured via
+  /// CreateParams::allow_atomics_wait.
+  pub fn set_allow_atomics_wait(&mut self, allow: bool) {
+    unsafe { v8__Isolate__SetAllowAtomicsWait(self, allow) }
+  }
+
+  /// Embedder injection point for `WebAssembly.compileStreaming(source)`.
+  /// The expectation is that the embedder sets it at most once.
+  ///
+  /// The callback receives the source argument (string, Promise, etc.)
+  /// and an instance of [WasmStreaming]. The [WasmStreaming] instance
+  /// can outlive the callback and is used to feed data chunks to V8
   /// asynchronously.
   pub fn set_wasm_streaming_callback<F>(&mut self, _: F)
   where
@@ -824,6 +1816,16 @@ impl Isolate {
   /// Disposes the isolate.  The isolate must not be entered by any
   /// thread to be disposable.
   unsafe fn dispose(&mut self) {
+    self.dispose_annex();
+
+    // No test case in rusty_v8 show this, but there have been situations in
+    // deno where dropping Annex before the states causes a segfault.
+    v8__Isolate__Dispose(self)
+  }
+
+  /// Drops the scope stack and everything else that rusty_v8 keeps for the
+  /// isolate, before it is disposed.
+  unsafe fn dispose_annex(&mut self) {
     // Drop the scope stack.
     ScopeData::drop_root(self);
 
@@ -836,6 +1838,22 @@ impl Isolate {
       self.low_memory_notification();
     }
 
+    // Unregister and drop the GC and near-heap-limit callbacks, so that V8
+    // doesn't call them while it tears down the heap.
+    self.remove_deferred_gc_callbacks();
+    let gc_callbacks = std::mem::take(&mut self.get_annex_mut().gc_callbacks);
+    for (_, entry) in gc_callbacks {
+      GCCallbackEntry::remove(entry, self);
+    }
+    if !self.get_annex().near_heap_limit_closures.is_empty() {
+      v8__Isolate__RemoveNearHeapLimitCallback(
+        self,
+        near_heap_limit_trampoline,
+        0,
+      );
+      self.get_annex_mut().near_heap_limit_closures.clear();
+    }
+
     // Set the `isolate` pointer inside the annex struct to null, so any
     // IsolateHandle that outlives the isolate will know that it can't call
     // methods on the isolate.
@@ -848,14 +1866,21 @@ impl Isolate {
     // Clear slots and drop owned objects that were taken out of `CreateParams`.
     annex.create_param_allocations = Box::new(());
     annex.slots.clear();
+    annex.c_function_infos.clear();
+
+    // Drop closures that were never called.
+    annex.pending_microtasks.clear();
+    annex.promise_hook_closure = None;
+    annex.pending_interrupts.lock().unwrap().clear();
+    // Take the resolutions out before dropping them: dropping their handles
+    // locks `isolate_mutex`, which must not be done while holding the lock.
+    let pending_resolutions =
+      std::mem::take(&mut *annex.pending_resolutions.lock().unwrap());
+    drop(pending_resolutions);
 
     // Subtract one from the Arc<IsolateAnnex> reference count.
     Arc::from_raw(annex);
     self.set_data(0, null_mut());
-
-    // No test case in rusty_v8 show this, but there have been situations in
-    // deno where dropping Annex before the states causes a segfault.
-    v8__Isolate__Dispose(self)
   }
 
   /// Take a heap snapshot. The callback is invoked one or more times
@@ -864,26 +1889,224 @@ impl Isolate {
   /// a single document, e.g., by writing them to a file.
   /// Note that Chrome DevTools refuses to load snapshots without
   /// a .heapsnapshot suffix.
-  pub fn take_heap_snapshot<F>(&mut self, mut callback: F)
+  pub fn take_heap_snapshot<F>(&mut self, callback: F)
   where
     F: FnMut(&[u8]) -> bool,
+  {
+    self.take_heap_snapshot_with_options(Default::default(), callback);
+  }
+
+  /// Like `take_heap_snapshot()`, but allows configuring the chunk size of
+  /// the serialized snapshot and reporting progress while the snapshot is
+  /// taken. Returns false if taking the snapshot failed or was aborted, either
+  /// by the progress callback or by `callback` returning false.
+  pub fn take_heap_snapshot_with_options<F>(
+    &mut self,
+    options: HeapSnapshotOptions,
+    mut callback: F,
+  ) -> bool
+  where
+    F: FnMut(&[u8]) -> bool,
+  {
+    type Progress<'a> = &'a mut dyn FnMut(u32, u32) -> bool;
+
+    extern "C" fn progress_trampoline(
+      arg: *mut c_void,
+      done: u32,
+      total: u32,
+    ) -> bool {
+      let progress = unsafe { &mut *(arg as *mut Progress) };
+      progress(done, total)
+    }
+
+    let arg = &mut callback as *mut F as *mut c_void;
+    let mut progress = options.progress;
+    let (progress_callback, progress_arg) = match &mut progress {
+      Some(progress) => (
+        Some(progress_trampoline as _),
+        progress as *mut Progress as *mut c_void,
+      ),
+      None => (None, null_mut()),
+    };
+    unsafe {
+      v8__HeapProfiler__TakeHeapSnapshot(
+        self,
+        heap_snapshot_trampoline::<F>,
+        arg,
+        heap_snapshot_chunk_size(options.chunk_size),
+        progress_callback,
+        progress_arg,
+      )
+    }
+  }
+
+  /// Takes a heap snapshot and streams it as JSON into `writer`, e.g. a
+  /// `.heapsnapshot` file, without keeping the whole document in memory.
+  pub fn write_heap_snapshot(
+    &mut self,
+    writer: &mut impl Write,
+  ) -> io::Result<()> {
+    write_heap_snapshot_chunks(writer, |callback| {
+      self.take_heap_snapshot_with_options(Default::default(), callback)
+    })
+  }
+
+  /// Takes a heap snapshot that is retained by the heap profiler, so that it
+  /// can be serialized later, or compared with other snapshots in DevTools.
+  /// Returns `None` if taking the snapshot failed.
+  pub fn take_retained_heap_snapshot(&mut self) -> Option<&HeapSnapshot> {
+    unsafe { v8__HeapProfiler__TakeRetainedHeapSnapshot(self).as_ref() }
+  }
+
+  /// Returns the number of heap snapshots retained by the heap profiler.
+  pub fn get_heap_snapshot_count(&mut self) -> usize {
+    unsafe { v8__HeapProfiler__GetSnapshotCount(self) as usize }
+  }
+
+  /// Returns the retained heap snapshot at `index`, or `None` if `index` is
+  /// out of bounds.
+  pub fn get_heap_snapshot(&mut self, index: usize) -> Option<&HeapSnapshot> {
+    if index >= self.get_heap_snapshot_count() {
+      return None;
+    }
+    unsafe { v8__HeapProfiler__GetHeapSnapshot(self, index as int).as_ref() }
+  }
+
+  /// Deletes the retained heap snapshot with the given uid, releasing its
+  /// memory. Returns false if there is no such snapshot.
+  pub fn delete_heap_snapshot(&mut self, uid: u32) -> bool {
+    unsafe { v8__HeapProfiler__DeleteHeapSnapshot(self, uid) }
+  }
+
+  /// Deletes all retained heap snapshots, releasing their memory.
+  pub fn delete_all_heap_snapshots(&mut self) {
+    unsafe { v8__HeapProfiler__DeleteAllHeapSnapshots(self) }
+  }
+
+  /// Returns the id of a heap object, which is the same as the id of the
+  /// object's node in heap snapshots. Returns `None` for values that aren't
+  /// heap objects, e.g. small integers.
+  ///
+  /// Use `HandleScope::find_object_by_heap_id()` to look up an object by its
+  /// id.
+  pub fn get_heap_object_id(
+    &mut self,
+    value: Local<Value>,
+  ) -> Option<SnapshotObjectId> {
+    match unsafe { v8__HeapProfiler__GetObjectId(self, &*value) } {
+      0 => None,
+      id => Some(id),
+    }
+  }
+
+  /// Starts tracking heap objects, so that `get_heap_stats()` can report
+  /// which objects were allocated or freed since tracking started. If
+  /// `track_allocations` is true, the stack traces of allocations are recorded
+  /// as well and included in heap snapshots.
+  pub fn start_tracking_heap_objects(&mut self, track_allocations: bool) {
+    unsafe {
+      v8__HeapProfiler__StartTrackingHeapObjects(self, track_allocations)
+    }
+  }
+
+  /// Stops tracking heap objects and discards the collected heap stats.
+  pub fn stop_tracking_heap_objects(&mut self) {
+    unsafe { v8__HeapProfiler__StopTrackingHeapObjects(self) }
+  }
+
+  /// Reports the heap stats collected since the last call to this method, or
+  /// since `start_tracking_heap_objects()` was called. The callback is invoked
+  /// one or more times with the time intervals whose stats changed, and can
+  /// return false to abort. Returns the id of the most recently allocated
+  /// object.
+  ///
+  /// Heap object tracking must have been started for there to be any stats.
+  pub fn get_heap_stats<F>(&mut self, mut callback: F) -> SnapshotObjectId
+  where
+    F: FnMut(&[HeapStatsUpdate]) -> bool,
   {
     extern "C" fn trampoline<F>(
       arg: *mut c_void,
-      data: *const u8,
-      size: usize,
+      data: *const HeapStatsUpdate,
+      count: usize,
     ) -> bool
     where
-      F: FnMut(&[u8]) -> bool,
+      F: FnMut(&[HeapStatsUpdate]) -> bool,
     {
-      let p = arg as *mut F;
-      let callback = unsafe { &mut *p };
-      let slice = unsafe { std::slice::from_raw_parts(data, size) };
-      callback(slice)
+      let callback = unsafe { &mut *(arg as *mut F) };
+      let updates = match count {
+        0 => &[][..],
+        count => unsafe { std::slice::from_raw_parts(data, count) },
+      };
+      callback(updates)
     }
 
     let arg = &mut callback as *mut F as *mut c_void;
-    unsafe { v8__HeapProfiler__TakeHeapSnapshot(self, trampoline::<F>, arg) }
+    unsafe { v8__HeapProfiler__GetHeapStats(self, trampoline::<F>, arg) }
+  }
+
+  /// Forgets the ids that were assigned to heap objects. Objects get new ids
+  /// in subsequent heap snapshots, so those can no longer be compared with
+  /// snapshots that were taken before.
+  pub fn clear_heap_object_ids(&mut self) {
+    unsafe { v8__HeapProfiler__ClearObjectIds(self) }
+  }
+
+  /// Starts the sampling heap profiler, which samples allocations with an
+  /// average interval of `sample_interval` bytes, recording up to
+  /// `stack_depth` frames of the stack that made each allocation. The
+  /// overhead is low enough to keep it running in production. Use
+  /// `HandleScope::get_allocation_profile()` to get the sampled allocations.
+  ///
+  /// V8's defaults are an interval of 512 KiB and a stack depth of 16. Returns
+  /// false if the profiler was already started.
+  pub fn start_sampling_heap_profiler(
+    &mut self,
+    sample_interval: u64,
+    stack_depth: i32,
+    flags: SamplingHeapProfilerFlags,
+  ) -> bool {
+    unsafe {
+      v8__HeapProfiler__StartSamplingHeapProfiler(
+        self,
+        sample_interval,
+        stack_depth,
+        flags,
+      )
+    }
+  }
+
+  /// Stops the sampling heap profiler and discards the collected samples.
+  pub fn stop_sampling_heap_profiler(&mut self) {
+    unsafe { v8__HeapProfiler__StopSamplingHeapProfiler(self) }
+  }
+
+  /// Adds a callback that is called every time a heap snapshot is taken. The
+  /// callback can add the embedder's own objects (e.g. sockets or timers) to
+  /// the `EmbedderGraph`, along with the V8 objects they hold on to, so that
+  /// they show up as retainers in DevTools.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // False positive.
+  pub fn add_build_embedder_graph_callback(
+    &mut self,
+    callback: BuildEmbedderGraphCallback,
+    data: *mut c_void,
+  ) {
+    unsafe {
+      v8__HeapProfiler__AddBuildEmbedderGraphCallback(self, callback, data)
+    }
+  }
+
+  /// Removes a callback that was added with
+  /// `add_build_embedder_graph_callback()` with the same `data` argument.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // False positive.
+  pub fn remove_build_embedder_graph_callback(
+    &mut self,
+    callback: BuildEmbedderGraphCallback,
+    data: *mut c_void,
+  ) {
+    unsafe {
+      v8__HeapProfiler__RemoveBuildEmbedderGraphCallback(self, callback, data)
+    }
   }
 }
 
@@ -891,6 +2114,40 @@ pub(crate) struct IsolateAnnex {
   create_param_allocations: Box<dyn Any>,
   slots: HashMap<TypeId, RawSlot, BuildTypeIdHasher>,
   finalizer_map: FinalizerMap,
+  initial_heap_limit: usize,
+  // Closures that have been passed to V8 as interrupts or microtasks, but
+  // haven't been called yet. They are keyed by the address that is passed to
+  // V8 as the callback data, and are dropped when the isolate is disposed.
+  pending_interrupts: Mutex<HashMap<usize, Box<InterruptClosure>>>,
+  pending_microtasks: HashMap<usize, Box<PendingMicrotask>>,
+  // Closures added with `Isolate::add_near_heap_limit_closure()`. The most
+  // recently added closure is at the end.
+  near_heap_limit_closures: Vec<NearHeapLimitClosure>,
+  // The closure set with `Isolate::set_promise_hook_closure()`. It is taken
+  // out while it's being called.
+  promise_hook_closure: Option<PromiseHookClosure>,
+  // The contexts that have been entered since execution time tracking was
+  // enabled with `Isolate::set_context_execution_time_tracking()`, and
+  // haven't been exited yet. The innermost context is at the end. This is
+  // `None` while tracking is disabled.
+  entered_contexts: Option<Vec<EnteredContext>>,
+  // Promise resolutions that were sent from other threads with a
+  // `ResolverHandle`, and that haven't been moved to the microtask queue yet.
+  pending_resolutions: Mutex<Vec<MicrotaskSendClosure>>,
+  // Closures that have been added as GC prologue or epilogue callbacks, keyed
+  // by the address that is passed to V8 as the callback data.
+  gc_callbacks: HashMap<usize, NonNull<GCCallbackEntry>>,
+  /// GC callbacks that were removed while V8 was running GC callbacks, and
+  /// are still registered with V8.
+  removed_gc_callbacks: Vec<NonNull<GCCallbackEntry>>,
+  /// The number of GC callbacks that are currently running.
+  gc_callback_depth: usize,
+  // The signatures of fast functions that have been passed to V8, keyed by
+  // the address of the function.
+  c_function_infos: HashMap<CFunctionInfoKey, OwnedCFunctionInfo>,
+  // Global handles of dropped `SendableGlobal`s, which are released from an
+  // interrupt on the isolate's thread.
+  deferred_global_resets: Mutex<Vec<NonNull<Data>>>,
   // The `isolate` and `isolate_mutex` fields are there so an `IsolateHandle`
   // (which may outlive the isolate itself) can determine whether the isolate
   // is still alive, and if so, get a reference to it. Safety rules:
@@ -907,16 +2164,154 @@ impl IsolateAnnex {
     isolate: &mut Isolate,
     create_param_allocations: Box<dyn Any>,
   ) -> Self {
+    let initial_heap_limit = unsafe { v8__Isolate__GetHeapLimit(isolate) };
     Self {
       create_param_allocations,
       slots: HashMap::default(),
       finalizer_map: FinalizerMap::default(),
+      initial_heap_limit,
+      pending_interrupts: Mutex::default(),
+      pending_microtasks: HashMap::default(),
+      near_heap_limit_closures: Vec::new(),
+      promise_hook_closure: None,
+      entered_contexts: None,
+      pending_resolutions: Mutex::default(),
+      gc_callbacks: HashMap::default(),
+      removed_gc_callbacks: Vec::new(),
+      gc_callback_depth: 0,
+      c_function_infos: HashMap::default(),
+      deferred_global_resets: Mutex::default(),
       isolate,
       isolate_mutex: Mutex::new(()),
     }
   }
 }
 
+/// The execution time of a context, which is stored in a slot of the context.
+/// It is shared with the isolate's stack of entered contexts, so that it can
+/// be updated when the context is exited without accessing the context.
+#[derive(Clone, Default)]
+struct ContextExecutionTime(Rc<Cell<Duration>>);
+
+impl ContextExecutionTime {
+  fn add(&self, duration: Duration) {
+    self.0.set(self.0.get() + duration);
+  }
+}
+
+struct EnteredContext {
+  execution_time: ContextExecutionTime,
+  /// The thread CPU time at which the context was entered, or at which the
+  /// last nested context was exited.
+  started: Duration,
+}
+
+/// Returns the CPU time of the current thread, or the time since an
+/// arbitrary point if that isn't supported on the current platform.
+fn thread_cpu_time() -> Duration {
+  Duration::from_micros(unsafe { v8__base__ThreadTicks__Now() } as u64)
+}
+
+struct PendingMicrotask {
+  isolate: *mut Isolate,
+  callback: MicrotaskClosure,
+}
+
+pub(crate) type MicrotaskSendClosure = Box<dyn FnOnce(&mut Isolate) + Send>;
+
+type RawGCCallback = extern "C" fn(
+  isolate: *mut Isolate,
+  gc_type: GCType,
+  flags: GCCallbackFlags,
+  data: *mut c_void,
+);
+
+#[derive(Clone, Copy)]
+enum GCCallbackKind {
+  Prologue,
+  Epilogue,
+}
+
+type GCCallback = Box<dyn FnMut(&mut Isolate, GCType, GCCallbackFlags)>;
+
+/// A GC callback closure. Entries are allocated with `Box::leak()`, and their
+/// address is the `data` pointer they are registered with.
+struct GCCallbackEntry {
+  kind: GCCallbackKind,
+  callback: GCCallback,
+  /// Set when the callback was removed while GC callbacks were running.
+  removed: Cell<bool>,
+}
+
+impl GCCallbackEntry {
+  /// Unregisters the entry from V8, and drops it.
+  unsafe fn remove(entry: NonNull<Self>, isolate: *mut Isolate) {
+    let data = entry.as_ptr() as *mut c_void;
+    match (*entry.as_ptr()).kind {
+      GCCallbackKind::Prologue => v8__Isolate__RemoveGCPrologueCallback(
+        isolate,
+        gc_callback_trampoline,
+        data,
+      ),
+      GCCallbackKind::Epilogue => v8__Isolate__RemoveGCEpilogueCallback(
+        isolate,
+        gc_callback_trampoline,
+        data,
+      ),
+    }
+    drop(Box::from_raw(entry.as_ptr()));
+  }
+}
+
+extern "C" fn near_heap_limit_trampoline(
+  data: *mut c_void,
+  current_heap_limit: usize,
+  initial_heap_limit: usize,
+) -> usize {
+  let closures = unsafe { &mut *(data as *mut Vec<NearHeapLimitClosure>) };
+  let callback = closures.last_mut().unwrap();
+  callback(current_heap_limit, initial_heap_limit)
+}
+
+extern "C" fn promise_hook_trampoline(
+  type_: PromiseHookType,
+  promise: Local<Promise>,
+  parent: Local<Value>,
+) {
+  let scope = &mut unsafe { CallbackScope::new(promise) };
+  let mut hook = match scope.get_annex_mut().promise_hook_closure.take() {
+    Some(hook) => hook,
+    None => return,
+  };
+  {
+    let scope = &mut HandleScope::new(scope);
+    hook(scope, type_, promise, parent);
+  }
+  // Put the closure back, unless it has been replaced in the meantime.
+  let annex = scope.get_annex_mut();
+  if annex.promise_hook_closure.is_none() {
+    annex.promise_hook_closure = Some(hook);
+  }
+}
+
+extern "C" fn gc_callback_trampoline(
+  isolate: *mut Isolate,
+  gc_type: GCType,
+  flags: GCCallbackFlags,
+  data: *mut c_void,
+) {
+  let entry = data as *mut GCCallbackEntry;
+  let isolate = unsafe { &mut *isolate };
+  if unsafe { (*entry).removed.get() } {
+    return;
+  }
+  // SAFETY: the entry isn't dropped while `gc_callback_depth` is nonzero, and
+  // only the closure is borrowed while it runs.
+  isolate.get_annex_mut().gc_callback_depth += 1;
+  unsafe { ((*entry).callback)(isolate, gc_type, flags) };
+  isolate.get_annex_mut().gc_callback_depth -= 1;
+}
+
 impl Debug for IsolateAnnex {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     f.debug_struct("IsolateAnnex")
@@ -938,6 +2333,16 @@ pub struct IsolateHandle(Arc<IsolateAnnex>);
 unsafe impl Send for IsolateHandle {}
 unsafe impl Sync for IsolateHandle {}
 
+/// Two handles are equal if they refer to the same isolate. This can be used
+/// from any thread, and keeps working after the isolate has been disposed.
+impl PartialEq for IsolateHandle {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
+}
+
+impl Eq for IsolateHandle {}
+
 impl IsolateHandle {
   // This function is marked unsafe because it must be called only with either
   // IsolateAnnex::mutex locked, or from the main thread associated with the V8
@@ -950,6 +2355,65 @@ impl IsolateHandle {
     Self(isolate.get_annex_arc())
   }
 
+  /// Schedules a global handle to be released on the isolate's thread. Does
+  /// nothing if the isolate has already been disposed, since that released
+  /// all its global handles.
+  pub(crate) fn defer_global_reset(&self, data: NonNull<Data>) {
+    extern "C" fn release(isolate: &mut Isolate, _data: *mut c_void) {
+      let deferred = std::mem::take(
+        &mut *isolate.get_annex().deferred_global_resets.lock().unwrap(),
+      );
+      for data in deferred {
+        unsafe { reset_global(data) };
+      }
+    }
+
+    let _lock = self.0.isolate_mutex.lock().unwrap();
+    if self.0.isolate.is_null() {
+      return;
+    }
+    let mut deferred = self.0.deferred_global_resets.lock().unwrap();
+    deferred.push(data);
+    // Only one interrupt is needed to release all handles that are deferred
+    // before it runs.
+    if deferred.len() == 1 {
+      unsafe {
+        v8__Isolate__RequestInterrupt(self.0.isolate, release, null_mut())
+      };
+    }
+  }
+
+  /// Queues a closure to be run as a microtask on the isolate's thread. The
+  /// closure is moved to the microtask queue from an interrupt, or at the
+  /// start of the next `Isolate::perform_microtask_checkpoint()`, whichever
+  /// comes first. Returns false, and drops the closure, if the isolate has
+  /// been disposed.
+  pub(crate) fn enqueue_resolution(
+    &self,
+    resolution: MicrotaskSendClosure,
+  ) -> bool {
+    extern "C" fn enqueue(isolate: &mut Isolate, _data: *mut c_void) {
+      isolate.enqueue_pending_resolutions();
+    }
+
+    // Note that `resolution` is only dropped after the lock is released,
+    // which matters because dropping its handles locks `isolate_mutex` too.
+    let _lock = self.0.isolate_mutex.lock().unwrap();
+    if self.0.isolate.is_null() {
+      return false;
+    }
+    let mut pending = self.0.pending_resolutions.lock().unwrap();
+    pending.push(resolution);
+    // Only one interrupt is needed to enqueue all resolutions that are pending
+    // before it runs.
+    if pending.len() == 1 {
+      unsafe {
+        v8__Isolate__RequestInterrupt(self.0.isolate, enqueue, null_mut())
+      };
+    }
+    true
+  }
+
   /// Forcefully terminate the current thread of JavaScript execution
   /// in the given isolate.
   ///
@@ -1032,6 +2496,47 @@ impl IsolateHandle {
       true
     }
   }
+
+  /// Like `request_interrupt()`, but takes a Rust closure. The closure is
+  /// owned by the isolate until it is called; if the isolate is disposed
+  /// before that, the closure is dropped without being called.
+  ///
+  /// The closure runs on top of the interrupted code, so it can e.g. call
+  /// `terminate_execution()`, which makes it safe to use from a watchdog
+  /// thread. To create handles, it should open a `CallbackScope`; the context
+  /// of the interrupted code is available through
+  /// `HandleScope::try_get_current_context()`.
+  ///
+  /// Returns false if Isolate was already destroyed, in which case the
+  /// closure is dropped immediately.
+  pub fn request_interrupt_closure(&self, callback: InterruptClosure) -> bool {
+    extern "C" fn trampoline(isolate: &mut Isolate, data: *mut c_void) {
+      let callback = isolate
+        .get_annex()
+        .pending_interrupts
+        .lock()
+        .unwrap()
+        .remove(&(data as usize));
+      if let Some(callback) = callback {
+        callback(isolate);
+      }
+    }
+
+    let _lock = self.0.isolate_mutex.lock().unwrap();
+    if self.0.isolate.is_null() {
+      return false;
+    }
+    let callback = Box::new(callback);
+    let data = &*callback as *const InterruptClosure as *mut c_void;
+    self
+      .0
+      .pending_interrupts
+      .lock()
+      .unwrap()
+      .insert(data as usize, callback);
+    unsafe { v8__Isolate__RequestInterrupt(self.0.isolate, trampoline, data) };
+    true
+  }
 }
 
 /// Same as Isolate but gets disposed when it goes out of scope.
@@ -1069,6 +2574,212 @@ impl DerefMut for OwnedIsolate {
   }
 }
 
+/// An isolate that is not entered by any thread, created with
+/// `Isolate::new_unentered()`. It can be moved to another thread, but has to
+/// be locked with a `Locker` before it can be used. To use the isolate from
+/// several threads, turn it into a `SharedIsolate` with `into_shared()`. The
+/// isolate is disposed when it goes out of scope.
+///
+/// Note that everything the isolate owns (e.g. its slots, and the closures
+/// that were registered with it) moves along with it.
+#[derive(Debug)]
+pub struct UnenteredIsolate {
+  pub(crate) cxx_isolate: NonNull<Isolate>,
+}
+
+// The isolate can only be used through a `Locker`, which holds V8's lock for
+// the isolate on the current thread. The caller of `Isolate::new_unentered()`
+// vouches for the state it owns.
+unsafe impl Send for UnenteredIsolate {}
+
+impl UnenteredIsolate {
+  pub(crate) fn new(cxx_isolate: *mut Isolate) -> Self {
+    let cxx_isolate = NonNull::new(cxx_isolate).unwrap();
+    Self { cxx_isolate }
+  }
+
+  /// Returns a handle that can be used to interrupt or terminate the isolate
+  /// while another thread is using it.
+  pub fn thread_safe_handle(&self) -> IsolateHandle {
+    IsolateHandle::new(unsafe { self.cxx_isolate.as_ref() })
+  }
+
+  /// Turns the isolate into one that can be shared between threads.
+  pub fn into_shared(self) -> SharedIsolate {
+    SharedIsolate(Arc::new(self))
+  }
+}
+
+impl Drop for UnenteredIsolate {
+  fn drop(&mut self) {
+    // Tearing down the isolate runs finalizers and unregisters callbacks, so
+    // it is locked and entered like for any other use. No `Locker` can exist
+    // at this point, since they borrow the isolate. V8 itself requires the
+    // isolate to be exited before it is disposed.
+    unsafe {
+      let mut locker = Locker::lock(self.cxx_isolate);
+      locker.dispose_annex();
+      drop(locker);
+      v8__Isolate__Dispose(self.cxx_isolate.as_ptr())
+    }
+  }
+}
+
+/// A reference-counted `UnenteredIsolate` that can be used by several
+/// threads, created with `UnenteredIsolate::into_shared()`. Each thread locks
+/// the isolate with `lock()` for as long as it uses it, which blocks while
+/// another thread holds the lock; a thread can give the lock up temporarily
+/// with an `Unlocker`. The isolate is disposed when the last clone is dropped.
+#[derive(Clone, Debug)]
+pub struct SharedIsolate(Arc<UnenteredIsolate>);
+
+// V8's lock serializes the threads that use the isolate. See
+// `Isolate::new_unentered()` for the state it owns.
+unsafe impl Send for SharedIsolate {}
+unsafe impl Sync for SharedIsolate {}
+
+impl SharedIsolate {
+  /// Locks the isolate for the current thread and enters it, blocking until
+  /// no other thread holds the lock.
+  ///
+  /// Panics if the current thread has locked the isolate already, since the
+  /// `Locker` hands out mutable access to the isolate.
+  ///
+  /// # Safety
+  ///
+  /// Everything that is stored on the isolate while it is locked must be
+  /// `Send`, and handles created under the lock must not be used or dropped
+  /// after it is released. See `Isolate::new_unentered()`.
+  pub unsafe fn lock(&self) -> Locker<'_> {
+    let isolate = self.0.cxx_isolate;
+    assert!(
+      !Locker::is_locked(isolate.as_ref()),
+      "the isolate is already locked by the current thread"
+    );
+    Locker::lock(isolate)
+  }
+
+  /// Returns a handle that can be used to interrupt or terminate the isolate
+  /// while another thread is using it.
+  pub fn thread_safe_handle(&self) -> IsolateHandle {
+    self.0.thread_safe_handle()
+  }
+}
+
+impl AsMut<Isolate> for OwnedIsolate {
+  fn as_mut(&mut self) -> &mut Isolate {
+    self
+  }
+}
+
+impl AsMut<Isolate> for Isolate {
+  fn as_mut(&mut self) -> &mut Isolate {
+    self
+  }
+}
+
+/// Enters an isolate for as long as the scope is alive, like
+/// `v8::Isolate::Scope` in C++. This makes it possible to temporarily switch
+/// to another isolate on a thread that has several of them; the previously
+/// entered isolate becomes the current one again when the scope is dropped.
+///
+/// The scope dereferences to the isolate it was created from:
+///
+/// ```ignore
+/// let isolate = &mut v8::IsolateScope::new(&mut other_isolate);
+/// let scope = &mut v8::HandleScope::new(&mut **isolate);
+/// ```
+///
+/// Scopes of different isolates must be dropped in the reverse order of
+/// their creation. Dropping a scope panics if another isolate has been
+/// entered since it was created and hasn't been exited yet.
+pub struct IsolateScope<'a, P: AsMut<Isolate>> {
+  parent: &'a mut P,
+}
+
+impl<'a, P: AsMut<Isolate>> IsolateScope<'a, P> {
+  pub fn new(parent: &'a mut P) -> Self {
+    unsafe { parent.as_mut().enter() };
+    Self { parent }
+  }
+}
+
+impl<'a, P: AsMut<Isolate>> Deref for IsolateScope<'a, P> {
+  type Target = P;
+  fn deref(&self) -> &P {
+    self.parent
+  }
+}
+
+impl<'a, P: AsMut<Isolate>> DerefMut for IsolateScope<'a, P> {
+  fn deref_mut(&mut self) -> &mut P {
+    self.parent
+  }
+}
+
+impl<'a, P: AsMut<Isolate>> Drop for IsolateScope<'a, P> {
+  fn drop(&mut self) {
+    let isolate = self.parent.as_mut();
+    assert!(
+      isolate.is_current(),
+      "IsolateScope dropped while another isolate is entered"
+    );
+    unsafe { isolate.exit() }
+  }
+}
+
+#[repr(C)]
+struct RawSafeForTerminationScope(Opaque);
+
+/// Declares that the code that runs while this scope is alive can safely be
+/// terminated with `terminate_execution()`. This only matters for isolates that
+/// were created with `CreateParams::only_terminate_in_safe_scope(true)`; in
+/// such isolates, termination is postponed until a `SafeForTerminationScope`
+/// is entered, so that embedders can make sure that termination never
+/// interrupts code that can't handle it.
+///
+/// The scope dereferences to the scope or isolate it was created from, so it
+/// can be used to run JavaScript:
+///
+/// ```ignore
+/// let scope = &mut v8::SafeForTerminationScope::new(scope);
+/// script.run(scope);
+/// ```
+pub struct SafeForTerminationScope<'a, P> {
+  raw: NonNull<RawSafeForTerminationScope>,
+  parent: &'a mut P,
+}
+
+impl<'a, P: AsMut<Isolate>> SafeForTerminationScope<'a, P> {
+  pub fn new(parent: &'a mut P) -> Self {
+    let raw =
+      unsafe { v8__Isolate__SafeForTerminationScope__NEW(parent.as_mut()) };
+    Self {
+      raw: NonNull::new(raw).unwrap(),
+      parent,
+    }
+  }
+}
+
+impl<'a, P> Deref for SafeForTerminationScope<'a, P> {
+  type Target = P;
+  fn deref(&self) -> &P {
+    self.parent
+  }
+}
+
+impl<'a, P> DerefMut for SafeForTerminationScope<'a, P> {
+  fn deref_mut(&mut self) -> &mut P {
+    self.parent
+  }
+}
+
+impl<'a, P> Drop for SafeForTerminationScope<'a, P> {
+  fn drop(&mut self) {
+    unsafe { v8__Isolate__SafeForTerminationScope__DELETE(self.raw.as_ptr()) }
+  }
+}
+
 impl HeapStatistics {
   pub fn total_heap_size(&self) -> usize {
     unsafe { v8__HeapStatistics__total_heap_size(self) }
@@ -1082,6 +2793,8 @@ impl HeapStatistics {
     unsafe { v8__HeapStatistics__total_physical_size(self) }
   }
 
+  /// The number of bytes that can still be allocated before the heap reaches
+  /// `heap_size_limit()`.
   pub fn total_available_size(&self) -> usize {
     unsafe { v8__HeapStatistics__total_available_size(self) }
   }
@@ -1098,6 +2811,8 @@ impl HeapStatistics {
     unsafe { v8__HeapStatistics__used_heap_size(self) }
   }
 
+  /// The maximum size of the heap, including the young generation. This
+  /// grows when a `NearHeapLimitCallback` raises the heap limit.
   pub fn heap_size_limit(&self) -> usize {
     unsafe { v8__HeapStatistics__heap_size_limit(self) }
   }
@@ -1172,6 +2887,44 @@ where
   }
 }
 
+impl<F> MapFnFrom<F> for HostImportModuleDynamicallyCallback
+where
+  F: UnitType
+    + for<'s> Fn(
+      &mut HandleScope<'s>,
+      Referrer<'s>,
+      Local<'s, String>,
+      Local<'s, FixedArray>,
+    ) -> Option<Local<'s, Promise>>,
+{
+  fn mapping() -> Self {
+    extern "C" fn c_fn<F>(
+      context: Local<Context>,
+      host_defined_options: Local<Data>,
+      resource_name: Local<Value>,
+      specifier: Local<String>,
+      import_assertions: Local<FixedArray>,
+    ) -> *mut Promise
+    where
+      F: UnitType
+        + for<'s> Fn(
+          &mut HandleScope<'s>,
+          Referrer<'s>,
+          Local<'s, String>,
+          Local<'s, FixedArray>,
+        ) -> Option<Local<'s, Promise>>,
+    {
+      let scope = &mut unsafe { CallbackScope::new(context) };
+      let referrer = Referrer::new(resource_name, host_defined_options);
+      match (F::get())(scope, referrer, specifier, import_assertions) {
+        Some(promise) => &*promise as *const Promise as *mut Promise,
+        None => null_mut(),
+      }
+    }
+    c_fn::<F>
+  }
+}
+
 /// A special hasher that is optimized for hashing `std::any::TypeId` values.
 /// `TypeId` values are actually 64-bit values which themselves come out of some
 /// hash function, so it's unnecessary to shuffle their bits any further.
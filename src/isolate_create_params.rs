@@ -9,10 +9,13 @@ use crate::support::Opaque;
 use crate::support::SharedPtr;
 
 use std::any::Any;
+use std::borrow::Borrow;
 use std::convert::TryFrom;
+use std::fmt::{self, Debug, Formatter};
 use std::iter::once;
 use std::mem::size_of;
 use std::mem::MaybeUninit;
+use std::ops::Deref;
 use std::os::raw::c_char;
 use std::ptr::null;
 
@@ -21,6 +24,11 @@ use std::ptr::null;
 pub type CounterLookupCallback = extern "C" fn(name: *const c_char) -> *mut i32;
 
 /// Initial configuration parameters for a new Isolate.
+///
+/// `CreateParams` is `Send`: the snapshot blob, external references and array
+/// buffer allocator it holds on to are all owned by it (or `'static`) and
+/// thread-safe, so it can be built on one thread and moved to a worker thread
+/// that creates its own isolate from it.
 #[must_use]
 #[derive(Debug, Default)]
 pub struct CreateParams {
@@ -28,6 +36,11 @@ pub struct CreateParams {
   allocations: CreateParamAllocations,
 }
 
+// SAFETY: the raw pointers in `raw` only point into `allocations`, or to
+// `'static` data, and everything in `allocations` is `Send`; the setters below
+// don't accept anything else.
+unsafe impl Send for CreateParams {}
+
 impl CreateParams {
   /// Enables the host application to provide a mechanism for recording
   /// statistics counters.
@@ -39,8 +52,15 @@ impl CreateParams {
     self
   }
 
-  /// Explicitly specify a startup snapshot blob.
-  pub fn snapshot_blob(mut self, data: impl Allocated<[u8]>) -> Self {
+  /// Explicitly specify a startup snapshot blob. The blob is not copied; it is
+  /// kept alive until the isolate is disposed. It can be a `&'static [u8]`
+  /// (e.g. from `include_bytes!()`), an owned `Vec<u8>` or `Box<[u8]>`, or a
+  /// `StartupData` produced by a `SnapshotCreator`.
+  ///
+  /// To create many isolates from the same blob, pass it as an `Arc<[u8]>`
+  /// and clone the `Arc` for each isolate.
+  pub fn snapshot_blob(mut self, data: impl Allocated<[u8]> + Send) -> Self {
+    self.allocations.snapshot_blob_decompressor = None;
     let data = Allocation::of(data);
     let header = Allocation::of(raw::StartupData::boxed_header(&data));
     self.raw.snapshot_blob = &*header;
@@ -49,6 +69,47 @@ impl CreateParams {
     self
   }
 
+  /// Like `snapshot_blob()`, but accepts any owner of the blob that
+  /// dereferences to something that can be viewed as bytes, such as a
+  /// memory-mapped file, or an `Arc` holding one. The owner is dropped when the
+  /// isolate is disposed, so e.g. an `Arc` of a memory mapping can be used to
+  /// create many isolates from the same mapping without copying it.
+  pub fn snapshot_blob_from<D>(self, owner: D) -> Self
+  where
+    D: Deref + Send + 'static,
+    D::Target: AsRef<[u8]>,
+  {
+    self.snapshot_blob(SnapshotBlobOwner(owner))
+  }
+
+  /// Specifies a startup snapshot blob that is stored in compressed form, e.g.
+  /// to reduce the size of a binary that embeds a large custom snapshot with
+  /// `include_bytes!()`. The compression format is up to the embedder:
+  /// `decompress` is called with the compressed data when the isolate is
+  /// created, and must return the snapshot blob. It may panic if the data is
+  /// corrupt.
+  ///
+  /// Both the compressed data and the decompressed blob are dropped when the
+  /// isolate is disposed. To create many isolates from the same snapshot
+  /// without decompressing it every time, decompress it once and pass it to
+  /// `snapshot_blob()` as an `Arc<[u8]>` instead.
+  pub fn compressed_snapshot_blob<D, F>(
+    mut self,
+    compressed: D,
+    decompress: F,
+  ) -> Self
+  where
+    D: Deref + Send + 'static,
+    D::Target: AsRef<[u8]>,
+    F: FnOnce(&[u8]) -> Vec<u8> + Send + 'static,
+  {
+    self.allocations.snapshot_blob_decompressor =
+      Some(SnapshotBlobDecompressor(Box::new(move || {
+        decompress((*compressed).as_ref())
+      })));
+    self
+  }
+
   /// The ArrayBuffer::ArrayBufferAllocator to use for allocating and freeing
   /// the backing store of ArrayBuffers.
   pub fn array_buffer_allocator(
@@ -65,7 +126,7 @@ impl CreateParams {
   /// entire lifetime of the isolate.
   pub fn external_references(
     mut self,
-    ext_refs: impl Allocated<[intptr_t]>,
+    ext_refs: impl Allocated<[intptr_t]> + Send,
   ) -> Self {
     let last_non_null = ext_refs
       .iter()
@@ -110,7 +171,8 @@ impl CreateParams {
     self
   }
 
-  /// Termination is postponed when there is no active SafeForTerminationScope.
+  /// Termination is postponed when there is no active
+  /// `SafeForTerminationScope`.
   pub fn only_terminate_in_safe_scope(mut self, value: bool) -> Self {
     self.raw.only_terminate_in_safe_scope = value;
     self
@@ -129,6 +191,20 @@ impl CreateParams {
     self
   }
 
+  /// The amount of virtual memory reserved for generated code, in bytes. This
+  /// is relevant for 64-bit architectures that rely on the code range for
+  /// calls in code. Zero (the default) lets V8 choose the size. Lowering it
+  /// can help on platforms that limit the amount of memory that can be mapped
+  /// as executable. It has no effect when running with `--jitless`. See
+  /// `Isolate::get_code_range()`.
+  pub fn code_range_size(mut self, size_in_bytes: usize) -> Self {
+    self
+      .raw
+      .constraints
+      .set_code_range_size_in_bytes(size_in_bytes);
+    self
+  }
+
   /// Configures the constraints with reasonable default values based on the
   /// provided lower and upper bounds.
   ///
@@ -159,6 +235,11 @@ impl CreateParams {
   }
 
   pub(crate) fn finalize(mut self) -> (raw::CreateParams, Box<dyn Any>) {
+    if let Some(decompressor) =
+      self.allocations.snapshot_blob_decompressor.take()
+    {
+      self = self.snapshot_blob(decompressor.0());
+    }
     if self.raw.array_buffer_allocator_shared.is_null() {
       self = self.array_buffer_allocator(array_buffer::new_default_allocator());
     }
@@ -167,6 +248,38 @@ impl CreateParams {
   }
 }
 
+/// Adapts an arbitrary owner of a snapshot blob to `Allocated<[u8]>`.
+struct SnapshotBlobOwner<D>(D);
+
+impl<D: Deref> Deref for SnapshotBlobOwner<D>
+where
+  D::Target: AsRef<[u8]>,
+{
+  type Target = [u8];
+  fn deref(&self) -> &[u8] {
+    (*self.0).as_ref()
+  }
+}
+
+impl<D: Deref> Borrow<[u8]> for SnapshotBlobOwner<D>
+where
+  D::Target: AsRef<[u8]>,
+{
+  fn borrow(&self) -> &[u8] {
+    self
+  }
+}
+
+/// Decompresses a snapshot blob when the isolate is created. See
+/// `CreateParams::compressed_snapshot_blob()`.
+struct SnapshotBlobDecompressor(Box<dyn FnOnce() -> Vec<u8> + Send>);
+
+impl Debug for SnapshotBlobDecompressor {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("SnapshotBlobDecompressor").finish()
+  }
+}
+
 #[derive(Debug, Default)]
 struct CreateParamAllocations {
   // Owner of the snapshot data buffer itself.
@@ -176,6 +289,13 @@ struct CreateParamAllocations {
   // stable pointer to it in `CreateParams`.
   snapshot_blob_header: Option<Allocation<raw::StartupData>>,
   external_references: Option<Allocation<[intptr_t]>>,
+  snapshot_blob_decompressor: Option<SnapshotBlobDecompressor>,
+}
+
+#[test]
+fn create_params_is_send() {
+  fn assert_send<T: Send>() {}
+  assert_send::<CreateParams>();
 }
 
 #[test]
@@ -265,6 +385,10 @@ pub(crate) mod raw {
   }
 
   impl ResourceConstraints {
+    pub fn set_code_range_size_in_bytes(&mut self, limit: usize) {
+      self.code_range_size_ = limit;
+    }
+
     pub fn configure_defaults_from_heap_size(
       &mut self,
       initial_heap_size_in_bytes: usize,
@@ -0,0 +1,134 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+//! Support for consuming JavaScript iterables from Rust.
+use crate::Function;
+use crate::HandleScope;
+use crate::Local;
+use crate::Object;
+use crate::String;
+use crate::Symbol;
+use crate::TryCatch;
+use crate::Value;
+
+/// Iterates over `iterable` the way a `for...of` loop does: an iterator is
+/// obtained by calling `iterable[Symbol.iterator]()`, and `f` is called with
+/// each value produced by its `next()` method until the iterator is done.
+///
+/// `f` is called in a new `HandleScope` for every value. It returns `None` to
+/// stop the iteration early, which it must do if an exception was thrown. The
+/// iterator is then closed by calling its `return()` method, like when a
+/// `for...of` loop is exited with `break` or `throw`.
+///
+/// Returns `None` if an exception was thrown, either by `f` or by the
+/// iterator protocol (e.g. because `iterable` isn't iterable), or if `f`
+/// stopped the iteration. An exception thrown by `f` takes precedence over one
+/// thrown while closing the iterator.
+pub fn for_each<'s, F>(
+  scope: &mut HandleScope<'s>,
+  iterable: Local<Value>,
+  mut f: F,
+) -> Option<()>
+where
+  F: for<'t> FnMut(&mut HandleScope<'t>, Local<'t, Value>) -> Option<()>,
+{
+  let scope = &mut HandleScope::new(scope);
+
+  let iterator_symbol = Symbol::get_iterator(scope);
+  let method = iterable
+    .to_object(scope)?
+    .get(scope, iterator_symbol.into())?;
+  let method = match Local::<Function>::try_from(method) {
+    Ok(method) => method,
+    Err(_) => {
+      scope.throw_type_error("object is not iterable");
+      return None;
+    }
+  };
+  let iterator = method.call(scope, iterable, &[])?;
+  let iterator = match Local::<Object>::try_from(iterator) {
+    Ok(iterator) => iterator,
+    Err(_) => {
+      scope.throw_type_error(
+        "Result of the Symbol.iterator method is not an object",
+      );
+      return None;
+    }
+  };
+
+  let next_key = String::new(scope, "next").unwrap();
+  let next = iterator.get(scope, next_key.into())?;
+  let next = match Local::<Function>::try_from(next) {
+    Ok(next) => next,
+    Err(_) => {
+      scope.throw_type_error("iterator.next is not a function");
+      return None;
+    }
+  };
+
+  let done_key = String::new(scope, "done").unwrap();
+  let value_key = String::new(scope, "value").unwrap();
+  loop {
+    let scope = &mut HandleScope::new(scope);
+    let result = next.call(scope, iterator.into(), &[])?;
+    let result = match Local::<Object>::try_from(result) {
+      Ok(result) => result,
+      Err(_) => {
+        scope.throw_type_error("Iterator result is not an object");
+        return None;
+      }
+    };
+    if result.get(scope, done_key.into())?.boolean_value(scope) {
+      return Some(());
+    }
+    let value = result.get(scope, value_key.into())?;
+
+    let exception = {
+      let scope = &mut TryCatch::new(scope);
+      if f(scope, value).is_some() {
+        continue;
+      }
+      if scope.has_terminated() {
+        scope.rethrow();
+        return None;
+      }
+      scope.exception()
+    };
+    match exception {
+      Some(exception) => {
+        {
+          let scope = &mut TryCatch::new(scope);
+          close_iterator(scope, iterator);
+        }
+        scope.throw_exception(exception);
+      }
+      None => {
+        close_iterator(scope, iterator)?;
+      }
+    }
+    return None;
+  }
+}
+
+/// Calls the `return()` method of `iterator`, if it has one.
+fn close_iterator(
+  scope: &mut HandleScope,
+  iterator: Local<Object>,
+) -> Option<()> {
+  let return_key = String::new(scope, "return").unwrap();
+  let method = iterator.get(scope, return_key.into())?;
+  if method.is_null_or_undefined() {
+    return Some(());
+  }
+  let method = match Local::<Function>::try_from(method) {
+    Ok(method) => method,
+    Err(_) => {
+      scope.throw_type_error("iterator.return is not a function");
+      return None;
+    }
+  };
+  let result = method.call(scope, iterator.into(), &[])?;
+  if !result.is_object() {
+    scope.throw_type_error("Iterator result is not an object");
+    return None;
+  }
+  Some(())
+}
@@ -0,0 +1,133 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+use std::ffi::c_void;
+use std::slice;
+
+use crate::Isolate;
+use crate::UnboundScript;
+
+/// Called by V8 when code is added, moved or removed, so that external code
+/// profilers (e.g. `perf` or ETW-based tools) can map addresses to functions.
+/// See `Isolate::set_jit_code_event_handler()`.
+pub type JitCodeEventHandler = extern "C" fn(event: &JitCodeEvent);
+
+/// Options for `Isolate::set_jit_code_event_handler()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitCodeEventOptions {
+  Default = 0,
+  /// Generate callbacks for already existing code.
+  EnumExisting = 1,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitCodeEventType {
+  CodeAdded,
+  CodeMoved,
+  CodeRemoved,
+  CodeAddLinePosInfo,
+  CodeStartLineInfoRecording,
+  CodeEndLineInfoRecording,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitCodeType {
+  ByteCode,
+  JitCode,
+  WasmCode,
+}
+
+/// Indicates what kind of source position a `CodeAddLinePosInfo` event
+/// refers to.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JitCodePositionType {
+  Position,
+  StatementPosition,
+}
+
+/// Describes a code event. Mirrors the layout of `v8::JitCodeEvent`.
+#[repr(C)]
+pub struct JitCodeEvent {
+  /// The type of the event.
+  pub type_: JitCodeEventType,
+  /// The kind of code that the event refers to.
+  pub code_type: JitCodeType,
+  /// The start of the code. For `CodeRemoved` events this is the start of the
+  /// code that was removed.
+  pub code_start: *mut c_void,
+  /// The size of the code. Only valid for `CodeAdded` events.
+  pub code_len: usize,
+  _script: *const UnboundScript,
+  /// User data that can be set by the handler in response to a
+  /// `CodeStartLineInfoRecording` event, and is passed back in subsequent
+  /// line info events for the same code.
+  pub user_data: *mut c_void,
+  _wasm_source_info: *const c_void,
+  data: JitCodeEventData,
+  /// The isolate that the event belongs to.
+  pub isolate: *mut Isolate,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct JitCodeEventName {
+  str: *const u8,
+  len: usize,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct JitCodeEventLineInfo {
+  offset: usize,
+  pos: usize,
+  position_type: JitCodePositionType,
+}
+
+#[repr(C)]
+union JitCodeEventData {
+  name: JitCodeEventName,
+  line_info: JitCodeEventLineInfo,
+  new_code_start: *mut c_void,
+}
+
+impl JitCodeEvent {
+  /// Returns the name of the code, which is only available for `CodeAdded`
+  /// events. Names are not necessarily valid UTF-8.
+  pub fn name(&self) -> Option<Cow<'_, str>> {
+    if self.type_ != JitCodeEventType::CodeAdded {
+      return None;
+    }
+    let name = unsafe { self.data.name };
+    let bytes = match name.len {
+      0 => &[][..],
+      len => unsafe { slice::from_raw_parts(name.str, len) },
+    };
+    Some(std::string::String::from_utf8_lossy(bytes))
+  }
+
+  /// Returns the new start address of the code, which is only available for
+  /// `CodeMoved` events.
+  pub fn new_code_start(&self) -> Option<*mut c_void> {
+    match self.type_ {
+      JitCodeEventType::CodeMoved => Some(unsafe { self.data.new_code_start }),
+      _ => None,
+    }
+  }
+
+  /// Returns the offset of an instruction in the code, the source position it
+  /// corresponds to, and the type of that position. Only available for
+  /// `CodeAddLinePosInfo` events.
+  pub fn line_info(&self) -> Option<(usize, usize, JitCodePositionType)> {
+    match self.type_ {
+      JitCodeEventType::CodeAddLinePosInfo => {
+        let line_info = unsafe { self.data.line_info };
+        Some((line_info.offset, line_info.pos, line_info.position_type))
+      }
+      _ => None,
+    }
+  }
+}
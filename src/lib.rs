@@ -29,12 +29,15 @@ extern crate bitflags;
 #[macro_use]
 extern crate lazy_static;
 
+mod allocation_profile;
 mod array_buffer;
 mod array_buffer_view;
 mod bigint;
 mod context;
+mod cpu_profiler;
 mod data;
 mod date;
+mod embedder_graph;
 mod exception;
 mod external;
 mod external_references;
@@ -45,6 +48,10 @@ mod handle;
 pub mod icu;
 mod isolate;
 mod isolate_create_params;
+mod jit_code_event;
+mod locker;
+mod measure_memory;
+mod microtask;
 mod module;
 mod name;
 mod number;
@@ -73,17 +80,38 @@ mod value_deserializer;
 mod value_serializer;
 mod wasm;
 
+pub mod debug;
 pub mod inspector;
+pub mod iterator;
 pub mod json;
+pub mod object_wrap;
 pub mod script_compiler;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+pub mod unhandled_rejection;
 // This module is intentionally named "V8" rather than "v8" to match the
 // C++ namespace "v8::V8".
 #[allow(non_snake_case)]
 pub mod V8;
 
+pub use allocation_profile::AllocationProfile;
+pub use allocation_profile::AllocationProfileAllocation;
+pub use allocation_profile::AllocationProfileNode;
+pub use allocation_profile::AllocationProfileSample;
+pub use allocation_profile::SamplingHeapProfilerFlags;
 pub use array_buffer::*;
 pub use bigint::*;
+pub use cpu_profiler::CpuProfile;
+pub use cpu_profiler::CpuProfileDeoptFrame;
+pub use cpu_profiler::CpuProfileDeoptInfo;
+pub use cpu_profiler::CpuProfileLineTick;
+pub use cpu_profiler::CpuProfileNode;
+pub use cpu_profiler::CpuProfiler;
+pub use cpu_profiler::CpuProfilingStatus;
 pub use data::*;
+pub use embedder_graph::BuildEmbedderGraphCallback;
+pub use embedder_graph::EmbedderGraph;
+pub use embedder_graph::EmbedderGraphNode;
 pub use exception::*;
 pub use external_references::ExternalReference;
 pub use external_references::ExternalReferences;
@@ -91,32 +119,70 @@ pub use function::*;
 pub use handle::Global;
 pub use handle::Handle;
 pub use handle::Local;
+pub use handle::SendableGlobal;
 pub use handle::Weak;
+pub use isolate::AbortOnUncaughtExceptionCallback;
+pub use isolate::GCCallbackFlags;
+pub use isolate::GCCallbackId;
+pub use isolate::GCType;
+pub use isolate::HeapSnapshot;
+pub use isolate::HeapSnapshotOptions;
 pub use isolate::HeapStatistics;
+pub use isolate::HeapStatsUpdate;
 pub use isolate::HostCreateShadowRealmContextCallback;
 pub use isolate::HostImportModuleDynamicallyCallback;
 pub use isolate::HostInitializeImportMetaObjectCallback;
+pub use isolate::InterruptClosure;
 pub use isolate::Isolate;
 pub use isolate::IsolateHandle;
+pub use isolate::IsolateScope;
+pub use isolate::LogEventCallback;
+pub use isolate::LogEventStatus;
+pub use isolate::MemoryPressureLevel;
 pub use isolate::MessageCallback;
+pub use isolate::MicrotaskClosure;
+pub use isolate::MicrotasksCompletedCallback;
 pub use isolate::MicrotasksPolicy;
 pub use isolate::NearHeapLimitCallback;
+pub use isolate::NearHeapLimitClosure;
 pub use isolate::OomDetails;
 pub use isolate::OomErrorCallback;
 pub use isolate::OwnedIsolate;
 pub use isolate::PromiseHook;
+pub use isolate::PromiseHookClosure;
 pub use isolate::PromiseHookType;
 pub use isolate::PromiseRejectCallback;
+pub use isolate::SafeForTerminationScope;
+pub use isolate::SharedIsolate;
+pub use isolate::SnapshotObjectId;
+pub use isolate::UnenteredIsolate;
 pub use isolate::WasmAsyncSuccess;
 pub use isolate_create_params::CreateParams;
+pub use jit_code_event::JitCodeEvent;
+pub use jit_code_event::JitCodeEventHandler;
+pub use jit_code_event::JitCodeEventOptions;
+pub use jit_code_event::JitCodeEventType;
+pub use jit_code_event::JitCodePositionType;
+pub use jit_code_event::JitCodeType;
+pub use locker::Locker;
+pub use locker::Unlocker;
+pub use measure_memory::MeasureMemoryDelegate;
+pub use measure_memory::MeasureMemoryExecution;
+pub use microtask::MicrotaskQueue;
+pub use microtask::MicrotasksScope;
 pub use module::*;
+pub use name::NameKind;
 pub use object::*;
 pub use platform::new_default_platform;
 pub use platform::new_single_threaded_default_platform;
+pub use platform::ForegroundTaskPostedCallback;
 pub use platform::Platform;
+pub use platform::PlatformClockCallback;
 pub use primitives::*;
 pub use private::*;
-pub use promise::{PromiseRejectEvent, PromiseRejectMessage, PromiseState};
+pub use promise::{
+  PromiseRejectEvent, PromiseRejectMessage, PromiseState, ResolverHandle,
+};
 pub use property_attribute::*;
 pub use proxy::*;
 pub use scope::CallbackScope;
@@ -126,9 +192,11 @@ pub use scope::HandleScope;
 pub use scope::TryCatch;
 pub use script::ScriptOrigin;
 pub use script_compiler::CachedData;
+pub use script_or_module::Referrer;
 pub use snapshot::FunctionCodeHandling;
 pub use snapshot::SnapshotCreator;
 pub use snapshot::StartupData;
+pub use string::CachedPropertyName;
 pub use string::NewStringType;
 pub use string::WriteOptions;
 pub use support::SharedPtr;
@@ -137,6 +205,7 @@ pub use support::UniquePtr;
 pub use support::UniqueRef;
 pub use symbol::*;
 pub use template::*;
+pub use value::ValueDebug;
 pub use value_deserializer::ValueDeserializer;
 pub use value_deserializer::ValueDeserializerHelper;
 pub use value_deserializer::ValueDeserializerImpl;
@@ -145,6 +214,10 @@ pub use value_serializer::ValueSerializerHelper;
 pub use value_serializer::ValueSerializerImpl;
 pub use wasm::CompiledWasmModule;
 pub use wasm::WasmStreaming;
+pub use V8::VERSION;
+
+#[cfg(feature = "macros")]
+pub use v8_macros::v8_class;
 
 // TODO(piscisaureus): Ideally this trait would not be exported.
 pub use support::MapFnTo;
@@ -0,0 +1,146 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::ptr::NonNull;
+
+use crate::Isolate;
+use crate::UnenteredIsolate;
+
+extern "C" {
+  fn v8__Locker__CONSTRUCT(
+    buf: *mut MaybeUninit<RawLocker>,
+    isolate: *mut Isolate,
+  );
+  fn v8__Locker__DESTRUCT(this: *mut RawLocker);
+  fn v8__Locker__IsLocked(isolate: *const Isolate) -> bool;
+  fn v8__Unlocker__CONSTRUCT(
+    buf: *mut MaybeUninit<RawUnlocker>,
+    isolate: *mut Isolate,
+  );
+  fn v8__Unlocker__DESTRUCT(this: *mut RawUnlocker);
+}
+
+#[repr(C)]
+struct RawLocker([MaybeUninit<usize>; 2]);
+
+#[repr(C)]
+struct RawUnlocker([MaybeUninit<usize>; 1]);
+
+/// Locks an `UnenteredIsolate` for the current thread and enters it, for as
+/// long as the locker is alive, like `v8::Locker` in C++. Other threads that
+/// try to lock the same isolate block until the lock is released.
+///
+/// A locker is created either from an `UnenteredIsolate` that is owned by
+/// the current thread, or with `SharedIsolate::lock()` when several threads
+/// use the isolate. The locker dereferences to the isolate:
+///
+/// ```ignore
+/// let isolate =
+///   unsafe { v8::Isolate::new_unentered(Default::default()) }.into_shared();
+/// let worker = isolate.clone();
+/// std::thread::spawn(move || {
+///   let locker = &mut unsafe { worker.lock() };
+///   let scope = &mut v8::HandleScope::new(&mut **locker);
+///   // ...
+/// });
+/// ```
+///
+/// Handles must not outlive the locker they were created under, since the
+/// isolate can be used by another thread once it is unlocked.
+pub struct Locker<'a> {
+  raw: Box<MaybeUninit<RawLocker>>,
+  isolate: NonNull<Isolate>,
+  _phantom: PhantomData<&'a mut Isolate>,
+}
+
+impl<'a> Locker<'a> {
+  /// Locks and enters `isolate`.
+  ///
+  /// # Safety
+  ///
+  /// Everything that is stored on the isolate while it is locked must be
+  /// `Send`, and handles created under the lock must not be used or dropped
+  /// after it is released. See `Isolate::new_unentered()`.
+  pub unsafe fn new(isolate: &'a mut UnenteredIsolate) -> Self {
+    Self::lock(isolate.cxx_isolate)
+  }
+
+  /// Locks and enters `isolate`. The caller must make sure that the isolate
+  /// outlives the locker, and that the current thread doesn't lock it
+  /// already, since the locker hands out a `&mut Isolate`.
+  pub(crate) unsafe fn lock(mut isolate: NonNull<Isolate>) -> Self {
+    let mut raw = Box::new(MaybeUninit::uninit());
+    v8__Locker__CONSTRUCT(&mut *raw, isolate.as_ptr());
+    isolate.as_mut().enter();
+    Self {
+      raw,
+      isolate,
+      _phantom: PhantomData,
+    }
+  }
+
+  /// Returns whether the given isolate is locked by the current thread.
+  pub fn is_locked(isolate: &Isolate) -> bool {
+    unsafe { v8__Locker__IsLocked(isolate) }
+  }
+}
+
+impl<'a> Drop for Locker<'a> {
+  fn drop(&mut self) {
+    unsafe {
+      self.isolate.as_mut().exit();
+      v8__Locker__DESTRUCT(self.raw.as_mut_ptr());
+    }
+  }
+}
+
+impl<'a> Deref for Locker<'a> {
+  type Target = Isolate;
+  fn deref(&self) -> &Isolate {
+    unsafe { self.isolate.as_ref() }
+  }
+}
+
+impl<'a> DerefMut for Locker<'a> {
+  fn deref_mut(&mut self) -> &mut Isolate {
+    unsafe { self.isolate.as_mut() }
+  }
+}
+
+impl<'a> AsMut<Isolate> for Locker<'a> {
+  fn as_mut(&mut self) -> &mut Isolate {
+    self
+  }
+}
+
+/// Temporarily releases the lock that a `Locker` holds, like `v8::Unlocker`
+/// in C++, so that other threads can use the isolate while the current one
+/// does blocking work. The lock is acquired again when the unlocker is
+/// dropped, which blocks until no other thread holds it.
+///
+/// The locker is borrowed for as long as the unlocker is alive, so the
+/// isolate can't be used by the current thread in the meantime.
+pub struct Unlocker<'a, 'b> {
+  raw: Box<MaybeUninit<RawUnlocker>>,
+  _locker: &'a mut Locker<'b>,
+}
+
+impl<'a, 'b> Unlocker<'a, 'b> {
+  pub fn new(locker: &'a mut Locker<'b>) -> Self {
+    let mut raw = Box::new(MaybeUninit::uninit());
+    unsafe { v8__Unlocker__CONSTRUCT(&mut *raw, &mut **locker) };
+    Self {
+      raw,
+      _locker: locker,
+    }
+  }
+}
+
+impl<'a, 'b> Drop for Unlocker<'a, 'b> {
+  fn drop(&mut self) {
+    unsafe { v8__Unlocker__DESTRUCT(self.raw.as_mut_ptr()) };
+  }
+}
@@ -0,0 +1,93 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use std::ffi::c_void;
+
+use crate::CallbackScope;
+use crate::Context;
+use crate::HandleScope;
+use crate::Isolate;
+use crate::Local;
+
+/// Controls how soon the garbage collection that performs a memory
+/// measurement is started. See `Isolate::measure_memory()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasureMemoryExecution {
+  /// The measurement is performed by the next major garbage collection, or
+  /// by a garbage collection that is scheduled after a short delay.
+  Default,
+  /// A garbage collection that performs the measurement is started as soon
+  /// as possible.
+  Eager,
+  /// The measurement is only performed by the next major garbage collection,
+  /// whenever that happens.
+  Lazy,
+}
+
+/// Receives the results of a memory measurement started with
+/// `Isolate::measure_memory()`. This allows attributing the size of the
+/// heap to individual contexts, e.g. to enforce a memory quota per tenant of
+/// an isolate.
+pub trait MeasureMemoryDelegate {
+  /// Returns whether the size of the given context needs to be measured.
+  /// This is called during garbage collection, so it must not allocate on
+  /// the V8 heap or run JavaScript.
+  fn should_measure(&mut self, context: Local<Context>) -> bool;
+
+  /// Called once the measurement is complete, with the size of each context
+  /// for which `should_measure()` returned true. Objects that can't be
+  /// attributed to a single context (e.g. because they are shared between
+  /// contexts) are counted in `unattributed_size_in_bytes`.
+  fn measurement_complete(
+    &mut self,
+    scope: &mut HandleScope<()>,
+    context_sizes_in_bytes: &[(Local<Context>, usize)],
+    unattributed_size_in_bytes: usize,
+  );
+}
+
+type RawMeasureMemoryDelegate = Box<dyn MeasureMemoryDelegate>;
+
+/// Leaks the delegate into a thin pointer that is owned by the C++ side
+/// until it calls `v8__MeasureMemoryDelegate__BASE__DROP`.
+pub(crate) fn into_raw_delegate(
+  delegate: Box<dyn MeasureMemoryDelegate>,
+) -> *mut c_void {
+  Box::into_raw(Box::new(delegate)) as *mut c_void
+}
+
+#[no_mangle]
+unsafe extern "C" fn v8__MeasureMemoryDelegate__BASE__ShouldMeasure(
+  this: *mut c_void,
+  context: *const Context,
+) -> bool {
+  let this = &mut *(this as *mut RawMeasureMemoryDelegate);
+  this.should_measure(Local::from_raw(context).unwrap())
+}
+
+#[no_mangle]
+unsafe extern "C" fn v8__MeasureMemoryDelegate__BASE__MeasurementComplete(
+  this: *mut c_void,
+  isolate: *mut Isolate,
+  contexts: *const *const Context,
+  sizes: *const usize,
+  len: usize,
+  unattributed_size_in_bytes: usize,
+) {
+  let scope = &mut CallbackScope::new(&mut *isolate);
+  let scope = &mut HandleScope::new(scope);
+  let context_sizes_in_bytes = (0..len)
+    .map(|i| (Local::from_raw(*contexts.add(i)).unwrap(), *sizes.add(i)))
+    .collect::<Vec<_>>();
+  let this = &mut *(this as *mut RawMeasureMemoryDelegate);
+  this.measurement_complete(
+    scope,
+    &context_sizes_in_bytes,
+    unattributed_size_in_bytes,
+  );
+}
+
+#[no_mangle]
+unsafe extern "C" fn v8__MeasureMemoryDelegate__BASE__DROP(this: *mut c_void) {
+  drop(Box::from_raw(this as *mut RawMeasureMemoryDelegate));
+}
@@ -0,0 +1,150 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+use std::ffi::c_void;
+
+use crate::support::int;
+use crate::support::Opaque;
+use crate::Function;
+use crate::Isolate;
+use crate::Local;
+use crate::MicrotasksCompletedCallback;
+use crate::MicrotasksPolicy;
+use crate::UniqueRef;
+
+extern "C" {
+  fn v8__MicrotasksScope__IsRunningMicrotasks(isolate: *mut Isolate) -> bool;
+  fn v8__MicrotasksScope__GetCurrentDepth(isolate: *mut Isolate) -> i32;
+
+  fn v8__MicrotaskQueue__New(
+    isolate: *mut Isolate,
+    policy: MicrotasksPolicy,
+  ) -> *mut MicrotaskQueue;
+  fn v8__MicrotaskQueue__DELETE(this: *mut MicrotaskQueue);
+  fn v8__MicrotaskQueue__EnqueueMicrotask(
+    this: *const MicrotaskQueue,
+    isolate: *mut Isolate,
+    microtask: *const Function,
+  );
+  fn v8__MicrotaskQueue__PerformCheckpoint(
+    this: *const MicrotaskQueue,
+    isolate: *mut Isolate,
+  );
+  fn v8__MicrotaskQueue__IsRunningMicrotasks(
+    this: *const MicrotaskQueue,
+  ) -> bool;
+  fn v8__MicrotaskQueue__GetMicrotasksScopeDepth(
+    this: *const MicrotaskQueue,
+  ) -> int;
+  fn v8__MicrotaskQueue__AddMicrotasksCompletedCallback(
+    this: *const MicrotaskQueue,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  );
+  fn v8__MicrotaskQueue__RemoveMicrotasksCompletedCallback(
+    this: *const MicrotaskQueue,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  );
+}
+
+/// A queue of microtasks (e.g. promise reactions) that is separate from the
+/// isolate's default queue. Contexts created with
+/// `Context::new_with_microtask_queue()` enqueue their microtasks here, so
+/// that running the microtasks of one context doesn't run those of others.
+///
+/// The queue is run automatically or by `perform_checkpoint()`, depending on
+/// the `MicrotasksPolicy` it was created with.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MicrotaskQueue(Opaque);
+
+impl MicrotaskQueue {
+  pub fn new(
+    isolate: &mut Isolate,
+    policy: MicrotasksPolicy,
+  ) -> UniqueRef<Self> {
+    unsafe { UniqueRef::from_raw(v8__MicrotaskQueue__New(isolate, policy)) }
+  }
+
+  /// Enqueues `microtask` to be called with no arguments.
+  pub fn enqueue_microtask(
+    &self,
+    isolate: &mut Isolate,
+    microtask: Local<Function>,
+  ) {
+    unsafe { v8__MicrotaskQueue__EnqueueMicrotask(self, isolate, &*microtask) }
+  }
+
+  /// Runs the microtasks in this queue until it is empty. Does nothing if
+  /// the queue is already being run, or if microtasks are suppressed by a
+  /// `MicrotasksScope`. Any exceptions thrown by microtasks are swallowed.
+  pub fn perform_checkpoint(&self, isolate: &mut Isolate) {
+    unsafe { v8__MicrotaskQueue__PerformCheckpoint(self, isolate) }
+  }
+
+  /// Returns true while the microtasks of this queue are being run.
+  pub fn is_running_microtasks(&self) -> bool {
+    unsafe { v8__MicrotaskQueue__IsRunningMicrotasks(self) }
+  }
+
+  /// Returns the current depth of nested microtask scopes for this queue.
+  pub fn get_microtasks_scope_depth(&self) -> i32 {
+    unsafe { v8__MicrotaskQueue__GetMicrotasksScopeDepth(self) }
+  }
+
+  /// Adds a callback that is invoked every time this queue has been run.
+  /// See `Isolate::add_microtasks_completed_callback()`.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // False positive.
+  pub fn add_microtasks_completed_callback(
+    &self,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  ) {
+    unsafe {
+      v8__MicrotaskQueue__AddMicrotasksCompletedCallback(self, callback, data)
+    }
+  }
+
+  /// Removes a callback that was added with
+  /// `add_microtasks_completed_callback()` with the same `data` argument.
+  #[allow(clippy::not_unsafe_ptr_arg_deref)] // False positive.
+  pub fn remove_microtasks_completed_callback(
+    &self,
+    callback: MicrotasksCompletedCallback,
+    data: *mut c_void,
+  ) {
+    unsafe {
+      v8__MicrotaskQueue__RemoveMicrotasksCompletedCallback(
+        self, callback, data,
+      )
+    }
+  }
+}
+
+impl Drop for MicrotaskQueue {
+  fn drop(&mut self) {
+    unsafe { v8__MicrotaskQueue__DELETE(self) }
+  }
+}
+
+/// Provides information about the state of microtask execution in an
+/// isolate. This type can't be instantiated; it only has associated functions
+/// that mirror the static methods of V8's `MicrotasksScope`.
+pub struct MicrotasksScope {
+  _private: (),
+}
+
+impl MicrotasksScope {
+  /// Returns true while microtasks are being run, e.g. during
+  /// `Isolate::perform_microtask_checkpoint()`. Can be used to detect that
+  /// embedder code was (re-)entered from within a microtask.
+  pub fn is_running_microtasks(isolate: &mut Isolate) -> bool {
+    unsafe { v8__MicrotasksScope__IsRunningMicrotasks(isolate) }
+  }
+
+  /// Returns the current depth of nested microtask scopes. Microtasks are not
+  /// run automatically while this is greater than zero.
+  pub fn get_current_depth(isolate: &mut Isolate) -> i32 {
+    unsafe { v8__MicrotasksScope__GetCurrentDepth(isolate) }
+  }
+}
@@ -8,6 +8,7 @@ use crate::support::MapFnTo;
 use crate::support::MaybeBool;
 use crate::support::ToCFn;
 use crate::support::UnitType;
+use crate::Array;
 use crate::Context;
 use crate::FixedArray;
 use crate::HandleScope;
@@ -15,7 +16,9 @@ use crate::Isolate;
 use crate::Local;
 use crate::Module;
 use crate::ModuleRequest;
+use crate::Object;
 use crate::String;
+use crate::TryCatch;
 use crate::UnboundModuleScript;
 use crate::Value;
 
@@ -142,6 +145,10 @@ extern "C" {
     out: *mut MaybeUninit<Location>,
   ) -> Location;
   fn v8__Module__GetModuleNamespace(this: *const Module) -> *const Value;
+  fn v8__Module__GetExportNames(
+    this: *const Module,
+    context: *const Context,
+  ) -> *const Array;
   fn v8__Module__GetIdentityHash(this: *const Module) -> int;
   fn v8__Module__ScriptId(this: *const Module) -> int;
   fn v8__Module__InstantiateModule(
@@ -213,6 +220,19 @@ pub enum ModuleStatus {
   Errored,
 }
 
+/// The current value of a module export. See `Module::get_export()`.
+#[derive(Debug, Clone, Copy)]
+pub enum ModuleExport<'s> {
+  /// The export's binding has been initialized and has the given value.
+  Initialized(Local<'s, Value>),
+  /// The export's binding hasn't been initialized yet, i.e. it is in its
+  /// temporal dead zone, so reading it from JavaScript throws a
+  /// ReferenceError. This is the case for `let`, `const` and `class`
+  /// exports of a module that hasn't been evaluated yet, or that is being
+  /// evaluated or threw before their declaration was reached.
+  Uninitialized,
+}
+
 impl Module {
   /// Returns the module's current status.
   pub fn get_status(&self) -> ModuleStatus {
@@ -272,6 +292,55 @@ impl Module {
     unsafe { Local::from_raw(v8__Module__GetModuleNamespace(self)).unwrap() }
   }
 
+  /// Returns the names of the exports of this module, i.e. the keys of its
+  /// namespace object, in sorted order. Unlike enumerating the namespace
+  /// object from JavaScript, this doesn't throw if an export hasn't been
+  /// initialized yet.
+  ///
+  /// The module's status must be at least kInstantiated.
+  pub fn get_export_names<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+  ) -> Vec<Local<'s, String>> {
+    let names = unsafe {
+      scope.cast_local(|sd| {
+        v8__Module__GetExportNames(self, sd.get_current_context())
+      })
+    }
+    .unwrap();
+    (0..names.length())
+      .map(|i| names.get_index(scope, i).unwrap().try_into().unwrap())
+      .collect()
+  }
+
+  /// Reads the current value of the export with the given name from the
+  /// module's namespace object. Since exports are live bindings, the value
+  /// reflects any assignments the module made since it was evaluated.
+  ///
+  /// Returns `ModuleExport::Uninitialized` rather than throwing if the
+  /// binding is in its temporal dead zone. Returns None if the module has no
+  /// such export, or if execution is being terminated.
+  ///
+  /// The module's status must be at least kInstantiated.
+  pub fn get_export<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    name: Local<String>,
+  ) -> Option<ModuleExport<'s>> {
+    let namespace: Local<Object> =
+      self.get_module_namespace().try_into().unwrap();
+    let scope = &mut TryCatch::new(scope);
+    if !namespace.has(scope, name.into())? {
+      return None;
+    }
+    match namespace.get(scope, name.into()) {
+      Some(value) => Some(ModuleExport::Initialized(value)),
+      // Reading an export only throws if its binding is uninitialized.
+      None if scope.can_continue() => Some(ModuleExport::Uninitialized),
+      None => None,
+    }
+  }
+
   /// Instantiates the module and its dependencies.
   ///
   /// Returns an empty Maybe<bool> if an exception occurred during
@@ -414,4 +483,9 @@ impl ModuleRequest {
     unsafe { Local::from_raw(v8__ModuleRequest__GetImportAssertions(self)) }
       .unwrap()
   }
+
+  // TODO: bind ModuleRequest::GetPhase() and
+  // Isolate::SetHostImportModuleWithPhaseDynamicallyCallback() for the source
+  // phase imports proposal. These were added in V8 12.x and are not available
+  // in the V8 version this crate currently builds against.
 }
@@ -3,7 +3,10 @@
 use std::num::NonZeroI32;
 
 use crate::support::int;
+use crate::Local;
 use crate::Name;
+use crate::String;
+use crate::Symbol;
 
 extern "C" {
   fn v8__Name__GetIdentityHash(this: *const Name) -> int;
@@ -19,3 +22,39 @@ impl Name {
     unsafe { NonZeroI32::new_unchecked(v8__Name__GetIdentityHash(self)) }
   }
 }
+
+/// A `Name` resolved to its concrete type, which is either a `String` or a
+/// `Symbol`. This allows matching on a name (e.g. a property key passed to an
+/// interceptor) without any unchecked casts:
+///
+/// ```ignore
+/// match v8::NameKind::from(key) {
+///   v8::NameKind::String(s) => println!("{}", s.to_rust_string_lossy(scope)),
+///   v8::NameKind::Symbol(_) => println!("<symbol>"),
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub enum NameKind<'s> {
+  String(Local<'s, String>),
+  Symbol(Local<'s, Symbol>),
+}
+
+impl<'s> From<Local<'s, Name>> for NameKind<'s> {
+  fn from(name: Local<'s, Name>) -> Self {
+    if name.is_string() {
+      Self::String(unsafe { Local::cast(name) })
+    } else {
+      debug_assert!(name.is_symbol());
+      Self::Symbol(unsafe { Local::cast(name) })
+    }
+  }
+}
+
+impl<'s> From<NameKind<'s>> for Local<'s, Name> {
+  fn from(kind: NameKind<'s>) -> Self {
+    match kind {
+      NameKind::String(s) => s.into(),
+      NameKind::Symbol(s) => s.into(),
+    }
+  }
+}
@@ -7,6 +7,7 @@ use crate::support::MaybeBool;
 use crate::AccessorNameGetterCallback;
 use crate::AccessorNameSetterCallback;
 use crate::Array;
+use crate::CachedPropertyName;
 use crate::Context;
 use crate::HandleScope;
 use crate::Local;
@@ -15,6 +16,7 @@ use crate::Name;
 use crate::Object;
 use crate::Private;
 use crate::PropertyAttribute;
+use crate::String;
 use crate::Value;
 use std::convert::TryFrom;
 use std::num::NonZeroI32;
@@ -107,6 +109,49 @@ extern "C" {
     context: *const Context,
     key: *const Name,
   ) -> MaybeBool;
+  fn v8__Object__GetOwnPropertyDescriptor(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+  ) -> *const Value;
+  fn v8__Object__HasRealNamedProperty(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+  ) -> MaybeBool;
+  fn v8__Object__HasRealIndexedProperty(
+    this: *const Object,
+    context: *const Context,
+    index: u32,
+  ) -> MaybeBool;
+  fn v8__Object__HasRealNamedCallbackProperty(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+  ) -> MaybeBool;
+  fn v8__Object__GetRealNamedProperty(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+  ) -> *const Value;
+  fn v8__Object__GetRealNamedPropertyInPrototypeChain(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+  ) -> *const Value;
+  fn v8__Object__GetRealNamedPropertyAttributes(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+    out: *mut PropertyAttribute,
+  ) -> bool;
+  fn v8__Object__DefineRealOwnProperty(
+    this: *const Object,
+    context: *const Context,
+    key: *const Name,
+    value: *const Value,
+    attr: PropertyAttribute,
+  ) -> MaybeBool;
   fn v8__Object__Delete(
     this: *const Object,
     context: *const Context,
@@ -118,6 +163,7 @@ extern "C" {
     index: u32,
   ) -> MaybeBool;
   fn v8__Object__InternalFieldCount(this: *const Object) -> int;
+  fn v8__Object__IsApiWrapper(this: *const Object) -> bool;
   fn v8__Object__GetInternalField(
     this: *const Object,
     index: int,
@@ -322,6 +368,30 @@ impl Object {
     .into()
   }
 
+  /// Like `define_own_property()`, but doesn't call the interceptors of the
+  /// object. This can be used to implement the default behavior from inside
+  /// an interceptor callback without recursing into the interceptor.
+  ///
+  /// Returns true on success.
+  pub fn define_real_own_property(
+    &self,
+    scope: &mut HandleScope,
+    key: Local<Name>,
+    value: Local<Value>,
+    attr: PropertyAttribute,
+  ) -> Option<bool> {
+    unsafe {
+      v8__Object__DefineRealOwnProperty(
+        self,
+        &*scope.get_current_context(),
+        &*key,
+        &*value,
+        attr,
+      )
+    }
+    .into()
+  }
+
   pub fn get<'s>(
     &self,
     scope: &mut HandleScope<'s>,
@@ -345,6 +415,29 @@ impl Object {
     }
   }
 
+  /// Like `get()`, but the key is a name that is only created once per
+  /// isolate.
+  pub fn get_cached<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: &'static CachedPropertyName,
+  ) -> Option<Local<'s, Value>> {
+    let key = key.get(scope);
+    self.get(scope, key.into())
+  }
+
+  /// Like `set()`, but the key is a name that is only created once per
+  /// isolate.
+  pub fn set_cached(
+    &self,
+    scope: &mut HandleScope,
+    key: &'static CachedPropertyName,
+    value: Local<Value>,
+  ) -> Option<bool> {
+    let key = key.get(scope);
+    self.set(scope, key.into(), value)
+  }
+
   /// Get the prototype object. This does not skip objects marked to be
   /// skipped by proto and it does not consult the security handler.
   pub fn get_prototype<'s>(
@@ -477,6 +570,130 @@ impl Object {
     .into()
   }
 
+  /// Like `has_own_property()`, but doesn't call interceptors, so it can be
+  /// used to check for an own property without side effects.
+  pub fn has_real_named_property<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: Local<Name>,
+  ) -> Option<bool> {
+    unsafe {
+      v8__Object__HasRealNamedProperty(
+        self,
+        &*scope.get_current_context(),
+        &*key,
+      )
+    }
+    .into()
+  }
+
+  /// Like `has_real_named_property()`, but for an indexed property.
+  pub fn has_real_indexed_property<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    index: u32,
+  ) -> Option<bool> {
+    unsafe {
+      v8__Object__HasRealIndexedProperty(
+        self,
+        &*scope.get_current_context(),
+        index,
+      )
+    }
+    .into()
+  }
+
+  /// Returns whether the object has an own accessor property with native
+  /// callbacks (e.g. one added with `set_accessor()`), without calling
+  /// interceptors.
+  pub fn has_real_named_callback_property<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: Local<Name>,
+  ) -> Option<bool> {
+    unsafe {
+      v8__Object__HasRealNamedCallbackProperty(
+        self,
+        &*scope.get_current_context(),
+        &*key,
+      )
+    }
+    .into()
+  }
+
+  /// Looks up a property on the object and its prototype chain without
+  /// calling interceptors. Returns None if no real property was found.
+  pub fn get_real_named_property<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: Local<Name>,
+  ) -> Option<Local<'s, Value>> {
+    unsafe {
+      scope.cast_local(|sd| {
+        v8__Object__GetRealNamedProperty(self, sd.get_current_context(), &*key)
+      })
+    }
+  }
+
+  /// Like `get_real_named_property()`, but only looks at the prototype chain,
+  /// skipping the object's own properties.
+  pub fn get_real_named_property_in_prototype_chain<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: Local<Name>,
+  ) -> Option<Local<'s, Value>> {
+    unsafe {
+      scope.cast_local(|sd| {
+        v8__Object__GetRealNamedPropertyInPrototypeChain(
+          self,
+          sd.get_current_context(),
+          &*key,
+        )
+      })
+    }
+  }
+
+  /// Returns the attributes of the property found by
+  /// `get_real_named_property()`, or None if there is no such property.
+  pub fn get_real_named_property_attributes<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: Local<Name>,
+  ) -> Option<PropertyAttribute> {
+    let mut attr = PropertyAttribute::default();
+    unsafe {
+      v8__Object__GetRealNamedPropertyAttributes(
+        self,
+        &*scope.get_current_context(),
+        &*key,
+        &mut attr,
+      )
+    }
+    .then_some(attr)
+  }
+
+  /// Implements Object.getOwnPropertyDescriptor(O, P) (ECMA-262, 19.1.2.6).
+  ///
+  /// Returns the property descriptor object for the own property `key`, or
+  /// `undefined` if the object has no such own property. Accessor properties
+  /// are described with `get` and `set` fields, data properties with `value`
+  /// and `writable`.
+  pub fn get_own_property_descriptor<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    key: Local<Name>,
+  ) -> Option<Local<'s, Value>> {
+    unsafe {
+      scope.cast_local(|sd| {
+        v8__Object__GetOwnPropertyDescriptor(
+          self,
+          sd.get_current_context(),
+          &*key,
+        )
+      })
+    }
+  }
+
   pub fn delete<'s>(
     &self,
     scope: &mut HandleScope<'s>,
@@ -503,6 +720,14 @@ impl Object {
     usize::try_from(count).expect("bad internal field count") // Can't happen.
   }
 
+  /// Returns true if this object can carry embedder data in its internal
+  /// fields, i.e. it was created from an `ObjectTemplate` with a non-zero
+  /// internal field count. This doesn't allocate, so it can be used to check
+  /// the receiver of a fast API call.
+  pub fn is_api_wrapper(&self) -> bool {
+    unsafe { v8__Object__IsApiWrapper(self) }
+  }
+
   /// Gets the value from an internal field.
   pub fn get_internal_field<'s>(
     &self,
@@ -531,6 +756,11 @@ impl Object {
     &self,
     index: i32,
   ) -> *const c_void {
+    debug_assert!(
+      self.has_internal_field(index),
+      "internal field index {} out of bounds",
+      index
+    );
     v8__Object__GetAlignedPointerFromInternalField(self, index)
   }
 
@@ -542,9 +772,18 @@ impl Object {
     index: i32,
     value: *const c_void,
   ) {
+    debug_assert!(
+      self.has_internal_field(index),
+      "internal field index {} out of bounds",
+      index
+    );
     unsafe { v8__Object__SetAlignedPointerInInternalField(self, index, value) }
   }
 
+  fn has_internal_field(&self, index: i32) -> bool {
+    usize::try_from(index).is_ok_and(|i| i < self.internal_field_count())
+  }
+
   /// Sets the integrity level of the object.
   pub fn set_integrity_level(
     &self,
@@ -687,6 +926,38 @@ impl Array {
     .unwrap()
   }
 
+  /// Creates a JavaScript array with the given length, whose elements are
+  /// produced one at a time by `next_value`. This avoids collecting all the
+  /// elements up front, and each element is created in its own `HandleScope`,
+  /// so the number of live handles doesn't grow with the length of the array.
+  ///
+  /// This is an emulation, not a binding: V8 10.5 has no `Array::New()`
+  /// overload that takes a callback. The array is created empty and the
+  /// elements are added with `create_data_property()`, so setters on
+  /// `Array.prototype` are not invoked, but the array is not created in one
+  /// step the way `Array::new_with_elements()` creates it.
+  ///
+  /// Returns `None` if `next_value` returns `None`, which it should do if an
+  /// exception was thrown, or if an exception is thrown while adding an
+  /// element.
+  pub fn new_with_callback<'s, F>(
+    scope: &mut HandleScope<'s>,
+    length: u32,
+    mut next_value: F,
+  ) -> Option<Local<'s, Array>>
+  where
+    F: for<'t> FnMut(&mut HandleScope<'t>) -> Option<Local<'t, Value>>,
+  {
+    let array = Self::new(scope, i32::try_from(length).unwrap_or(i32::MAX));
+    for index in 0..length {
+      let scope = &mut HandleScope::new(scope);
+      let value = next_value(scope)?;
+      let key = String::new(scope, &index.to_string())?;
+      array.create_data_property(scope, key.into(), value)?;
+    }
+    Some(array)
+  }
+
   pub fn length(&self) -> u32 {
     unsafe { v8__Array__Length(self) }
   }
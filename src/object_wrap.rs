@@ -0,0 +1,254 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+//! Support for exposing Rust types to JavaScript as native classes.
+//!
+//! A type implementing [`ObjectWrap`] gets a `FunctionTemplate` that is
+//! created once per isolate. Instances created from it own a value of the
+//! Rust type, which is stored in the object's first internal field and
+//! dropped by a weak finalizer once the object has been garbage collected (or
+//! when the isolate is disposed). The value is tagged with its type, so that
+//! an object can't be unwrapped as a different type, e.g. when the template of
+//! one class inherits from that of another.
+//!
+//! The `#[v8_class]` attribute macro, available with the `macros` feature,
+//! implements `ObjectWrap` for an `impl` block:
+//!
+//! ```ignore
+//! struct Counter(std::cell::Cell<i32>);
+//!
+//! #[v8::v8_class]
+//! impl Counter {
+//!   #[constructor]
+//!   fn new(
+//!     _scope: &mut v8::HandleScope,
+//!     _args: v8::FunctionCallbackArguments,
+//!   ) -> Option<Self> {
+//!     Some(Self(Default::default()))
+//!   }
+//!
+//!   #[method]
+//!   fn increment(
+//!     &self,
+//!     _scope: &mut v8::HandleScope,
+//!     _args: v8::FunctionCallbackArguments,
+//!     _rv: v8::ReturnValue,
+//!   ) {
+//!     self.0.set(self.0.get() + 1);
+//!   }
+//!
+//!   #[getter]
+//!   fn count(&self, _scope: &mut v8::HandleScope, mut rv: v8::ReturnValue) {
+//!     rv.set_int32(self.0.get());
+//!   }
+//! }
+//!
+//! let template = v8::object_wrap::template::<Counter>(scope);
+//! ```
+
+use std::any::TypeId;
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::ptr::null;
+use std::ptr::NonNull;
+
+use crate::handle::WeakData;
+use crate::FunctionCallbackArguments;
+use crate::FunctionTemplate;
+use crate::Global;
+use crate::HandleScope;
+use crate::Local;
+use crate::Object;
+use crate::ReturnValue;
+use crate::String;
+use crate::Weak;
+
+/// A Rust type that can be exposed to JavaScript as a class. Usually
+/// implemented with the `#[v8_class]` attribute macro.
+pub trait ObjectWrap: Sized + 'static {
+  /// The name of the JavaScript class.
+  const CLASS_NAME: &'static str;
+
+  /// The number of internal fields that instances have in addition to the one
+  /// holding the Rust value. They start at index [`FIRST_INTERNAL_FIELD`] and
+  /// are free for the embedder to use.
+  const INTERNAL_FIELD_COUNT: usize = 0;
+
+  /// Called when the class is constructed from JavaScript, e.g. with
+  /// `new ClassName()`. Returns `None` if the object could not be created, in
+  /// which case an exception should have been thrown.
+  ///
+  /// The default implementation throws a `TypeError`, so that instances can
+  /// only be created from Rust with [`wrap()`].
+  fn construct(
+    scope: &mut HandleScope,
+    args: FunctionCallbackArguments,
+  ) -> Option<Self> {
+    let _ = args;
+    scope.throw_type_error("Illegal constructor");
+    None
+  }
+
+  /// Called once per isolate, right after the class template has been
+  /// created, to add methods and accessors to it.
+  fn init_template(
+    scope: &mut HandleScope<()>,
+    template: Local<FunctionTemplate>,
+  ) {
+    let _ = (scope, template);
+  }
+}
+
+/// Keeps the template of class `T` alive for the lifetime of the isolate.
+struct ClassTemplate<T> {
+  template: Global<FunctionTemplate>,
+  _phantom: PhantomData<T>,
+}
+
+/// The index of the first internal field of instances that is not used by
+/// this module. See [`ObjectWrap::INTERNAL_FIELD_COUNT`].
+pub const FIRST_INTERNAL_FIELD: usize = 1;
+
+/// The value stored in the internal field of a wrapper object.
+#[repr(C)]
+struct Wrapped<T> {
+  /// Must be the first field, so that it can be checked before the pointer is
+  /// cast to a `Wrapped<T>` of the wrong type.
+  type_tag: TypeId,
+  value: T,
+  weak: Cell<Option<NonNull<WeakData<Object>>>>,
+}
+
+/// Returns the `FunctionTemplate` of class `T`, creating it the first time
+/// this is called in the isolate.
+pub fn template<'s, T: ObjectWrap>(
+  scope: &mut HandleScope<'s, ()>,
+) -> Local<'s, FunctionTemplate> {
+  let cached = scope
+    .get_slot::<ClassTemplate<T>>()
+    .map(|class| class.template.clone());
+  if let Some(template) = cached {
+    return Local::new(scope, template);
+  }
+
+  let template = FunctionTemplate::new(scope, construct_callback::<T>);
+  let class_name = String::new(scope, T::CLASS_NAME).unwrap();
+  template.set_class_name(class_name);
+  template
+    .instance_template(scope)
+    .set_internal_field_count(FIRST_INTERNAL_FIELD + T::INTERNAL_FIELD_COUNT);
+  T::init_template(scope, template);
+
+  let class = ClassTemplate::<T> {
+    template: Global::new(scope, template),
+    _phantom: PhantomData,
+  };
+  scope.set_slot(class);
+  template
+}
+
+/// Creates a new instance of class `T` that owns `value`, without calling the
+/// JavaScript constructor. Returns `None` if an exception was thrown.
+pub fn wrap<'s, T: ObjectWrap>(
+  scope: &mut HandleScope<'s>,
+  value: T,
+) -> Option<Local<'s, Object>> {
+  let template = template::<T>(scope);
+  let object = template.instance_template(scope).new_instance(scope)?;
+  attach(scope, object, value);
+  Some(object)
+}
+
+/// Returns a reference to the Rust value owned by `object`, or `None` if
+/// `object` is not an instance of class `T`.
+///
+/// This also returns `None` if `object` is an instance of class `T` that owns
+/// a value of another type, which happens when the template of another class
+/// inherits from that of `T`. JavaScript code can pass such an object to the
+/// methods of `T`, e.g. with `Base.prototype.method.call(derived)`.
+///
+/// The reference is valid for as long as the handle to the object is, since
+/// the object (and thereby the value) can't be garbage collected before that.
+pub fn unwrap<'a, T: ObjectWrap>(
+  scope: &mut HandleScope,
+  object: Local<'a, Object>,
+) -> Option<&'a T> {
+  let template = template::<T>(scope);
+  if !template.has_instance(object.into()) || object.internal_field_count() < 1
+  {
+    return None;
+  }
+  // SAFETY: the first internal field of instances of the class template is
+  // initialized with an aligned pointer before any JavaScript code can observe
+  // the object.
+  let wrapped = unsafe { object.get_aligned_pointer_from_internal_field(0) }
+    as *const Wrapped<T>;
+  if wrapped.is_null() {
+    return None;
+  }
+  // SAFETY: `type_tag` is the first field of every `Wrapped<_>`, so it can be
+  // read without knowing which type the object actually wraps.
+  let type_tag = unsafe { *(wrapped as *const TypeId) };
+  if type_tag != TypeId::of::<T>() {
+    return None;
+  }
+  Some(unsafe { &(*wrapped).value })
+}
+
+/// Like [`unwrap()`], but throws a `TypeError` if `receiver` is not an
+/// instance of class `T`. Used by the code generated by `#[v8_class]`.
+#[doc(hidden)]
+pub fn unwrap_receiver<'a, T: ObjectWrap>(
+  scope: &mut HandleScope,
+  receiver: Local<'a, Object>,
+) -> Option<&'a T> {
+  let value = unwrap::<T>(scope, receiver);
+  if value.is_none() {
+    scope.throw_type_error("Illegal invocation");
+  }
+  value
+}
+
+fn construct_callback<T: ObjectWrap>(
+  scope: &mut HandleScope,
+  args: FunctionCallbackArguments,
+  _rv: ReturnValue,
+) {
+  if args.new_target().is_undefined() {
+    scope.throw_type_error(format_args!(
+      "Class constructor {} cannot be invoked without 'new'",
+      T::CLASS_NAME
+    ));
+    return;
+  }
+  let this = args.this();
+  // Make sure `unwrap()` never reads an uninitialized internal field, even if
+  // `construct()` fails.
+  this.set_aligned_pointer_in_internal_field(0, null());
+  if let Some(value) = T::construct(scope, args) {
+    attach(scope, this, value);
+  }
+}
+
+/// Moves `value` into the first internal field of `object`, and installs a
+/// finalizer that drops it once `object` has been garbage collected.
+fn attach<T: ObjectWrap>(
+  scope: &mut HandleScope,
+  object: Local<Object>,
+  value: T,
+) {
+  let wrapped = Box::into_raw(Box::new(Wrapped {
+    type_tag: TypeId::of::<T>(),
+    value,
+    weak: Cell::new(None),
+  }));
+  object.set_aligned_pointer_in_internal_field(0, wrapped as *const _);
+  let weak = Weak::with_finalizer(
+    scope,
+    object,
+    Box::new(move |isolate| {
+      let wrapped = unsafe { Box::from_raw(wrapped) };
+      drop(unsafe { Weak::from_raw(isolate, wrapped.weak.take()) });
+    }),
+  );
+  unsafe { &*wrapped }.weak.set(weak.into_raw());
+}
@@ -14,6 +14,17 @@ extern "C" {
     thread_pool_size: int,
     idle_task_support: bool,
   ) -> *mut Platform;
+  fn v8__Platform__NewNotifyingPlatform(
+    thread_pool_size: int,
+    idle_task_support: bool,
+    callback: ForegroundTaskPostedCallback,
+  ) -> *mut Platform;
+  fn v8__Platform__NewClockPlatform(
+    thread_pool_size: int,
+    idle_task_support: bool,
+    monotonic_time: PlatformClockCallback,
+    current_clock_time_millis: PlatformClockCallback,
+  ) -> *mut Platform;
   fn v8__Platform__NewSingleThreadedDefaultPlatform(
     idle_task_support: bool,
   ) -> *mut Platform;
@@ -50,6 +61,24 @@ extern "C" {
 #[derive(Debug)]
 pub struct Platform(Opaque);
 
+/// Called by a platform created with
+/// `Platform::new_with_foreground_task_posted_callback()` right after a
+/// foreground task has been posted for `isolate`, so that the embedder knows
+/// when to call `Platform::pump_message_loop()`. For delayed tasks,
+/// `delay_in_seconds` is the time after which the task can run; it is zero for
+/// other tasks.
+///
+/// This can be called on any thread, including V8's worker threads, so
+/// `isolate` must only be used to identify the isolate and must not be
+/// dereferenced.
+pub type ForegroundTaskPostedCallback =
+  extern "C" fn(isolate: *mut Isolate, delay_in_seconds: f64);
+
+/// Returns the current time of a clock. See `Platform::new_with_clock()`.
+///
+/// This can be called on any thread.
+pub type PlatformClockCallback = extern "C" fn() -> f64;
+
 /// Returns a new instance of the default v8::Platform implementation.
 ///
 /// |thread_pool_size| is the number of worker threads to allocate for
@@ -98,6 +127,47 @@ impl Platform {
     }
   }
 
+  /// The same as new(), but `callback` is called whenever a foreground task
+  /// is posted for an isolate. This allows the embedder to wait for tasks
+  /// without polling `pump_message_loop()`.
+  pub fn new_with_foreground_task_posted_callback(
+    thread_pool_size: u32,
+    idle_task_support: bool,
+    callback: ForegroundTaskPostedCallback,
+  ) -> UniqueRef<Self> {
+    unsafe {
+      UniqueRef::from_raw(v8__Platform__NewNotifyingPlatform(
+        thread_pool_size.min(16) as i32,
+        idle_task_support,
+        callback,
+      ))
+    }
+  }
+
+  /// The same as new(), but the platform's clocks are provided by the
+  /// embedder, which allows `Date.now()` and timers to be made deterministic,
+  /// e.g. for replay testing or reproducible snapshots. Use
+  /// `V8::set_entropy_source()` to do the same for `Math.random()`.
+  ///
+  /// `monotonic_time` returns the time in seconds since an arbitrary point in
+  /// the past, and must never decrease. `current_clock_time_millis` returns
+  /// the wall clock time in milliseconds since the Unix epoch.
+  pub fn new_with_clock(
+    thread_pool_size: u32,
+    idle_task_support: bool,
+    monotonic_time: PlatformClockCallback,
+    current_clock_time_millis: PlatformClockCallback,
+  ) -> UniqueRef<Self> {
+    unsafe {
+      UniqueRef::from_raw(v8__Platform__NewClockPlatform(
+        thread_pool_size.min(16) as i32,
+        idle_task_support,
+        monotonic_time,
+        current_clock_time_millis,
+      ))
+    }
+  }
+
   /// The same as new() but disables the worker thread pool.
   /// It must be used with the --single-threaded V8 flag.
   ///
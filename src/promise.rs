@@ -1,12 +1,18 @@
 use std::marker::PhantomData;
 
 use crate::support::MaybeBool;
+use crate::CallbackScope;
 use crate::Context;
+use crate::ContextScope;
+use crate::Exception;
 use crate::Function;
+use crate::Global;
 use crate::HandleScope;
 use crate::Local;
 use crate::Promise;
 use crate::PromiseResolver;
+use crate::SendableGlobal;
+use crate::StackTrace;
 use crate::Value;
 
 extern "C" {
@@ -191,15 +197,104 @@ impl PromiseResolver {
   }
 }
 
+/// A handle to a `PromiseResolver` that can be sent to other threads, so that
+/// e.g. async Rust code can settle a promise that it returned to JavaScript.
+///
+/// Settling the promise is queued, and happens in a microtask on the
+/// isolate's thread, at the next microtask checkpoint (or soon after, if the
+/// isolate is running JavaScript code at the time). Since values can only be
+/// created on the isolate's thread, `resolve()` and `reject()` take a closure
+/// that creates the value in the context the handle was created in.
+///
+/// If a `ResolverHandle` is dropped without settling the promise, the promise
+/// stays pending.
+#[derive(Debug)]
+pub struct ResolverHandle {
+  resolver: SendableGlobal<PromiseResolver>,
+  context: SendableGlobal<Context>,
+}
+
+impl ResolverHandle {
+  /// Creates a handle for `resolver`, which settles its promise in the current
+  /// context of `scope`.
+  pub fn new(
+    scope: &mut HandleScope,
+    resolver: Local<PromiseResolver>,
+  ) -> Self {
+    let context = scope.get_current_context();
+    Self {
+      resolver: Global::new(scope, resolver).into_sendable(),
+      context: Global::new(scope, context).into_sendable(),
+    }
+  }
+
+  /// Queues resolving the promise with the value returned by `value`. Returns
+  /// false if the isolate has been disposed.
+  pub fn resolve<F>(self, value: F) -> bool
+  where
+    F:
+      for<'s> FnOnce(&mut HandleScope<'s>) -> Local<'s, Value> + Send + 'static,
+  {
+    self.settle(move |scope| Ok(value(scope)))
+  }
+
+  /// Queues rejecting the promise with the value returned by `reason`. Returns
+  /// false if the isolate has been disposed.
+  pub fn reject<F>(self, reason: F) -> bool
+  where
+    F:
+      for<'s> FnOnce(&mut HandleScope<'s>) -> Local<'s, Value> + Send + 'static,
+  {
+    self.settle(move |scope| Err(reason(scope)))
+  }
+
+  /// Queues resolving the promise if `result` returns `Ok`, or rejecting it if
+  /// it returns `Err`. Returns false if the isolate has been disposed.
+  pub fn settle<F>(self, result: F) -> bool
+  where
+    F: for<'s> FnOnce(
+        &mut HandleScope<'s>,
+      ) -> Result<Local<'s, Value>, Local<'s, Value>>
+      + Send
+      + 'static,
+  {
+    let isolate_handle = self.resolver.isolate_handle().clone();
+    let Self { resolver, context } = self;
+    isolate_handle.enqueue_resolution(Box::new(move |isolate| {
+      let resolver = resolver.into_global(isolate);
+      let context = context.into_global(isolate);
+      let scope = &mut unsafe { CallbackScope::new(isolate) };
+      let scope = &mut HandleScope::new(scope);
+      let context = Local::new(scope, context);
+      let scope = &mut ContextScope::new(scope, context);
+      let resolver = Local::new(scope, resolver);
+      match result(scope) {
+        Ok(value) => resolver.resolve(scope, value),
+        Err(reason) => resolver.reject(scope, reason),
+      };
+    }))
+  }
+}
+
+/// The kind of event a `PromiseRejectCallback` is invoked for. See
+/// `Isolate::set_promise_reject_callback()`.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[repr(C)]
 pub enum PromiseRejectEvent {
+  /// A promise was rejected while it had no handler. The handler may still be
+  /// added later, in which case `PromiseHandlerAddedAfterReject` follows.
   PromiseRejectWithNoHandler,
+  /// A handler was added to a promise that was previously reported with
+  /// `PromiseRejectWithNoHandler`. The message has no value.
   PromiseHandlerAddedAfterReject,
+  /// A promise that was already resolved was rejected again, e.g. by calling
+  /// both the resolve and the reject function of a `new Promise()` executor.
   PromiseRejectAfterResolved,
+  /// A promise that was already resolved was resolved again.
   PromiseResolveAfterResolved,
 }
 
+/// The argument of a `PromiseRejectCallback`.
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct PromiseRejectMessage<'msg>([usize; 3], PhantomData<&'msg ()>);
@@ -214,7 +309,32 @@ impl<'msg> PromiseRejectMessage<'msg> {
     unsafe { v8__PromiseRejectMessage__GetEvent(self) }
   }
 
+  /// Returns the rejection reason, or the value the promise was resolved
+  /// with for `PromiseResolveAfterResolved`. Returns `None` for
+  /// `PromiseHandlerAddedAfterReject`.
   pub fn get_value(&self) -> Option<Local<'msg, Value>> {
     unsafe { Local::from_raw(v8__PromiseRejectMessage__GetValue(self)) }
   }
+
+  /// Returns the stack trace of the site where the promise was rejected (or
+  /// resolved again). If the value is an error object that captured a stack
+  /// trace when it was created (see
+  /// `Isolate::set_capture_stack_trace_for_uncaught_exceptions()`), that
+  /// stack trace is returned. Otherwise, since the callback is invoked
+  /// synchronously by the rejection, the current stack is captured, with at
+  /// most `frame_limit` frames. Returns `None` for
+  /// `PromiseHandlerAddedAfterReject`.
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn get_stack_trace<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+    frame_limit: usize,
+  ) -> Option<Local<'s, StackTrace>> {
+    let value = self.get_value()?;
+    match Exception::get_stack_trace(scope, value) {
+      Some(stack_trace) => Some(stack_trace),
+      None => StackTrace::current_stack_trace(scope, frame_limit),
+    }
+  }
 }
@@ -82,6 +82,8 @@ use std::alloc::Layout;
 use std::any::type_name;
 use std::cell::Cell;
 use std::convert::TryInto;
+use std::ffi::c_void;
+use std::fmt::Display;
 
 use std::marker::PhantomData;
 use std::mem::MaybeUninit;
@@ -93,9 +95,13 @@ use std::ptr::NonNull;
 
 use crate::function::FunctionCallbackInfo;
 use crate::function::PropertyCallbackInfo;
+use crate::AllocationProfile;
+use crate::Array;
 use crate::Context;
 use crate::Data;
 use crate::DataError;
+use crate::Exception;
+use crate::ExceptionReport;
 use crate::Handle;
 use crate::Isolate;
 use crate::Local;
@@ -104,6 +110,8 @@ use crate::Object;
 use crate::OwnedIsolate;
 use crate::Primitive;
 use crate::PromiseRejectMessage;
+use crate::SnapshotObjectId;
+use crate::String;
 use crate::Value;
 
 /// Stack-allocated class which sets the execution context for all operations
@@ -195,6 +203,18 @@ impl<'s> HandleScope<'s> {
       unsafe { raw::v8__Isolate__GetEnteredOrMicrotaskContext(isolate_ptr) };
     unsafe { Local::from_raw(context_ptr) }.unwrap()
   }
+
+  /// Returns the incumbent context, as defined by the HTML spec's "incumbent
+  /// settings object". This is usually the context of the JavaScript function
+  /// that (directly or indirectly) called into the embedder. If no JavaScript
+  /// is running, it falls back to the entered (or microtask) context.
+  pub fn get_incumbent_context(&self) -> Local<'s, Context> {
+    let data = data::ScopeData::get(self);
+    let isolate_ptr = data.get_isolate_ptr();
+    let context_ptr =
+      unsafe { raw::v8__Isolate__GetIncumbentContext(isolate_ptr) };
+    unsafe { Local::from_raw(context_ptr) }.unwrap()
+  }
 }
 
 impl<'s> HandleScope<'s, ()> {
@@ -217,6 +237,63 @@ impl<'s> HandleScope<'s, ()> {
     .unwrap()
   }
 
+  /// Returns the heap object with the given id, or `None` if the object has
+  /// been garbage collected or the id is unknown. See
+  /// `Isolate::get_heap_object_id()`.
+  pub fn find_object_by_heap_id(
+    &mut self,
+    id: SnapshotObjectId,
+  ) -> Option<Local<'s, Value>> {
+    unsafe {
+      self.cast_local(|sd| {
+        raw::v8__HeapProfiler__FindObjectById(sd.get_isolate_ptr(), id)
+      })
+    }
+  }
+
+  /// Returns the allocations that have been sampled by the sampling heap
+  /// profiler so far, or `None` if the profiler isn't running. See
+  /// `Isolate::start_sampling_heap_profiler()`.
+  pub fn get_allocation_profile(&mut self) -> Option<AllocationProfile> {
+    AllocationProfile::get(self)
+  }
+
+  /// Iterates through all external resources referenced from the current
+  /// isolate heap and calls `callback` for each external string, e.g. to
+  /// find strings that are worth externalizing with
+  /// `String::make_external_onebyte()`. GC is not allowed during the
+  /// iteration, so the callback must not allocate on the V8 heap.
+  pub fn visit_external_resources<F>(&mut self, mut callback: F)
+  where
+    F: FnMut(Local<'s, String>),
+  {
+    extern "C" fn trampoline<'s, F>(arg: *mut c_void, string: *const String)
+    where
+      F: FnMut(Local<'s, String>),
+    {
+      let callback = unsafe { &mut *(arg as *mut F) };
+      callback(unsafe { Local::from_raw(string) }.unwrap())
+    }
+
+    let arg = &mut callback as *mut F as *mut c_void;
+    unsafe {
+      raw::v8__Isolate__VisitExternalResources(
+        self.get_isolate_ptr(),
+        trampoline::<F>,
+        arg,
+      )
+    }
+  }
+
+  /// Returns the context of the currently running JavaScript, or the context
+  /// on the top of the stack if no JavaScript is running. Unlike
+  /// `get_current_context()`, this can be called on a `HandleScope` that was
+  /// created without a context, and returns `None` if no context is entered.
+  pub fn try_get_current_context(&self) -> Option<Local<'s, Context>> {
+    let context_ptr = data::ScopeData::get(self).get_current_context();
+    unsafe { Local::from_raw(context_ptr) }
+  }
+
   pub(crate) unsafe fn cast_local<T>(
     &mut self,
     f: impl FnOnce(&mut data::ScopeData) -> *const T,
@@ -284,6 +361,65 @@ impl<'s> HandleScope<'s> {
   }
 }
 
+/// Convenience functions not present in the original V8 API. Each of them
+/// creates an error object of the respective type with the given message and
+/// throws it, as if by calling `throw_exception()`. Like that function, they
+/// return the `undefined` value, which callbacks can pass on as their result:
+///
+/// ```ignore
+/// if args.length() == 0 {
+///   scope.throw_type_error(format_args!("{} requires an argument", name));
+///   return;
+/// }
+/// ```
+impl<'s> HandleScope<'s> {
+  pub fn throw_error(&mut self, message: impl Display) -> Local<'s, Value> {
+    self.throw_error_with(message, Exception::error)
+  }
+
+  pub fn throw_range_error(
+    &mut self,
+    message: impl Display,
+  ) -> Local<'s, Value> {
+    self.throw_error_with(message, Exception::range_error)
+  }
+
+  pub fn throw_reference_error(
+    &mut self,
+    message: impl Display,
+  ) -> Local<'s, Value> {
+    self.throw_error_with(message, Exception::reference_error)
+  }
+
+  pub fn throw_syntax_error(
+    &mut self,
+    message: impl Display,
+  ) -> Local<'s, Value> {
+    self.throw_error_with(message, Exception::syntax_error)
+  }
+
+  pub fn throw_type_error(
+    &mut self,
+    message: impl Display,
+  ) -> Local<'s, Value> {
+    self.throw_error_with(message, Exception::type_error)
+  }
+
+  /// Internal helper to make the above functions less repetitive.
+  fn throw_error_with(
+    &mut self,
+    message: impl Display,
+    constructor: for<'t> fn(
+      &mut HandleScope<'t>,
+      Local<String>,
+    ) -> Local<'t, Value>,
+  ) -> Local<'s, Value> {
+    let message = String::new(self, &message.to_string()).unwrap();
+    let exception = constructor(self, message);
+    self.throw_exception(exception)
+  }
+}
+
 /// A HandleScope which first allocates a handle in the current scope
 /// which will be later filled with the escape value.
 // TODO(piscisaureus): type parameter `C` is not very useful in practice; being
@@ -308,6 +444,17 @@ impl<'s, 'e: 's> EscapableHandleScope<'s, 'e> {
       .new_escapable_handle_scope_data()
       .as_scope()
   }
+
+  /// Escapes several values at once, by wrapping them in a new `Array` and
+  /// escaping that instead. This works around the fact that only a single
+  /// value can be escaped from an `EscapableHandleScope`. The values can be
+  /// read back in the outer scope with `Object::get_index()`.
+  ///
+  /// Cannot be called twice, nor together with `escape()`.
+  pub fn escape_array(&mut self, values: &[Local<Value>]) -> Local<'e, Array> {
+    let array = Array::new_with_elements(self, values);
+    self.escape(array)
+  }
 }
 
 impl<'s, 'e: 's, C> EscapableHandleScope<'s, 'e, C> {
@@ -480,6 +627,93 @@ where
       })
     }
   }
+
+  /// Convenience function not present in the original V8 API. Collects the
+  /// caught exception, its message, the location where it was thrown and the
+  /// formatted stack trace into an `ExceptionReport`. Returns `None` if no
+  /// exception has been caught.
+  pub fn exception_report(&mut self) -> Option<ExceptionReport<'p>> {
+    let exception = unsafe {
+      self
+        .as_mut()
+        .cast_local(|sd| raw::v8__TryCatch__Exception(sd.get_try_catch()))
+    }?;
+    let message = unsafe {
+      self
+        .as_mut()
+        .cast_local(|sd| raw::v8__TryCatch__Message(sd.get_try_catch()))
+    };
+
+    // Converting values to strings and reading the `.stack` property may call
+    // into JavaScript, which can throw. Do that in a nested try/catch block,
+    // so that such an exception does not replace the one being reported.
+    let scope = &mut TryCatch::new(self.as_mut());
+    let stack_trace = Local::<Object>::try_from(exception)
+      .ok()
+      .and_then(|object| {
+        let key = String::new(scope, "stack")?;
+        object.get(scope, key.into())
+      })
+      .filter(|stack| stack.is_string())
+      .map(|stack| stack.to_rust_string_lossy(scope));
+    let report = match message {
+      Some(message) => ExceptionReport {
+        exception,
+        message: message.get(scope).to_rust_string_lossy(scope),
+        resource_name: message
+          .get_script_resource_name(scope)
+          .filter(|name| !name.is_null_or_undefined())
+          .map(|name| name.to_rust_string_lossy(scope)),
+        line_number: message.get_line_number(scope),
+        start_column: Some(message.get_start_column()),
+        stack_trace,
+      },
+      None => ExceptionReport {
+        exception,
+        message: exception.to_rust_string_lossy(scope),
+        resource_name: None,
+        line_number: None,
+        start_column: None,
+        stack_trace,
+      },
+    };
+    Some(report)
+  }
+
+  /// Convenience function not present in the original V8 API. Turns the
+  /// `result` of an operation that was performed in this try/catch block into
+  /// a `Result`, with an `ExceptionReport` describing what was caught if the
+  /// operation failed. The error is `None` if the operation failed without
+  /// throwing an exception, e.g. because execution was terminated.
+  pub fn check<T>(
+    &mut self,
+    result: Option<T>,
+  ) -> Result<T, Option<ExceptionReport<'p>>> {
+    match result {
+      Some(value) => Ok(value),
+      None => Err(self.exception_report()),
+    }
+  }
+
+  /// Convenience function not present in the original V8 API. Performs the
+  /// operations in `f` in this try/catch block, and returns the
+  /// `ExceptionReport` (see `check()`) if `f` returns `None`. Since the result
+  /// of every fallible operation is an `Option`, the operations can be chained
+  /// with `?` inside `f`:
+  ///
+  /// ```ignore
+  /// let result = try_catch.catch(|scope| {
+  ///   let script = v8::Script::compile(scope, code, None)?;
+  ///   script.run(scope)
+  /// });
+  /// ```
+  pub fn catch<T>(
+    &mut self,
+    f: impl FnOnce(&mut Self) -> Option<T>,
+  ) -> Result<T, Option<ExceptionReport<'p>>> {
+    let result = f(self);
+    self.check(result)
+  }
 }
 
 /// A `CallbackScope` can be used to bootstrap a `HandleScope` and
@@ -1027,14 +1261,89 @@ pub(crate) mod data {
       isolate.set_current_scope_data(None);
     }
 
+    /// Returns an iterator over the scopes on the scope stack of `isolate`,
+    /// starting with the innermost one. The root `ScopeData` is not included.
+    fn iter_stack(isolate: &Isolate) -> impl Iterator<Item = &Self> {
+      let mut current = isolate
+        .get_current_scope_data()
+        .map(|nn| unsafe { &*nn.as_ptr() });
+      std::iter::from_fn(move || {
+        let data = current.take().filter(|data| data.previous.is_some())?;
+        current = data.previous.map(|nn| unsafe { &*nn.as_ptr() });
+        Some(data)
+      })
+    }
+
+    /// Returns the number of contexts that are entered by the scopes on the
+    /// scope stack of `isolate`, including zombie scopes that haven't been
+    /// exited yet.
+    pub(crate) fn count_entered_contexts(isolate: &Isolate) -> usize {
+      Self::iter_stack(isolate)
+        .filter(|data| data.scope_type_specific_data.enters_context())
+        .count()
+    }
+
+    /// Returns a description of every scope on the scope stack of `isolate`,
+    /// starting with the innermost one.
+    pub(crate) fn describe_stack(
+      isolate: &Isolate,
+    ) -> Vec<std::string::String> {
+      Self::iter_stack(isolate)
+        .map(|data| match data.status.get() {
+          ScopeStatus::Current { zombie: true }
+          | ScopeStatus::Shadowed { zombie: true } => {
+            format!("{} (zombie)", data.scope_type_name())
+          }
+          _ => data.scope_type_name().to_owned(),
+        })
+        .collect()
+    }
+
+    fn scope_type_name(&self) -> &'static str {
+      match &self.scope_type_specific_data {
+        ScopeTypeSpecificData::None => "CallbackScope",
+        ScopeTypeSpecificData::ContextScope { .. } => "ContextScope",
+        ScopeTypeSpecificData::HandleScope {
+          raw_context_scope: Some(_),
+          ..
+        } => "HandleScope (with context)",
+        ScopeTypeSpecificData::HandleScope { .. } => "HandleScope",
+        ScopeTypeSpecificData::EscapableHandleScope { .. } => {
+          "EscapableHandleScope"
+        }
+        ScopeTypeSpecificData::TryCatch { .. } => "TryCatch",
+      }
+    }
+
+    /// In debug builds, prints scopes being entered and exited to stderr if
+    /// the `RUSTY_V8_TRACE_SCOPES` environment variable is set.
+    #[inline(always)]
+    fn trace(&self, event: &str) {
+      lazy_static! {
+        static ref TRACE_SCOPES: bool =
+          std::env::var_os("RUSTY_V8_TRACE_SCOPES").is_some();
+      }
+      if cfg!(debug_assertions) && *TRACE_SCOPES {
+        let depth = Self::iter_stack(self.get_isolate()).count();
+        eprintln!(
+          "rusty_v8: {} {} (isolate {:p}, depth {})",
+          event,
+          self.scope_type_name(),
+          self.isolate,
+          depth
+        );
+      }
+    }
+
     pub(super) fn new_context_scope_data<'s>(
       &'s mut self,
       context: Local<'s, Context>,
     ) -> &'s mut Self {
       self.new_scope_data_with(move |data| {
+        let isolate = data.isolate;
         data.scope_type_specific_data.init_with(|| {
           ScopeTypeSpecificData::ContextScope {
-            _raw_context_scope: raw::ContextScope::new(context),
+            _raw_context_scope: raw::ContextScope::new(isolate, context),
           }
         });
         data.context.set(Some(context.as_non_null()));
@@ -1100,7 +1409,7 @@ pub(crate) mod data {
           debug_assert!(raw_context_scope.is_none());
           ptr::write(
             raw_context_scope,
-            Some(raw::ContextScope::new(local_context)),
+            Some(raw::ContextScope::new(isolate, local_context)),
           );
           // Also store the newly created `Local<Context>` in the `Cell` that
           // serves as a look-up cache for the current context.
@@ -1182,9 +1491,10 @@ pub(crate) mod data {
       // In debug builds, `zombie` is initially set to `true`, and the flag is
       // later cleared in the `as_scope()` method, to verify that we're
       // always creating exactly one scope from any `ScopeData` object.
-      // For performance reasons this check is not performed in release builds.
+      // For performance reasons this check is not performed in release builds,
+      // unless the `handle_checks` feature is enabled.
       new_scope_data.status = Cell::new(ScopeStatus::Current {
-        zombie: cfg!(debug_assertions),
+        zombie: cfg!(any(debug_assertions, feature = "handle_checks")),
       });
       // Store fields inherited from the parent scope.
       new_scope_data.context = context;
@@ -1195,6 +1505,7 @@ pub(crate) mod data {
       new_scope_data
         .get_isolate_mut()
         .set_current_scope_data(Some(new_scope_nn));
+      new_scope_data.trace("enter");
       new_scope_data
     }
 
@@ -1226,7 +1537,7 @@ pub(crate) mod data {
       // In debug builds, a new initialized `ScopeStatus` will have the `zombie`
       // flag set, so we have to reset it. In release builds, new `ScopeStatus`
       // objects come with the `zombie` flag cleared, so no update is necessary.
-      if cfg!(debug_assertions) {
+      if cfg!(any(debug_assertions, feature = "handle_checks")) {
         assert_eq!(self.status.get(), ScopeStatus::Current { zombie: true });
         self.status.set(ScopeStatus::Current { zombie: false });
       }
@@ -1281,6 +1592,7 @@ pub(crate) mod data {
     }
 
     fn exit_scope(&mut self) -> &mut Self {
+      self.trace("exit");
       // Clear out the scope type specific data field. None of the other fields
       // have a destructor, and there's no need to do any cleanup on them.
       self.scope_type_specific_data = Default::default();
@@ -1488,6 +1800,17 @@ pub(crate) mod data {
       matches!(self, Self::None)
     }
 
+    fn enters_context(&self) -> bool {
+      matches!(
+        self,
+        Self::ContextScope { .. }
+          | Self::HandleScope {
+            raw_context_scope: Some(_),
+            ..
+          }
+      )
+    }
+
     /// Replaces a `ScopeTypeSpecificData::None` value with the value returned
     /// from the specified closure. This function exists because initializing
     /// scopes is performance critical, and `ptr::write()` produces more
@@ -1513,19 +1836,29 @@ mod raw {
   #[derive(Debug)]
   pub(super) struct ContextScope {
     entered_context: NonNull<Context>,
+    isolate: NonNull<Isolate>,
+    // Whether the context's execution time is tracked, see
+    // `Isolate::set_context_execution_time_tracking()`.
+    tracked: bool,
   }
 
   impl ContextScope {
-    pub fn new(context: Local<Context>) -> Self {
+    pub fn new(isolate: NonNull<Isolate>, context: Local<Context>) -> Self {
       unsafe { v8__Context__Enter(&*context) };
+      let tracked = unsafe { (*isolate.as_ptr()).context_entered(&context) };
       Self {
         entered_context: context.as_non_null(),
+        isolate,
+        tracked,
       }
     }
   }
 
   impl Drop for ContextScope {
     fn drop(&mut self) {
+      if self.tracked {
+        unsafe { (*self.isolate.as_ptr()).context_exited() };
+      }
       unsafe { v8__Context__Exit(self.entered_context.as_ptr()) };
     }
   }
@@ -1623,10 +1956,22 @@ mod raw {
     pub(super) fn v8__Isolate__GetEnteredOrMicrotaskContext(
       isolate: *mut Isolate,
     ) -> *const Context;
+    pub(super) fn v8__Isolate__GetIncumbentContext(
+      isolate: *mut Isolate,
+    ) -> *const Context;
     pub(super) fn v8__Isolate__ThrowException(
       isolate: *mut Isolate,
       exception: *const Value,
     ) -> *const Value;
+    pub(super) fn v8__HeapProfiler__FindObjectById(
+      isolate: *mut Isolate,
+      id: SnapshotObjectId,
+    ) -> *const Value;
+    pub(super) fn v8__Isolate__VisitExternalResources(
+      isolate: *mut Isolate,
+      callback: extern "C" fn(*mut c_void, *const String),
+      arg: *mut c_void,
+    );
     pub(super) fn v8__Isolate__GetDataFromSnapshotOnce(
       this: *mut Isolate,
       index: usize,
@@ -1,12 +1,16 @@
 // Copyright 2019-2021 the Deno authors. All rights reserved. MIT license.
 use std::{marker::PhantomData, mem::MaybeUninit};
 
+use crate::support::Opaque;
+use crate::ExceptionReport;
 use crate::Function;
 use crate::Local;
 use crate::Module;
 use crate::Object;
 use crate::ScriptOrigin;
 use crate::String;
+use crate::TryCatch;
+use crate::Value;
 use crate::{Context, Isolate, Script, UnboundScript};
 use crate::{HandleScope, UniqueRef};
 
@@ -16,6 +20,7 @@ extern "C" {
     source_string: *const String,
     origin: *const ScriptOrigin,
     cached_data: *mut CachedData,
+    consume_cache_task: *mut ConsumeCodeCacheTask,
   );
   fn v8__ScriptCompiler__Source__DESTRUCT(this: *mut Source);
   fn v8__ScriptCompiler__Source__GetCachedData<'a>(
@@ -26,6 +31,16 @@ extern "C" {
     length: i32,
   ) -> *mut CachedData<'a>;
   fn v8__ScriptCompiler__CachedData__DELETE<'a>(this: *mut CachedData<'a>);
+  fn v8__ScriptCompiler__StartConsumingCodeCache<'a>(
+    isolate: *mut Isolate,
+    cached_data: *mut CachedData<'a>,
+  ) -> *mut ConsumeCodeCacheTask<'a>;
+  fn v8__ScriptCompiler__ConsumeCodeCacheTask__Run(
+    this: *mut ConsumeCodeCacheTask,
+  );
+  fn v8__ScriptCompiler__ConsumeCodeCacheTask__DELETE(
+    this: *mut ConsumeCodeCacheTask,
+  );
   fn v8__ScriptCompiler__CompileModule(
     isolate: *mut Isolate,
     source: *mut Source,
@@ -86,6 +101,10 @@ impl<'a> Drop for CachedData<'a> {
 }
 
 impl<'a> CachedData<'a> {
+  /// Creates cached data that borrows `data` without copying it
+  /// (`BufferPolicy::BufferNotOwned`), e.g. to feed a memory-mapped cache
+  /// file to the compiler. The borrow is tied to the lifetime of the returned
+  /// object.
   pub fn new(data: &'a [u8]) -> UniqueRef<Self> {
     let cached_data = unsafe {
       UniqueRef::from_raw(v8__ScriptCompiler__CachedData__NEW(
@@ -100,7 +119,18 @@ impl<'a> CachedData<'a> {
     cached_data
   }
 
-  pub(crate) fn buffer_policy(&self) -> BufferPolicy {
+  /// Whether the code cache was rejected by the compiler, e.g. because it was
+  /// produced by a different V8 version or with different flags, or doesn't
+  /// match the source. Since the compile functions consume the `Source` that
+  /// owns the data, use `compile_and_check_cache()` or
+  /// `compile_unbound_script_and_check_cache()` to find out after compiling.
+  pub fn rejected(&self) -> bool {
+    self.rejected
+  }
+
+  /// Whether the data buffer is owned by this object (and freed with it),
+  /// which is the case for code caches created by V8, or borrowed.
+  pub fn buffer_policy(&self) -> BufferPolicy {
     self.buffer_policy
   }
 }
@@ -112,11 +142,39 @@ impl<'a> std::ops::Deref for CachedData<'a> {
   }
 }
 
-#[allow(dead_code)]
+/// A task which deserializes a code cache off the main thread. Obtain one with
+/// `start_consuming_code_cache()`, call `run()` on any thread, and then pass
+/// it to `Source::new_with_consume_cache_task()` to finish compilation.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ConsumeCodeCacheTask<'a>(Opaque, PhantomData<&'a ()>);
+
+unsafe impl<'a> Send for ConsumeCodeCacheTask<'a> {}
+
+// The task owns no isolate-bound state, so it can be moved to (and destroyed
+// on) another thread to run it there.
+unsafe impl<'a> Send for UniqueRef<ConsumeCodeCacheTask<'a>> {}
+
+impl<'a> ConsumeCodeCacheTask<'a> {
+  /// Deserializes the code cache. This may be called on any thread, but must
+  /// be called before the task is used to compile a script.
+  pub fn run(&mut self) {
+    unsafe { v8__ScriptCompiler__ConsumeCodeCacheTask__Run(self) }
+  }
+}
+
+impl<'a> Drop for ConsumeCodeCacheTask<'a> {
+  fn drop(&mut self) {
+    unsafe { v8__ScriptCompiler__ConsumeCodeCacheTask__DELETE(self) }
+  }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub(crate) enum BufferPolicy {
+pub enum BufferPolicy {
+  /// The buffer is borrowed and outlives the CachedData.
   BufferNotOwned = 0,
+  /// The buffer was allocated by V8 and is freed with the CachedData.
   BufferOwned,
 }
 
@@ -132,6 +190,7 @@ impl Source {
         &*source_string,
         origin.map(|x| x as *const _).unwrap_or(std::ptr::null()),
         std::ptr::null_mut(),
+        std::ptr::null_mut(),
       );
       buf.assume_init()
     }
@@ -149,6 +208,28 @@ impl Source {
         &*source_string,
         origin.map(|x| x as *const _).unwrap_or(std::ptr::null()),
         cached_data.into_raw(), // Source constructor takes ownership.
+        std::ptr::null_mut(),
+      );
+      buf.assume_init()
+    }
+  }
+
+  /// Creates a source whose code cache was deserialized ahead of time by a
+  /// ConsumeCodeCacheTask. Compile it with `CompileOptions::ConsumeCodeCache`
+  /// to finish consuming the cache on the current thread.
+  pub fn new_with_consume_cache_task(
+    source_string: Local<String>,
+    origin: Option<&ScriptOrigin>,
+    consume_cache_task: UniqueRef<ConsumeCodeCacheTask>,
+  ) -> Self {
+    let mut buf = MaybeUninit::<Self>::uninit();
+    unsafe {
+      v8__ScriptCompiler__Source__CONSTRUCT(
+        &mut buf,
+        &*source_string,
+        origin.map(|x| x as *const _).unwrap_or(std::ptr::null()),
+        std::ptr::null_mut(),
+        consume_cache_task.into_raw(), // Source constructor takes ownership.
       );
       buf.assume_init()
     }
@@ -178,6 +259,9 @@ pub enum CompileOptions {
   NoCompileOptions = 0,
   ConsumeCodeCache,
   EagerCompile,
+  // TODO: add the compile hints options (ProduceCompileHints,
+  // ConsumeCompileHints, FollowCompileHintsMagicComment) and the compile hint
+  // callback once V8 is upgraded; they were introduced in V8 11.x/12.x.
 }
 
 /// The reason for which we are not requesting or providing a code cache.
@@ -255,6 +339,32 @@ pub fn compile<'s>(
   }
 }
 
+/// Same as `compile()`, but also returns whether the compiler rejected the
+/// code cache owned by `source`, in which case the embedder should discard the
+/// cache and produce a new one. The flag is `false` if `source` has no cached
+/// data or `options` is not `CompileOptions::ConsumeCodeCache`.
+pub fn compile_and_check_cache<'s>(
+  scope: &mut HandleScope<'s>,
+  mut source: Source,
+  options: CompileOptions,
+  no_cache_reason: NoCacheReason,
+) -> (Option<Local<'s, Script>>, bool) {
+  let script = unsafe {
+    scope.cast_local(|sd| {
+      v8__ScriptCompiler__Compile(
+        &*sd.get_current_context(),
+        &mut source,
+        options,
+        no_cache_reason,
+      )
+    })
+  };
+  let rejected = source
+    .get_cached_data()
+    .is_some_and(|cached_data| cached_data.rejected());
+  (script, rejected)
+}
+
 pub fn compile_function<'s>(
   scope: &mut HandleScope<'s>,
   mut source: Source,
@@ -281,6 +391,35 @@ pub fn compile_function<'s>(
   }
 }
 
+/// Convenience function not present in the original V8 API. Compiles `source`
+/// as the body of a function with the given parameter names, like
+/// `compile_function()`, and calls it with `recv` as the receiver (`this`) and
+/// `args` as the arguments. Unlike with `Script::run()`, the code can't
+/// declare global variables, and uses `return` to produce its result.
+///
+/// Exceptions thrown while compiling or running the code are caught and
+/// returned as an `ExceptionReport`, which includes the stack trace. Returns
+/// `Err(None)` if execution was terminated without an exception being caught.
+pub fn compile_function_and_call<'s>(
+  scope: &mut HandleScope<'s>,
+  source: Source,
+  parameter_names: &[Local<String>],
+  recv: Local<Value>,
+  args: &[Local<Value>],
+) -> Result<Local<'s, Value>, Option<ExceptionReport<'s>>> {
+  let scope = &mut TryCatch::new(scope);
+  let result = compile_function(
+    scope,
+    source,
+    parameter_names,
+    &[],
+    CompileOptions::NoCompileOptions,
+    NoCacheReason::NoReason,
+  )
+  .and_then(|function| function.call(scope, recv, args));
+  result.ok_or_else(|| scope.exception_report())
+}
+
 pub fn compile_unbound_script<'s>(
   scope: &mut HandleScope<'s>,
   mut source: Source,
@@ -299,6 +438,116 @@ pub fn compile_unbound_script<'s>(
   }
 }
 
+/// Same as `compile_unbound_script()`, but also returns whether the compiler
+/// rejected the code cache owned by `source`. See `compile_and_check_cache()`.
+pub fn compile_unbound_script_and_check_cache<'s>(
+  scope: &mut HandleScope<'s>,
+  mut source: Source,
+  options: CompileOptions,
+  no_cache_reason: NoCacheReason,
+) -> (Option<Local<'s, UnboundScript>>, bool) {
+  let script = unsafe {
+    scope.cast_local(|sd| {
+      v8__ScriptCompiler__CompileUnboundScript(
+        sd.get_isolate_ptr(),
+        &mut source,
+        options,
+        no_cache_reason,
+      )
+    })
+  };
+  let rejected = source
+    .get_cached_data()
+    .is_some_and(|cached_data| cached_data.rejected());
+  (script, rejected)
+}
+
+/// Convenience function not present in the original V8 API. Checks `source`
+/// for syntax errors without running it, e.g. to validate user code in a
+/// linter or loader.
+///
+/// V8 has no parse-only mode, so the script is compiled lazily: only its
+/// top-level code is compiled to bytecode, while the functions it declares
+/// are only pre-parsed, which is cheap but still reports all syntax errors.
+///
+/// Returns the `SyntaxError` as an `ExceptionReport`, which includes its
+/// location. Returns `Err(None)` if execution was terminated without an
+/// exception being caught.
+pub fn check_syntax<'s>(
+  scope: &mut HandleScope<'s>,
+  source: Source,
+) -> Result<(), Option<ExceptionReport<'s>>> {
+  let scope = &mut TryCatch::new(scope);
+  let script = compile_unbound_script(
+    scope,
+    source,
+    CompileOptions::NoCompileOptions,
+    NoCacheReason::NoReason,
+  );
+  match script {
+    Some(_) => Ok(()),
+    None => Err(scope.exception_report()),
+  }
+}
+
+/// Like `check_syntax()`, but for an ES module. The source's origin must
+/// have `is_module` set. The module is compiled, but not instantiated or
+/// evaluated, so its imports are not resolved.
+pub fn check_module_syntax<'s>(
+  scope: &mut HandleScope<'s>,
+  source: Source,
+) -> Result<(), Option<ExceptionReport<'s>>> {
+  let scope = &mut TryCatch::new(scope);
+  match compile_module(scope, source) {
+    Some(_) => Ok(()),
+    None => Err(scope.exception_report()),
+  }
+}
+
+/// Starts deserializing `cached_data` for a classic script. The returned task
+/// can be sent to a background thread and run there, so that only the final
+/// step of consuming the code cache happens on the isolate's thread.
+///
+/// Returns `None` if concurrent code cache deserialization is disabled
+/// (`--no-concurrent-cache-deserialization`).
+pub fn start_consuming_code_cache<'a>(
+  scope: &mut HandleScope,
+  cached_data: UniqueRef<CachedData<'a>>,
+) -> Option<UniqueRef<ConsumeCodeCacheTask<'a>>> {
+  unsafe {
+    UniqueRef::try_from_raw(v8__ScriptCompiler__StartConsumingCodeCache(
+      scope.get_isolate_ptr(),
+      cached_data.into_raw(),
+    ))
+  }
+}
+
+/// Compiles `source` as a classic script in a temporary isolate and returns
+/// the resulting code cache, without touching any existing isolate.
+///
+/// Code cache produced this way can be consumed by any isolate running the
+/// same V8 version with the same flags (see `cached_data_version_tag()`), so
+/// this can be used to produce caches on a worker thread instead of blocking
+/// the main thread. V8 must be initialized before calling this function.
+///
+/// Returns `None` if the script fails to compile or cannot be serialized.
+pub fn compile_to_code_cache(source: &str) -> Option<Vec<u8>> {
+  let isolate = &mut Isolate::new(Default::default());
+  let scope = &mut HandleScope::new(isolate);
+  let context = Context::new(scope);
+  let scope = &mut crate::ContextScope::new(scope, context);
+
+  let source = String::new(scope, source)?;
+  let script = compile_unbound_script(
+    scope,
+    Source::new(source, None),
+    CompileOptions::EagerCompile,
+    NoCacheReason::NoReason,
+  )?;
+  let code_cache = script.create_code_cache()?;
+  Some(code_cache.to_vec())
+}
+
 /// Return a version tag for CachedData for the current V8 version & flags.
 ///
 /// This value is meant only for determining whether a previously generated
@@ -39,3 +39,46 @@ impl ScriptOrModule {
     }
   }
 }
+
+/// Identifies the script or module that triggered a host callback, e.g. the
+/// one that called `import()`. This carries the same information as a
+/// `ScriptOrModule`: the resource name and host-defined options that the
+/// embedder passed in the `ScriptOrigin` when compiling the referrer.
+#[derive(Debug, Clone, Copy)]
+pub struct Referrer<'s> {
+  resource_name: Local<'s, Value>,
+  host_defined_options: Local<'s, Data>,
+}
+
+impl<'s> Referrer<'s> {
+  pub(crate) fn new(
+    resource_name: Local<'s, Value>,
+    host_defined_options: Local<'s, Data>,
+  ) -> Self {
+    Self {
+      resource_name,
+      host_defined_options,
+    }
+  }
+
+  /// The name that was passed by the embedder as ResourceName to the
+  /// ScriptOrigin. This can be either a v8::String or v8::Undefined.
+  pub fn resource_name(&self) -> Local<'s, Value> {
+    self.resource_name
+  }
+
+  /// The options that were passed by the embedder as HostDefinedOptions to the
+  /// ScriptOrigin.
+  pub fn host_defined_options(&self) -> Local<'s, Data> {
+    self.host_defined_options
+  }
+}
+
+impl<'s> From<&'s ScriptOrModule> for Referrer<'s> {
+  fn from(script_or_module: &'s ScriptOrModule) -> Self {
+    Self::new(
+      script_or_module.get_resource_name(),
+      script_or_module.host_defined_options(),
+    )
+  }
+}
@@ -38,6 +38,10 @@ extern "C" {
     deleter: BackingStoreDeleterCallback,
     deleter_data: *mut c_void,
   ) -> *mut BackingStore;
+  fn v8__SharedArrayBuffer__TryNewBackingStore(
+    isolate: *mut Isolate,
+    byte_length: usize,
+  ) -> *mut BackingStore;
 }
 
 impl SharedArrayBuffer {
@@ -108,6 +112,23 @@ impl SharedArrayBuffer {
     }
   }
 
+  /// Like `new_backing_store()`, but returns `None` instead of crashing the
+  /// process if the array buffer allocator of the isolate fails to allocate
+  /// the memory. See `ArrayBuffer::try_new_backing_store()`.
+  ///
+  /// Convenience function not present in the original V8 API.
+  pub fn try_new_backing_store(
+    scope: &mut Isolate,
+    byte_length: usize,
+  ) -> Option<UniqueRef<BackingStore>> {
+    unsafe {
+      UniqueRef::try_from_raw(v8__SharedArrayBuffer__TryNewBackingStore(
+        scope,
+        byte_length,
+      ))
+    }
+  }
+
   /// Returns a new standalone BackingStore that takes over the ownership of
   /// the given buffer.
   ///
@@ -52,6 +52,10 @@ pub struct StartupData {
   raw_size: int,
 }
 
+// The blob is an immutable heap allocation that is owned by `StartupData`.
+unsafe impl Send for StartupData {}
+unsafe impl Sync for StartupData {}
+
 impl Deref for StartupData {
   type Target = [u8];
   fn deref(&self) -> &Self::Target {
@@ -1,10 +1,14 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::default::Default;
 use std::mem::forget;
+use std::ptr::slice_from_raw_parts_mut;
 use std::slice;
 
 use crate::support::char;
 use crate::support::int;
+use crate::Global;
 use crate::HandleScope;
 use crate::Isolate;
 use crate::Local;
@@ -73,17 +77,37 @@ extern "C" {
     length: int,
   ) -> *const String;
 
+  fn v8__String__NewExternalOneByte(
+    isolate: *mut Isolate,
+    buffer: *mut char,
+    length: int,
+    deleter: extern "C" fn(*mut char, usize),
+  ) -> *const String;
+
   fn v8__String__NewExternalTwoByteStatic(
     isolate: *mut Isolate,
     buffer: *const u16,
     length: int,
   ) -> *const String;
 
+  fn v8__String__MakeExternalOneByte(
+    this: *const String,
+    buffer: *mut char,
+    length: usize,
+    deleter: extern "C" fn(*mut char, usize),
+  ) -> bool;
+
+  fn v8__String__MakeExternalTwoByte(
+    this: *const String,
+    buffer: *mut u16,
+    length: usize,
+    deleter: extern "C" fn(*mut u16, usize),
+  ) -> bool;
+
   #[allow(dead_code)]
   fn v8__String__IsExternal(this: *const String) -> bool;
   fn v8__String__IsExternalOneByte(this: *const String) -> bool;
   fn v8__String__IsExternalTwoByte(this: *const String) -> bool;
-  #[allow(dead_code)]
   fn v8__String__IsOneByte(this: *const String) -> bool;
   fn v8__String__ContainsOnlyOneByte(this: *const String) -> bool;
 }
@@ -298,6 +322,52 @@ impl String {
     }
   }
 
+  /// Creates an external one-byte string backed by `buffer`, which must be
+  /// Latin-1 or ASCII, not UTF-8. A borrowed buffer is used in place, as with
+  /// `new_external_onebyte_static()`. An owned buffer is moved into the
+  /// string's external resource and freed when the string is garbage
+  /// collected. In neither case is the data copied.
+  pub fn new_external_onebyte<'s>(
+    scope: &mut HandleScope<'s, ()>,
+    buffer: Cow<'static, [u8]>,
+  ) -> Option<Local<'s, String>> {
+    let buffer = match buffer {
+      Cow::Borrowed(buffer) => {
+        return Self::new_external_onebyte_static(scope, buffer)
+      }
+      Cow::Owned(buffer) => buffer.into_boxed_slice(),
+    };
+    let buffer_len = buffer.len().try_into().ok()?;
+    let buffer = Box::into_raw(buffer) as *mut u8 as *mut char;
+    // If creating the string fails, the resource is deleted by the binding,
+    // which frees the buffer; V8 doesn't take ownership of it in that case.
+    unsafe {
+      scope.cast_local(|sd| {
+        v8__String__NewExternalOneByte(
+          sd.get_isolate_ptr(),
+          buffer,
+          buffer_len,
+          delete_onebyte_buffer,
+        )
+      })
+    }
+  }
+
+  /// Creates a v8::String from a `&'static str`. ASCII strings, such as most
+  /// JavaScript sources embedded in the binary, become external strings that
+  /// point at the original data. Other strings are copied onto the V8 heap,
+  /// because V8 has no external representation for UTF-8.
+  pub fn new_from_static_str<'s>(
+    scope: &mut HandleScope<'s, ()>,
+    value: &'static str,
+  ) -> Option<Local<'s, String>> {
+    if value.is_ascii() {
+      Self::new_external_onebyte_static(scope, value.as_bytes())
+    } else {
+      Self::new(scope, value)
+    }
+  }
+
   // Creates a v8::String from a `&'static [u16]`.
   pub fn new_external_twobyte_static<'s>(
     scope: &mut HandleScope<'s, ()>,
@@ -315,6 +385,52 @@ impl String {
     }
   }
 
+  /// Converts this one-byte string to an external string that is backed by
+  /// `buffer`, so that its contents no longer take up space on the V8 heap.
+  /// This is useful when the embedder already holds the characters, e.g. the
+  /// source code of a cached module. `buffer` is owned by the string's
+  /// external resource, and freed when the string is garbage collected.
+  ///
+  /// `buffer` must hold the same characters as the string, as Latin-1.
+  /// Returns false (and frees `buffer`) if the string could not be
+  /// externalized, e.g. because it is already external, is too short, lives
+  /// in read-only space, or isn't a one-byte string of the same length.
+  pub fn make_external_onebyte(&self, buffer: Box<[u8]>) -> bool {
+    let is_onebyte = unsafe { v8__String__IsOneByte(self) };
+    if !is_onebyte || buffer.len() != self.length() {
+      return false;
+    }
+    let length = buffer.len();
+    let buffer = Box::into_raw(buffer) as *mut u8 as *mut char;
+    unsafe {
+      v8__String__MakeExternalOneByte(
+        self,
+        buffer,
+        length,
+        delete_onebyte_buffer,
+      )
+    }
+  }
+
+  /// Like `make_external_onebyte()`, but for two-byte strings. `buffer` must
+  /// hold the same characters as the string, as UTF-16.
+  pub fn make_external_twobyte(&self, buffer: Box<[u16]>) -> bool {
+    let is_onebyte = unsafe { v8__String__IsOneByte(self) };
+    if is_onebyte || buffer.len() != self.length() {
+      return false;
+    }
+    let length = buffer.len();
+    let buffer = Box::into_raw(buffer) as *mut u16;
+    unsafe {
+      v8__String__MakeExternalTwoByte(
+        self,
+        buffer,
+        length,
+        delete_twobyte_buffer,
+      )
+    }
+  }
+
   /// True if string is external
   pub fn is_external(&self) -> bool {
     // TODO: re-enable on next v8-release
@@ -372,4 +488,93 @@ impl String {
     );
     unsafe { std::string::String::from_raw_parts(data, length, capacity) }
   }
+
+  /// Convenience function not present in the original V8 API. Returns the
+  /// contents of the string as UTF-16 code units. Unlike
+  /// `to_rust_string_lossy()`, this preserves unpaired surrogates, so the
+  /// result can be turned back into an identical string with
+  /// `new_from_two_byte()`.
+  pub fn to_rust_utf16(&self, scope: &mut Isolate) -> Vec<u16> {
+    let mut buffer = vec![0u16; self.length()];
+    let length =
+      self.write(scope, &mut buffer, 0, WriteOptions::NO_NULL_TERMINATION);
+    buffer.truncate(length);
+    buffer
+  }
+}
+
+extern "C" fn delete_onebyte_buffer(buffer: *mut char, length: usize) {
+  drop(unsafe {
+    Box::from_raw(slice_from_raw_parts_mut(buffer as *mut u8, length))
+  });
+}
+
+extern "C" fn delete_twobyte_buffer(buffer: *mut u16, length: usize) {
+  drop(unsafe { Box::from_raw(slice_from_raw_parts_mut(buffer, length)) });
+}
+
+/// A property name that is created as an internalized string the first time
+/// it is used in an isolate, and reused after that. This avoids creating the
+/// string from UTF-8 every time a property with a well-known name is
+/// accessed. Meant to be declared as a `static`:
+///
+/// ```ignore
+/// static MESSAGE: v8::CachedPropertyName =
+///   v8::CachedPropertyName::new("message");
+///
+/// let message = error.get_cached(scope, &MESSAGE);
+/// ```
+#[derive(Debug)]
+pub struct CachedPropertyName {
+  name: &'static str,
+}
+
+/// The strings created for `CachedPropertyName`s in an isolate, keyed by the
+/// address of the `CachedPropertyName`.
+#[derive(Default)]
+struct CachedPropertyNames(HashMap<usize, Global<String>>);
+
+impl CachedPropertyName {
+  pub const fn new(name: &'static str) -> Self {
+    Self { name }
+  }
+
+  pub fn name(&self) -> &'static str {
+    self.name
+  }
+
+  /// Returns the string for this name, creating it if this is the first time
+  /// it's used in the isolate.
+  pub fn get<'s>(
+    &'static self,
+    scope: &mut HandleScope<'s, ()>,
+  ) -> Local<'s, String> {
+    let key = self as *const Self as usize;
+    let cached = scope
+      .get_slot::<CachedPropertyNames>()
+      .and_then(|names| names.0.get(&key))
+      .map(|name| name as *const Global<String>);
+    if let Some(name) = cached {
+      // SAFETY: the global is owned by the isolate and only removed when the
+      // isolate is disposed, so it outlives this call.
+      return Local::new(scope, unsafe { &*name });
+    }
+
+    let name = String::new_from_utf8(
+      scope,
+      self.name.as_bytes(),
+      NewStringType::Internalized,
+    )
+    .unwrap();
+    let global = Global::new(scope, name);
+    if scope.get_slot::<CachedPropertyNames>().is_none() {
+      scope.set_slot(CachedPropertyNames::default());
+    }
+    scope
+      .get_slot_mut::<CachedPropertyNames>()
+      .unwrap()
+      .0
+      .insert(key, global);
+    name
+  }
 }
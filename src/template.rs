@@ -1,12 +1,9 @@
-use libc::c_void;
-
 use crate::data::Data;
 use crate::data::FunctionTemplate;
 use crate::data::Name;
 use crate::data::ObjectTemplate;
 use crate::data::Template;
-use crate::fast_api::CFunctionInfo;
-use crate::fast_api::CTypeInfo;
+use crate::fast_api::CFunction;
 use crate::fast_api::FastFunction;
 use crate::isolate::Isolate;
 use crate::support::int;
@@ -49,15 +46,17 @@ extern "C" {
     length: i32,
     constructor_behavior: ConstructorBehavior,
     side_effect_type: SideEffectType,
-    func_ptr1: *const c_void,
-    c_function1: *const CFunctionInfo,
-    func_ptr2: *const c_void,
-    c_function2: *const CFunctionInfo,
+    c_functions: *const CFunction,
+    c_functions_len: usize,
   ) -> *const FunctionTemplate;
   fn v8__FunctionTemplate__GetFunction(
     this: *const FunctionTemplate,
     context: *const Context,
   ) -> *const Function;
+  fn v8__FunctionTemplate__HasInstance(
+    this: *const FunctionTemplate,
+    object: *const Value,
+  ) -> bool;
   fn v8__FunctionTemplate__PrototypeTemplate(
     this: *const FunctionTemplate,
   ) -> *const ObjectTemplate;
@@ -151,36 +150,50 @@ impl<'s> FunctionBuilder<'s, FunctionTemplate> {
           self.constructor_behavior,
           self.side_effect_type,
           null(),
-          null(),
-          null(),
-          null(),
+          0,
         )
       })
     }
     .unwrap()
   }
 
+  /// Creates the function template, with one or more fast API overloads of
+  /// the callback that optimized code can call directly. V8 picks the
+  /// overload to call based on the arguments, e.g. one overload may take a
+  /// `Uint32Array` and another a plain `Array`. Note that V8 requires an
+  /// overload taking a `TypedArray` to be accompanied by one that takes a
+  /// `Sequence` (i.e. an `Array`) in the same position.
+  ///
+  /// Panics if more than two overloads are passed; V8 does not support more.
   pub fn build_fast(
     self,
     scope: &mut HandleScope<'s, ()>,
-    overload1: &dyn FastFunction,
-    overload2: Option<&dyn FastFunction>,
+    overloads: &[&dyn FastFunction],
   ) -> Local<'s, FunctionTemplate> {
+    assert!(
+      overloads.len() <= 2,
+      "V8 supports at most two fast API overloads, got {}",
+      overloads.len()
+    );
+
+    #[cfg(debug_assertions)]
+    for overload in overloads {
+      crate::fast_api::validate_fast_function(
+        *overload,
+        self.length,
+        self.signature.is_some(),
+      );
+    }
+
+    let c_functions = overloads
+      .iter()
+      .map(|overload| {
+        let info = scope.get_c_function_info(*overload);
+        CFunction::new(overload.function(), info)
+      })
+      .collect::<Vec<_>>();
+
     unsafe {
-      let args = CTypeInfo::new_from_slice(overload1.args());
-      let ret = CTypeInfo::new(overload1.return_type());
-      let c_fn1 =
-        CFunctionInfo::new(args.as_ptr(), overload1.args().len(), ret.as_ptr());
-
-      let c_fn2 = match overload2 {
-        Some(overload) => {
-          let args = CTypeInfo::new_from_slice(overload.args());
-          let ret = CTypeInfo::new(overload.return_type());
-          CFunctionInfo::new(args.as_ptr(), overload.args().len(), ret.as_ptr())
-            .as_ptr()
-        }
-        None => null(),
-      };
       scope.cast_local(|sd| {
         v8__FunctionTemplate__New(
           sd.get_isolate_ptr(),
@@ -190,10 +203,8 @@ impl<'s> FunctionBuilder<'s, FunctionTemplate> {
           self.length,
           ConstructorBehavior::Throw,
           self.side_effect_type,
-          overload1.function(),
-          c_fn1.as_ptr(),
-          overload2.map_or(null(), |f| f.function()),
-          c_fn2,
+          c_functions.as_ptr(),
+          c_functions.len(),
         )
       })
     }
@@ -228,6 +239,9 @@ impl FunctionTemplate {
     FunctionBuilder::new(callback)
   }
 
+  /// Create a FunctionBuilder to configure a FunctionTemplate with a raw
+  /// callback. This is the same as
+  /// FunctionBuilder::<FunctionTemplate>::new_raw().
   pub fn builder_raw<'s>(
     callback: FunctionCallback,
   ) -> FunctionBuilder<'s, Self> {
@@ -242,6 +256,8 @@ impl FunctionTemplate {
     Self::builder(callback).build(scope)
   }
 
+  /// Creates a function template for a given raw FunctionCallback. See
+  /// `FunctionBuilder::new_raw()`.
   pub fn new_raw<'s>(
     scope: &mut HandleScope<'s, ()>,
     callback: FunctionCallback,
@@ -250,6 +266,13 @@ impl FunctionTemplate {
   }
 
   /// Returns the unique function instance in the current execution context.
+  ///
+  /// The function is instantiated the first time this is called for a given
+  /// context and cached in that context afterwards, so subsequent calls in the
+  /// same context are cheap and return the same function. Each new context
+  /// gets its own instance, which means instantiating a large number of
+  /// templates dominates the cost of creating a context; prefer creating
+  /// functions lazily, or from a snapshot, when that matters.
   pub fn get_function<'s>(
     &self,
     scope: &mut HandleScope<'s>,
@@ -261,6 +284,13 @@ impl FunctionTemplate {
     }
   }
 
+  /// Returns true if the given object is an instance of this function
+  /// template, i.e. if it was created from the instance template of this
+  /// function template or of one that inherits from it.
+  pub fn has_instance(&self, object: Local<Value>) -> bool {
+    unsafe { v8__FunctionTemplate__HasInstance(self, &*object) }
+  }
+
   /// Set the class name of the FunctionTemplate. This is used for
   /// printing objects created with the function created from the
   /// FunctionTemplate as its constructor.
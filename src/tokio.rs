@@ -0,0 +1,176 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+//! Integration with the tokio runtime, available with the `tokio` feature.
+//!
+//! V8 posts foreground tasks (e.g. to resolve `Atomics.waitAsync()` promises,
+//! or to finish work that was started on a worker thread) to a per-isolate
+//! task runner, which the embedder has to drain by calling
+//! `Platform::pump_message_loop()`. Instead of polling it, an embedder that
+//! runs on tokio can create its platform with [`new_platform()`] and await
+//! [`pump_message_loop()`], which wakes up as soon as V8 posts a task for the
+//! isolate, or once a delayed task becomes due.
+//!
+//! ```ignore
+//! v8::V8::initialize_platform(v8::tokio::new_platform(0, false).make_shared());
+//! v8::V8::initialize();
+//!
+//! let platform = v8::V8::get_current_platform();
+//! loop {
+//!   v8::tokio::pump_message_loop(&platform, isolate).await;
+//!   isolate.perform_microtask_checkpoint();
+//! }
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use std::time::Instant;
+
+use ::tokio::time::Sleep;
+
+use crate::Isolate;
+use crate::Platform;
+use crate::SharedRef;
+use crate::UniqueRef;
+
+/// The foreground tasks that have been posted for an isolate since it was
+/// last pumped.
+#[derive(Default)]
+struct PostedTasks {
+  waker: Option<Waker>,
+  /// The times at which the delayed tasks that have been posted become due.
+  deadlines: BinaryHeap<Reverse<Instant>>,
+}
+
+lazy_static! {
+  static ref POSTED_TASKS: Mutex<HashMap<usize, PostedTasks>> =
+    Default::default();
+}
+
+/// Stored in an isolate slot, so that its entry in `POSTED_TASKS` is removed
+/// when the isolate is disposed.
+struct Registration(usize);
+
+impl Drop for Registration {
+  fn drop(&mut self) {
+    POSTED_TASKS.lock().unwrap().remove(&self.0);
+  }
+}
+
+extern "C" fn task_posted(isolate: *mut Isolate, delay_in_seconds: f64) {
+  let waker = {
+    let mut posted_tasks = POSTED_TASKS.lock().unwrap();
+    let tasks = posted_tasks.entry(isolate as usize).or_default();
+    if delay_in_seconds > 0.0 {
+      let delay = Duration::from_secs_f64(delay_in_seconds);
+      tasks.deadlines.push(Reverse(Instant::now() + delay));
+    }
+    tasks.waker.clone()
+  };
+  // Don't wake the task while holding the lock, in case the waker polls it
+  // synchronously.
+  if let Some(waker) = waker {
+    waker.wake();
+  }
+}
+
+/// Returns a new instance of the default `Platform` implementation that
+/// notifies the futures returned by [`pump_message_loop()`] when tasks are
+/// posted. See `new_default_platform()` for the meaning of the arguments.
+pub fn new_platform(
+  thread_pool_size: u32,
+  idle_task_support: bool,
+) -> UniqueRef<Platform> {
+  Platform::new_with_foreground_task_posted_callback(
+    thread_pool_size,
+    idle_task_support,
+    task_posted,
+  )
+}
+
+/// Returns a future that runs the pending foreground tasks of `isolate`, and
+/// resolves once at least one task has been run. If no task is pending, the
+/// future waits until V8 posts one, or until a delayed task becomes due.
+///
+/// `platform` must have been created with [`new_platform()`]; otherwise the
+/// future is never woken up.
+pub fn pump_message_loop<'a>(
+  platform: &'a SharedRef<Platform>,
+  isolate: &'a mut Isolate,
+) -> PumpMessageLoop<'a> {
+  PumpMessageLoop {
+    platform,
+    isolate,
+    sleep: None,
+  }
+}
+
+/// The future returned by [`pump_message_loop()`].
+pub struct PumpMessageLoop<'a> {
+  platform: &'a SharedRef<Platform>,
+  isolate: &'a mut Isolate,
+  sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<'a> Future for PumpMessageLoop<'a> {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+    let this = self.get_mut();
+    let key = &mut *this.isolate as *mut Isolate as usize;
+    if this.isolate.get_slot::<Registration>().is_none() {
+      this.isolate.set_slot(Registration(key));
+    }
+
+    loop {
+      // Register the waker before pumping, so that a task posted after the
+      // message loop has been drained isn't missed.
+      let deadline = {
+        let mut posted_tasks = POSTED_TASKS.lock().unwrap();
+        let tasks = posted_tasks.entry(key).or_default();
+        match &tasks.waker {
+          Some(waker) if waker.will_wake(cx.waker()) => {}
+          _ => tasks.waker = Some(cx.waker().clone()),
+        }
+        let now = Instant::now();
+        while matches!(tasks.deadlines.peek(), Some(Reverse(d)) if *d <= now) {
+          tasks.deadlines.pop();
+        }
+        tasks.deadlines.peek().map(|Reverse(deadline)| *deadline)
+      };
+
+      let mut ran_task = false;
+      while Platform::pump_message_loop(this.platform, this.isolate, false) {
+        ran_task = true;
+      }
+      if ran_task {
+        return Poll::Ready(());
+      }
+
+      let deadline = match deadline {
+        Some(deadline) => ::tokio::time::Instant::from_std(deadline),
+        None => {
+          this.sleep = None;
+          return Poll::Pending;
+        }
+      };
+      let sleep = this
+        .sleep
+        .get_or_insert_with(|| Box::pin(::tokio::time::sleep_until(deadline)));
+      if sleep.deadline() != deadline {
+        sleep.as_mut().reset(deadline);
+      }
+      if sleep.as_mut().poll(cx).is_pending() {
+        return Poll::Pending;
+      }
+      // The delayed task is due; pump the message loop again.
+    }
+  }
+}
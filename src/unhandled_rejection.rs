@@ -0,0 +1,258 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+
+//! Tracking of unhandled promise rejections.
+//!
+//! V8 reports promise rejections through the `PromiseRejectCallback` as they
+//! happen, which is too early to tell whether a rejection is unhandled: a
+//! handler is commonly attached later in the same tick, e.g. by
+//! `await`ing the promise. Like Node.js, the tracker collects the rejections
+//! that have no handler, and only reports the ones that still have none once
+//! the microtask queue has been drained. A handler that is attached to a
+//! promise after it has been reported is reported as well, which corresponds
+//! to Node's `rejectionHandled` event.
+//!
+//! ```ignore
+//! v8::unhandled_rejection::UnhandledRejectionTracker::install(
+//!   isolate,
+//!   |scope, event| match event {
+//!     v8::unhandled_rejection::RejectionEvent::Unhandled { reason, .. } => {
+//!       eprintln!("Uncaught (in promise) {}", reason.to_rust_string_lossy(scope));
+//!     }
+//!     v8::unhandled_rejection::RejectionEvent::Handled { .. } => {}
+//!   },
+//! );
+//! ```
+
+use std::ffi::c_void;
+use std::ptr::null_mut;
+
+use crate::CallbackScope;
+use crate::Context;
+use crate::ContextScope;
+use crate::Global;
+use crate::HandleScope;
+use crate::Isolate;
+use crate::Local;
+use crate::Promise;
+use crate::PromiseRejectEvent;
+use crate::PromiseRejectMessage;
+use crate::StackTrace;
+use crate::Value;
+use crate::Weak;
+
+/// The maximum number of frames captured for rejections whose reason doesn't
+/// carry a stack trace of its own.
+const STACK_TRACE_FRAME_LIMIT: usize = 10;
+
+/// An event reported by the `UnhandledRejectionTracker`.
+#[derive(Debug)]
+pub enum RejectionEvent<'s> {
+  /// A promise was rejected, and still had no handler once the microtask
+  /// queue was drained.
+  Unhandled {
+    promise: Local<'s, Promise>,
+    reason: Local<'s, Value>,
+    /// The stack trace of the rejection's reason if it is an error object,
+    /// otherwise the stack at the point where the promise was rejected.
+    stack_trace: Option<Local<'s, StackTrace>>,
+  },
+  /// A handler was attached to a promise that was previously reported as
+  /// `Unhandled`.
+  Handled { promise: Local<'s, Promise> },
+}
+
+/// The function that `RejectionEvent`s are reported to. It is called in the
+/// context that the promise was created in.
+pub type RejectionHandler =
+  Box<dyn for<'s> FnMut(&mut HandleScope<'s>, RejectionEvent<'s>)>;
+
+struct PendingRejection {
+  context: Global<Context>,
+  promise: Global<Promise>,
+  reason: Global<Value>,
+  stack_trace: Option<Global<StackTrace>>,
+}
+
+/// Collects the unhandled promise rejections of an isolate, and reports them
+/// after each microtask checkpoint. See the module documentation.
+///
+/// The tracker is stored in a slot of the isolate, and replaces the isolate's
+/// `PromiseRejectCallback`.
+pub struct UnhandledRejectionTracker {
+  /// Taken out while it is being called, so that reentrant calls to
+  /// `process()` don't report events out of order.
+  handler: Option<RejectionHandler>,
+  /// Rejections without a handler that haven't been reported yet.
+  pending: Vec<PendingRejection>,
+  /// Promises that were reported as unhandled, but have a handler now.
+  handled: Vec<(Global<Context>, Global<Promise>)>,
+  /// Promises that were reported as unhandled. These are weak, so that a
+  /// promise that is never handled can still be garbage collected.
+  reported: Vec<Weak<Promise>>,
+}
+
+impl UnhandledRejectionTracker {
+  /// Installs a tracker that reports to `handler`, replacing any tracker that
+  /// was installed before. The handler is invoked after each microtask
+  /// checkpoint of the isolate's default `MicrotaskQueue`, and may run
+  /// JavaScript code.
+  pub fn install(
+    isolate: &mut Isolate,
+    handler: impl for<'s> FnMut(&mut HandleScope<'s>, RejectionEvent<'s>) + 'static,
+  ) {
+    isolate.set_slot(Self {
+      handler: Some(Box::new(handler)),
+      pending: Vec::new(),
+      handled: Vec::new(),
+      reported: Vec::new(),
+    });
+    isolate.set_promise_reject_callback(promise_reject_callback);
+    isolate.add_microtasks_completed_callback(microtasks_completed, null_mut());
+  }
+
+  /// Removes the tracker, dropping the rejections that haven't been reported
+  /// yet. The isolate's `PromiseRejectCallback` is not reset.
+  pub fn uninstall(isolate: &mut Isolate) {
+    isolate
+      .remove_microtasks_completed_callback(microtasks_completed, null_mut());
+    isolate.remove_slot::<Self>();
+  }
+
+  /// Reports the rejections that have been collected so far, without waiting
+  /// for the next microtask checkpoint. This is useful when microtasks aren't
+  /// run by the default `MicrotaskQueue`, or before exiting the event loop.
+  pub fn process(scope: &mut HandleScope<()>) {
+    let tracker = match scope.get_slot_mut::<Self>() {
+      Some(tracker) => tracker,
+      None => return,
+    };
+    let mut handler = match tracker.handler.take() {
+      Some(handler) => handler,
+      None => return,
+    };
+    let handled = std::mem::take(&mut tracker.handled);
+    let pending = std::mem::take(&mut tracker.pending);
+
+    // Report handled rejections first, since they were reported as unhandled
+    // by an earlier checkpoint.
+    for (context, promise) in handled {
+      let scope = &mut HandleScope::new(scope);
+      let context = Local::new(scope, context);
+      let scope = &mut ContextScope::new(scope, context);
+      let promise = Local::new(scope, promise);
+      handler(scope, RejectionEvent::Handled { promise });
+    }
+    for rejection in pending {
+      let scope = &mut HandleScope::new(scope);
+      let context = Local::new(scope, rejection.context);
+      let scope = &mut ContextScope::new(scope, context);
+      let promise = Local::new(scope, rejection.promise);
+      let weak = Weak::new(scope, promise);
+      if let Some(tracker) = scope.get_slot_mut::<Self>() {
+        tracker.reported.push(weak);
+      }
+      let reason = Local::new(scope, rejection.reason);
+      let stack_trace = rejection
+        .stack_trace
+        .map(|stack_trace| Local::new(scope, stack_trace));
+      handler(
+        scope,
+        RejectionEvent::Unhandled {
+          promise,
+          reason,
+          stack_trace,
+        },
+      );
+    }
+
+    // The handler may have uninstalled or replaced the tracker.
+    if let Some(tracker) = scope.get_slot_mut::<Self>() {
+      tracker.reported.retain(|weak| !weak.is_empty());
+      if tracker.handler.is_none() {
+        tracker.handler = Some(handler);
+      }
+    }
+  }
+
+  fn reject_with_no_handler(
+    scope: &mut HandleScope,
+    message: &PromiseRejectMessage,
+  ) {
+    let reason = match message.get_value() {
+      Some(reason) => reason,
+      None => return,
+    };
+    let stack_trace = message
+      .get_stack_trace(scope, STACK_TRACE_FRAME_LIMIT)
+      .map(|stack_trace| Global::new(scope, stack_trace));
+    let context = scope.get_current_context();
+    let rejection = PendingRejection {
+      context: Global::new(scope, context),
+      promise: Global::new(scope, message.get_promise()),
+      reason: Global::new(scope, reason),
+      stack_trace,
+    };
+    if let Some(tracker) = scope.get_slot_mut::<Self>() {
+      tracker.pending.push(rejection);
+    }
+  }
+
+  fn handler_added_after_reject(
+    scope: &mut HandleScope,
+    promise: Local<Promise>,
+  ) {
+    let tracker = match scope.get_slot_mut::<Self>() {
+      Some(tracker) => tracker,
+      None => return,
+    };
+    // A promise that hasn't been reported yet is simply not reported at all.
+    if let Some(index) = tracker
+      .pending
+      .iter()
+      .position(|rejection| rejection.promise == promise)
+    {
+      tracker.pending.remove(index);
+      return;
+    }
+
+    let reported = std::mem::take(&mut tracker.reported);
+    let index = reported
+      .iter()
+      .position(|weak| weak.to_local(scope) == Some(promise));
+    let handled = index.map(|_| {
+      let context = scope.get_current_context();
+      (Global::new(scope, context), Global::new(scope, promise))
+    });
+    if let Some(tracker) = scope.get_slot_mut::<Self>() {
+      tracker.reported = reported;
+      if let (Some(index), Some(handled)) = (index, handled) {
+        tracker.reported.remove(index);
+        tracker.handled.push(handled);
+      }
+    }
+  }
+}
+
+extern "C" fn promise_reject_callback(message: PromiseRejectMessage) {
+  let scope = &mut unsafe { CallbackScope::new(&message) };
+  let scope = &mut HandleScope::new(scope);
+  match message.get_event() {
+    PromiseRejectEvent::PromiseRejectWithNoHandler => {
+      UnhandledRejectionTracker::reject_with_no_handler(scope, &message)
+    }
+    PromiseRejectEvent::PromiseHandlerAddedAfterReject => {
+      UnhandledRejectionTracker::handler_added_after_reject(
+        scope,
+        message.get_promise(),
+      )
+    }
+    PromiseRejectEvent::PromiseRejectAfterResolved
+    | PromiseRejectEvent::PromiseResolveAfterResolved => {}
+  }
+}
+
+extern "C" fn microtasks_completed(isolate: &mut Isolate, _data: *mut c_void) {
+  let scope = &mut unsafe { CallbackScope::new(isolate) };
+  let scope = &mut HandleScope::new(scope);
+  UnhandledRejectionTracker::process(scope);
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::num::NonZeroI32;
 
 use crate::support::int;
@@ -73,6 +74,8 @@ extern "C" {
   fn v8__Value__IsProxy(this: *const Value) -> bool;
   fn v8__Value__IsWasmMemoryObject(this: *const Value) -> bool;
   fn v8__Value__IsWasmModuleObject(this: *const Value) -> bool;
+  fn v8__Value__IsWasmFunction(this: *const Value) -> bool;
+  fn v8__Value__IsWasmObject(this: *const Value) -> bool;
   fn v8__Value__IsModuleNamespaceObject(this: *const Value) -> bool;
   fn v8__Value__StrictEquals(this: *const Value, that: *const Value) -> bool;
   fn v8__Value__SameValue(this: *const Value, that: *const Value) -> bool;
@@ -114,6 +117,10 @@ extern "C" {
     this: *const Value,
     context: *const Context,
   ) -> *const Int32;
+  fn v8__Value__ToArrayIndex(
+    this: *const Value,
+    context: *const Context,
+  ) -> *const Uint32;
   fn v8__Value__ToBoolean(
     this: *const Value,
     isolate: *mut Isolate,
@@ -430,6 +437,22 @@ impl Value {
     unsafe { v8__Value__IsWasmModuleObject(self) }
   }
 
+  /// Returns true if this value is a function that can be used as a Wasm
+  /// `funcref`, i.e. a function exported from a Wasm instance, or one created
+  /// with the `WebAssembly.Function` constructor. Note that `externref`
+  /// values are ordinary JavaScript values, which are passed through Wasm
+  /// unchanged.
+  pub fn is_wasm_function(&self) -> bool {
+    unsafe { v8__Value__IsWasmFunction(self) }
+  }
+
+  /// Returns true if this value is a Wasm GC struct or array that was passed
+  /// out of Wasm code. These objects are opaque to JavaScript: they have no
+  /// properties, and can only be passed back to Wasm.
+  pub fn is_wasm_object(&self) -> bool {
+    unsafe { v8__Value__IsWasmObject(self) }
+  }
+
   /// Returns true if the value is a Module Namespace Object.
   pub fn is_module_namespace_object(&self) -> bool {
     unsafe { v8__Value__IsModuleNamespaceObject(self) }
@@ -515,6 +538,60 @@ impl Value {
     }
   }
 
+  /// Convenience function not present in the original V8 API. Returns a
+  /// value implementing `Debug` that prints the value's type followed by a
+  /// preview of its contents, as produced by `to_detail_string()`. The scope
+  /// is only needed while building the preview, so the result can outlive
+  /// any borrow of it:
+  ///
+  /// ```ignore
+  /// println!("{:?}", value.debug(scope)); // => object #<Object>
+  /// ```
+  pub fn debug<'s>(&self, scope: &mut HandleScope<'s>) -> ValueDebug {
+    let mut preview = self
+      .to_detail_string(scope)
+      .map_or_else(std::string::String::new, |s| s.to_rust_string_lossy(scope));
+    if preview.chars().count() > ValueDebug::MAX_PREVIEW_LEN {
+      preview = preview
+        .chars()
+        .take(ValueDebug::MAX_PREVIEW_LEN)
+        .chain(std::iter::once('…'))
+        .collect();
+    }
+    ValueDebug {
+      type_name: self.type_name(),
+      quoted: self.is_string(),
+      preview,
+    }
+  }
+
+  /// Returns the name of the value's type, following the categories of the
+  /// JavaScript `typeof` operator, except that `null` and arrays are reported
+  /// as such rather than as "object".
+  fn type_name(&self) -> &'static str {
+    if self.is_undefined() {
+      "undefined"
+    } else if self.is_null() {
+      "null"
+    } else if self.is_boolean() {
+      "boolean"
+    } else if self.is_number() {
+      "number"
+    } else if self.is_big_int() {
+      "bigint"
+    } else if self.is_string() {
+      "string"
+    } else if self.is_symbol() {
+      "symbol"
+    } else if self.is_function() {
+      "function"
+    } else if self.is_array() {
+      "array"
+    } else {
+      "object"
+    }
+  }
+
   pub fn to_object<'s>(
     &self,
     scope: &mut HandleScope<'s>,
@@ -555,6 +632,25 @@ impl Value {
     }
   }
 
+  /// Attempts to convert a string to an array index. Returns `None` if the
+  /// value is not a string (or number) that represents an array index, i.e. an
+  /// integer in the range `0..2^32 - 1`. This is useful to tell apart the
+  /// indexed and named properties of an object, e.g. when inspecting the
+  /// property keys returned by `Object::get_own_property_names()`.
+  ///
+  /// The value is converted to a string first, so calling this on a symbol
+  /// throws a `TypeError`; use `NameKind` to tell symbol keys apart first.
+  pub fn to_array_index<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+  ) -> Option<Local<'s, Uint32>> {
+    unsafe {
+      scope.cast_local(|sd| {
+        v8__Value__ToArrayIndex(self, &*sd.get_current_context())
+      })
+    }
+  }
+
   /// Perform the equivalent of Boolean(value) in JS. This can never fail.
   pub fn to_boolean<'s>(
     &self,
@@ -628,3 +724,25 @@ impl Value {
     unsafe { NonZeroI32::new_unchecked(v8__Value__GetHash(self)) }
   }
 }
+
+/// The `Debug` representation of a `Value`, as returned by `Value::debug()`.
+#[derive(Clone)]
+pub struct ValueDebug {
+  type_name: &'static str,
+  quoted: bool,
+  preview: std::string::String,
+}
+
+impl ValueDebug {
+  const MAX_PREVIEW_LEN: usize = 80;
+}
+
+impl fmt::Debug for ValueDebug {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.quoted {
+      write!(f, "{} {:?}", self.type_name, self.preview)
+    } else {
+      write!(f, "{} {}", self.type_name, self.preview)
+    }
+  }
+}
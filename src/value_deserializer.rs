@@ -116,6 +116,10 @@ extern "C" {
     supports_legacy_wire_format: bool,
   );
 
+  fn v8__ValueDeserializer__GetWireFormatVersion(
+    this: *const CxxValueDeserializer,
+  ) -> u32;
+
   fn v8__ValueDeserializer__ReadHeader(
     this: *mut CxxValueDeserializer,
     context: Local<Context>,
@@ -261,6 +265,17 @@ pub trait ValueDeserializerHelper {
     .into()
   }
 
+  /// Reads the underlying wire format version. Likely mostly to be useful to
+  /// legacy code reading old wire format versions. Must be called after
+  /// read_header().
+  fn get_wire_format_version(&mut self) -> u32 {
+    unsafe {
+      v8__ValueDeserializer__GetWireFormatVersion(
+        self.get_cxx_value_deserializer(),
+      )
+    }
+  }
+
   fn read_value<'s>(
     &mut self,
     context: Local<'s, Context>,
@@ -317,6 +332,11 @@ pub trait ValueDeserializerHelper {
     }
   }
 
+  /// Provides the ArrayBuffer for a buffer that the serializer marked as
+  /// transferred with `ValueSerializerHelper::transfer_array_buffer()`. The
+  /// ArrayBuffer usually wraps the transferred backing store, so that its
+  /// contents are moved without being copied. Must be called before
+  /// `read_value()`.
   fn transfer_array_buffer(
     &mut self,
     transfer_id: u32,
@@ -400,6 +420,10 @@ impl<'a, 's> ValueDeserializer<'a, 's> {
 }
 
 impl<'a, 's> ValueDeserializer<'a, 's> {
+  /// By default the deserializer rejects payloads that don't start with a
+  /// version header. Passing `true` allows reading the legacy wire format,
+  /// i.e. data that was written without calling write_header(), in which case
+  /// get_wire_format_version() reports 0. Must be called before read_header().
   pub fn set_supports_legacy_wire_format(
     &mut self,
     supports_legacy_wire_format: bool,
@@ -63,6 +63,35 @@ pub unsafe extern "C" fn v8__ValueSerializer__Delegate__WriteHostObject(
   ))
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn v8__ValueSerializer__Delegate__HasCustomHostObject(
+  this: &mut CxxValueSerializerDelegate,
+  isolate: *mut Isolate,
+) -> bool {
+  let value_serializer_heap = ValueSerializerHeap::dispatch_mut(this);
+  value_serializer_heap
+    .value_serializer_impl
+    .as_mut()
+    .has_custom_host_object(&mut *isolate)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn v8__ValueSerializer__Delegate__IsHostObject(
+  this: &mut CxxValueSerializerDelegate,
+  _isolate: *mut Isolate,
+  object: Local<Object>,
+) -> MaybeBool {
+  let value_serializer_heap = ValueSerializerHeap::dispatch_mut(this);
+  let scope =
+    &mut crate::scope::CallbackScope::new(value_serializer_heap.context);
+  MaybeBool::from(
+    value_serializer_heap
+      .value_serializer_impl
+      .as_mut()
+      .is_host_object(scope, object),
+  )
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn v8__ValueSerializer__Delegate__GetSharedArrayBufferId(
   this: &mut CxxValueSerializerDelegate,
@@ -178,6 +207,10 @@ extern "C" {
   );
 
   fn v8__ValueSerializer__WriteHeader(this: *mut CxxValueSerializer);
+  fn v8__ValueSerializer__SetTreatArrayBufferViewsAsHostObjects(
+    this: *mut CxxValueSerializer,
+    mode: bool,
+  );
   fn v8__ValueSerializer__WriteValue(
     this: *mut CxxValueSerializer,
     context: Local<Context>,
@@ -225,6 +258,30 @@ pub trait ValueSerializerImpl {
     None
   }
 
+  /// Returns whether `is_host_object()` should be called to decide which
+  /// objects are host objects. Otherwise, objects with internal fields are
+  /// treated as host objects. This is called once, when the serializer is
+  /// created.
+  fn has_custom_host_object(&mut self, _isolate: &mut Isolate) -> bool {
+    false
+  }
+
+  /// Returns whether `object` is a host object, which is serialized with
+  /// `write_host_object()`. Only called if `has_custom_host_object()`
+  /// returned true.
+  fn is_host_object<'s>(
+    &mut self,
+    scope: &mut HandleScope<'s>,
+    _object: Local<'s, Object>,
+  ) -> Option<bool> {
+    let msg =
+      String::new(scope, "Deno serializer: is_host_object not implemented")
+        .unwrap();
+    let exc = Exception::error(scope, msg);
+    scope.throw_exception(exc);
+    None
+  }
+
   fn get_shared_array_buffer_id<'s>(
     &mut self,
     scope: &mut HandleScope<'s>,
@@ -360,6 +417,16 @@ pub trait ValueSerializerHelper {
     };
   }
 
+  /// Marks an ArrayBuffer as having its contents transferred out of band.
+  /// Instead of copying its contents, the buffer is written as a reference to
+  /// `transfer_id`, which the deserializer resolves to the ArrayBuffer that
+  /// was passed to its own `transfer_array_buffer()` with the same id.
+  ///
+  /// This implements the transfer list of `postMessage()`: the embedder keeps
+  /// the buffer's backing store (see `ArrayBuffer::get_backing_store()`),
+  /// detaches the buffer once serialization is done, and recreates it on the
+  /// receiving side with `ArrayBuffer::with_backing_store()`. Must be called
+  /// before the buffer is written with `write_value()`.
   fn transfer_array_buffer(
     &mut self,
     transfer_id: u32,
@@ -441,6 +508,18 @@ impl<'a, 's> ValueSerializer<'a, 's> {
 }
 
 impl<'a, 's> ValueSerializer<'a, 's> {
+  /// Indicate whether to treat ArrayBufferView objects as host objects, i.e.
+  /// pass them to `ValueSerializerImpl::write_host_object()` for custom
+  /// serialization. Default: false.
+  pub fn set_treat_array_buffer_views_as_host_objects(&mut self, mode: bool) {
+    unsafe {
+      v8__ValueSerializer__SetTreatArrayBufferViewsAsHostObjects(
+        &mut (*self.value_serializer_heap).cxx_value_serializer,
+        mode,
+      )
+    }
+  }
+
   pub fn release(mut self) -> Vec<u8> {
     unsafe {
       let mut size: usize = 0;
@@ -7,9 +7,11 @@ use crate::scope::HandleScope;
 use crate::support::char;
 use crate::support::Opaque;
 use crate::support::UnitType;
+use crate::ArrayBuffer;
 use crate::Isolate;
 use crate::Local;
 use crate::Value;
+use crate::WasmMemoryObject;
 use crate::WasmModuleObject;
 use std::ptr::null;
 use std::ptr::null_mut;
@@ -121,6 +123,25 @@ impl WasmModuleObject {
   }
 }
 
+impl WasmMemoryObject {
+  /// Returns underlying ArrayBuffer.
+  ///
+  /// The returned buffer aliases the memory of the WebAssembly instance, so
+  /// the host can read and write it directly. Note that the buffer is
+  /// detached (and a new one created) when the memory grows.
+  pub fn buffer<'s>(
+    &self,
+    scope: &mut HandleScope<'s>,
+  ) -> Local<'s, ArrayBuffer> {
+    unsafe { scope.cast_local(|_| v8__WasmMemoryObject__Buffer(self)) }.unwrap()
+  }
+
+  // TODO: V8 10.5 has no public API for creating a WebAssembly.Memory from an
+  // embedder-provided backing store, or for querying the size of its guard
+  // regions; memories can only be allocated by V8 itself. Add constructors
+  // for pooled/preallocated memories once V8 exposes them.
+}
+
 // Type-erased v8::CompiledWasmModule. We need this because the C++
 // v8::CompiledWasmModule must be destructed because its private fields hold
 // pointers that must be freed, but v8::CompiledWasmModule itself doesn't have
@@ -132,6 +153,11 @@ struct InternalCompiledWasmModule(Opaque);
 
 /// Wrapper around a compiled WebAssembly module, which is potentially shared by
 /// different WasmModuleObjects.
+///
+/// A CompiledWasmModule is not tied to the isolate it was obtained from: it can
+/// be sent (or cloned) to other threads and turned back into a
+/// WasmModuleObject in another isolate with
+/// `WasmModuleObject::from_compiled_module()`, without recompiling the module.
 pub struct CompiledWasmModule(*mut InternalCompiledWasmModule);
 
 impl CompiledWasmModule {
@@ -154,7 +180,17 @@ impl CompiledWasmModule {
   }
 }
 
-// TODO(andreubotella): Safety???
+impl Clone for CompiledWasmModule {
+  /// Creates another handle to the same compiled module. The underlying
+  /// compiled code is shared, not copied.
+  fn clone(&self) -> Self {
+    Self(unsafe { v8__CompiledWasmModule__COPY(self.0) })
+  }
+}
+
+// v8::CompiledWasmModule only holds a std::shared_ptr to the underlying
+// wasm::NativeModule, which V8 itself shares between isolates and threads,
+// and an immutable source URL string.
 unsafe impl Send for CompiledWasmModule {}
 unsafe impl Sync for CompiledWasmModule {}
 
@@ -215,6 +251,9 @@ extern "C" {
     isolate: *mut Isolate,
     compiled_module: *const InternalCompiledWasmModule,
   ) -> *const WasmModuleObject;
+  fn v8__WasmMemoryObject__Buffer(
+    this: *const WasmMemoryObject,
+  ) -> *const ArrayBuffer;
   fn v8__WasmModuleObject__GetCompiledModule(
     this: *const WasmModuleObject,
   ) -> *mut InternalCompiledWasmModule;
@@ -232,5 +271,8 @@ extern "C" {
     this: *mut InternalCompiledWasmModule,
     length: *mut usize,
   ) -> *const char;
+  fn v8__CompiledWasmModule__COPY(
+    this: *const InternalCompiledWasmModule,
+  ) -> *mut InternalCompiledWasmModule;
   fn v8__CompiledWasmModule__DELETE(this: *mut InternalCompiledWasmModule);
 }
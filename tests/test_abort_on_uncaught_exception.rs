@@ -0,0 +1,35 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+static CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn abort_on_uncaught_exception(_: &mut v8::Isolate) -> bool {
+  CALL_COUNT.fetch_add(1, Ordering::SeqCst);
+  false
+}
+
+fn eval(scope: &mut v8::HandleScope, code: &str) -> bool {
+  let source = v8::String::new(scope, code).unwrap();
+  let script = v8::Script::compile(scope, source, None).unwrap();
+  script.run(scope).is_some()
+}
+
+#[test]
+fn abort_on_uncaught_exception_callback() {
+  v8::V8::set_flags_from_string("--abort-on-uncaught-exception");
+  v8::V8::initialize_platform(v8::new_default_platform(0, false).make_shared());
+  v8::V8::initialize();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  isolate.set_abort_on_uncaught_exception_callback(abort_on_uncaught_exception);
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  // Exceptions that are caught in JavaScript are not reported.
+  assert!(eval(scope, "try { throw new Error('caught') } catch {}"));
+  assert_eq!(CALL_COUNT.load(Ordering::SeqCst), 0);
+
+  // The process is not aborted because the callback returns false.
+  assert!(!eval(scope, "throw new Error('uncaught')"));
+  assert_eq!(CALL_COUNT.load(Ordering::SeqCst), 1);
+}
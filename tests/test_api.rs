@@ -11,7 +11,7 @@ use std::hash::Hash;
 use std::mem::MaybeUninit;
 use std::os::raw::c_char;
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::sync::Mutex;
 use v8::fast_api;
@@ -142,6 +142,58 @@ fn global_handles() {
   }
 }
 
+#[test]
+fn global_scopeless_access() {
+  let _setup_guard = setup();
+  let isolate1 = &mut v8::Isolate::new(Default::default());
+  let isolate2 = &mut v8::Isolate::new(Default::default());
+
+  let (object, string) = {
+    let scope = &mut v8::HandleScope::new(isolate1);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let object = v8::Object::new(scope);
+    let string = v8::String::new(scope, "foo").unwrap();
+    (
+      v8::Global::new(scope, object),
+      v8::Global::new(scope, string),
+    )
+  };
+
+  // Everything below only needs a shared reference to the isolate.
+  let isolate1: &v8::Isolate = isolate1;
+  let isolate2: &v8::Isolate = isolate2;
+  assert!(object.is_hosted_by(isolate1));
+  assert!(!object.is_hosted_by(isolate2));
+
+  let hash = object.get(isolate1).get_identity_hash();
+  let object_clone = object.clone();
+  assert_eq!(object_clone.get(isolate1).get_identity_hash(), hash);
+  assert!(object_clone == object);
+  assert!(string.get(isolate1).is_string());
+  assert_eq!(string.get(isolate1).length(), 3);
+
+  let handle1 = isolate1.thread_safe_handle();
+  assert!(handle1 == isolate1.thread_safe_handle());
+  assert!(handle1 != isolate2.thread_safe_handle());
+}
+
+#[test]
+#[should_panic(expected = "not its host")]
+fn global_get_wrong_isolate() {
+  let _setup_guard = setup();
+  let isolate1 = &mut v8::Isolate::new(Default::default());
+  let isolate2 = &mut v8::Isolate::new(Default::default());
+  let object = {
+    let scope = &mut v8::HandleScope::new(isolate1);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let object = v8::Object::new(scope);
+    v8::Global::new(scope, object)
+  };
+  object.get(isolate2);
+}
+
 #[test]
 fn global_from_into_raw() {
   let _setup_guard = setup();
@@ -192,6 +244,35 @@ fn local_handle_deref() {
   }
 }
 
+#[test]
+fn sendable_global() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let object = v8::Object::new(scope);
+  let sendable = v8::Global::new(scope, object).into_sendable();
+  let sendable = std::thread::spawn(move || sendable).join().unwrap();
+  let global = sendable.into_global(scope);
+  assert!(global.open(scope).strict_equals(object.into()));
+
+  // Dropping on another thread defers releasing the handle until the isolate
+  // runs JavaScript again.
+  fn drop_object(scope: &mut v8::HandleScope) -> v8::Weak<v8::Object> {
+    let scope = &mut v8::HandleScope::new(scope);
+    let object = v8::Object::new(scope);
+    let sendable = v8::Global::new(scope, object).into_sendable();
+    std::thread::spawn(move || drop(sendable)).join().unwrap();
+    v8::Weak::new(scope, object)
+  }
+  let weak = drop_object(scope);
+  eval(scope, "(function(x){return x;})(1);").unwrap();
+  scope.low_memory_notification();
+  assert!(weak.is_empty());
+}
+
 #[test]
 fn global_handle_drop() {
   let _setup_guard = setup();
@@ -277,6 +358,23 @@ fn test_string() {
     assert_eq!(2, local.length());
     assert_eq!(4, local.utf8_length(scope));
     assert_eq!("🦕", local.to_rust_string_lossy(scope));
+    assert_eq!(vec![0xD83E, 0xDD95], local.to_rust_utf16(scope));
+  }
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    // An unpaired surrogate can't be represented in UTF-8, but survives a
+    // round trip through UTF-16.
+    let utf16 = [0x61, 0xD800, 0x62];
+    let local =
+      v8::String::new_from_two_byte(scope, &utf16, v8::NewStringType::Normal)
+        .unwrap();
+    assert_eq!(3, local.length());
+    assert_eq!("a\u{FFFD}b", local.to_rust_string_lossy(scope));
+    assert_eq!(utf16.to_vec(), local.to_rust_utf16(scope));
+    let mut buffer = [0u16; 2];
+    let options = v8::WriteOptions::NO_NULL_TERMINATION;
+    assert_eq!(2, local.write(scope, &mut buffer, 1, options));
+    assert_eq!([0xD800, 0x62], buffer);
   }
   {
     let scope = &mut v8::HandleScope::new(isolate);
@@ -344,6 +442,27 @@ fn escapable_handle_scope() {
   }
 }
 
+#[test]
+fn escapable_handle_scope_escape_array() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let array = {
+    let escapable_scope = &mut v8::EscapableHandleScope::new(scope);
+    let number = v8::Number::new(escapable_scope, 1.5);
+    let string = v8::String::new(escapable_scope, "two").unwrap();
+    escapable_scope.escape_array(&[number.into(), string.into()])
+  };
+  assert_eq!(array.length(), 2);
+  let number = array.get_index(scope, 0).unwrap();
+  assert_eq!(number.number_value(scope), Some(1.5));
+  let string = array.get_index(scope, 1).unwrap();
+  assert_eq!(string.to_rust_string_lossy(scope), "two");
+}
+
 #[test]
 #[should_panic(expected = "EscapableHandleScope::escape() called twice")]
 fn escapable_handle_scope_can_escape_only_once() {
@@ -368,11 +487,14 @@ fn context_scope() {
   let isolate = &mut v8::Isolate::new(Default::default());
 
   let scope = &mut v8::HandleScope::new(isolate);
+  assert!(!scope.in_context());
   let context1 = v8::Context::new(scope);
   let scope = &mut v8::ContextScope::new(scope, context1);
 
+  assert!(scope.in_context());
   assert!(scope.get_current_context() == context1);
   assert!(scope.get_entered_or_microtask_context() == context1);
+  assert!(scope.get_incumbent_context() == context1);
 
   {
     let context2 = v8::Context::new(scope);
@@ -380,10 +502,12 @@ fn context_scope() {
 
     assert!(scope.get_current_context() == context2);
     assert!(scope.get_entered_or_microtask_context() == context2);
+    assert!(scope.get_incumbent_context() == context2);
   }
 
   assert!(scope.get_current_context() == context1);
   assert!(scope.get_entered_or_microtask_context() == context1);
+  assert!(scope.get_incumbent_context() == context1);
 }
 
 #[test]
@@ -474,6 +598,52 @@ fn microtasks() {
   }
 }
 
+#[test]
+fn microtasks_completed_callback() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  isolate.set_microtasks_policy(v8::MicrotasksPolicy::Explicit);
+
+  extern "C" fn completed(isolate: &mut v8::Isolate, data: *mut c_void) {
+    assert!(!v8::MicrotasksScope::is_running_microtasks(isolate));
+    let count = unsafe { &*(data as *const AtomicUsize) };
+    count.fetch_add(1, Ordering::SeqCst);
+  }
+  let completed_count = AtomicUsize::new(0);
+  let data = &completed_count as *const AtomicUsize as *mut c_void;
+  isolate.add_microtasks_completed_callback(completed, data);
+
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    static RUNNING: AtomicBool = AtomicBool::new(false);
+    let function = v8::Function::new(
+      scope,
+      |scope: &mut v8::HandleScope,
+       _: v8::FunctionCallbackArguments,
+       _: v8::ReturnValue| {
+        let running = v8::MicrotasksScope::is_running_microtasks(scope);
+        RUNNING.store(running, Ordering::SeqCst);
+      },
+    )
+    .unwrap();
+    scope.enqueue_microtask(function);
+
+    assert!(!v8::MicrotasksScope::is_running_microtasks(scope));
+    assert_eq!(v8::MicrotasksScope::get_current_depth(scope), 0);
+    scope.perform_microtask_checkpoint();
+    assert!(RUNNING.load(Ordering::SeqCst));
+    assert_eq!(completed_count.load(Ordering::SeqCst), 1);
+
+    scope.remove_microtasks_completed_callback(completed, data);
+    scope.enqueue_microtask(function);
+    scope.perform_microtask_checkpoint();
+    assert_eq!(completed_count.load(Ordering::SeqCst), 1);
+  }
+}
+
 #[test]
 fn get_isolate_from_handle() {
   extern "C" {
@@ -652,6 +822,35 @@ fn array_buffer() {
   }
 }
 
+#[test]
+fn backing_store_reallocate() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  let bs = v8::ArrayBuffer::new_backing_store(isolate, 4);
+  for (i, byte) in bs.iter().enumerate() {
+    byte.set(i as u8 + 1);
+  }
+
+  let bs = v8::BackingStore::reallocate(isolate, bs, 8);
+  assert_eq!(8, bs.byte_length());
+  assert_eq!(
+    bs[..4].iter().map(|b| b.get()).collect::<Vec<_>>(),
+    vec![1, 2, 3, 4]
+  );
+
+  let bs = v8::BackingStore::reallocate(isolate, bs, 2);
+  assert_eq!(2, bs.byte_length());
+  assert_eq!(bs[0].get(), 1);
+  assert_eq!(bs[1].get(), 2);
+
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let ab = v8::ArrayBuffer::with_backing_store(scope, &bs.make_shared());
+  assert_eq!(2, ab.byte_length());
+}
+
 #[test]
 fn backing_store_segfault() {
   let _setup_guard = setup();
@@ -697,6 +896,69 @@ fn shared_array_buffer_allocator() {
   alloc2.assert_use_count_eq(0);
 }
 
+#[test]
+fn counting_array_buffer_allocator() {
+  let _setup_guard = setup();
+  let (allocator, stats) = v8::new_counting_allocator(Some(2048));
+  assert_eq!(stats.limit(), Some(2048));
+  {
+    let params = v8::Isolate::create_params().array_buffer_allocator(allocator);
+    let isolate = &mut v8::Isolate::new(params);
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let ab = v8::ArrayBuffer::new(scope, 1024);
+    assert_eq!(ab.byte_length(), 1024);
+    assert_eq!(stats.allocated_bytes(), 1024);
+    assert_eq!(stats.peak_allocated_bytes(), 1024);
+
+    let scope = &mut v8::TryCatch::new(scope);
+    assert!(eval(scope, "new ArrayBuffer(4096)").is_none());
+    let exception = scope.exception().unwrap();
+    let range_error = eval(scope, "RangeError").unwrap();
+    let range_error = v8::Local::<v8::Object>::try_from(range_error).unwrap();
+    assert_eq!(exception.instance_of(scope, range_error), Some(true));
+    assert_eq!(stats.allocated_bytes(), 1024);
+  }
+  assert_eq!(stats.allocated_bytes(), 0);
+  assert_eq!(stats.peak_allocated_bytes(), 1024);
+}
+
+#[test]
+fn array_buffer_try_new() {
+  let _setup_guard = setup();
+  let (allocator, stats) = v8::new_counting_allocator(Some(2048));
+  {
+    let params = v8::Isolate::create_params().array_buffer_allocator(allocator);
+    let isolate = &mut v8::Isolate::new(params);
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let ab = v8::ArrayBuffer::try_new(scope, 1024).unwrap();
+    assert_eq!(ab.byte_length(), 1024);
+    assert_eq!(stats.allocated_bytes(), 1024);
+    assert!(ab.get_backing_store().iter().all(|b| b.get() == 0));
+
+    assert!(v8::ArrayBuffer::try_new(scope, 4096).is_none());
+    assert!(v8::ArrayBuffer::try_new(scope, usize::MAX).is_none());
+    assert!(v8::ArrayBuffer::try_new_backing_store(scope, 4096).is_none());
+    assert!(v8::SharedArrayBuffer::try_new_backing_store(scope, 4096).is_none());
+    assert_eq!(stats.allocated_bytes(), 1024);
+
+    let bs = v8::SharedArrayBuffer::try_new_backing_store(scope, 512).unwrap();
+    assert!(bs.is_shared());
+    assert_eq!(stats.allocated_bytes(), 1536);
+    drop(bs);
+    assert_eq!(stats.allocated_bytes(), 1024);
+
+    let ab = v8::ArrayBuffer::try_new(scope, 0).unwrap();
+    assert_eq!(ab.byte_length(), 0);
+  }
+  assert_eq!(stats.allocated_bytes(), 0);
+}
+
 #[test]
 fn array_buffer_with_shared_backing_store() {
   let _setup_guard = setup();
@@ -888,6 +1150,44 @@ fn try_catch() {
   }
 }
 
+#[test]
+fn try_catch_check() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let scope = &mut v8::TryCatch::new(scope);
+
+  let value = scope
+    .catch(|scope| {
+      let code = v8::String::new(scope, "1 + 2")?;
+      let script = v8::Script::compile(scope, code, None)?;
+      script.run(scope)
+    })
+    .unwrap();
+  assert_eq!(value.int32_value(scope), Some(3));
+
+  let error = scope
+    .catch(|scope| {
+      let code = v8::String::new(scope, "throw new RangeError('nope')")?;
+      let script = v8::Script::compile(scope, code, None)?;
+      script.run(scope)
+    })
+    .unwrap_err()
+    .unwrap();
+  assert!(error.exception.is_native_error());
+  assert_eq!(error.message, "Uncaught RangeError: nope");
+  scope.reset();
+
+  let result = eval(scope, "undefinedVariable");
+  let error = scope.check(result).unwrap_err().unwrap();
+  assert_eq!(
+    error.message,
+    "Uncaught ReferenceError: undefinedVariable is not defined"
+  );
+}
+
 #[test]
 fn try_catch_caught_lifetime() {
   let _setup_guard = setup();
@@ -916,6 +1216,76 @@ fn try_catch_caught_lifetime() {
     .contains("DANG"));
 }
 
+#[test]
+fn try_catch_exception_report() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  {
+    let tc = &mut v8::TryCatch::new(scope);
+    let resource_name = v8::String::new(tc, "foo.js").unwrap();
+    let source_map_url = v8::String::new(tc, "").unwrap();
+    let script_origin = v8::ScriptOrigin::new(
+      tc,
+      resource_name.into(),
+      0,
+      0,
+      false,
+      0,
+      source_map_url.into(),
+      false,
+      false,
+      false,
+    );
+    let source =
+      v8::String::new(tc, "function f() {\n  throw new Error('foo');\n}\nf();")
+        .unwrap();
+    let script = v8::Script::compile(tc, source, Some(&script_origin)).unwrap();
+    assert!(script.run(tc).is_none());
+
+    let report = tc.exception_report().unwrap();
+    assert!(report.exception.is_native_error());
+    assert_eq!(report.message, "Uncaught Error: foo");
+    assert_eq!(report.resource_name.as_deref(), Some("foo.js"));
+    assert_eq!(report.line_number, Some(2));
+    assert_eq!(report.start_column, Some(2));
+    let stack_trace = report.stack_trace.as_deref().unwrap();
+    assert!(stack_trace.starts_with("Error: foo\n    at f (foo.js:2:9)"));
+    assert!(report.to_string().starts_with("foo.js:2:2: Error: foo\n"));
+  }
+  {
+    // A thrown primitive has no stack trace.
+    let tc = &mut v8::TryCatch::new(scope);
+    eval(tc, "throw 'bar'");
+    let report = tc.exception_report().unwrap();
+    assert!(report.exception.is_string());
+    assert_eq!(report.message, "Uncaught bar");
+    assert!(report.stack_trace.is_none());
+    assert!(report.to_string().ends_with("Uncaught bar"));
+  }
+  {
+    // Reading the report must not replace the caught exception.
+    let tc = &mut v8::TryCatch::new(scope);
+    eval(
+      tc,
+      "throw { get stack() { throw 1 }, toString() { throw 2 } }",
+    );
+    let exception = tc.exception().unwrap();
+    let report = tc.exception_report().unwrap();
+    assert!(report.exception.strict_equals(exception));
+    assert!(report.stack_trace.is_none());
+    assert!(tc.exception().unwrap().strict_equals(exception));
+  }
+  {
+    // No error thrown.
+    let tc = &mut v8::TryCatch::new(scope);
+    eval(tc, "1 + 1");
+    assert!(tc.exception_report().is_none());
+  }
+}
+
 #[test]
 fn throw_exception() {
   let _setup_guard = setup();
@@ -937,6 +1307,163 @@ fn throw_exception() {
   }
 }
 
+#[test]
+fn throw_error_helpers() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  fn check_thrown(
+    scope: &mut v8::HandleScope,
+    throw: for<'s> fn(&mut v8::HandleScope<'s>) -> v8::Local<'s, v8::Value>,
+    expected: &str,
+  ) {
+    let tc = &mut v8::TryCatch::new(scope);
+    let result = throw(tc);
+    assert!(result.is_undefined());
+    assert!(tc.has_caught());
+    let exception = tc.exception().unwrap();
+    assert!(exception.is_native_error());
+    assert_eq!(exception.to_rust_string_lossy(tc), expected);
+  }
+
+  check_thrown(scope, |s| s.throw_error("plain"), "Error: plain");
+  check_thrown(
+    scope,
+    |s| s.throw_range_error(format_args!("{} out of range", 42)),
+    "RangeError: 42 out of range",
+  );
+  check_thrown(
+    scope,
+    |s| s.throw_reference_error(String::from("x is not defined")),
+    "ReferenceError: x is not defined",
+  );
+  check_thrown(
+    scope,
+    |s| s.throw_syntax_error("unexpected token"),
+    "SyntaxError: unexpected token",
+  );
+  check_thrown(
+    scope,
+    |s| s.throw_type_error(format_args!("expected {}", "a number")),
+    "TypeError: expected a number",
+  );
+
+  // Errors thrown from a callback propagate to the calling JavaScript.
+  let function = v8::Function::new(
+    scope,
+    |scope: &mut v8::HandleScope,
+     args: v8::FunctionCallbackArguments,
+     _: v8::ReturnValue| {
+      scope.throw_type_error(format_args!("got {} arguments", args.length()));
+    },
+  )
+  .unwrap();
+  let name = v8::String::new(scope, "f").unwrap();
+  context
+    .global(scope)
+    .set(scope, name.into(), function.into());
+  let result = eval(
+    scope,
+    "try { f(1, 2) } catch (e) { e instanceof TypeError && e.message }",
+  )
+  .unwrap();
+  assert_eq!(result.to_rust_string_lossy(scope), "got 2 arguments");
+}
+
+#[test]
+fn has_pending_exception() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  assert!(!scope.has_pending_exception());
+
+  static PENDING_AFTER_THROW: AtomicBool = AtomicBool::new(false);
+  let function = v8::Function::new(
+    scope,
+    |scope: &mut v8::HandleScope,
+     _: v8::FunctionCallbackArguments,
+     _: v8::ReturnValue| {
+      assert!(!scope.has_pending_exception());
+      scope.throw_error("boom");
+      PENDING_AFTER_THROW
+        .store(scope.has_pending_exception(), Ordering::SeqCst);
+    },
+  )
+  .unwrap();
+  let name = v8::String::new(scope, "f").unwrap();
+  context
+    .global(scope)
+    .set(scope, name.into(), function.into());
+
+  {
+    let tc = &mut v8::TryCatch::new(scope);
+    let result = eval(tc, "f()");
+    assert!(result.is_none());
+    assert!(tc.has_caught());
+    assert!(PENDING_AFTER_THROW.load(Ordering::SeqCst));
+    // The exception has been caught, so it is no longer pending.
+    assert!(!tc.has_pending_exception());
+  }
+  assert!(!scope.has_pending_exception());
+
+  let result = eval(scope, "let r; try { f() } catch { r = 'caught' }; r");
+  assert_eq!(result.unwrap().to_rust_string_lossy(scope), "caught");
+  assert!(!scope.has_pending_exception());
+}
+
+#[test]
+fn scope_stack_introspection() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  assert_eq!(isolate.get_entered_context_count(), 0);
+  assert!(isolate.describe_scope_stack().is_empty());
+
+  let global_context = {
+    let scope = &mut v8::HandleScope::new(isolate);
+    assert!(scope.try_get_current_context().is_none());
+    assert_eq!(scope.describe_scope_stack(), vec!["HandleScope"]);
+
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    assert!(scope.try_get_current_context().unwrap() == context);
+    assert_eq!(scope.get_entered_context_count(), 1);
+
+    let scope = &mut v8::TryCatch::new(scope);
+    assert_eq!(
+      scope.describe_scope_stack(),
+      vec!["TryCatch", "ContextScope", "HandleScope"]
+    );
+    v8::Global::new(scope, context)
+  };
+  // A dropped `HandleScope` is not exited until its parent is used again.
+  assert_eq!(isolate.get_entered_context_count(), 0);
+  assert_eq!(isolate.describe_scope_stack(), vec!["HandleScope (zombie)"]);
+
+  {
+    let scope = &mut v8::HandleScope::with_context(isolate, &global_context);
+    assert_eq!(scope.get_entered_context_count(), 1);
+    assert_eq!(
+      scope.describe_scope_stack(),
+      vec!["HandleScope (with context)"]
+    );
+
+    let other_context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, other_context);
+    assert_eq!(scope.get_entered_context_count(), 2);
+    assert!(scope.try_get_current_context().unwrap() == other_context);
+  }
+  assert_eq!(isolate.get_entered_context_count(), 1);
+  assert_eq!(
+    isolate.describe_scope_stack(),
+    vec!["HandleScope (with context) (zombie)"]
+  );
+}
+
 #[test]
 fn isolate_termination_methods() {
   let _setup_guard = setup();
@@ -1024,19 +1551,76 @@ fn terminate_execution() {
   t.join().expect("join t");
 }
 
-// TODO(ry) This test should use threads
 #[test]
-fn request_interrupt_small_scripts() {
+fn isolate_scope() {
   let _setup_guard = setup();
-  let isolate = &mut v8::Isolate::new(Default::default());
-  let handle = isolate.thread_safe_handle();
+  let first = &mut v8::Isolate::new(Default::default());
+  let second = &mut v8::Isolate::new(Default::default());
+  // Isolates are entered when they are created.
+  assert!(second.is_current());
+  assert!(!first.is_current());
+
   {
-    let scope = &mut v8::HandleScope::new(isolate);
+    let isolate = &mut v8::IsolateScope::new(first);
+    assert!(isolate.is_current());
+    let scope = &mut v8::HandleScope::new(&mut **isolate);
     let context = v8::Context::new(scope);
     let scope = &mut v8::ContextScope::new(scope, context);
+    let result = eval(scope, "6 * 7").unwrap();
+    assert_eq!(result.int32_value(scope), Some(42));
 
-    static CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
-    extern "C" fn callback(
+    // Scopes can be nested.
+    let nested = v8::IsolateScope::new(second);
+    assert!(nested.is_current());
+    drop(nested);
+    assert!(scope.is_current());
+  }
+  assert!(second.is_current());
+  assert!(!first.is_current());
+}
+
+#[test]
+fn terminate_execution_in_safe_scope() {
+  let _setup_guard = setup();
+  let params = v8::CreateParams::default().only_terminate_in_safe_scope(true);
+  let isolate = &mut v8::Isolate::new(params);
+  let handle = isolate.thread_safe_handle();
+
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  // Termination is postponed outside of a `SafeForTerminationScope`.
+  handle.terminate_execution();
+  assert_eq!(eval(scope, "1 + 1").unwrap().int32_value(scope), Some(2));
+
+  let t = std::thread::spawn(move || {
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    handle.terminate_execution();
+  });
+  {
+    let scope = &mut v8::SafeForTerminationScope::new(scope);
+    assert!(eval(scope, "for(;;) {}").is_none());
+    assert!(scope.is_execution_terminating());
+  }
+  t.join().unwrap();
+  scope.cancel_terminate_execution();
+  assert!(eval(scope, "1 + 1").is_some());
+}
+
+// TODO(ry) This test should use threads
+#[test]
+fn request_interrupt_small_scripts() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let handle = isolate.thread_safe_handle();
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    static CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+    extern "C" fn callback(
       _isolate: &mut v8::Isolate,
       data: *mut std::ffi::c_void,
     ) {
@@ -1049,6 +1633,106 @@ fn request_interrupt_small_scripts() {
   }
 }
 
+#[test]
+fn interrupt_and_microtask_closures() {
+  let _setup_guard = setup();
+  let dropped = Arc::new(AtomicUsize::new(0));
+  struct DropCounter(Arc<AtomicUsize>);
+  impl Drop for DropCounter {
+    fn drop(&mut self) {
+      self.0.fetch_add(1, Ordering::SeqCst);
+    }
+  }
+
+  let calls = Arc::new(AtomicUsize::new(0));
+  {
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let handle = isolate.thread_safe_handle();
+
+    let counter = DropCounter(dropped.clone());
+    let calls_ = calls.clone();
+    assert!(handle.request_interrupt_closure(Box::new(move |_isolate| {
+      let _counter = counter;
+      calls_.fetch_add(1, Ordering::SeqCst);
+    })));
+
+    {
+      let scope = &mut v8::HandleScope::new(isolate);
+      let context = v8::Context::new(scope);
+      let scope = &mut v8::ContextScope::new(scope, context);
+      eval(scope, "(function(x){return x;})(1);");
+      assert_eq!(calls.load(Ordering::SeqCst), 1);
+      assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+      let counter = DropCounter(dropped.clone());
+      let calls_ = calls.clone();
+      scope.enqueue_microtask_closure(Box::new(move |_isolate| {
+        let _counter = counter;
+        calls_.fetch_add(1, Ordering::SeqCst);
+      }));
+      assert_eq!(calls.load(Ordering::SeqCst), 1);
+      scope.perform_microtask_checkpoint();
+      assert_eq!(calls.load(Ordering::SeqCst), 2);
+      assert_eq!(dropped.load(Ordering::SeqCst), 2);
+    }
+
+    // Closures that are never called are dropped with the isolate.
+    let counter = DropCounter(dropped.clone());
+    assert!(handle.request_interrupt_closure(Box::new(move |_isolate| {
+      let _counter = counter;
+      unreachable!();
+    })));
+    let counter = DropCounter(dropped.clone());
+    isolate.enqueue_microtask_closure(Box::new(move |_isolate| {
+      let _counter = counter;
+      unreachable!();
+    }));
+    assert_eq!(dropped.load(Ordering::SeqCst), 2);
+
+    let handle = handle.clone();
+    std::thread::spawn(move || {
+      handle.request_interrupt_closure(Box::new(|_isolate| unreachable!()))
+    })
+    .join()
+    .unwrap();
+  }
+  assert_eq!(calls.load(Ordering::SeqCst), 2);
+  assert_eq!(dropped.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn request_interrupt_closure_watchdog() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let handle = isolate.thread_safe_handle();
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let (tx, rx) = std::sync::mpsc::channel();
+  let watchdog = std::thread::spawn(move || {
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    handle.request_interrupt_closure(Box::new(move |isolate| {
+      // The closure runs on top of the looping script.
+      let scope = &mut unsafe { v8::CallbackScope::new(isolate) };
+      let scope = &mut v8::HandleScope::new(scope);
+      let context = scope.try_get_current_context().unwrap();
+      let scope = &mut v8::ContextScope::new(scope, context);
+      let global = context.global(scope);
+      let key = v8::String::new(scope, "iterations").unwrap();
+      let iterations = global.get(scope, key.into()).unwrap();
+      tx.send(iterations.integer_value(scope).unwrap()).unwrap();
+      scope.terminate_execution();
+    }))
+  });
+
+  let result = eval(scope, "for (globalThis.iterations = 0;;) iterations++");
+  assert!(result.is_none());
+  assert!(watchdog.join().unwrap());
+  assert!(rx.recv().unwrap() > 0);
+  scope.cancel_terminate_execution();
+}
+
 #[test]
 fn add_message_listener() {
   let _setup_guard = setup();
@@ -1215,6 +1899,61 @@ fn script_origin() {
 #[test]
 fn get_version() {
   assert!(v8::V8::get_version().len() > 3);
+  let (major, minor, build) = v8::VERSION;
+  let prefix = format!("{}.{}.{}", major, minor, build);
+  assert!(v8::V8::get_version().starts_with(&prefix));
+}
+
+#[test]
+fn get_build_config() {
+  let _setup_guard = setup();
+  let config = v8::V8::get_build_config();
+  // The tests link against a V8 built with ICU, and don't set --jitless.
+  assert!(config.i18n);
+  assert!(!config.jitless);
+}
+
+#[test]
+fn code_range() {
+  let _setup_guard = setup();
+  let params = v8::CreateParams::default().code_range_size(64 * 1024 * 1024);
+  let isolate = &mut v8::Isolate::new(params);
+  let code_range = isolate.get_code_range();
+  if cfg!(target_pointer_width = "64") {
+    assert!(!code_range.unwrap().is_empty());
+  }
+
+  // Memory protection keys are only used if the hardware supports them.
+  assert!(
+    !v8::V8::is_memory_protection_keys_enabled()
+      || v8::V8::has_memory_protection_key_support()
+  );
+}
+
+#[test]
+fn features() {
+  let _setup_guard = setup();
+  let js_features = v8::V8::get_js_features();
+  let shadow_realm = js_features
+    .iter()
+    .find(|feature| feature.flag == "harmony_shadow_realm")
+    .unwrap();
+  // Enabled by the flags passed in `setup()`.
+  assert!(shadow_realm.enabled);
+  assert!(!shadow_realm.description.is_empty());
+
+  let wasm_features = v8::V8::get_wasm_features();
+  assert!(!wasm_features.is_empty());
+  for feature in &wasm_features {
+    assert!(feature.flag.starts_with("experimental_wasm_"));
+  }
+  assert!(wasm_features
+    .iter()
+    .filter(|feature| feature.status == v8::V8::FeatureStatus::Shipped)
+    .all(|feature| feature.enabled));
+
+  assert!(v8::V8::is_wasm_exposed());
+  assert!(v8::V8::is_asm_validation_enabled());
 }
 
 #[test]
@@ -1258,6 +1997,46 @@ fn inspector_string_buffer() {
   }
 }
 
+#[test]
+fn inspector_string_buffer_from_vec() {
+  let chars = b"Hello Mars!".to_vec();
+  let ptr = chars.as_ptr();
+  let buf = v8::inspector::StringBuffer::from_vec8(chars);
+  let view = buf.as_ref().unwrap().string();
+  // The buffer was not copied.
+  assert_eq!(view.characters8().unwrap().as_ptr(), ptr);
+  assert_eq!(view.to_string(), "Hello Mars!");
+
+  let chars = "Hello Jupiter!".encode_utf16().collect::<Vec<u16>>();
+  let buf = v8::inspector::StringBuffer::from_vec16(chars);
+  let view = buf.as_ref().unwrap().string();
+  assert!(!view.is_8bit());
+  assert_eq!(view.to_string(), "Hello Jupiter!");
+}
+
+#[test]
+fn inspector_cbor_conversion() {
+  use v8::inspector::*;
+
+  let json =
+    br#"{"id":1,"method":"Runtime.evaluate","params":{"expression":"1+1"}}"#;
+  assert!(!is_cbor_message(json));
+  let cbor = json_to_cbor(StringView::from(&json[..])).unwrap();
+  assert!(is_cbor_message(&cbor));
+  assert_eq!(cbor_to_json(&cbor).unwrap(), &json[..]);
+
+  let json16 = std::str::from_utf8(json)
+    .unwrap()
+    .encode_utf16()
+    .collect::<Vec<u16>>();
+  assert_eq!(json_to_cbor(StringView::from(&json16[..])).unwrap(), cbor);
+
+  let err = json_to_cbor(StringView::from(&b"{\"id\":"[..])).unwrap_err();
+  assert!(!err.message().is_empty());
+  assert!(err.position() > 0);
+  assert!(cbor_to_json(&cbor[..cbor.len() - 1]).is_err());
+}
+
 #[test]
 fn test_primitives() {
   let _setup_guard = setup();
@@ -1363,6 +2142,71 @@ fn json() {
   }
 }
 
+#[test]
+fn iterator_for_each() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let map = eval(scope, "new Map([['a', 1], ['b', 2]])").unwrap();
+  let mut entries = Vec::new();
+  v8::iterator::for_each(scope, map, |scope, entry| {
+    let entry = v8::Local::<v8::Array>::try_from(entry).unwrap();
+    let key = entry.get_index(scope, 0)?.to_rust_string_lossy(scope);
+    let value = entry.get_index(scope, 1)?.int32_value(scope)?;
+    entries.push((key, value));
+    Some(())
+  })
+  .unwrap();
+  assert_eq!(entries, vec![("a".to_string(), 1), ("b".to_string(), 2)]);
+
+  let string = v8::String::new(scope, "ab").unwrap();
+  let mut chars = std::string::String::new();
+  v8::iterator::for_each(scope, string.into(), |scope, c| {
+    chars.push_str(&c.to_rust_string_lossy(scope));
+    Some(())
+  })
+  .unwrap();
+  assert_eq!(chars, "ab");
+
+  {
+    let scope = &mut v8::TryCatch::new(scope);
+    let number = v8::Integer::new(scope, 1);
+    assert!(v8::iterator::for_each(scope, number.into(), |_, _| {
+      unreachable!()
+    })
+    .is_none());
+    assert!(scope.has_caught());
+  }
+
+  // Stopping early closes the iterator, and an exception thrown by the
+  // callback is propagated.
+  let generator = eval(
+    scope,
+    "globalThis.closed = false;
+     (function* () {
+       try { yield 1; yield 2; } finally { globalThis.closed = true; }
+     })()",
+  )
+  .unwrap();
+  {
+    let scope = &mut v8::TryCatch::new(scope);
+    let mut count = 0;
+    let result = v8::iterator::for_each(scope, generator, |scope, _| {
+      count += 1;
+      scope.throw_type_error("boom");
+      None
+    });
+    assert!(result.is_none());
+    assert_eq!(count, 1);
+    let exception = scope.exception().unwrap();
+    assert_eq!(exception.to_rust_string_lossy(scope), "TypeError: boom");
+  }
+  assert!(eval(scope, "closed").unwrap().is_true());
+}
+
 #[test]
 fn no_internal_field() {
   let _setup_guard = setup();
@@ -1530,6 +2374,45 @@ fn function_template_signature() {
   }
 }
 
+#[test]
+fn function_template_has_instance() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context1 = v8::Context::new(scope);
+  let context2 = v8::Context::new(scope);
+
+  let parent_templ = v8::FunctionTemplate::new(scope, fortytwo_callback);
+  let child_templ = v8::FunctionTemplate::new(scope, fortytwo_callback);
+  child_templ.inherit(parent_templ);
+  let other_templ = v8::FunctionTemplate::new(scope, fortytwo_callback);
+
+  let (child_fn1, child_obj) = {
+    let scope = &mut v8::ContextScope::new(scope, context1);
+    let child_fn = child_templ.get_function(scope).unwrap();
+    // The function is cached per context.
+    assert!(child_fn == child_templ.get_function(scope).unwrap());
+    let child_obj = child_fn.new_instance(scope, &[]).unwrap();
+    (child_fn, child_obj)
+  };
+  let child_fn2 = {
+    let scope = &mut v8::ContextScope::new(scope, context2);
+    child_templ.get_function(scope).unwrap()
+  };
+  assert!(child_fn1 != child_fn2);
+
+  assert!(child_templ.has_instance(child_obj.into()));
+  assert!(parent_templ.has_instance(child_obj.into()));
+  assert!(!other_templ.has_instance(child_obj.into()));
+  let plain_obj = {
+    let scope = &mut v8::ContextScope::new(scope, context1);
+    v8::Object::new(scope)
+  };
+  assert!(!child_templ.has_instance(plain_obj.into()));
+  let number = v8::Number::new(scope, 1.0);
+  assert!(!child_templ.has_instance(number.into()));
+}
+
 #[test]
 fn function_template_prototype() {
   let _setup_guard = setup();
@@ -1762,6 +2645,33 @@ fn object_template_set_accessor() {
   }
 }
 
+#[test]
+fn object_cached_property_name() {
+  static FOO: v8::CachedPropertyName = v8::CachedPropertyName::new("foo");
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let name1 = FOO.get(scope);
+  let name2 = FOO.get(scope);
+  assert_eq!(name1, name2);
+  assert_eq!(name1.to_rust_string_lossy(scope), "foo");
+
+  let object = eval(scope, "({ foo: 42 })").unwrap();
+  let object = v8::Local::<v8::Object>::try_from(object).unwrap();
+  let value = object.get_cached(scope, &FOO).unwrap();
+  assert_eq!(value.int32_value(scope), Some(42));
+
+  let value = v8::Integer::new(scope, 7);
+  assert_eq!(object.set_cached(scope, &FOO, value.into()), Some(true));
+  let foo = v8::String::new(scope, "foo").unwrap();
+  let value = object.get(scope, foo.into()).unwrap();
+  assert_eq!(value.int32_value(scope), Some(7));
+}
+
 #[test]
 fn object() {
   let _setup_guard = setup();
@@ -1929,6 +2839,39 @@ fn array() {
     assert!(array.has_index(scope, 1).unwrap());
     assert!(array.delete_index(scope, 1).unwrap());
     assert!(!array.has_index(scope, 1).unwrap());
+
+    let mut next = 0;
+    let array = v8::Array::new_with_callback(scope, 1000, |scope| {
+      next += 1;
+      Some(v8::Integer::new(scope, next).into())
+    })
+    .unwrap();
+    assert_eq!(array.length(), 1000);
+    let last = array.get_index(scope, 999).unwrap();
+    assert_eq!(last.int32_value(scope), Some(1000));
+
+    let mut remaining = 3;
+    let array = v8::Array::new_with_callback(scope, 5, |scope| {
+      if remaining == 0 {
+        return None;
+      }
+      remaining -= 1;
+      Some(v8::undefined(scope).into())
+    });
+    assert!(array.is_none());
+
+    // Setters on Array.prototype are not invoked.
+    eval(
+      scope,
+      "Object.defineProperty(Array.prototype, 0, { set() { throw 1 } })",
+    )
+    .unwrap();
+    let array = v8::Array::new_with_callback(scope, 1, |scope| {
+      Some(v8::Integer::new(scope, 7).into())
+    })
+    .unwrap();
+    let first = array.get_index(scope, 0).unwrap();
+    assert_eq!(first.int32_value(scope), Some(7));
   }
 }
 
@@ -1962,6 +2905,160 @@ fn create_data_property() {
   }
 }
 
+#[test]
+fn object_get_own_property_descriptor() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let obj = eval(scope, "({ a: 1, get b() { return 2; } })")
+    .unwrap()
+    .to_object(scope)
+    .unwrap();
+
+  let key = v8::String::new(scope, "a").unwrap();
+  let desc = obj
+    .get_own_property_descriptor(scope, key.into())
+    .unwrap()
+    .to_object(scope)
+    .unwrap();
+  let value_key = v8::String::new(scope, "value").unwrap();
+  let value = desc.get(scope, value_key.into()).unwrap();
+  assert_eq!(value.int32_value(scope).unwrap(), 1);
+  let writable_key = v8::String::new(scope, "writable").unwrap();
+  assert!(desc.get(scope, writable_key.into()).unwrap().is_true());
+
+  let key = v8::String::new(scope, "b").unwrap();
+  let desc = obj
+    .get_own_property_descriptor(scope, key.into())
+    .unwrap()
+    .to_object(scope)
+    .unwrap();
+  let get_key = v8::String::new(scope, "get").unwrap();
+  assert!(desc.get(scope, get_key.into()).unwrap().is_function());
+  assert!(!desc.has_own_property(scope, value_key.into()).unwrap());
+
+  let key = v8::String::new(scope, "toString").unwrap();
+  let desc = obj.get_own_property_descriptor(scope, key.into()).unwrap();
+  assert!(desc.is_undefined());
+}
+
+#[test]
+fn object_real_named_properties() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let obj = eval(
+    scope,
+    "Object.create({ inherited: 1 }, { own: { value: 2 } })",
+  )
+  .unwrap()
+  .to_object(scope)
+  .unwrap();
+
+  let own = v8::String::new(scope, "own").unwrap();
+  let inherited = v8::String::new(scope, "inherited").unwrap();
+  let missing = v8::String::new(scope, "missing").unwrap();
+
+  assert!(obj.has_real_named_property(scope, own.into()).unwrap());
+  assert!(!obj
+    .has_real_named_property(scope, inherited.into())
+    .unwrap());
+  assert!(!obj.has_real_named_property(scope, missing.into()).unwrap());
+
+  let value = obj.get_real_named_property(scope, own.into()).unwrap();
+  assert_eq!(value.int32_value(scope).unwrap(), 2);
+  let value = obj
+    .get_real_named_property(scope, inherited.into())
+    .unwrap();
+  assert_eq!(value.int32_value(scope).unwrap(), 1);
+  assert!(obj.get_real_named_property(scope, missing.into()).is_none());
+
+  assert!(obj
+    .get_real_named_property_in_prototype_chain(scope, own.into())
+    .is_none());
+  let value = obj
+    .get_real_named_property_in_prototype_chain(scope, inherited.into())
+    .unwrap();
+  assert_eq!(value.int32_value(scope).unwrap(), 1);
+
+  let attr = obj
+    .get_real_named_property_attributes(scope, own.into())
+    .unwrap();
+  assert!(attr.is_read_only());
+  assert!(attr.is_dont_enum());
+  assert!(attr.is_dont_delete());
+  let attr = obj
+    .get_real_named_property_attributes(scope, inherited.into())
+    .unwrap();
+  assert!(attr.is_none());
+  assert!(obj
+    .get_real_named_property_attributes(scope, missing.into())
+    .is_none());
+
+  let array = eval(scope, "[1, , 3]").unwrap().to_object(scope).unwrap();
+  assert!(array.has_real_indexed_property(scope, 0).unwrap());
+  assert!(!array.has_real_indexed_property(scope, 1).unwrap());
+  assert!(array.has_real_indexed_property(scope, 2).unwrap());
+
+  let key = v8::String::new(scope, "accessor").unwrap();
+  let getter = |_: &mut v8::HandleScope,
+                _: v8::Local<v8::Name>,
+                _: v8::PropertyCallbackArguments,
+                mut rv: v8::ReturnValue| {
+    rv.set_int32(42);
+  };
+  assert!(obj.set_accessor(scope, key.into(), getter).unwrap());
+  assert!(obj
+    .has_real_named_callback_property(scope, key.into())
+    .unwrap());
+  assert!(!obj
+    .has_real_named_callback_property(scope, own.into())
+    .unwrap());
+
+  let key = v8::String::new(scope, "defined").unwrap();
+  let value = v8::Integer::new(scope, 3);
+  assert!(obj
+    .define_real_own_property(
+      scope,
+      key.into(),
+      value.into(),
+      v8::READ_ONLY + v8::DONT_ENUM
+    )
+    .unwrap());
+  assert!(obj.has_real_named_property(scope, key.into()).unwrap());
+  let value = obj.get(scope, key.into()).unwrap();
+  assert_eq!(value.int32_value(scope).unwrap(), 3);
+  let attr = obj
+    .get_real_named_property_attributes(scope, key.into())
+    .unwrap();
+  assert!(attr.is_read_only());
+  assert!(attr.is_dont_enum());
+  assert!(!attr.is_dont_delete());
+
+  // Exotic objects keep their own semantics, e.g. redefining the length of
+  // an array truncates it.
+  let array = eval(scope, "[1, 2, 3]").unwrap();
+  let array = v8::Local::<v8::Array>::try_from(array).unwrap();
+  let key = v8::String::new(scope, "length").unwrap();
+  let value = v8::Integer::new(scope, 1);
+  assert!(array
+    .define_real_own_property(
+      scope,
+      key.into(),
+      value.into(),
+      v8::DONT_ENUM + v8::DONT_DELETE
+    )
+    .unwrap());
+  assert_eq!(array.length(), 1);
+  assert!(!array.has_index(scope, 1).unwrap());
+}
+
 #[test]
 fn object_set_accessor() {
   let _setup_guard = setup();
@@ -2164,6 +3261,51 @@ fn promise_rejected() {
     assert_eq!(result.to_rust_string_lossy(scope), "test".to_string());
   }
 }
+
+#[test]
+fn resolver_handle() {
+  let _setup_guard = setup();
+  let mut isolate = v8::Isolate::new(Default::default());
+  let late_handle = {
+    let scope = &mut v8::HandleScope::new(&mut isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let resolver = v8::PromiseResolver::new(scope).unwrap();
+    let resolved = resolver.get_promise(scope);
+    let handle = v8::ResolverHandle::new(scope, resolver);
+    let resolver = v8::PromiseResolver::new(scope).unwrap();
+    let rejected = resolver.get_promise(scope);
+    let handle2 = v8::ResolverHandle::new(scope, resolver);
+    let resolver = v8::PromiseResolver::new(scope).unwrap();
+    let late_handle = v8::ResolverHandle::new(scope, resolver);
+
+    std::thread::spawn(move || {
+      assert!(handle.resolve(|scope| v8::Integer::new(scope, 42).into()));
+      assert!(handle2.settle(|scope| {
+        Err(v8::String::new(scope, "nope").unwrap().into())
+      }));
+    })
+    .join()
+    .unwrap();
+
+    // The promises are settled at the next microtask checkpoint.
+    assert_eq!(resolved.state(), v8::PromiseState::Pending);
+    assert_eq!(rejected.state(), v8::PromiseState::Pending);
+    scope.perform_microtask_checkpoint();
+    assert_eq!(resolved.state(), v8::PromiseState::Fulfilled);
+    assert_eq!(resolved.result(scope).integer_value(scope), Some(42));
+    assert_eq!(rejected.state(), v8::PromiseState::Rejected);
+    let reason = rejected.result(scope);
+    assert_eq!(reason.to_rust_string_lossy(scope), "nope");
+
+    late_handle
+  };
+
+  // Settling a promise of a disposed isolate does nothing.
+  drop(isolate);
+  assert!(!late_handle.resolve(|_| unreachable!()));
+}
 #[test]
 fn proxy() {
   let _setup_guard = setup();
@@ -2318,6 +3460,39 @@ fn function_builder_raw() {
   }
 }
 
+#[test]
+fn function_raw_without_scope() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  // A raw callback that neither creates a CallbackScope nor allocates handles.
+  extern "C" fn add(info: *const v8::FunctionCallbackInfo) {
+    let args = unsafe {
+      v8::FunctionCallbackArguments::from_function_callback_info(info)
+    };
+    let mut rv = unsafe { v8::ReturnValue::from_function_callback_info(info) };
+    let a = v8::Local::<v8::Integer>::try_from(args.get(0)).unwrap();
+    let b = v8::Local::<v8::Integer>::try_from(args.get(1)).unwrap();
+    rv.set_int32((a.value() + b.value()) as i32);
+  }
+
+  let template = v8::FunctionTemplate::new_raw(scope, add);
+  let function = template.get_function(scope).unwrap();
+  let global = context.global(scope);
+  let name = v8::String::new(scope, "add").unwrap();
+  global.set(scope, name.into(), function.into());
+
+  let result = eval(
+    scope,
+    "let sum = 0; for (let i = 0; i < 100; i++) sum = add(sum, i); sum",
+  )
+  .unwrap();
+  assert_eq!(result.int32_value(scope), Some(4950));
+}
+
 #[test]
 fn return_value() {
   let _setup_guard = setup();
@@ -2744,12 +3919,146 @@ fn promise_reject_callback_no_value() {
 }
 
 #[test]
-fn promise_hook() {
-  extern "C" fn hook(
-    type_: v8::PromiseHookType,
-    promise: v8::Local<v8::Promise>,
-    _parent: v8::Local<v8::Value>,
-  ) {
+fn promise_reject_callback_stack_trace() {
+  extern "C" fn promise_reject_callback(m: v8::PromiseRejectMessage) {
+    let scope = &mut unsafe { v8::CallbackScope::new(&m) };
+    let scope = &mut v8::HandleScope::new(scope);
+    let stack_trace = m.get_stack_trace(scope, 10);
+    let name = match m.get_event() {
+      v8::PromiseRejectEvent::PromiseHandlerAddedAfterReject => {
+        assert!(stack_trace.is_none());
+        "handler added".to_string()
+      }
+      event => {
+        let frame = stack_trace.unwrap().get_frame(scope, 0).unwrap();
+        let name = frame.get_function_name(scope).unwrap();
+        format!("{:?} in {}", event, name.to_rust_string_lossy(scope))
+      }
+    };
+    let name = v8::String::new(scope, &name).unwrap();
+    let context = scope.get_current_context();
+    let global = context.global(scope);
+    let key = v8::String::new(scope, "events").unwrap();
+    let events = global.get(scope, key.into()).unwrap();
+    let events = v8::Local::<v8::Array>::try_from(events).unwrap();
+    events.set_index(scope, events.length(), name.into());
+  }
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  isolate.set_promise_reject_callback(promise_reject_callback);
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let source = r#"
+      var events = [];
+      new Promise(function rejectTwice(resolve, reject) {
+        resolve();
+        reject(42);
+      });
+      function rejectWithoutHandler() {
+        return Promise.reject(42);
+      }
+      rejectWithoutHandler().catch(() => {});
+      events.join(", ")
+    "#;
+    let result = eval(scope, source).unwrap();
+    assert_eq!(
+      result.to_rust_string_lossy(scope),
+      "PromiseRejectAfterResolved in rejectTwice, \
+       PromiseRejectWithNoHandler in rejectWithoutHandler, \
+       handler added"
+    );
+  }
+}
+
+#[test]
+fn unhandled_rejection_tracker() {
+  use v8::unhandled_rejection::RejectionEvent;
+  use v8::unhandled_rejection::UnhandledRejectionTracker;
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  isolate.set_microtasks_policy(v8::MicrotasksPolicy::Explicit);
+
+  let events = std::rc::Rc::new(RefCell::new(Vec::new()));
+  {
+    let events = events.clone();
+    UnhandledRejectionTracker::install(isolate, move |scope, event| {
+      let event = match event {
+        RejectionEvent::Unhandled {
+          reason,
+          stack_trace,
+          ..
+        } => {
+          let frame = stack_trace.unwrap().get_frame(scope, 0).unwrap();
+          let name = frame.get_function_name(scope).unwrap();
+          format!(
+            "unhandled {} in {}",
+            reason.to_rust_string_lossy(scope),
+            name.to_rust_string_lossy(scope)
+          )
+        }
+        RejectionEvent::Handled { promise } => {
+          let context = scope.get_current_context();
+          let key = v8::String::new(scope, "late").unwrap();
+          let late = context.global(scope).get(scope, key.into()).unwrap();
+          assert!(late.strict_equals(promise.into()));
+          "handled late".to_string()
+        }
+      };
+      events.borrow_mut().push(event);
+    });
+  }
+
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let source = r#"
+    function rejectUnhandled() {
+      Promise.reject("unhandled");
+    }
+    function rejectHandledLater() {
+      return Promise.reject("handled later");
+    }
+    function rejectLate() {
+      return Promise.reject("late");
+    }
+    rejectUnhandled();
+    rejectHandledLater().catch(() => {});
+    var late = rejectLate();
+  "#;
+  eval(scope, source).unwrap();
+  assert!(events.borrow().is_empty());
+  scope.perform_microtask_checkpoint();
+  assert_eq!(
+    *events.borrow(),
+    vec![
+      "unhandled unhandled in rejectUnhandled",
+      "unhandled late in rejectLate"
+    ]
+  );
+
+  events.borrow_mut().clear();
+  eval(scope, "late.catch(() => {})").unwrap();
+  scope.perform_microtask_checkpoint();
+  assert_eq!(*events.borrow(), vec!["handled late"]);
+
+  events.borrow_mut().clear();
+  UnhandledRejectionTracker::uninstall(scope);
+  eval(scope, "Promise.reject(42)").unwrap();
+  scope.perform_microtask_checkpoint();
+  assert!(events.borrow().is_empty());
+}
+
+#[test]
+fn promise_hook() {
+  extern "C" fn hook(
+    type_: v8::PromiseHookType,
+    promise: v8::Local<v8::Promise>,
+    _parent: v8::Local<v8::Value>,
+  ) {
     // Check that PromiseHookType implements Clone and PartialEq.
     #[allow(clippy::clone_on_copy)]
     if type_.clone() == v8::PromiseHookType::Init {}
@@ -2797,6 +4106,42 @@ fn promise_hook() {
   }
 }
 
+#[test]
+fn promise_hook_closure() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let events = std::rc::Rc::new(RefCell::new(Vec::new()));
+  let events_ = events.clone();
+  isolate.set_promise_hook_closure(Box::new(
+    move |scope, type_, promise, parent| {
+      // The closure receives locals that can be used with its scope.
+      let context = promise.get_creation_context(scope).unwrap();
+      assert_eq!(context, scope.get_current_context());
+      events_.borrow_mut().push((type_, parent.is_promise()));
+    },
+  ));
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    eval(scope, "new Promise(() => {}).then(() => {})").unwrap();
+    assert_eq!(
+      *events.borrow(),
+      vec![
+        (v8::PromiseHookType::Init, false),
+        // The promise created by `then()` has the first one as its parent.
+        (v8::PromiseHookType::Init, true),
+      ]
+    );
+    events.borrow_mut().clear();
+    eval(scope, "Promise.resolve().then(() => {})").unwrap();
+    scope.perform_microtask_checkpoint();
+    let events = events.borrow();
+    assert!(events.contains(&(v8::PromiseHookType::Before, false)));
+    assert!(events.contains(&(v8::PromiseHookType::After, false)));
+  }
+}
+
 #[test]
 fn context_get_extras_binding_object() {
   let _setup_guard = setup();
@@ -2810,6 +4155,206 @@ fn context_get_extras_binding_object() {
   }
 }
 
+#[test]
+fn context_detach_global() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  let first = v8::Context::new(scope);
+  let global_proxy = first.global(scope);
+  {
+    let scope = &mut v8::ContextScope::new(scope, first);
+    eval(scope, "globalThis.page = 1").unwrap();
+  }
+
+  first.detach_global();
+  let second = v8::Context::new_with_global_proxy(scope, None, global_proxy);
+  assert!(second.global(scope) == global_proxy);
+
+  let scope = &mut v8::ContextScope::new(scope, second);
+  // The global proxy now refers to the second context's global object.
+  let key = v8::String::new(scope, "page").unwrap();
+  assert!(global_proxy.get(scope, key.into()).unwrap().is_undefined());
+  eval(scope, "globalThis.page = 2").unwrap();
+  let page = global_proxy.get(scope, key.into()).unwrap();
+  assert_eq!(page.int32_value(scope), Some(2));
+}
+
+#[test]
+fn context_microtask_queue() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  isolate.set_microtasks_policy(v8::MicrotasksPolicy::Explicit);
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  let queue = v8::MicrotaskQueue::new(scope, v8::MicrotasksPolicy::Explicit);
+  let first = v8::Context::new_with_microtask_queue(scope, queue);
+  let queue = v8::MicrotaskQueue::new(scope, v8::MicrotasksPolicy::Explicit);
+  let second = v8::Context::new_with_microtask_queue(scope, queue);
+  let default = v8::Context::new(scope);
+
+  for context in [first, second, default] {
+    let scope = &mut v8::ContextScope::new(scope, context);
+    eval(scope, "Promise.resolve().then(() => globalThis.ran = true)").unwrap();
+  }
+  let ran = |scope: &mut v8::HandleScope<()>, context| {
+    let scope = &mut v8::ContextScope::new(scope, context);
+    eval(scope, "globalThis.ran === true").unwrap().is_true()
+  };
+
+  // Each queue only runs the microtasks of its own context.
+  scope.perform_microtask_checkpoint();
+  assert!(ran(scope, default));
+  assert!(!ran(scope, first));
+  assert!(!ran(scope, second));
+
+  let queue = first.get_microtask_queue();
+  assert!(!queue.is_running_microtasks());
+  queue.perform_checkpoint(scope);
+  assert!(ran(scope, first));
+  assert!(!ran(scope, second));
+
+  let function = {
+    let scope = &mut v8::ContextScope::new(scope, second);
+    let function = eval(scope, "() => globalThis.called = true").unwrap();
+    v8::Local::<v8::Function>::try_from(function).unwrap()
+  };
+  second
+    .get_microtask_queue()
+    .enqueue_microtask(scope, function);
+  second.get_microtask_queue().perform_checkpoint(scope);
+  assert!(ran(scope, second));
+  {
+    let scope = &mut v8::ContextScope::new(scope, second);
+    assert!(eval(scope, "globalThis.called").unwrap().is_true());
+  }
+
+  // Clearing the slots of a context doesn't drop its queue.
+  first.clear_all_slots(scope);
+  {
+    let scope = &mut v8::ContextScope::new(scope, first);
+    eval(scope, "Promise.resolve().then(() => globalThis.ran = 2)").unwrap();
+  }
+  first.get_microtask_queue().perform_checkpoint(scope);
+  let scope = &mut v8::ContextScope::new(scope, first);
+  assert!(eval(scope, "globalThis.ran === 2").unwrap().is_true());
+}
+
+#[test]
+fn context_execution_time() {
+  use std::time::Duration;
+
+  // Keeps the CPU busy for a while, so that the thread's CPU time advances
+  // even on platforms where it has a coarse resolution.
+  const BUSY_LOOP: &str = r#"
+    for (let start = Date.now(); Date.now() - start < 50;);
+  "#;
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context1 = v8::Context::new(scope);
+  let context2 = v8::Context::new(scope);
+
+  {
+    let scope = &mut v8::ContextScope::new(scope, context1);
+    eval(scope, BUSY_LOOP).unwrap();
+  }
+  assert_eq!(context1.get_execution_time(scope), Duration::ZERO);
+
+  scope.set_context_execution_time_tracking(true);
+  {
+    let scope = &mut v8::ContextScope::new(scope, context1);
+    eval(scope, BUSY_LOOP).unwrap();
+    // The time since the context was entered is included.
+    let time = context1.get_execution_time(scope);
+    assert!(time > Duration::ZERO);
+    eval(scope, BUSY_LOOP).unwrap();
+    assert!(context1.get_execution_time(scope) > time);
+  }
+  let time1 = context1.get_execution_time(scope);
+  assert!(time1 > Duration::ZERO);
+  assert_eq!(context2.get_execution_time(scope), Duration::ZERO);
+
+  // Time spent in a nested context is only attributed to that context.
+  {
+    let scope = &mut v8::ContextScope::new(scope, context1);
+    let scope = &mut v8::ContextScope::new(scope, context2);
+    eval(scope, BUSY_LOOP).unwrap();
+  }
+  let time2 = context2.get_execution_time(scope);
+  assert!(time2 > Duration::ZERO);
+  assert!(context1.get_execution_time(scope) - time1 < time2);
+
+  scope.set_context_execution_time_tracking(false);
+  {
+    let scope = &mut v8::ContextScope::new(scope, context2);
+    eval(scope, BUSY_LOOP).unwrap();
+  }
+  assert_eq!(context2.get_execution_time(scope), time2);
+}
+
+#[test]
+fn continuation_preserved_embedder_data() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  assert!(context
+    .get_continuation_preserved_embedder_data(scope)
+    .is_undefined());
+
+  let get_data = v8::Function::new(
+    scope,
+    |scope: &mut v8::HandleScope,
+     _: v8::FunctionCallbackArguments,
+     mut rv: v8::ReturnValue| {
+      let context = scope.get_current_context();
+      rv.set(context.get_continuation_preserved_embedder_data(scope));
+    },
+  )
+  .unwrap();
+  let global = context.global(scope);
+  let name = v8::String::new(scope, "getData").unwrap();
+  global.set(scope, name.into(), get_data.into()).unwrap();
+
+  let set_data = |scope: &mut v8::HandleScope, data: &str| {
+    let data = v8::String::new(scope, data).unwrap();
+    context.set_continuation_preserved_embedder_data(data.into());
+  };
+
+  set_data(scope, "a");
+  eval(
+    scope,
+    r#"
+      var results = [];
+      (async () => {
+        await null;
+        results.push(getData());
+      })();
+    "#,
+  )
+  .unwrap();
+  set_data(scope, "b");
+  eval(
+    scope,
+    r#"
+      Promise.resolve().then(() => results.push(getData()));
+      results.push(getData());
+    "#,
+  )
+  .unwrap();
+  set_data(scope, "c");
+  scope.perform_microtask_checkpoint();
+  let results = eval(scope, "results.join()").unwrap();
+  assert_eq!(results.to_rust_string_lossy(scope), "b,a,b");
+  let data = context.get_continuation_preserved_embedder_data(scope);
+  assert_eq!(data.to_rust_string_lossy(scope), "c");
+}
+
 #[test]
 fn context_promise_hooks() {
   let _setup_guard = setup();
@@ -3132,6 +4677,70 @@ fn module_evaluation() {
   }
 }
 
+#[test]
+fn module_exports() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let source = mock_source(
+    scope,
+    "foo.js",
+    "export let counter = 1;\n\
+     export const answer = 42;\n\
+     export function increment() { counter++; }",
+  );
+  let module = v8::script_compiler::compile_module(scope, source).unwrap();
+  assert!(module
+    .instantiate_module(scope, unexpected_module_resolve_callback)
+    .unwrap());
+
+  let names = module
+    .get_export_names(scope)
+    .into_iter()
+    .map(|name| name.to_rust_string_lossy(scope))
+    .collect::<Vec<_>>();
+  assert_eq!(names, ["answer", "counter", "increment"]);
+
+  // Before evaluation, `let` and `const` bindings are uninitialized, while
+  // function declarations are hoisted.
+  let answer = v8::String::new(scope, "answer").unwrap();
+  let counter = v8::String::new(scope, "counter").unwrap();
+  let increment = v8::String::new(scope, "increment").unwrap();
+  let missing = v8::String::new(scope, "missing").unwrap();
+  assert!(matches!(
+    module.get_export(scope, answer),
+    Some(v8::ModuleExport::Uninitialized)
+  ));
+  assert!(matches!(
+    module.get_export(scope, increment),
+    Some(v8::ModuleExport::Initialized(value)) if value.is_function()
+  ));
+  assert!(module.get_export(scope, missing).is_none());
+  assert!(!scope.has_pending_exception());
+
+  module.evaluate(scope).unwrap();
+  let get_int =
+    |scope: &mut v8::HandleScope, name| match module.get_export(scope, name) {
+      Some(v8::ModuleExport::Initialized(value)) => value.int32_value(scope),
+      _ => None,
+    };
+  assert_eq!(get_int(scope, answer), Some(42));
+  assert_eq!(get_int(scope, counter), Some(1));
+
+  // Exports are live bindings.
+  let function = match module.get_export(scope, increment) {
+    Some(v8::ModuleExport::Initialized(value)) => value,
+    _ => unreachable!(),
+  };
+  let function = v8::Local::<v8::Function>::try_from(function).unwrap();
+  let undefined = v8::undefined(scope).into();
+  function.call(scope, undefined, &[]).unwrap();
+  assert_eq!(get_int(scope, counter), Some(2));
+}
+
 #[test]
 fn import_assertions() {
   let _setup_guard = setup();
@@ -3640,57 +5249,198 @@ fn create_params_snapshot_blob() {
   let arc_slice: std::sync::Arc<[u8]> = std::fs::read(file!()).unwrap().into();
   let _ = v8::CreateParams::default().snapshot_blob(arc_slice.clone());
   let _ = v8::CreateParams::default().snapshot_blob(arc_slice);
-}
 
-#[test]
-fn uint8_array() {
-  let _setup_guard = setup();
-  let isolate = &mut v8::Isolate::new(Default::default());
-  {
-    let scope = &mut v8::HandleScope::new(isolate);
-    let context = v8::Context::new(scope);
-    let scope = &mut v8::ContextScope::new(scope, context);
-    let source =
-      v8::String::new(scope, "new Uint8Array([23,23,23,23])").unwrap();
-    let script = v8::Script::compile(scope, source, None).unwrap();
-    source.to_rust_string_lossy(scope);
-    let result: v8::Local<v8::ArrayBufferView> =
-      script.run(scope).unwrap().try_into().unwrap();
-    assert_eq!(result.byte_length(), 4);
-    assert_eq!(result.byte_offset(), 0);
-    let mut dest = [0; 4];
-    let copy_bytes = result.copy_contents(&mut dest);
-    assert_eq!(copy_bytes, 4);
-    assert_eq!(dest, [23, 23, 23, 23]);
-    let maybe_ab = result.buffer(scope);
-    assert!(maybe_ab.is_some());
-    let ab = maybe_ab.unwrap();
-    let uint8_array = v8::Uint8Array::new(scope, ab, 0, 0);
-    assert!(uint8_array.is_some());
+  // Stands in for a memory-mapped file.
+  struct Mapping(Vec<u8>);
+  impl AsRef<[u8]> for Mapping {
+    fn as_ref(&self) -> &[u8] {
+      &self.0
+    }
   }
+  let mapping = std::sync::Arc::new(Mapping(std::fs::read(file!()).unwrap()));
+  let _ = v8::CreateParams::default().snapshot_blob_from(mapping.clone());
+  let _ = v8::CreateParams::default().snapshot_blob_from(mapping);
+  let _ = v8::CreateParams::default().snapshot_blob_from(vec![1u8, 2, 3]);
 }
 
 #[test]
-fn typed_array_constructors() {
+fn compressed_snapshot_blob() {
   let _setup_guard = setup();
-  let isolate = &mut v8::Isolate::new(Default::default());
+  let startup_data = {
+    let mut snapshot_creator = v8::SnapshotCreator::new(None);
+    // TODO(ry) this shouldn't be necessary. workaround unfinished business in
+    // the scope type system.
+    let mut isolate = unsafe { snapshot_creator.get_owned_isolate() };
+    {
+      let scope = &mut v8::HandleScope::new(&mut isolate);
+      let context = v8::Context::new(scope);
+      let scope = &mut v8::ContextScope::new(scope, context);
+      eval(scope, "globalThis.compressed = true").unwrap();
+      snapshot_creator.set_default_context(context);
+    }
+    std::mem::forget(isolate); // TODO(ry) this shouldn't be necessary.
+    snapshot_creator
+      .create_blob(v8::FunctionCodeHandling::Clear)
+      .unwrap()
+  };
+
+  // A stand-in for a real compression format.
+  let compressed = startup_data.iter().rev().copied().collect::<Vec<u8>>();
+  let decompress_calls = Arc::new(AtomicUsize::new(0));
+  let calls = decompress_calls.clone();
+  let params = v8::CreateParams::default().compressed_snapshot_blob(
+    compressed,
+    move |data| {
+      calls.fetch_add(1, Ordering::SeqCst);
+      data.iter().rev().copied().collect()
+    },
+  );
+  // The blob is only decompressed when the isolate is created.
+  assert_eq!(decompress_calls.load(Ordering::SeqCst), 0);
+  let isolate = &mut v8::Isolate::new(params);
+  assert_eq!(decompress_calls.load(Ordering::SeqCst), 1);
+
   let scope = &mut v8::HandleScope::new(isolate);
   let context = v8::Context::new(scope);
   let scope = &mut v8::ContextScope::new(scope, context);
+  assert!(eval(scope, "compressed").unwrap().is_true());
+}
 
-  let ab = v8::ArrayBuffer::new(scope, 8);
-
-  let t = v8::Uint8Array::new(scope, ab, 0, 0).unwrap();
-  assert!(t.is_uint8_array());
+#[test]
+fn snapshot_blob_shared_between_isolates() {
+  let _setup_guard = setup();
 
-  let t = v8::Uint8ClampedArray::new(scope, ab, 0, 0).unwrap();
-  assert!(t.is_uint8_clamped_array());
+  fn create_snapshot(source: &str) -> v8::StartupData {
+    let mut snapshot_creator = v8::SnapshotCreator::new(None);
+    // TODO(ry) this shouldn't be necessary. workaround unfinished business in
+    // the scope type system.
+    let mut isolate = unsafe { snapshot_creator.get_owned_isolate() };
+    {
+      let scope = &mut v8::HandleScope::new(&mut isolate);
+      let context = v8::Context::new(scope);
+      let scope = &mut v8::ContextScope::new(scope, context);
+      eval(scope, source).unwrap();
+      snapshot_creator.set_default_context(context);
+    }
+    std::mem::forget(isolate); // TODO(ry) this shouldn't be necessary.
+    snapshot_creator
+      .create_blob(v8::FunctionCodeHandling::Clear)
+      .unwrap()
+  }
 
-  let t = v8::Int8Array::new(scope, ab, 0, 0).unwrap();
-  assert!(t.is_int8_array());
+  let snapshots = [
+    std::sync::Arc::new(create_snapshot("globalThis.kind = 'first'")),
+    std::sync::Arc::new(create_snapshot("globalThis.kind = 'second'")),
+  ];
 
-  let t = v8::Uint16Array::new(scope, ab, 0, 0).unwrap();
-  assert!(t.is_uint16_array());
+  for index in [0, 1, 0, 1] {
+    let expected = ["first", "second"][index];
+    let params =
+      v8::Isolate::create_params().snapshot_blob_from(snapshots[index].clone());
+    let isolate = &mut v8::Isolate::new(params);
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let kind = eval(scope, "kind").unwrap();
+    assert_eq!(kind.to_rust_string_lossy(scope), expected);
+  }
+  // The isolates have released their references to the snapshots.
+  for snapshot in &snapshots {
+    assert_eq!(std::sync::Arc::strong_count(snapshot), 1);
+  }
+}
+
+#[test]
+fn create_params_moved_to_thread() {
+  let _setup_guard = setup();
+  let startup_data = {
+    let mut snapshot_creator = v8::SnapshotCreator::new(None);
+    // TODO(ry) this shouldn't be necessary. workaround unfinished business in
+    // the scope type system.
+    let mut isolate = unsafe { snapshot_creator.get_owned_isolate() };
+    {
+      let scope = &mut v8::HandleScope::new(&mut isolate);
+      let context = v8::Context::new(scope);
+      let scope = &mut v8::ContextScope::new(scope, context);
+      eval(scope, "globalThis.origin = 'main thread'").unwrap();
+      snapshot_creator.set_default_context(context);
+    }
+    std::mem::forget(isolate); // TODO(ry) this shouldn't be necessary.
+    snapshot_creator
+      .create_blob(v8::FunctionCodeHandling::Clear)
+      .unwrap()
+  };
+
+  // Everything the isolate needs is created on this thread...
+  let (allocator, stats) = v8::new_counting_allocator(None);
+  let params = v8::Isolate::create_params()
+    .snapshot_blob(startup_data)
+    .array_buffer_allocator(allocator);
+
+  // ...and the isolate itself on another one.
+  let origin = std::thread::spawn(move || {
+    let isolate = &mut v8::Isolate::new(params);
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let origin = eval(scope, "new ArrayBuffer(1024); origin").unwrap();
+    origin.to_rust_string_lossy(scope)
+  })
+  .join()
+  .unwrap();
+  assert_eq!(origin, "main thread");
+  assert!(stats.peak_allocated_bytes() >= 1024);
+}
+
+#[test]
+fn uint8_array() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let source =
+      v8::String::new(scope, "new Uint8Array([23,23,23,23])").unwrap();
+    let script = v8::Script::compile(scope, source, None).unwrap();
+    source.to_rust_string_lossy(scope);
+    let result: v8::Local<v8::ArrayBufferView> =
+      script.run(scope).unwrap().try_into().unwrap();
+    assert_eq!(result.byte_length(), 4);
+    assert_eq!(result.byte_offset(), 0);
+    let mut dest = [0; 4];
+    let copy_bytes = result.copy_contents(&mut dest);
+    assert_eq!(copy_bytes, 4);
+    assert_eq!(dest, [23, 23, 23, 23]);
+    let maybe_ab = result.buffer(scope);
+    assert!(maybe_ab.is_some());
+    let ab = maybe_ab.unwrap();
+    let uint8_array = v8::Uint8Array::new(scope, ab, 0, 0);
+    assert!(uint8_array.is_some());
+  }
+}
+
+#[test]
+fn typed_array_constructors() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let ab = v8::ArrayBuffer::new(scope, 8);
+
+  let t = v8::Uint8Array::new(scope, ab, 0, 0).unwrap();
+  assert!(t.is_uint8_array());
+
+  let t = v8::Uint8ClampedArray::new(scope, ab, 0, 0).unwrap();
+  assert!(t.is_uint8_clamped_array());
+
+  let t = v8::Int8Array::new(scope, ab, 0, 0).unwrap();
+  assert!(t.is_int8_array());
+
+  let t = v8::Uint16Array::new(scope, ab, 0, 0).unwrap();
+  assert!(t.is_uint16_array());
 
   let t = v8::Int16Array::new(scope, ab, 0, 0).unwrap();
   assert!(t.is_int16_array());
@@ -3781,6 +5531,67 @@ fn dynamic_import() {
   }
 }
 
+#[test]
+fn dynamic_import_referrer() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  static CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+  fn dynamic_import_cb<'s>(
+    scope: &mut v8::HandleScope<'s>,
+    referrer: v8::Referrer<'s>,
+    specifier: v8::Local<'s, v8::String>,
+    _import_assertions: v8::Local<'s, v8::FixedArray>,
+  ) -> Option<v8::Local<'s, v8::Promise>> {
+    assert!(
+      specifier.strict_equals(v8::String::new(scope, "bar.js").unwrap().into())
+    );
+    assert_eq!(
+      referrer.resource_name().to_rust_string_lossy(scope),
+      "foo.js"
+    );
+    let e = v8::String::new(scope, "boom").unwrap();
+    scope.throw_exception(e.into());
+    CALL_COUNT.fetch_add(1, Ordering::SeqCst);
+    None
+  }
+  isolate
+    .set_host_import_module_dynamically_referrer_callback(dynamic_import_cb);
+
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let resource_name = v8::String::new(scope, "foo.js").unwrap();
+    let source_map_url = v8::undefined(scope);
+    let origin = v8::ScriptOrigin::new(
+      scope,
+      resource_name.into(),
+      0,
+      0,
+      false,
+      0,
+      source_map_url.into(),
+      false,
+      false,
+      false,
+    );
+    let source = v8::String::new(
+      scope,
+      "(async function () {\n\
+         let x = await import('bar.js');\n\
+       })();",
+    )
+    .unwrap();
+    let script = v8::Script::compile(scope, source, Some(&origin)).unwrap();
+    let result = script.run(scope);
+    assert!(result.is_some());
+    assert_eq!(CALL_COUNT.load(Ordering::SeqCst), 1);
+  }
+}
+
 #[test]
 fn shared_array_buffer() {
   let _setup_guard = setup();
@@ -4190,6 +6001,115 @@ fn value_checker() {
   }
 }
 
+#[test]
+fn value_debug() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  fn debug(scope: &mut v8::HandleScope, code: &str) -> String {
+    let value = eval(scope, code).unwrap();
+    format!("{:?}", value.debug(scope))
+  }
+  assert_eq!(debug(scope, "undefined"), "undefined undefined");
+  assert_eq!(debug(scope, "null"), "null null");
+  assert_eq!(debug(scope, "true"), "boolean true");
+  assert_eq!(debug(scope, "1.5"), "number 1.5");
+  assert_eq!(debug(scope, "42n"), "bigint 42");
+  assert_eq!(debug(scope, "'a\"b'"), "string \"a\\\"b\"");
+  assert_eq!(debug(scope, "Symbol('foo')"), "symbol Symbol(foo)");
+  assert_eq!(debug(scope, "[1, 2, 3]"), "array 1,2,3");
+  assert_eq!(debug(scope, "({})"), "object #<Object>");
+  assert!(debug(scope, "(function f() {})").starts_with("function function f"));
+
+  let long = debug(scope, "'x'.repeat(1000)");
+  assert!(long.starts_with("string \"xxx"));
+  assert!(long.ends_with("…\""));
+  assert!(long.len() < 100);
+}
+
+#[test]
+fn local_cast_helpers() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let object = v8::Object::new(scope);
+  let value = object.upcast::<v8::Value>();
+  assert!(value == object);
+  assert!(value.try_cast::<v8::Object>().unwrap() == object);
+  assert!(value.try_cast::<v8::Array>().is_err());
+  assert!(object.upcast::<v8::Data>().try_cast::<v8::Object>().is_ok());
+
+  let string = v8::String::new(scope, "foo").unwrap();
+  let name = string.upcast::<v8::Name>();
+  match v8::NameKind::from(name) {
+    v8::NameKind::String(s) => {
+      assert!(s == string);
+      assert_eq!(s.to_rust_string_lossy(scope), "foo");
+    }
+    v8::NameKind::Symbol(_) => unreachable!(),
+  }
+  assert!(name.try_cast::<v8::String>().is_ok());
+  assert!(name.try_cast::<v8::Symbol>().is_err());
+
+  let symbol = v8::Symbol::new(scope, None);
+  let name = symbol.upcast::<v8::Name>();
+  match v8::NameKind::from(name) {
+    v8::NameKind::String(_) => unreachable!(),
+    v8::NameKind::Symbol(s) => assert!(s == symbol),
+  }
+  let kind = v8::NameKind::from(name);
+  assert!(v8::Local::<v8::Name>::from(kind) == name);
+}
+
+#[test]
+fn property_key_classification() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let keys = eval(
+    scope,
+    "Reflect.ownKeys({ a: 1, 7: 2, [Symbol('s')]: 3, [Symbol()]: 4 })",
+  )
+  .unwrap();
+  let keys = v8::Local::<v8::Array>::try_from(keys).unwrap();
+  let mut described = Vec::new();
+  for i in 0..keys.length() {
+    let key = keys.get_index(scope, i).unwrap();
+    let key = v8::Local::<v8::Name>::try_from(key).unwrap();
+    described.push(match v8::NameKind::from(key) {
+      v8::NameKind::String(s) => match s.to_array_index(scope) {
+        Some(index) => format!("index {}", index.value()),
+        None => format!("string {}", s.to_rust_string_lossy(scope)),
+      },
+      v8::NameKind::Symbol(s) => {
+        let description = s.description(scope);
+        format!("symbol {}", description.to_rust_string_lossy(scope))
+      }
+    });
+  }
+  assert_eq!(
+    described,
+    ["index 7", "string a", "symbol s", "symbol undefined"]
+  );
+
+  let value: v8::Local<v8::Value> = v8::Integer::new(scope, 42).into();
+  assert_eq!(value.to_array_index(scope).unwrap().value(), 42);
+  let value: v8::Local<v8::Value> = v8::Integer::new(scope, -1).into();
+  assert!(value.to_array_index(scope).is_none());
+  let value: v8::Local<v8::Value> =
+    v8::String::new(scope, "01").unwrap().into();
+  assert!(value.to_array_index(scope).is_none());
+}
+
 #[test]
 fn try_from_data() {
   let _setup_guard = setup();
@@ -4519,6 +6439,101 @@ fn inspector_can_dispatch_method() {
   assert!(!V8InspectorSession::can_dispatch_method(string_view));
 }
 
+#[test]
+fn inspector_context_registration() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  use v8::inspector::*;
+  let mut default_client = ClientCounter::new();
+  let mut inspector = V8Inspector::create(isolate, &mut default_client);
+
+  let scope = &mut v8::HandleScope::new(isolate);
+  let main = v8::Context::new(scope);
+  let realm = v8::Context::new(scope);
+
+  let name = b"main";
+  let info =
+    V8ContextInfo::new(main, 1, StringView::from(&name[..])).is_default(true);
+  inspector.context_created_with_info(&info);
+  let name = b"realm";
+  let info =
+    V8ContextInfo::new(realm, 1, StringView::from(&name[..])).is_default(false);
+  inspector.context_created_with_info(&info);
+
+  let main_id = V8Inspector::context_id(main);
+  let realm_id = V8Inspector::context_id(realm);
+  assert_ne!(main_id, realm_id);
+  assert_eq!(inspector.context_by_id(scope, main_id), Some(main));
+  assert_eq!(inspector.context_by_id(scope, realm_id), Some(realm));
+
+  let mut channel = ChannelCounter::new();
+  let state = b"{}";
+  let mut session = inspector.connect(
+    1,
+    &mut channel,
+    StringView::from(&state[..]),
+    V8InspectorClientTrustLevel::FullyTrusted,
+  );
+  let message = br#"{"id":1,"method":"Runtime.enable"}"#;
+  session.dispatch_protocol_message(StringView::from(&message[..]));
+  assert_eq!(channel.count_send_response, 1);
+  // One `Runtime.executionContextCreated` notification for each context.
+  assert_eq!(channel.count_send_notification, 2);
+
+  inspector.context_destroyed(realm);
+  assert_eq!(channel.count_send_notification, 3);
+  assert!(inspector.context_by_id(scope, realm_id).is_none());
+
+  inspector.reset_context_group(1);
+  assert!(inspector.context_by_id(scope, main_id).is_none());
+}
+
+#[test]
+fn debug_evaluate_global() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  eval(scope, "var config = { depth: 3 };").unwrap();
+
+  use v8::debug::EvaluateGlobalMode;
+  let source = v8::String::new(scope, "config.depth * 2").unwrap();
+  let result = v8::debug::evaluate_global(
+    scope,
+    source,
+    EvaluateGlobalMode::DisableBreaksAndThrowOnSideEffect,
+    false,
+  )
+  .unwrap();
+  assert_eq!(result.int32_value(scope), Some(6));
+
+  {
+    let tc = &mut v8::TryCatch::new(scope);
+    let source = v8::String::new(tc, "config.depth = 4").unwrap();
+    assert!(v8::debug::evaluate_global(
+      tc,
+      source,
+      EvaluateGlobalMode::DisableBreaksAndThrowOnSideEffect,
+      false,
+    )
+    .is_none());
+    assert!(tc.has_caught());
+    let exception = tc.exception().unwrap();
+    assert!(exception.to_rust_string_lossy(tc).starts_with("EvalError"));
+  }
+  let depth = eval(scope, "config.depth").unwrap();
+  assert_eq!(depth.int32_value(scope), Some(3));
+
+  let source = v8::String::new(scope, "config.depth = 4").unwrap();
+  v8::debug::evaluate_global(scope, source, EvaluateGlobalMode::Default, false)
+    .unwrap();
+  let depth = eval(scope, "config.depth").unwrap();
+  assert_eq!(depth.int32_value(scope), Some(4));
+}
+
 #[test]
 fn inspector_dispatch_protocol_message() {
   let _setup_guard = setup();
@@ -4730,49 +6745,433 @@ fn take_heap_snapshot() {
 }
 
 #[test]
-fn test_prototype_api() {
+fn write_heap_snapshot() {
+  struct FailingWriter;
+  impl std::io::Write for FailingWriter {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+      Err(std::io::ErrorKind::BrokenPipe.into())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+      Ok(())
+    }
+  }
+
   let _setup_guard = setup();
   let isolate = &mut v8::Isolate::new(Default::default());
   {
     let scope = &mut v8::HandleScope::new(isolate);
     let context = v8::Context::new(scope);
     let scope = &mut v8::ContextScope::new(scope, context);
+    let _ = eval(scope, "globalThis.eyecatcher = new (class Eyecatcher {})");
 
-    let obj = v8::Object::new(scope);
-    let proto_obj = v8::Object::new(scope);
-    let key_local: v8::Local<v8::Value> =
-      v8::String::new(scope, "test_proto_key").unwrap().into();
-    let value_local: v8::Local<v8::Value> =
-      v8::String::new(scope, "test_proto_value").unwrap().into();
-    proto_obj.set(scope, key_local, value_local);
-    obj.set_prototype(scope, proto_obj.into());
+    let mut buf = Vec::<u8>::new();
+    scope.write_heap_snapshot(&mut buf).unwrap();
+    let s = std::str::from_utf8(&buf).unwrap();
+    assert!(s.contains("Eyecatcher"));
 
-    assert!(obj
-      .get_prototype(scope)
-      .unwrap()
-      .same_value(proto_obj.into()));
+    let err = scope.write_heap_snapshot(&mut FailingWriter).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
 
-    let sub_gotten = obj.get(scope, key_local).unwrap();
-    assert!(sub_gotten.is_string());
-    assert_eq!(sub_gotten.to_rust_string_lossy(scope), "test_proto_value");
+    let snapshot = scope.take_retained_heap_snapshot().unwrap();
+    let mut buf = Vec::<u8>::new();
+    snapshot.write_to(&mut buf).unwrap();
+    let s = std::str::from_utf8(&buf).unwrap();
+    assert!(s.contains("Eyecatcher"));
   }
-  {
-    let scope = &mut v8::HandleScope::new(isolate);
-    let context = v8::Context::new(scope);
-    let scope = &mut v8::ContextScope::new(scope, context);
-
-    let obj = v8::Object::new(scope);
-    let null = v8::null(scope);
-    obj.set_prototype(scope, null.into());
+}
 
-    assert!(obj.get_prototype(scope).unwrap().is_null());
+#[test]
+fn build_embedder_graph_callback() {
+  extern "C" fn callback(
+    isolate: &mut v8::Isolate,
+    graph: &mut v8::EmbedderGraph,
+    data: *mut c_void,
+  ) {
+    let resource = unsafe { &*(data as *const v8::Global<v8::Object>) };
+    let resource = graph.v8_node(resource.open(isolate));
+    let pool = graph.add_root_node("RustSocketPool", 1234);
+    let socket = graph.add_node("RustSocket", 56);
+    graph.add_edge(pool, socket, None);
+    graph.add_edge(
+      socket,
+      resource,
+      Some(CStr::from_bytes_with_nul(b"pendingRead\0").unwrap()),
+    );
   }
-  {
-    let scope = &mut v8::HandleScope::new(isolate);
-    let context = v8::Context::new(scope);
-    let scope = &mut v8::ContextScope::new(scope, context);
 
-    let val = eval(scope, "({ __proto__: null })").unwrap();
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let object = eval(scope, "new (class Eyecatcher {})").unwrap();
+  let object = object.to_object(scope).unwrap();
+  let object = v8::Global::new(scope, object);
+  let data = &object as *const _ as *mut c_void;
+
+  scope.add_build_embedder_graph_callback(callback, data);
+  let mut buf = Vec::<u8>::new();
+  scope.write_heap_snapshot(&mut buf).unwrap();
+  let s = std::str::from_utf8(&buf).unwrap();
+  assert!(s.contains("RustSocketPool"));
+  assert!(s.contains("RustSocket"));
+  assert!(s.contains("pendingRead"));
+  assert!(s.contains("Eyecatcher"));
+
+  scope.remove_build_embedder_graph_callback(callback, data);
+  let mut buf = Vec::<u8>::new();
+  scope.write_heap_snapshot(&mut buf).unwrap();
+  let s = std::str::from_utf8(&buf).unwrap();
+  assert!(!s.contains("RustSocket"));
+}
+
+#[test]
+fn take_heap_snapshot_with_options() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let _ = eval(scope, "globalThis.eyecatcher = new (class Eyecatcher {})");
+
+    let mut progress_calls = 0;
+    let mut progress = |done: u32, total: u32| {
+      assert!(done <= total);
+      progress_calls += 1;
+      true
+    };
+    let mut chunks = Vec::<Vec<u8>>::new();
+    let options = v8::HeapSnapshotOptions {
+      chunk_size: Some(100),
+      progress: Some(&mut progress),
+    };
+    assert!(scope.take_heap_snapshot_with_options(options, |chunk| {
+      chunks.push(chunk.to_vec());
+      true
+    }));
+    assert!(progress_calls > 0);
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 100));
+    assert!(chunks.iter().any(|chunk| chunk.len() == 100));
+    let s = std::str::from_utf8(&chunks.concat()).unwrap().to_owned();
+    assert!(s.contains("Eyecatcher"));
+
+    // Abort from the progress callback.
+    let mut progress = |_: u32, _: u32| false;
+    let options = v8::HeapSnapshotOptions {
+      progress: Some(&mut progress),
+      ..Default::default()
+    };
+    let mut chunk_count = 0;
+    assert!(!scope.take_heap_snapshot_with_options(options, |_| {
+      chunk_count += 1;
+      true
+    }));
+    assert_eq!(chunk_count, 0);
+
+    // Abort from the output callback after the first chunk.
+    let mut chunk_count = 0;
+    assert!(
+      !scope.take_heap_snapshot_with_options(Default::default(), |_| {
+        chunk_count += 1;
+        false
+      })
+    );
+    assert_eq!(chunk_count, 1);
+  }
+}
+
+#[test]
+fn sampling_heap_profiler() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  assert!(scope.get_allocation_profile().is_none());
+  assert!(scope.start_sampling_heap_profiler(
+    128,
+    16,
+    v8::SamplingHeapProfilerFlags::NONE
+  ));
+  assert!(!scope.start_sampling_heap_profiler(
+    128,
+    16,
+    v8::SamplingHeapProfilerFlags::NONE
+  ));
+  let source = r#"
+    function allocate() {
+      const result = [];
+      for (let i = 0; i < 1e4; i++) result.push({ i });
+      return result;
+    }
+    globalThis.retained = allocate();
+  "#;
+  eval(scope, source).unwrap();
+
+  let profile = scope.get_allocation_profile().unwrap();
+  scope.stop_sampling_heap_profiler();
+  assert!(scope.get_allocation_profile().is_none());
+
+  assert_eq!(profile.nodes[0].name, "(root)");
+  let allocate = profile
+    .nodes
+    .iter()
+    .find(|node| node.name == "allocate")
+    .unwrap();
+  assert!(!allocate.allocations.is_empty());
+  assert!(allocate.line_number > 0);
+  for node in &profile.nodes {
+    for &child in &node.children {
+      assert!(profile.get_node(child).is_some());
+    }
+  }
+  assert!(!profile.samples.is_empty());
+  for sample in &profile.samples {
+    assert!(profile.get_node(sample.node_id).is_some());
+    assert!(sample.size > 0);
+  }
+}
+
+#[test]
+fn retained_heap_snapshots() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  assert_eq!(isolate.get_heap_snapshot_count(), 0);
+  assert!(isolate.get_heap_snapshot(0).is_none());
+
+  let first_uid = isolate.take_retained_heap_snapshot().unwrap().get_uid();
+  let second = isolate.take_retained_heap_snapshot().unwrap();
+  let second_uid = second.get_uid();
+  assert_ne!(first_uid, second_uid);
+  assert!(second.get_nodes_count() > 0);
+  assert_eq!(isolate.get_heap_snapshot_count(), 2);
+
+  let snapshot = isolate.get_heap_snapshot(1).unwrap();
+  assert_eq!(snapshot.get_uid(), second_uid);
+  let mut json = Vec::new();
+  assert!(snapshot.serialize(Some(4096), |chunk| {
+    json.extend_from_slice(chunk);
+    true
+  }));
+  assert!(std::str::from_utf8(&json).unwrap().starts_with('{'));
+
+  assert!(isolate.delete_heap_snapshot(first_uid));
+  assert!(!isolate.delete_heap_snapshot(first_uid));
+  assert_eq!(isolate.get_heap_snapshot_count(), 1);
+  assert_eq!(isolate.get_heap_snapshot(0).unwrap().get_uid(), second_uid);
+
+  isolate.take_retained_heap_snapshot().unwrap();
+  assert_eq!(isolate.get_heap_snapshot_count(), 2);
+  isolate.delete_all_heap_snapshots();
+  assert_eq!(isolate.get_heap_snapshot_count(), 0);
+}
+
+#[test]
+fn heap_object_ids() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let smi = v8::Integer::new(scope, 42);
+  assert!(scope.get_heap_object_id(smi.into()).is_none());
+
+  let object = eval(scope, "globalThis.leak = { a: 1 }").unwrap();
+  let id = scope.get_heap_object_id(object).unwrap();
+  assert_eq!(scope.get_heap_object_id(object), Some(id));
+  let found = scope.find_object_by_heap_id(id).unwrap();
+  assert!(found.strict_equals(object));
+
+  let max_id = scope
+    .take_retained_heap_snapshot()
+    .unwrap()
+    .get_max_snapshot_js_object_id();
+  assert!(max_id >= id);
+
+  scope.start_tracking_heap_objects(false);
+  let later = eval(scope, "globalThis.later = {}").unwrap();
+  let later_id = scope.get_heap_object_id(later).unwrap();
+  assert!(later_id > max_id);
+  // The id stays the same after the object was moved by the GC.
+  scope.low_memory_notification();
+  assert_eq!(scope.get_heap_object_id(later), Some(later_id));
+
+  let mut updates = 0;
+  let last_id = scope.get_heap_stats(|chunk| {
+    updates += chunk.len();
+    true
+  });
+  assert!(updates > 0);
+  assert!(last_id >= later_id);
+  scope.stop_tracking_heap_objects();
+
+  scope.delete_all_heap_snapshots();
+  scope.clear_heap_object_ids();
+}
+
+#[test]
+fn cpu_profiler() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let mut profiler = v8::CpuProfiler::new(isolate);
+  profiler.set_sampling_interval(100);
+
+  assert_eq!(
+    profiler.start_profiling("test", true),
+    v8::CpuProfilingStatus::Started
+  );
+  assert_eq!(
+    profiler.start_profiling("test", true),
+    v8::CpuProfilingStatus::AlreadyStarted
+  );
+  {
+    let scope = &mut v8::HandleScope::new(&mut *profiler);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let source = r#"
+      function busy() {
+        const end = Date.now() + 50;
+        while (Date.now() < end);
+      }
+      busy();
+    "#;
+    eval(scope, source).unwrap();
+  }
+  let profile = profiler.stop_profiling("test").unwrap();
+  assert!(profiler.stop_profiling("test").is_none());
+
+  assert_eq!(profile.nodes[0].function_name, "(root)");
+  assert!(profile
+    .nodes
+    .iter()
+    .any(|node| node.function_name == "busy"));
+  for node in &profile.nodes {
+    for &child in &node.children {
+      assert!(profile.get_node(child).is_some());
+    }
+  }
+
+  assert!(!profile.samples.is_empty());
+  assert_eq!(profile.samples.len(), profile.timestamps.len());
+  for &sample in &profile.samples {
+    assert!(profile.get_node(sample).is_some());
+  }
+  assert!(profile.start_time <= profile.end_time);
+  let deltas = profile.time_deltas();
+  assert_eq!(deltas.len(), profile.timestamps.len());
+  assert_eq!(
+    deltas.iter().sum::<i64>(),
+    profile.timestamps.last().unwrap() - profile.start_time
+  );
+
+  let busy = profile
+    .nodes
+    .iter()
+    .find(|node| node.function_name == "busy")
+    .unwrap();
+  assert!(
+    busy
+      .line_ticks
+      .iter()
+      .map(|tick| tick.hit_count)
+      .sum::<u32>()
+      <= busy.hit_count
+  );
+
+  drop(profiler);
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let json = v8::String::new(scope, &profile.to_json()).unwrap();
+  let json = v8::json::parse(scope, json).unwrap();
+  let json = v8::Local::<v8::Object>::try_from(json).unwrap();
+  let nodes = v8::String::new(scope, "nodes").unwrap();
+  let nodes = json.get(scope, nodes.into()).unwrap();
+  let nodes = v8::Local::<v8::Array>::try_from(nodes).unwrap();
+  assert_eq!(nodes.length() as usize, profile.nodes.len());
+}
+
+#[test]
+fn jit_code_event_handler() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  static CODE_ADDED: AtomicUsize = AtomicUsize::new(0);
+  extern "C" fn handler(event: &v8::JitCodeEvent) {
+    if event.type_ == v8::JitCodeEventType::CodeAdded {
+      assert!(event.name().is_some());
+      assert!(!event.code_start.is_null());
+      CODE_ADDED.fetch_add(1, Ordering::SeqCst);
+    } else {
+      assert!(event.name().is_none());
+    }
+  }
+
+  // Existing code (e.g. builtins) is reported right away.
+  isolate.set_jit_code_event_handler(
+    v8::JitCodeEventOptions::EnumExisting,
+    Some(handler),
+  );
+  assert!(CODE_ADDED.load(Ordering::SeqCst) > 0);
+
+  isolate.set_jit_code_event_handler(v8::JitCodeEventOptions::Default, None);
+  let count = CODE_ADDED.load(Ordering::SeqCst);
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    eval(scope, "(function f() { return 42 })()").unwrap();
+  }
+  assert_eq!(CODE_ADDED.load(Ordering::SeqCst), count);
+}
+
+#[test]
+fn test_prototype_api() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let obj = v8::Object::new(scope);
+    let proto_obj = v8::Object::new(scope);
+    let key_local: v8::Local<v8::Value> =
+      v8::String::new(scope, "test_proto_key").unwrap().into();
+    let value_local: v8::Local<v8::Value> =
+      v8::String::new(scope, "test_proto_value").unwrap().into();
+    proto_obj.set(scope, key_local, value_local);
+    obj.set_prototype(scope, proto_obj.into());
+
+    assert!(obj
+      .get_prototype(scope)
+      .unwrap()
+      .same_value(proto_obj.into()));
+
+    let sub_gotten = obj.get(scope, key_local).unwrap();
+    assert!(sub_gotten.is_string());
+    assert_eq!(sub_gotten.to_rust_string_lossy(scope), "test_proto_value");
+  }
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let obj = v8::Object::new(scope);
+    let null = v8::null(scope);
+    obj.set_prototype(scope, null.into());
+
+    assert!(obj.get_prototype(scope).unwrap().is_null());
+  }
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let val = eval(scope, "({ __proto__: null })").unwrap();
     let obj = val.to_object(scope).unwrap();
 
     assert!(obj.get_prototype(scope).unwrap().is_null());
@@ -4968,6 +7367,10 @@ fn heap_limits() {
   let params = v8::CreateParams::default().heap_limits(0, 10 << 20); // 10 MB.
   let isolate = &mut v8::Isolate::new(params);
 
+  let initial_heap_limit = isolate.get_initial_heap_limit();
+  assert!(initial_heap_limit > 0 && initial_heap_limit <= 10 << 20);
+  assert_eq!(isolate.get_current_heap_limit(), initial_heap_limit);
+
   let mut test_state = TestHeapLimitState::default();
   let state_ptr = &mut test_state as *mut _ as *mut c_void;
   isolate.add_near_heap_limit_callback(heap_limit_callback, state_ptr);
@@ -4994,6 +7397,57 @@ fn heap_limits() {
     }
   }
   assert_eq!(1, test_state.near_heap_limit_callback_calls);
+  // The callback doubled the heap limit.
+  assert_eq!(scope.get_current_heap_limit(), initial_heap_limit * 2);
+  assert_eq!(scope.get_initial_heap_limit(), initial_heap_limit);
+}
+
+#[test]
+fn near_heap_limit_closure() {
+  use std::cell::Cell;
+  use std::rc::Rc;
+
+  let _setup_guard = setup();
+
+  let params = v8::CreateParams::default().heap_limits(0, 10 << 20); // 10 MB.
+  let isolate = &mut v8::Isolate::new(params);
+  let initial_heap_limit = isolate.get_initial_heap_limit();
+
+  // Closures that are shadowed by a more recently added one aren't called.
+  isolate.add_near_heap_limit_closure(Box::new(|_, _| unreachable!()));
+  let calls = Rc::new(Cell::new(0));
+  {
+    let calls = calls.clone();
+    let handle = isolate.thread_safe_handle();
+    isolate.add_near_heap_limit_closure(Box::new(move |current, initial| {
+      assert_eq!(initial, initial_heap_limit);
+      calls.set(calls.get() + 1);
+      handle.terminate_execution();
+      current * 2
+    }));
+  }
+
+  {
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    let result = eval(
+      scope,
+      r#"
+        const arrays = [];
+        while (true) arrays.push(new Array(1000).fill("🦕"));
+      "#,
+    );
+    assert!(result.is_none());
+    scope.cancel_terminate_execution();
+  }
+  assert_eq!(calls.get(), 1);
+  assert_eq!(isolate.get_current_heap_limit(), initial_heap_limit * 2);
+
+  assert!(isolate.remove_near_heap_limit_closure(initial_heap_limit));
+  assert_eq!(Rc::strong_count(&calls), 1);
+  assert!(isolate.remove_near_heap_limit_closure(0));
+  assert!(!isolate.remove_near_heap_limit_closure(0));
 }
 
 #[test]
@@ -5043,55 +7497,222 @@ fn low_memory_notification() {
   isolate.low_memory_notification();
 }
 
-// Clippy thinks the return value doesn't need to be an Option, it's unaware
-// of the mapping that MapFnFrom<F> does for ResolveModuleCallback.
-#[allow(clippy::unnecessary_wraps)]
-fn synthetic_evaluation_steps<'a>(
-  context: v8::Local<'a, v8::Context>,
-  module: v8::Local<v8::Module>,
-) -> Option<v8::Local<'a, v8::Value>> {
-  let scope = &mut unsafe { v8::CallbackScope::new(context) };
-  let mut set = |name, value| {
-    let name = v8::String::new(scope, name).unwrap();
-    let value = v8::Number::new(scope, value).into();
-    module
-      .set_synthetic_module_export(scope, name, value)
-      .unwrap();
-  };
-  set("a", 1.0);
-  set("b", 2.0);
+#[test]
+fn memory_pressure_notification() {
+  use std::cell::Cell;
+  use std::rc::Rc;
 
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let collections = Rc::new(Cell::new(0));
   {
-    let scope = &mut v8::TryCatch::new(scope);
-    let name = v8::String::new(scope, "does not exist").unwrap();
-    let value = v8::undefined(scope).into();
-    assert!(module.set_synthetic_module_export(scope, name, value) == None);
-    assert!(scope.has_caught());
-    scope.reset();
+    let collections = collections.clone();
+    isolate.add_gc_epilogue_callback(
+      move |_, _, _| collections.set(collections.get() + 1),
+      v8::GCType::MARK_SWEEP_COMPACT,
+    );
   }
 
-  Some(v8::undefined(scope).into())
+  isolate.memory_pressure_notification(v8::MemoryPressureLevel::Moderate);
+  // On critical memory pressure, V8 collects garbage right away.
+  let before = collections.get();
+  isolate.memory_pressure_notification(v8::MemoryPressureLevel::Critical);
+  assert!(collections.get() > before);
+  isolate.memory_pressure_notification(v8::MemoryPressureLevel::None);
 }
 
 #[test]
-fn synthetic_module() {
+fn measure_memory() {
+  use std::rc::Rc;
+
+  type Sizes = Rc<RefCell<Option<Vec<(v8::Global<v8::Context>, usize)>>>>;
+
+  struct Delegate {
+    ignored: v8::Global<v8::Context>,
+    sizes: Sizes,
+  }
+
+  impl v8::MeasureMemoryDelegate for Delegate {
+    fn should_measure(&mut self, context: v8::Local<v8::Context>) -> bool {
+      context != self.ignored
+    }
+
+    fn measurement_complete(
+      &mut self,
+      scope: &mut v8::HandleScope<()>,
+      context_sizes_in_bytes: &[(v8::Local<v8::Context>, usize)],
+      _unattributed_size_in_bytes: usize,
+    ) {
+      let sizes = context_sizes_in_bytes
+        .iter()
+        .map(|&(context, size)| (v8::Global::new(scope, context), size))
+        .collect();
+      self.sizes.replace(Some(sizes));
+    }
+  }
+
   let _setup_guard = setup();
   let isolate = &mut v8::Isolate::new(Default::default());
-
   let scope = &mut v8::HandleScope::new(isolate);
+  let small = v8::Context::new(scope);
+  let large = v8::Context::new(scope);
+  let ignored = v8::Context::new(scope);
+  {
+    let scope = &mut v8::ContextScope::new(scope, large);
+    eval(scope, "globalThis.data = new Array(100000).fill({})").unwrap();
+  }
 
-  let context = v8::Context::new(scope);
-  let scope = &mut v8::ContextScope::new(scope, context);
+  let sizes = Sizes::default();
+  let delegate = Delegate {
+    ignored: v8::Global::new(scope, ignored),
+    sizes: sizes.clone(),
+  };
+  assert!(
+    scope.measure_memory(Box::new(delegate), v8::MeasureMemoryExecution::Eager)
+  );
 
-  let export_names = [
-    v8::String::new(scope, "a").unwrap(),
-    v8::String::new(scope, "b").unwrap(),
-  ];
-  let module_name = v8::String::new(scope, "synthetic module").unwrap();
-  let module = v8::Module::create_synthetic_module(
-    scope,
-    module_name,
-    &export_names,
+  // Force the garbage collection that performs the measurement, and run the
+  // task that reports the results.
+  for _ in 0..10 {
+    scope.low_memory_notification();
+    while v8::Platform::pump_message_loop(
+      &v8::V8::get_current_platform(),
+      scope,
+      false,
+    ) {}
+    if sizes.borrow().is_some() {
+      break;
+    }
+  }
+
+  let sizes = sizes.take().unwrap();
+  assert_eq!(sizes.len(), 2);
+  let size_of = |context| sizes.iter().find(|(c, _)| *c == context).unwrap().1;
+  assert!(size_of(large) > size_of(small));
+  assert!(sizes.iter().all(|(c, _)| *c != ignored));
+}
+
+#[test]
+fn gc_callbacks() {
+  use std::cell::Cell;
+  use std::rc::Rc;
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  let events = Rc::new(RefCell::new(Vec::new()));
+  let prologue = {
+    let events = events.clone();
+    isolate.add_gc_prologue_callback(
+      move |_, gc_type, flags| {
+        events.borrow_mut().push(("prologue", gc_type, flags))
+      },
+      v8::GCType::ALL,
+    )
+  };
+  let epilogue = {
+    let events = events.clone();
+    isolate.add_gc_epilogue_callback(
+      move |_, gc_type, flags| {
+        events.borrow_mut().push(("epilogue", gc_type, flags))
+      },
+      v8::GCType::MARK_SWEEP_COMPACT,
+    )
+  };
+
+  isolate.low_memory_notification();
+  {
+    let events = events.borrow();
+    let (kind, gc_type, flags) = events[0];
+    assert_eq!(kind, "prologue");
+    assert_eq!(gc_type, v8::GCType::MARK_SWEEP_COMPACT);
+    assert!(flags.intersects(
+      v8::GCCallbackFlags::FORCED
+        | v8::GCCallbackFlags::COLLECT_ALL_AVAILABLE_GARBAGE
+    ));
+    assert!(events.contains(&("epilogue", gc_type, flags)));
+  }
+
+  assert!(isolate.remove_gc_callback(prologue));
+  assert!(isolate.remove_gc_callback(epilogue));
+  assert!(!isolate.remove_gc_callback(prologue));
+  events.borrow_mut().clear();
+  isolate.low_memory_notification();
+  assert!(events.borrow().is_empty());
+  // The removed closures have been dropped.
+  assert_eq!(Rc::strong_count(&events), 1);
+
+  // A callback can remove itself, in which case it isn't called again.
+  let id = Rc::new(Cell::new(None));
+  let calls = Rc::new(Cell::new(0));
+  id.set(Some({
+    let id = id.clone();
+    let calls = calls.clone();
+    isolate.add_gc_prologue_callback(
+      move |isolate, _, _| {
+        calls.set(calls.get() + 1);
+        assert!(isolate.remove_gc_callback(id.get().unwrap()));
+      },
+      v8::GCType::ALL,
+    )
+  }));
+  isolate.low_memory_notification();
+  isolate.low_memory_notification();
+  assert_eq!(calls.get(), 1);
+  assert!(!isolate.remove_gc_callback(id.get().unwrap()));
+  // The closure has been dropped now that it no longer runs.
+  assert_eq!(Rc::strong_count(&calls), 1);
+}
+
+// Clippy thinks the return value doesn't need to be an Option, it's unaware
+// of the mapping that MapFnFrom<F> does for ResolveModuleCallback.
+#[allow(clippy::unnecessary_wraps)]
+fn synthetic_evaluation_steps<'a>(
+  context: v8::Local<'a, v8::Context>,
+  module: v8::Local<v8::Module>,
+) -> Option<v8::Local<'a, v8::Value>> {
+  let scope = &mut unsafe { v8::CallbackScope::new(context) };
+  let mut set = |name, value| {
+    let name = v8::String::new(scope, name).unwrap();
+    let value = v8::Number::new(scope, value).into();
+    module
+      .set_synthetic_module_export(scope, name, value)
+      .unwrap();
+  };
+  set("a", 1.0);
+  set("b", 2.0);
+
+  {
+    let scope = &mut v8::TryCatch::new(scope);
+    let name = v8::String::new(scope, "does not exist").unwrap();
+    let value = v8::undefined(scope).into();
+    assert!(module.set_synthetic_module_export(scope, name, value) == None);
+    assert!(scope.has_caught());
+    scope.reset();
+  }
+
+  Some(v8::undefined(scope).into())
+}
+
+#[test]
+fn synthetic_module() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let export_names = [
+    v8::String::new(scope, "a").unwrap(),
+    v8::String::new(scope, "b").unwrap(),
+  ];
+  let module_name = v8::String::new(scope, "synthetic module").unwrap();
+  let module = v8::Module::create_synthetic_module(
+    scope,
+    module_name,
+    &export_names,
     synthetic_evaluation_steps,
   );
   assert!(!module.is_source_text_module());
@@ -5376,6 +7997,7 @@ fn value_serializer_and_deserializer() {
     let mut value_deserializer =
       Custom1Value::deserializer(scope, &buffer, &mut array_buffers);
     assert_eq!(value_deserializer.read_header(context), Some(true));
+    assert!(value_deserializer.get_wire_format_version() > 0);
     assert!(value_deserializer.read_double(&mut double));
     assert!(value_deserializer.read_uint32(&mut int32));
 
@@ -5386,6 +8008,39 @@ fn value_serializer_and_deserializer() {
   assert_eq!(int32, 22);
 }
 
+#[test]
+fn value_deserializer_legacy_wire_format() {
+  use v8::ValueDeserializerHelper;
+  use v8::ValueSerializerHelper;
+
+  let _setup_guard = setup();
+  let mut array_buffers = ArrayBuffers::new();
+  let isolate = &mut v8::Isolate::new(Default::default());
+
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let buffer;
+  {
+    let mut value_serializer =
+      Custom1Value::serializer(scope, &mut array_buffers);
+    value_serializer.write_uint32(22);
+    buffer = value_serializer.release();
+  }
+
+  {
+    let mut value_deserializer =
+      Custom1Value::deserializer(scope, &buffer, &mut array_buffers);
+    value_deserializer.set_supports_legacy_wire_format(true);
+    assert_eq!(value_deserializer.read_header(context), Some(true));
+    assert_eq!(value_deserializer.get_wire_format_version(), 0);
+    let mut int32: u32 = 0;
+    assert!(value_deserializer.read_uint32(&mut int32));
+    assert_eq!(int32, 22);
+  }
+}
+
 #[test]
 fn value_serializer_and_deserializer_js_objects() {
   use v8::ValueDeserializerHelper;
@@ -5567,6 +8222,78 @@ fn value_serializer_and_deserializer_array_buffers() {
   }
 }
 
+#[test]
+fn value_serializer_and_deserializer_transfer_array_buffer() {
+  use v8::ValueDeserializerHelper;
+  use v8::ValueSerializerHelper;
+
+  let buffer;
+  let backing_store;
+  let mut array_buffers = ArrayBuffers::new();
+  {
+    let _setup_guard = setup();
+    let isolate = &mut v8::Isolate::new(Default::default());
+
+    let scope = &mut v8::HandleScope::new(isolate);
+
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let value =
+      eval(scope, "var arr = new Uint8Array([1, 2, 3]); arr").unwrap();
+    let value = v8::Local::<v8::Uint8Array>::try_from(value).unwrap();
+    let array_buffer = value.buffer(scope).unwrap();
+
+    let mut value_serializer =
+      Custom1Value::serializer(scope, &mut array_buffers);
+    value_serializer.write_header();
+    value_serializer.transfer_array_buffer(0, array_buffer);
+    assert_eq!(
+      value_serializer.write_value(context, value.into()),
+      Some(true)
+    );
+    buffer = value_serializer.release();
+
+    backing_store = array_buffer.get_backing_store();
+    array_buffer.detach();
+    assert_eq!(array_buffer.byte_length(), 0);
+    let result = eval(scope, "arr.length").unwrap();
+    assert_eq!(result.int32_value(scope).unwrap(), 0);
+  }
+
+  {
+    let _setup_guard = setup();
+    let isolate = &mut v8::Isolate::new(Default::default());
+
+    let scope = &mut v8::HandleScope::new(isolate);
+
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let array_buffer =
+      v8::ArrayBuffer::with_backing_store(scope, &backing_store);
+
+    let mut value_deserializer =
+      Custom1Value::deserializer(scope, &buffer, &mut array_buffers);
+    assert_eq!(value_deserializer.read_header(context), Some(true));
+    value_deserializer.transfer_array_buffer(0, array_buffer);
+    let value = value_deserializer.read_value(context).unwrap();
+    drop(value_deserializer);
+
+    let value = v8::Local::<v8::Uint8Array>::try_from(value).unwrap();
+    assert!(value
+      .buffer(scope)
+      .unwrap()
+      .strict_equals(array_buffer.into()));
+
+    let name = v8::String::new(scope, "arr").unwrap();
+    context.global(scope).set(scope, name.into(), value.into());
+    let result = eval(scope, "arr.toString()").unwrap();
+    let expected = v8::String::new(scope, "1,2,3").unwrap();
+    assert!(expected.strict_equals(result));
+  }
+}
+
 struct Custom2Value {}
 
 impl<'a> Custom2Value {
@@ -5626,6 +8353,154 @@ fn value_serializer_not_implemented() {
   );
 }
 
+struct Custom3Value<'a> {
+  modules: &'a mut Vec<v8::CompiledWasmModule>,
+}
+
+impl<'a> v8::ValueSerializerImpl for Custom3Value<'a> {
+  fn throw_data_clone_error<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    message: v8::Local<'s, v8::String>,
+  ) {
+    let error = v8::Exception::error(scope, message);
+    scope.throw_exception(error);
+  }
+
+  fn has_custom_host_object(&mut self, _isolate: &mut v8::Isolate) -> bool {
+    true
+  }
+
+  fn is_host_object<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    object: v8::Local<'s, v8::Object>,
+  ) -> Option<bool> {
+    let key = v8::String::new(scope, "hostId").unwrap();
+    object.has_own_property(scope, key.into())
+  }
+
+  fn write_host_object<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    object: v8::Local<'s, v8::Object>,
+    value_serializer: &mut dyn v8::ValueSerializerHelper,
+  ) -> Option<bool> {
+    let key = v8::String::new(scope, "hostId").unwrap();
+    let id = object.get(scope, key.into())?.uint32_value(scope)?;
+    value_serializer.write_uint32(id);
+    Some(true)
+  }
+
+  fn get_wasm_module_transfer_id(
+    &mut self,
+    _scope: &mut v8::HandleScope<'_>,
+    module: v8::Local<v8::WasmModuleObject>,
+  ) -> Option<u32> {
+    self.modules.push(module.get_compiled_module());
+    Some(self.modules.len() as u32 - 1)
+  }
+}
+
+impl<'a> v8::ValueDeserializerImpl for Custom3Value<'a> {
+  fn read_host_object<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    value_deserializer: &mut dyn v8::ValueDeserializerHelper,
+  ) -> Option<v8::Local<'s, v8::Object>> {
+    let mut id = 0;
+    if !value_deserializer.read_uint32(&mut id) {
+      return None;
+    }
+    let object = v8::Object::new(scope);
+    let key = v8::String::new(scope, "restoredId").unwrap();
+    let value = v8::Integer::new_from_unsigned(scope, id);
+    object.set(scope, key.into(), value.into())?;
+    Some(object)
+  }
+
+  fn get_wasm_module_from_id<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    clone_id: u32,
+  ) -> Option<v8::Local<'s, v8::WasmModuleObject>> {
+    let module = self.modules.get(clone_id as usize)?;
+    v8::WasmModuleObject::from_compiled_module(scope, module)
+  }
+}
+
+#[test]
+fn value_serializer_and_deserializer_host_objects_and_wasm_modules() {
+  use v8::ValueDeserializerHelper;
+  use v8::ValueSerializerHelper;
+
+  let buffer;
+  let mut modules = Vec::new();
+  {
+    let _setup_guard = setup();
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    // (module)
+    let value = eval(
+      scope,
+      r#"({
+        module: new WebAssembly.Module(new Uint8Array([
+          0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        ])),
+        host: { hostId: 42 },
+        plain: { a: 1 },
+      })"#,
+    )
+    .unwrap();
+    let mut value_serializer = v8::ValueSerializer::new(
+      scope,
+      Box::new(Custom3Value {
+        modules: &mut modules,
+      }),
+    );
+    value_serializer.write_header();
+    assert_eq!(value_serializer.write_value(context, value), Some(true));
+    buffer = value_serializer.release();
+  }
+  assert_eq!(modules.len(), 1);
+
+  {
+    let _setup_guard = setup();
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let mut value_deserializer = v8::ValueDeserializer::new(
+      scope,
+      Box::new(Custom3Value {
+        modules: &mut modules,
+      }),
+      &buffer,
+    );
+    assert_eq!(value_deserializer.read_header(context), Some(true));
+    let value = value_deserializer.read_value(context).unwrap();
+    drop(value_deserializer);
+
+    let name = v8::String::new(scope, "value").unwrap();
+    context.global(scope).set(scope, name.into(), value);
+    let result = eval(
+      scope,
+      r#"[
+        value.module instanceof WebAssembly.Module,
+        value.host.restoredId,
+        value.host.hostId,
+        value.plain.a,
+      ].join()"#,
+    )
+    .unwrap();
+    assert_eq!(result.to_rust_string_lossy(scope), "true,42,,1");
+  }
+}
+
 // Flaky on aarch64-qemu (Stack corruption).
 #[cfg(not(target_os = "android"))]
 #[test]
@@ -6193,6 +9068,100 @@ fn function_code_cache() {
   assert_eq!(expected, function.call(scope, undefined, &[input]).unwrap());
 }
 
+#[test]
+fn compile_function_and_call() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let recv = eval(scope, "({ base: 10 })").unwrap();
+  let code = v8::String::new(scope, "return this.base + a * b;").unwrap();
+  let source = v8::script_compiler::Source::new(code, None);
+  let a = v8::String::new(scope, "a").unwrap();
+  let b = v8::String::new(scope, "b").unwrap();
+  let args = [
+    v8::Integer::new(scope, 2).into(),
+    v8::Integer::new(scope, 3).into(),
+  ];
+  let result = v8::script_compiler::compile_function_and_call(
+    scope,
+    source,
+    &[a, b],
+    recv,
+    &args,
+  )
+  .unwrap();
+  assert_eq!(result.int32_value(scope), Some(16));
+
+  let code = v8::String::new(scope, "throw new Error('boom')").unwrap();
+  let source = v8::script_compiler::Source::new(code, None);
+  let undefined = v8::undefined(scope).into();
+  let report = v8::script_compiler::compile_function_and_call(
+    scope,
+    source,
+    &[],
+    undefined,
+    &[],
+  )
+  .unwrap_err()
+  .unwrap();
+  assert_eq!(report.message, "Uncaught Error: boom");
+  assert!(report.stack_trace.unwrap().starts_with("Error: boom\n"));
+
+  let code = v8::String::new(scope, "return (").unwrap();
+  let source = v8::script_compiler::Source::new(code, None);
+  let report = v8::script_compiler::compile_function_and_call(
+    scope,
+    source,
+    &[],
+    undefined,
+    &[],
+  )
+  .unwrap_err()
+  .unwrap();
+  assert!(report.message.starts_with("Uncaught SyntaxError"));
+
+  // The exceptions were caught, so the outer scope can keep running code.
+  assert!(eval(scope, "1 + 1").is_some());
+}
+
+#[test]
+fn check_syntax() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let code = v8::String::new(scope, "globalThis.ran = true;").unwrap();
+  let source = v8::script_compiler::Source::new(code, None);
+  assert!(v8::script_compiler::check_syntax(scope, source).is_ok());
+  // The script was only compiled, not run.
+  assert!(eval(scope, "globalThis.ran").unwrap().is_undefined());
+
+  // Syntax errors inside functions are reported too, even though the
+  // functions aren't compiled eagerly.
+  let code = v8::String::new(scope, "let a = 1;\nfunction f() { (; }").unwrap();
+  let source = v8::script_compiler::Source::new(code, None);
+  let report = v8::script_compiler::check_syntax(scope, source)
+    .unwrap_err()
+    .unwrap();
+  assert!(report.message.starts_with("Uncaught SyntaxError"));
+  assert_eq!(report.line_number, Some(2));
+
+  let source = mock_source(scope, "foo.js", "import 'bar.js'; export {};");
+  assert!(v8::script_compiler::check_module_syntax(scope, source).is_ok());
+  let source = mock_source(scope, "foo.js", "export export;");
+  let report = v8::script_compiler::check_module_syntax(scope, source)
+    .unwrap_err()
+    .unwrap();
+  assert!(report.message.starts_with("Uncaught SyntaxError"));
+
+  assert!(eval(scope, "1 + 1").is_some());
+}
+
 #[test]
 fn eager_compile_script() {
   let _setup_guard = setup();
@@ -6233,7 +9202,12 @@ fn code_cache_script() {
       v8::script_compiler::NoCacheReason::NoReason,
     )
     .unwrap();
-    script.create_code_cache().unwrap().to_vec()
+    let code_cache = script.create_code_cache().unwrap();
+    assert_eq!(
+      code_cache.buffer_policy(),
+      v8::script_compiler::BufferPolicy::BufferOwned
+    );
+    code_cache.to_vec()
   };
 
   let isolate = &mut v8::Isolate::new(Default::default());
@@ -6241,6 +9215,100 @@ fn code_cache_script() {
   let context = v8::Context::new(scope);
   let scope = &mut v8::ContextScope::new(scope, context);
 
+  let code = v8::String::new(scope, CODE).unwrap();
+  let cached_data = v8::CachedData::new(&code_cache);
+  assert_eq!(
+    cached_data.buffer_policy(),
+    v8::script_compiler::BufferPolicy::BufferNotOwned
+  );
+  assert_eq!(cached_data.as_ptr(), code_cache.as_ptr());
+  let source =
+    v8::script_compiler::Source::new_with_cached_data(code, None, cached_data);
+  let script = v8::script_compiler::compile(
+    scope,
+    source,
+    v8::script_compiler::CompileOptions::ConsumeCodeCache,
+    v8::script_compiler::NoCacheReason::NoReason,
+  )
+  .unwrap();
+  let ret = script.run(scope).unwrap();
+  assert_eq!(ret.uint32_value(scope).unwrap(), 2);
+}
+
+#[test]
+fn code_cache_rejected() {
+  let _setup_guard = setup();
+  let code_cache = v8::script_compiler::compile_to_code_cache("1 + 1").unwrap();
+
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let code = v8::String::new(scope, "1 + 1").unwrap();
+  let cached_data = v8::CachedData::new(&code_cache);
+  assert!(!cached_data.rejected());
+  let source =
+    v8::script_compiler::Source::new_with_cached_data(code, None, cached_data);
+  let (script, rejected) = v8::script_compiler::compile_and_check_cache(
+    scope,
+    source,
+    v8::script_compiler::CompileOptions::ConsumeCodeCache,
+    v8::script_compiler::NoCacheReason::NoReason,
+  );
+  assert!(!rejected);
+  let ret = script.unwrap().run(scope).unwrap();
+  assert_eq!(ret.uint32_value(scope).unwrap(), 2);
+
+  // The cache doesn't match the source, so V8 compiles it from scratch.
+  let code = v8::String::new(scope, "2 + 2").unwrap();
+  let cached_data = v8::CachedData::new(&code_cache);
+  let source =
+    v8::script_compiler::Source::new_with_cached_data(code, None, cached_data);
+  let (script, rejected) = v8::script_compiler::compile_and_check_cache(
+    scope,
+    source,
+    v8::script_compiler::CompileOptions::ConsumeCodeCache,
+    v8::script_compiler::NoCacheReason::NoReason,
+  );
+  assert!(rejected);
+  let ret = script.unwrap().run(scope).unwrap();
+  assert_eq!(ret.uint32_value(scope).unwrap(), 4);
+
+  // Garbage is rejected too.
+  let code = v8::String::new(scope, "3 + 3").unwrap();
+  let garbage = vec![0u8; code_cache.len()];
+  let cached_data = v8::CachedData::new(&garbage);
+  let source =
+    v8::script_compiler::Source::new_with_cached_data(code, None, cached_data);
+  let (script, rejected) =
+    v8::script_compiler::compile_unbound_script_and_check_cache(
+      scope,
+      source,
+      v8::script_compiler::CompileOptions::ConsumeCodeCache,
+      v8::script_compiler::NoCacheReason::NoReason,
+    );
+  assert!(rejected);
+  let script = script.unwrap().bind_to_current_context(scope);
+  let ret = script.run(scope).unwrap();
+  assert_eq!(ret.uint32_value(scope).unwrap(), 6);
+}
+
+#[test]
+fn code_cache_compiled_off_thread() {
+  const CODE: &str = "1 + 1";
+  let _setup_guard = setup();
+  let code_cache = std::thread::spawn(|| {
+    v8::script_compiler::compile_to_code_cache(CODE).unwrap()
+  })
+  .join()
+  .unwrap();
+
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
   let code = v8::String::new(scope, CODE).unwrap();
   let source = v8::script_compiler::Source::new_with_cached_data(
     code,
@@ -6256,6 +9324,49 @@ fn code_cache_script() {
   .unwrap();
   let ret = script.run(scope).unwrap();
   assert_eq!(ret.uint32_value(scope).unwrap(), 2);
+
+  assert!(v8::script_compiler::compile_to_code_cache("(").is_none());
+}
+
+#[test]
+fn code_cache_consumed_off_thread() {
+  const CODE: &str = "1 + 1";
+  let _setup_guard = setup();
+  let code_cache: &'static [u8] = Box::leak(
+    v8::script_compiler::compile_to_code_cache(CODE)
+      .unwrap()
+      .into_boxed_slice(),
+  );
+
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let mut task = v8::script_compiler::start_consuming_code_cache(
+    scope,
+    v8::CachedData::new(code_cache),
+  )
+  .unwrap();
+  let task = std::thread::spawn(move || {
+    task.run();
+    task
+  })
+  .join()
+  .unwrap();
+
+  let code = v8::String::new(scope, CODE).unwrap();
+  let source =
+    v8::script_compiler::Source::new_with_consume_cache_task(code, None, task);
+  let script = v8::script_compiler::compile(
+    scope,
+    source,
+    v8::script_compiler::CompileOptions::ConsumeCodeCache,
+    v8::script_compiler::NoCacheReason::NoReason,
+  )
+  .unwrap();
+  let ret = script.run(scope).unwrap();
+  assert_eq!(ret.uint32_value(scope).unwrap(), 2);
 }
 
 #[test]
@@ -6352,6 +9463,81 @@ fn external_strings() {
   assert!(!gradients.contains_only_onebyte());
 }
 
+#[test]
+fn external_strings_from_cow() {
+  use std::borrow::Cow;
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let borrowed =
+    v8::String::new_external_onebyte(scope, Cow::Borrowed(b"borrowed"))
+      .unwrap();
+  assert!(borrowed.is_external_onebyte());
+  assert_eq!(borrowed.to_rust_string_lossy(scope), "borrowed");
+
+  {
+    let scope = &mut v8::HandleScope::new(scope);
+    for i in 0..100 {
+      let source = format!("'owned ' + {}", i).into_bytes();
+      let owned =
+        v8::String::new_external_onebyte(scope, Cow::Owned(source)).unwrap();
+      assert!(owned.is_external_onebyte());
+      let script = v8::Script::compile(scope, owned, None).unwrap();
+      let result = script.run(scope).unwrap();
+      assert_eq!(result.to_rust_string_lossy(scope), format!("owned {}", i));
+    }
+  }
+  // Collecting the strings frees their buffers.
+  eval(scope, "gc()").unwrap();
+
+  let empty = v8::String::new_external_onebyte(scope, Cow::Owned(vec![]));
+  assert_eq!(empty.unwrap().length(), 0);
+
+  let ascii = v8::String::new_from_static_str(scope, "1 + 2").unwrap();
+  assert!(ascii.is_external_onebyte());
+  assert_eq!(ascii.to_rust_string_lossy(scope), "1 + 2");
+  let unicode = v8::String::new_from_static_str(scope, "🦕").unwrap();
+  assert!(!unicode.is_external());
+  assert_eq!(unicode.to_rust_string_lossy(scope), "🦕");
+}
+
+#[test]
+fn make_external_strings() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let source = "a".repeat(100);
+  let one_byte = v8::String::new(scope, &source).unwrap();
+  assert!(!one_byte.is_external());
+  // The buffer must match the string.
+  assert!(!one_byte.make_external_onebyte(vec![b'a'; 99].into()));
+  assert!(!one_byte.make_external_twobyte(vec![b'a' as u16; 100].into()));
+  assert!(one_byte.make_external_onebyte(source.into_bytes().into()));
+  assert!(one_byte.is_external_onebyte());
+  assert_eq!(one_byte.to_rust_string_lossy(scope), "a".repeat(100));
+  // Already external.
+  assert!(!one_byte.make_external_onebyte(vec![b'a'; 100].into()));
+
+  let source = "∇".repeat(50);
+  let two_byte = v8::String::new(scope, &source).unwrap();
+  let utf16 = source.encode_utf16().collect::<Vec<u16>>();
+  assert!(two_byte.make_external_twobyte(utf16.into()));
+  assert!(two_byte.is_external_twobyte());
+  assert_eq!(two_byte.to_rust_string_lossy(scope), source);
+
+  let mut lengths = vec![];
+  scope.visit_external_resources(|string| lengths.push(string.length()));
+  assert!(lengths.contains(&100));
+  assert!(lengths.contains(&50));
+}
+
 #[test]
 fn counter_lookup_callback() {
   #[derive(Eq, PartialEq, Hash)]
@@ -6438,27 +9624,240 @@ fn compiled_wasm_module() {
 
     let global = context.global(scope);
 
-    let module =
-      v8::WasmModuleObject::from_compiled_module(scope, &compiled_module)
-        .unwrap();
+    let module =
+      v8::WasmModuleObject::from_compiled_module(scope, &compiled_module)
+        .unwrap();
+
+    let key = v8::String::new(scope, "module").unwrap().into();
+    global.set(scope, key, module.into());
+
+    let foo_ab: v8::Local<v8::ArrayBuffer> =
+      eval(scope, "WebAssembly.Module.customSections(module, 'foo')[0]")
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let foo_bs = foo_ab.get_backing_store();
+    let foo_section = unsafe {
+      std::slice::from_raw_parts(
+        foo_bs.data().unwrap().as_ptr() as *mut u8,
+        foo_bs.byte_length(),
+      )
+    };
+    assert_eq!(foo_section, b"bar");
+  }
+}
+
+#[cfg(not(target_os = "android"))]
+#[test]
+fn compiled_wasm_module_shared_between_threads() {
+  let _setup_guard = setup();
+
+  let compiled_module = {
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let wire_bytes = &[
+      0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x07, 0x03, 0x66,
+      0x6F, 0x6F, 0x62, 0x61, 0x72,
+    ];
+    let module = v8::WasmModuleObject::compile(scope, wire_bytes).unwrap();
+
+    module.get_compiled_module()
+  };
+
+  let threads = (0..2)
+    .map(|_| {
+      let compiled_module = compiled_module.clone();
+      std::thread::spawn(move || {
+        let isolate = &mut v8::Isolate::new(Default::default());
+        let scope = &mut v8::HandleScope::new(isolate);
+        let context = v8::Context::new(scope);
+        let scope = &mut v8::ContextScope::new(scope, context);
+
+        let module =
+          v8::WasmModuleObject::from_compiled_module(scope, &compiled_module)
+            .unwrap();
+        let roundtrip = module.get_compiled_module();
+        assert_eq!(
+          roundtrip.get_wire_bytes_ref(),
+          compiled_module.get_wire_bytes_ref()
+        );
+        assert_eq!(roundtrip.source_url(), compiled_module.source_url());
+      })
+    })
+    .collect::<Vec<_>>();
+
+  for thread in threads {
+    thread.join().unwrap();
+  }
+}
+
+#[test]
+fn wasm_instance_exports() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  // (module
+  //   (memory (export "memory") 1)
+  //   (func (export "answer") (result i32) i32.const 42))
+  let instance = eval(
+    scope,
+    r#"
+      new WebAssembly.Instance(new WebAssembly.Module(new Uint8Array([
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01,
+        0x60, 0x00, 0x01, 0x7f, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01,
+        0x00, 0x01, 0x07, 0x13, 0x02, 0x06, 0x61, 0x6e, 0x73, 0x77, 0x65,
+        0x72, 0x00, 0x00, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02,
+        0x00, 0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2a, 0x0b,
+      ])))
+    "#,
+  )
+  .unwrap()
+  .to_object(scope)
+  .unwrap();
+
+  let key = v8::String::new(scope, "exports").unwrap();
+  let exports = instance
+    .get(scope, key.into())
+    .unwrap()
+    .to_object(scope)
+    .unwrap();
+
+  let key = v8::String::new(scope, "answer").unwrap();
+  let answer: v8::Local<v8::Function> =
+    exports.get(scope, key.into()).unwrap().try_into().unwrap();
+  let recv = v8::undefined(scope).into();
+  let result = answer.call(scope, recv, &[]).unwrap();
+  assert_eq!(result.int32_value(scope), Some(42));
+
+  let key = v8::String::new(scope, "memory").unwrap();
+  let memory = exports.get(scope, key.into()).unwrap();
+  assert!(memory.is_wasm_memory_object());
+  let memory: v8::Local<v8::WasmMemoryObject> = memory.try_into().unwrap();
+  let buffer = memory.buffer(scope);
+  assert_eq!(buffer.byte_length(), 65536);
+}
+
+#[test]
+fn wasm_reference_values() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  // (module
+  //   (memory (export "memory") 1)
+  //   (func (export "answer") (result i32) i32.const 42))
+  let exports = eval(
+    scope,
+    r#"
+      new WebAssembly.Instance(new WebAssembly.Module(new Uint8Array([
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x05, 0x01,
+        0x60, 0x00, 0x01, 0x7f, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01,
+        0x00, 0x01, 0x07, 0x13, 0x02, 0x06, 0x61, 0x6e, 0x73, 0x77, 0x65,
+        0x72, 0x00, 0x00, 0x06, 0x6d, 0x65, 0x6d, 0x6f, 0x72, 0x79, 0x02,
+        0x00, 0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2a, 0x0b,
+      ]))).exports
+    "#,
+  )
+  .unwrap()
+  .to_object(scope)
+  .unwrap();
+
+  let key = v8::String::new(scope, "answer").unwrap();
+  let answer = exports.get(scope, key.into()).unwrap();
+  assert!(answer.is_function());
+  assert!(answer.is_wasm_function());
+  assert!(!answer.is_wasm_object());
+
+  let key = v8::String::new(scope, "memory").unwrap();
+  let memory = exports.get(scope, key.into()).unwrap();
+  assert!(!memory.is_wasm_function());
+  assert!(!memory.is_wasm_object());
+
+  for source in ["(function() {})", "({})", "[]", "null", "42"] {
+    let value = eval(scope, source).unwrap();
+    assert!(!value.is_wasm_function(), "{}", source);
+    assert!(!value.is_wasm_object(), "{}", source);
+  }
+}
+
+#[test]
+fn function_new_closure() {
+  use std::cell::Cell;
+  use std::rc::Rc;
+
+  let _setup_guard = setup();
+  let counter = Rc::new(Cell::new(0));
+  {
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let captured = counter.clone();
+    let function = v8::Function::new_closure(
+      scope,
+      move |scope: &mut v8::HandleScope,
+            args: v8::FunctionCallbackArguments,
+            mut rv: v8::ReturnValue| {
+        let step = args.get(0).int32_value(scope).unwrap();
+        captured.set(captured.get() + step);
+        rv.set_int32(captured.get());
+      },
+    )
+    .unwrap();
+    assert_eq!(Rc::strong_count(&counter), 2);
+
+    let global = context.global(scope);
+    let name = v8::String::new(scope, "increment").unwrap();
+    global.set(scope, name.into(), function.into());
+    let result = eval(scope, "increment(2); increment(3)").unwrap();
+    assert_eq!(result.int32_value(scope), Some(5));
+    assert_eq!(counter.get(), 5);
+  }
+  // The closure is dropped together with the isolate.
+  assert_eq!(Rc::strong_count(&counter), 1);
+}
+
+#[test]
+fn function_new_closure_collected() {
+  use std::cell::Cell;
+  use std::rc::Rc;
 
-    let key = v8::String::new(scope, "module").unwrap().into();
-    global.set(scope, key, module.into());
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
 
-    let foo_ab: v8::Local<v8::ArrayBuffer> =
-      eval(scope, "WebAssembly.Module.customSections(module, 'foo')[0]")
-        .unwrap()
-        .try_into()
-        .unwrap();
-    let foo_bs = foo_ab.get_backing_store();
-    let foo_section = unsafe {
-      std::slice::from_raw_parts(
-        foo_bs.data().unwrap().as_ptr() as *mut u8,
-        foo_bs.byte_length(),
-      )
-    };
-    assert_eq!(foo_section, b"bar");
+  let counter = Rc::new(Cell::new(0));
+  {
+    let scope = &mut v8::HandleScope::new(scope);
+    let captured = counter.clone();
+    v8::Function::new_closure(
+      scope,
+      move |_: &mut v8::HandleScope,
+            _: v8::FunctionCallbackArguments,
+            _: v8::ReturnValue| {
+        captured.set(captured.get() + 1);
+      },
+    )
+    .unwrap();
   }
+  assert_eq!(Rc::strong_count(&counter), 2);
+
+  // The closure is dropped once its function has been collected, without
+  // waiting for the isolate to be disposed.
+  let scope = &mut v8::HandleScope::new(scope);
+  eval(scope, "gc()").unwrap();
+  assert_eq!(Rc::strong_count(&counter), 1);
 }
 
 #[test]
@@ -6556,6 +9955,28 @@ fn backing_store_from_empty_vec() {
   let _ = v8::ArrayBuffer::with_backing_store(&mut scope, &store);
 }
 
+#[test]
+fn array_buffer_from_vec() {
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let mut data = Vec::with_capacity(16);
+  data.extend_from_slice(&[1, 2, 3, 4]);
+  let data_ptr = data.as_ptr();
+  let ab = v8::ArrayBuffer::from_vec(scope, data);
+  assert_eq!(4, ab.byte_length());
+
+  let bs = ab.get_backing_store();
+  assert_eq!(bs.data().unwrap().as_ptr() as *const u8, data_ptr);
+  assert_eq!(bs[3].get(), 4);
+
+  let empty = v8::ArrayBuffer::from_vec(scope, Vec::new());
+  assert_eq!(0, empty.byte_length());
+}
+
 #[test]
 fn current_stack_trace() {
   // Setup isolate
@@ -7013,35 +10434,293 @@ fn host_create_shadow_realm_context_callback() {
     };
     assert_eq!(scope.get_current_context(), main_context);
 
-    let new_context = v8::Context::new(scope);
-    {
-      let scope = &mut v8::ContextScope::new(scope, new_context);
-      let global = new_context.global(scope);
-      let key = v8::String::new(scope, "test").unwrap();
-      let value = v8::Integer::new(scope, 42);
-      global.set(scope, key.into(), value.into()).unwrap();
-    }
-    Some(new_context)
-  });
+    let new_context = v8::Context::new(scope);
+    {
+      let scope = &mut v8::ContextScope::new(scope, new_context);
+      let global = new_context.global(scope);
+      let key = v8::String::new(scope, "test").unwrap();
+      let value = v8::Integer::new(scope, 42);
+      global.set(scope, key.into(), value.into()).unwrap();
+    }
+    Some(new_context)
+  });
+
+  let value =
+    eval(scope, "new ShadowRealm().evaluate(`globalThis.test`)").unwrap();
+  assert_eq!(value.uint32_value(scope), Some(42));
+  assert!(scope.get_slot::<CheckData>().unwrap().callback_called);
+}
+
+#[test]
+#[should_panic(expected = "V8 supports at most two fast API overloads")]
+fn test_fast_calls_too_many_overloads() {
+  fn fast_fn(a: u32, b: u32) -> u32 {
+    a + b
+  }
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[fast_api::Type::Uint32, fast_api::Type::Uint32]
+    }
+
+    fn return_type(&self) -> fast_api::CType {
+      fast_api::CType::Uint32
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  v8::FunctionTemplate::builder(slow_fn)
+    .build_fast(scope, &[&FastTest, &FastTest, &FastTest]);
+}
+
+#[test]
+fn test_fast_calls() {
+  static mut WHO: &str = "none";
+  fn fast_fn(a: u32, b: u32) -> u32 {
+    unsafe { WHO = "fast" };
+    a + b
+  }
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[fast_api::Type::Uint32, fast_api::Type::Uint32]
+    }
+
+    fn return_type(&self) -> fast_api::CType {
+      fast_api::CType::Uint32
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    scope: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+  ) {
+    unsafe { WHO = "slow" };
+    rv.set(v8::Boolean::new(scope, false).into());
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let global = context.global(scope);
+
+  let template =
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
+
+  let name = v8::String::new(scope, "func").unwrap();
+  let value = template.get_function(scope).unwrap();
+  global.set(scope, name.into(), value.into()).unwrap();
+  let source = r#"
+  function f(x, y) { return func(x, y); }
+  %PrepareFunctionForOptimization(f);
+  f(1, 2);
+"#;
+  eval(scope, source).unwrap();
+  assert_eq!("slow", unsafe { WHO });
+
+  let source = r#"
+    %OptimizeFunctionOnNextCall(f);
+    f(1, 2);
+  "#;
+  eval(scope, source).unwrap();
+  assert_eq!("fast", unsafe { WHO });
+
+  // The signature of `FastTest` is shared by all templates that use it.
+  let template =
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
+  let name = v8::String::new(scope, "func2").unwrap();
+  let value = template.get_function(scope).unwrap();
+  global.set(scope, name.into(), value.into()).unwrap();
+  let source = r#"
+    function g(x, y) { return func2(x, y); }
+    %PrepareFunctionForOptimization(g);
+    g(1, 2);
+  "#;
+  eval(scope, source).unwrap();
+  assert_eq!("slow", unsafe { WHO });
+
+  let source = r#"
+    %OptimizeFunctionOnNextCall(g);
+    g(1, 2);
+  "#;
+  eval(scope, source).unwrap();
+  assert_eq!("fast", unsafe { WHO });
+}
+
+#[test]
+fn test_fast_calls_fast_fn_macro() {
+  use fast_api::FastFunction;
+
+  static mut WHO: &str = "none";
+
+  v8::fast_fn! {
+    pub struct FastTest;
+    extern "C" fn fast_fn(
+      _recv: v8::Local<v8::Object>,
+      a: u32,
+      data: *const fast_api::FastApiTypedArray<u32>,
+    ) -> u32 {
+      unsafe { WHO = "fast" };
+      a + unsafe { &*data }.get(0)
+    }
+  }
+
+  assert!(matches!(
+    FastTest.args(),
+    [
+      fast_api::Type::V8Value,
+      fast_api::Type::Uint32,
+      fast_api::Type::TypedArray(fast_api::CType::Uint32),
+    ]
+  ));
+  assert!(matches!(FastTest.return_type(), fast_api::CType::Uint32));
+
+  fn slow_fn(
+    scope: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+  ) {
+    unsafe { WHO = "slow" };
+    rv.set(v8::Boolean::new(scope, false).into());
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let global = context.global(scope);
+
+  let template =
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
+
+  let name = v8::String::new(scope, "func").unwrap();
+  let value = template.get_function(scope).unwrap();
+  global.set(scope, name.into(), value.into()).unwrap();
+  let source = r#"
+  function f(x, data) { return func(x, data); }
+  %PrepareFunctionForOptimization(f);
+  const data = new Uint32Array([2]);
+  f(1, data);
+"#;
+  eval(scope, source).unwrap();
+  assert_eq!("slow", unsafe { WHO });
+
+  let source = r#"
+    %OptimizeFunctionOnNextCall(f);
+    f(1, data);
+  "#;
+  let result = eval(scope, source).unwrap();
+  assert_eq!("fast", unsafe { WHO });
+  assert_eq!(result.uint32_value(scope), Some(3));
+}
+
+#[test]
+fn test_fast_calls_clamp() {
+  static mut WHO: &str = "none";
+  static mut VALUE: i32 = 0;
+  fn fast_fn(_recv: v8::Local<v8::Object>, value: i32) {
+    unsafe {
+      WHO = "fast";
+      VALUE = value;
+    }
+  }
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[
+        fast_api::Type::V8Value,
+        fast_api::Type::WithFlags(
+          &fast_api::Type::Int32,
+          fast_api::Flags::CLAMP,
+        ),
+      ]
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    _scope: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+    unsafe { WHO = "slow" };
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let global = context.global(scope);
 
-  let value =
-    eval(scope, "new ShadowRealm().evaluate(`globalThis.test`)").unwrap();
-  assert_eq!(value.uint32_value(scope), Some(42));
-  assert!(scope.get_slot::<CheckData>().unwrap().callback_called);
+  let template =
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
+
+  let name = v8::String::new(scope, "func").unwrap();
+  let value = template.get_function(scope).unwrap();
+  global.set(scope, name.into(), value.into()).unwrap();
+  let source = r#"
+  function f(x) { return func(x); }
+  %PrepareFunctionForOptimization(f);
+  f(2 ** 40);
+"#;
+  eval(scope, source).unwrap();
+  assert_eq!("slow", unsafe { WHO });
+
+  let source = r#"
+    %OptimizeFunctionOnNextCall(f);
+    f(2 ** 40);
+  "#;
+  eval(scope, source).unwrap();
+  assert_eq!("fast", unsafe { WHO });
+  assert_eq!(i32::MAX, unsafe { VALUE });
 }
 
 #[test]
-fn test_fast_calls() {
+fn test_fast_calls_receiver() {
   static mut WHO: &str = "none";
-  fn fast_fn(a: u32, b: u32) -> u32 {
+  fn fast_fn(recv: v8::Local<v8::Object>) -> u32 {
     unsafe { WHO = "fast" };
-    a + b
+    assert!(recv.is_api_wrapper());
+    let data = unsafe { recv.get_aligned_pointer_from_internal_field(0) };
+    unsafe { *(data as *const u32) }
   }
 
   pub struct FastTest;
   impl fast_api::FastFunction for FastTest {
     fn args(&self) -> &'static [fast_api::Type] {
-      &[fast_api::Type::Uint32, fast_api::Type::Uint32]
+      &[fast_api::Type::V8Value]
     }
 
     fn return_type(&self) -> fast_api::CType {
@@ -7055,41 +10734,74 @@ fn test_fast_calls() {
 
   fn slow_fn(
     scope: &mut v8::HandleScope,
-    _: v8::FunctionCallbackArguments,
+    args: v8::FunctionCallbackArguments,
     mut rv: v8::ReturnValue,
   ) {
     unsafe { WHO = "slow" };
-    rv.set(v8::Boolean::new(scope, false).into());
+    let data =
+      unsafe { args.this().get_aligned_pointer_from_internal_field(0) };
+    let value = unsafe { *(data as *const u32) };
+    rv.set(v8::Integer::new_from_unsigned(scope, value).into());
   }
 
+  fn constructor(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  static VALUE: u32 = 42;
+
   let _setup_guard = setup();
   let isolate = &mut v8::Isolate::new(Default::default());
   let scope = &mut v8::HandleScope::new(isolate);
   let context = v8::Context::new(scope);
   let scope = &mut v8::ContextScope::new(scope, context);
 
-  let global = context.global(scope);
-
-  let template =
-    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &FastTest, None);
+  let class = v8::FunctionTemplate::new(scope, constructor);
+  class.instance_template(scope).set_internal_field_count(1);
+  let signature = v8::Signature::new(scope, class);
+  let method = v8::FunctionTemplate::builder(slow_fn)
+    .signature(signature)
+    .build_fast(scope, &[&FastTest]);
+  let name = v8::String::new(scope, "get").unwrap();
+  class
+    .prototype_template(scope)
+    .set(name.into(), method.into());
+
+  let object = class
+    .get_function(scope)
+    .unwrap()
+    .new_instance(scope, &[])
+    .unwrap();
+  assert!(object.is_api_wrapper());
+  assert!(!v8::Object::new(scope).is_api_wrapper());
+  object.set_aligned_pointer_in_internal_field(
+    0,
+    &VALUE as *const u32 as *const c_void,
+  );
 
-  let name = v8::String::new(scope, "func").unwrap();
-  let value = template.get_function(scope).unwrap();
-  global.set(scope, name.into(), value.into()).unwrap();
+  let global = context.global(scope);
+  let name = v8::String::new(scope, "object").unwrap();
+  global.set(scope, name.into(), object.into()).unwrap();
   let source = r#"
-  function f(x, y) { return func(x, y); }
+  function f(o) { return o.get(); }
   %PrepareFunctionForOptimization(f);
-  f(1, 2);
+  f(object);
 "#;
-  eval(scope, source).unwrap();
+  assert_eq!(eval(scope, source).unwrap().uint32_value(scope), Some(42));
   assert_eq!("slow", unsafe { WHO });
 
   let source = r#"
     %OptimizeFunctionOnNextCall(f);
-    f(1, 2);
+    f(object);
   "#;
-  eval(scope, source).unwrap();
+  assert_eq!(eval(scope, source).unwrap().uint32_value(scope), Some(42));
   assert_eq!("fast", unsafe { WHO });
+
+  // Receivers that don't match the signature are rejected by V8.
+  assert!(eval(scope, "object.get.call({})").is_none());
 }
 
 #[test]
@@ -7144,7 +10856,7 @@ fn test_fast_calls_sequence() {
   let global = context.global(scope);
 
   let template =
-    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &FastTest, None);
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
 
   let name = v8::String::new(scope, "func").unwrap();
   let value = template.get_function(scope).unwrap();
@@ -7217,7 +10929,7 @@ fn test_fast_calls_arraybuffer() {
   let global = context.global(scope);
 
   let template =
-    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &FastTest, None);
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
 
   let name = v8::String::new(scope, "func").unwrap();
   let value = template.get_function(scope).unwrap();
@@ -7293,7 +11005,7 @@ fn test_fast_calls_typedarray() {
   let global = context.global(scope);
 
   let template =
-    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &FastTest, None);
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
 
   let name = v8::String::new(scope, "func").unwrap();
   let value = template.get_function(scope).unwrap();
@@ -7383,7 +11095,7 @@ fn test_fast_calls_reciever() {
   );
 
   let template =
-    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &FastTest, None);
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
 
   let name = v8::String::new(scope, "method").unwrap();
   let value = template.get_function(scope).unwrap();
@@ -7419,8 +11131,13 @@ fn test_fast_calls_overload() {
     unsafe { WHO = "fast_buf" };
     let buf = unsafe { &*data };
     assert_eq!(buf.byte_length, 2);
+    assert_eq!(buf.len(), 2);
     assert_eq!(buf.get(0), 6);
     assert_eq!(buf.get(1), 9);
+    assert_eq!(buf.get_storage(), Some(&[6, 9][..]));
+    let mut copy = [0; 2];
+    buf.copy_into(&mut copy);
+    assert_eq!(copy, [6, 9]);
   }
 
   fn fast_fn2(_recv: v8::Local<v8::Object>, data: v8::Local<v8::Array>) {
@@ -7473,11 +11190,8 @@ fn test_fast_calls_overload() {
 
   let global = context.global(scope);
 
-  let template = v8::FunctionTemplate::builder(slow_fn).build_fast(
-    scope,
-    &FastTest,
-    Some(&FastTest2),
-  );
+  let template = v8::FunctionTemplate::builder(slow_fn)
+    .build_fast(scope, &[&FastTest, &FastTest2]);
 
   let name = v8::String::new(scope, "func").unwrap();
   let value = template.get_function(scope).unwrap();
@@ -7551,7 +11265,7 @@ fn test_fast_calls_callback_options() {
   let global = context.global(scope);
 
   let template =
-    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &FastTest, None);
+    v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
 
   let name = v8::String::new(scope, "func").unwrap();
   let value = template.get_function(scope).unwrap();
@@ -7576,3 +11290,329 @@ fn test_fast_calls_callback_options() {
   eval(scope, source).unwrap();
   assert_eq!("slow", unsafe { WHO });
 }
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(
+  expected = "FastFunction argument 1 is CallbackOptions, which must be the last argument"
+)]
+fn test_fast_calls_invalid_callback_options_position() {
+  fn fast_fn(
+    _recv: v8::Local<v8::Object>,
+    _options: *mut fast_api::FastApiCallbackOptions,
+    _a: u32,
+  ) {
+  }
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[
+        fast_api::Type::V8Value,
+        fast_api::Type::CallbackOptions,
+        fast_api::Type::Uint32,
+      ]
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "FastFunction has an invalid return type")]
+fn test_fast_calls_invalid_return_type() {
+  fn fast_fn(_recv: v8::Local<v8::Object>) {}
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[fast_api::Type::V8Value]
+    }
+
+    fn return_type(&self) -> fast_api::CType {
+      fast_api::CType::V8Value
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  v8::FunctionTemplate::builder(slow_fn).build_fast(scope, &[&FastTest]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "but the slow callback is declared to take 1")]
+fn test_fast_calls_arity_mismatch() {
+  fn fast_fn(_recv: v8::Local<v8::Object>, a: u32, b: u32) -> u32 {
+    a + b
+  }
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[
+        fast_api::Type::V8Value,
+        fast_api::Type::Uint32,
+        fast_api::Type::Uint32,
+      ]
+    }
+
+    fn return_type(&self) -> fast_api::CType {
+      fast_api::CType::Uint32
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+
+  v8::FunctionTemplate::builder(slow_fn)
+    .length(1)
+    .build_fast(scope, &[&FastTest]);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "must take the receiver as its first argument")]
+fn test_fast_calls_missing_receiver() {
+  fn fast_fn(a: u32) -> u32 {
+    a
+  }
+
+  pub struct FastTest;
+  impl fast_api::FastFunction for FastTest {
+    fn args(&self) -> &'static [fast_api::Type] {
+      &[fast_api::Type::Uint32]
+    }
+
+    fn return_type(&self) -> fast_api::CType {
+      fast_api::CType::Uint32
+    }
+
+    fn function(&self) -> *const c_void {
+      fast_fn as _
+    }
+  }
+
+  fn slow_fn(
+    _: &mut v8::HandleScope,
+    _: v8::FunctionCallbackArguments,
+    _: v8::ReturnValue,
+  ) {
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let class = v8::FunctionTemplate::new(scope, slow_fn);
+  let signature = v8::Signature::new(scope, class);
+
+  v8::FunctionTemplate::builder(slow_fn)
+    .signature(signature)
+    .build_fast(scope, &[&FastTest]);
+}
+
+#[test]
+fn object_wrap() {
+  use std::cell::Cell;
+  use std::rc::Rc;
+
+  struct Point {
+    x: i32,
+    y: i32,
+    drops: Rc<Cell<usize>>,
+  }
+
+  impl Drop for Point {
+    fn drop(&mut self) {
+      self.drops.set(self.drops.get() + 1);
+    }
+  }
+
+  thread_local! {
+    static DROPS: Rc<Cell<usize>> = Rc::new(Cell::new(0));
+  }
+
+  impl v8::object_wrap::ObjectWrap for Point {
+    const CLASS_NAME: &'static str = "Point";
+    const INTERNAL_FIELD_COUNT: usize = 1;
+
+    fn construct(
+      scope: &mut v8::HandleScope,
+      args: v8::FunctionCallbackArguments,
+    ) -> Option<Self> {
+      let x = args.get(0).int32_value(scope)?;
+      let y = args.get(1).int32_value(scope)?;
+      let drops = DROPS.with(|drops| drops.clone());
+      Some(Self { x, y, drops })
+    }
+
+    fn init_template(
+      scope: &mut v8::HandleScope<()>,
+      template: v8::Local<v8::FunctionTemplate>,
+    ) {
+      fn sum(
+        scope: &mut v8::HandleScope,
+        args: v8::FunctionCallbackArguments,
+        mut rv: v8::ReturnValue,
+      ) {
+        if let Some(point) =
+          v8::object_wrap::unwrap::<Point>(scope, args.this())
+        {
+          rv.set_int32(point.x + point.y);
+        }
+      }
+      let name = v8::String::new(scope, "sum").unwrap();
+      let function = v8::FunctionTemplate::new(scope, sum);
+      template
+        .prototype_template(scope)
+        .set(name.into(), function.into());
+    }
+  }
+
+  let _setup_guard = setup();
+  let drops = DROPS.with(|drops| drops.clone());
+  {
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+
+    let template = v8::object_wrap::template::<Point>(scope);
+    assert!(template == v8::object_wrap::template::<Point>(scope));
+    let constructor = template.get_function(scope).unwrap();
+    let name = v8::String::new(scope, "Point").unwrap();
+    context
+      .global(scope)
+      .set(scope, name.into(), constructor.into());
+
+    let point = eval(scope, "new Point(1, 2)").unwrap();
+    let point = v8::Local::<v8::Object>::try_from(point).unwrap();
+    let unwrapped = v8::object_wrap::unwrap::<Point>(scope, point).unwrap();
+    assert_eq!((unwrapped.x, unwrapped.y), (1, 2));
+    assert_eq!(point.internal_field_count(), 2);
+    let label = v8::String::new(scope, "label").unwrap();
+    assert!(point
+      .set_internal_field(v8::object_wrap::FIRST_INTERNAL_FIELD, label.into()));
+    assert_eq!(
+      eval(scope, "new Point(3, 4).sum()")
+        .unwrap()
+        .int32_value(scope),
+      Some(7)
+    );
+
+    let point = v8::object_wrap::wrap(
+      scope,
+      Point {
+        x: 5,
+        y: 6,
+        drops: drops.clone(),
+      },
+    )
+    .unwrap();
+    let name = v8::String::new(scope, "point").unwrap();
+    context.global(scope).set(scope, name.into(), point.into());
+    assert!(eval(scope, "point instanceof Point").unwrap().is_true());
+    assert_eq!(
+      eval(scope, "point.sum()").unwrap().int32_value(scope),
+      Some(11)
+    );
+
+    let object = v8::Object::new(scope);
+    assert!(v8::object_wrap::unwrap::<Point>(scope, object).is_none());
+
+    {
+      let scope = &mut v8::TryCatch::new(scope);
+      assert!(eval(scope, "Point(1, 2)").is_none());
+      assert!(eval(scope, "Point.prototype.sum.call({})").is_some());
+      assert!(eval(scope, "new Point(Symbol(), 2)").is_none());
+      assert!(scope.has_caught());
+    }
+
+    assert_eq!(drops.get(), 0);
+    // The wrapper created by `new Point(3, 4)` is garbage, and is dropped once
+    // the GC gets around to it. The other two are still referenced.
+    for _ in 0..10 {
+      if drops.get() > 0 {
+        break;
+      }
+      scope.low_memory_notification();
+    }
+    assert_eq!(drops.get(), 1);
+  }
+  // All remaining values are dropped with the isolate.
+  assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn object_wrap_type_tag() {
+  struct Base;
+  struct Derived;
+
+  impl v8::object_wrap::ObjectWrap for Base {
+    const CLASS_NAME: &'static str = "Base";
+  }
+
+  impl v8::object_wrap::ObjectWrap for Derived {
+    const CLASS_NAME: &'static str = "Derived";
+
+    fn init_template(
+      scope: &mut v8::HandleScope<()>,
+      template: v8::Local<v8::FunctionTemplate>,
+    ) {
+      template.inherit(v8::object_wrap::template::<Base>(scope));
+    }
+  }
+
+  let _setup_guard = setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let derived = v8::object_wrap::wrap(scope, Derived).unwrap();
+  assert!(v8::object_wrap::unwrap::<Derived>(scope, derived).is_some());
+  // `derived` is an instance of `Base`'s template too, but must not be
+  // unwrapped as a `Base`.
+  assert!(v8::object_wrap::unwrap::<Base>(scope, derived).is_none());
+}
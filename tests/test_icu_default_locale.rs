@@ -0,0 +1,55 @@
+// Tests from the same file run in a single process. That's why this test
+// is in its own file, because the default locale is shared by the whole
+// process.
+
+fn eval_string(scope: &mut v8::HandleScope, code: &str) -> String {
+  let source = v8::String::new(scope, code).unwrap();
+  let script = v8::Script::compile(scope, source, None).unwrap();
+  let result = script.run(scope).unwrap();
+  result.to_rust_string_lossy(scope)
+}
+
+#[test]
+fn icu_default_locale() {
+  assert!(v8::icu::set_common_data_71(align_data::include_aligned!(
+    align_data::Align16,
+    "../third_party/icu/common/icudtl.dat"
+  ))
+  .is_ok());
+  v8::V8::initialize_platform(v8::new_default_platform(0, false).make_shared());
+  v8::V8::initialize();
+
+  let locales = v8::icu::get_available_locales();
+  assert!(locales.iter().any(|locale| locale == "de-CH"));
+
+  unsafe { v8::icu::set_default_locale("de-CH") }.unwrap();
+  assert_eq!(v8::icu::get_default_locale(), "de-CH");
+
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let code = "new Intl.NumberFormat().resolvedOptions().locale";
+  assert_eq!(eval_string(scope, code), "de-CH");
+
+  unsafe { v8::icu::set_default_locale("fr") }.unwrap();
+  scope.locale_configuration_change_notification();
+  assert_eq!(eval_string(scope, code), "fr");
+
+  // A per-isolate override doesn't affect other isolates.
+  scope.set_default_locale("ja").unwrap();
+  assert_eq!(eval_string(scope, code), "ja");
+  assert_eq!(v8::icu::get_default_locale(), "fr");
+  {
+    let isolate = &mut v8::Isolate::new(Default::default());
+    let scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(scope);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    assert_eq!(eval_string(scope, code), "fr");
+  }
+  assert!(scope.set_default_locale("not a locale").is_err());
+
+  // The override is discarded when the default locale changes.
+  scope.locale_configuration_change_notification();
+  assert_eq!(eval_string(scope, code), "fr");
+}
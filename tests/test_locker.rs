@@ -0,0 +1,89 @@
+// Tests from the same file run in a single process. That's why this test
+// is in its own file: once a `Locker` has been used, V8 requires every
+// isolate of the process to be locked before it can be used.
+
+use std::sync::mpsc;
+use std::thread;
+
+fn eval_int(scope: &mut v8::HandleScope, code: &str) -> i32 {
+  let source = v8::String::new(scope, code).unwrap();
+  let script = v8::Script::compile(scope, source, None).unwrap();
+  let result = script.run(scope).unwrap();
+  result.int32_value(scope).unwrap()
+}
+
+#[test]
+fn locker() {
+  v8::V8::initialize_platform(v8::new_default_platform(0, false).make_shared());
+  v8::V8::initialize();
+
+  // The only value that is stored on the isolate is a `Global`, and it is only
+  // used and dropped while the isolate is locked.
+  let mut isolate = unsafe { v8::Isolate::new_unentered(Default::default()) };
+  {
+    let locker = &mut unsafe { v8::Locker::new(&mut isolate) };
+    assert!(v8::Locker::is_locked(locker));
+    assert!(locker.is_current());
+    let scope = &mut v8::HandleScope::new(&mut **locker);
+    let context = v8::Context::new(scope);
+    let global_context = v8::Global::new(scope, context);
+    let scope = &mut v8::ContextScope::new(scope, context);
+    assert_eq!(eval_int(scope, "globalThis.counter = 1"), 1);
+    scope.set_slot(global_context);
+  }
+
+  // Move the isolate to another thread, and keep using the same context.
+  let isolate = thread::spawn(move || {
+    {
+      let locker = &mut unsafe { v8::Locker::new(&mut isolate) };
+      let context = locker.get_slot::<v8::Global<v8::Context>>().unwrap();
+      let context = context.clone();
+      let scope = &mut v8::HandleScope::with_context(&mut **locker, context);
+      assert_eq!(eval_int(scope, "++counter"), 2);
+    }
+    isolate
+  })
+  .join()
+  .unwrap();
+
+  // Share the isolate, and release the lock while another thread uses it.
+  let isolate = isolate.into_shared();
+  let (tx, rx) = mpsc::channel();
+  {
+    let locker = &mut unsafe { isolate.lock() };
+    let other = thread::spawn({
+      let isolate = isolate.clone();
+      move || {
+        // The main thread waits for this thread while the isolate is unlocked.
+        let locker = &mut unsafe { isolate.lock() };
+        let context = locker.get_slot::<v8::Global<v8::Context>>().unwrap();
+        let context = context.clone();
+        let scope = &mut v8::HandleScope::with_context(&mut **locker, context);
+        tx.send(eval_int(scope, "++counter")).unwrap();
+      }
+    });
+    {
+      let _unlocker = v8::Unlocker::new(locker);
+      assert_eq!(rx.recv().unwrap(), 3);
+      other.join().unwrap();
+    }
+    assert!(v8::Locker::is_locked(locker));
+    let context = locker.get_slot::<v8::Global<v8::Context>>().unwrap();
+    let context = context.clone();
+    let scope = &mut v8::HandleScope::with_context(&mut **locker, context);
+    assert_eq!(eval_int(scope, "counter"), 3);
+  }
+
+  // Locking the isolate again on the same thread would alias the isolate.
+  {
+    let _locker = unsafe { isolate.lock() };
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+      drop(unsafe { isolate.lock() });
+    }));
+    assert!(result.is_err());
+  }
+
+  drop(isolate);
+  unsafe { v8::V8::dispose() };
+  v8::V8::dispose_platform();
+}
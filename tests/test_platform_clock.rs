@@ -0,0 +1,34 @@
+// Tests from the same file run in a single process. That's why this test
+// is in its own file, because the platform is shared by the whole process.
+
+extern "C" fn monotonic_time() -> f64 {
+  42.0
+}
+
+extern "C" fn current_clock_time_millis() -> f64 {
+  1_000_000_000_000.0
+}
+
+#[test]
+fn platform_clock() {
+  v8::V8::initialize_platform(
+    v8::Platform::new_with_clock(
+      0,
+      false,
+      monotonic_time,
+      current_clock_time_millis,
+    )
+    .make_shared(),
+  );
+  v8::V8::initialize();
+
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let source =
+    v8::String::new(scope, "Date.now() + new Date().getTime()").unwrap();
+  let script = v8::Script::compile(scope, source, None).unwrap();
+  let result = script.run(scope).unwrap();
+  assert_eq!(result.number_value(scope), Some(2_000_000_000_000.0));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "tokio")]
+
+#[test]
+fn tokio_pump_message_loop() {
+  v8::V8::set_flags_from_string(
+    "--allow-natives-syntax --harmony-sharedarraybuffer",
+  );
+  v8::V8::initialize_platform(v8::tokio::new_platform(0, false).make_shared());
+  v8::V8::initialize();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let source = r#"
+    const sab = new SharedArrayBuffer(16);
+    const i32a = new Int32Array(sab);
+
+    var resolved = false;
+    Atomics.waitAsync(i32a, 0, 0).value.then(() => { resolved = true; });
+    Atomics.notify(i32a, 0, 1);
+  "#;
+  let source = v8::String::new(scope, source).unwrap();
+  let script = v8::Script::compile(scope, source, None).unwrap();
+  script.run(scope).unwrap();
+
+  let runtime = tokio::runtime::Builder::new_current_thread()
+    .enable_time()
+    .build()
+    .unwrap();
+  let platform = v8::V8::get_current_platform();
+  let key = v8::String::new(scope, "resolved").unwrap();
+  runtime.block_on(async {
+    loop {
+      let resolved = context
+        .global(scope)
+        .get(scope, key.into())
+        .unwrap()
+        .boolean_value(scope);
+      if resolved {
+        break;
+      }
+      v8::tokio::pump_message_loop(&platform, scope).await;
+      scope.perform_microtask_checkpoint();
+    }
+  });
+}
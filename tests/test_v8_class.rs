@@ -0,0 +1,177 @@
+// Copyright 2019-2022 the Deno authors. All rights reserved. MIT license.
+#![cfg(feature = "macros")]
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+fn setup() {
+  static START: std::sync::Once = std::sync::Once::new();
+  START.call_once(|| {
+    v8::V8::set_flags_from_string("--expose_gc");
+    v8::V8::initialize_platform(
+      v8::new_default_platform(0, false).make_shared(),
+    );
+    v8::V8::initialize();
+  });
+}
+
+fn eval<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  code: &str,
+) -> Option<v8::Local<'s, v8::Value>> {
+  let scope = &mut v8::EscapableHandleScope::new(scope);
+  let source = v8::String::new(scope, code).unwrap();
+  let script = v8::Script::compile(scope, source, None).unwrap();
+  let r = script.run(scope);
+  r.map(|v| scope.escape(v))
+}
+
+struct Counter {
+  count: Cell<i32>,
+  label: RefCell<String>,
+}
+
+#[v8::v8_class]
+impl Counter {
+  #[constructor]
+  fn new(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+  ) -> Option<Self> {
+    let count = if args.length() > 0 {
+      args.get(0).int32_value(scope)?
+    } else {
+      0
+    };
+    Some(Self {
+      count: Cell::new(count),
+      label: RefCell::new(String::new()),
+    })
+  }
+
+  #[method]
+  fn increment_by(
+    &self,
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+  ) {
+    if let Some(amount) = args.get(0).int32_value(scope) {
+      self.count.set(self.count.get() + amount);
+      rv.set_int32(self.count.get());
+    }
+  }
+
+  #[method(name = "toString")]
+  fn to_js_string(
+    &self,
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    mut rv: v8::ReturnValue,
+  ) {
+    let string = format!("{}: {}", self.label.borrow(), self.count.get());
+    rv.set(v8::String::new(scope, &string).unwrap().into());
+  }
+
+  #[getter]
+  fn count(&self, _scope: &mut v8::HandleScope, mut rv: v8::ReturnValue) {
+    rv.set_int32(self.count.get());
+  }
+
+  #[getter]
+  fn label(&self, scope: &mut v8::HandleScope, mut rv: v8::ReturnValue) {
+    rv.set(v8::String::new(scope, &self.label.borrow()).unwrap().into());
+  }
+
+  #[setter]
+  fn set_label(
+    &self,
+    scope: &mut v8::HandleScope,
+    value: v8::Local<v8::Value>,
+  ) {
+    *self.label.borrow_mut() = value.to_rust_string_lossy(scope);
+  }
+
+  // Not exposed to JavaScript.
+  fn reset(&self) {
+    self.count.set(0);
+  }
+}
+
+struct Opaque(u32);
+
+#[v8::v8_class(name = "OpaqueHandle")]
+impl Opaque {}
+
+mod renamed {
+  pub use v8 as engine;
+}
+
+struct Renamed;
+
+#[v8::v8_class(crate = "renamed::engine")]
+impl Renamed {}
+
+#[test]
+fn v8_class() {
+  setup();
+  let isolate = &mut v8::Isolate::new(Default::default());
+  let scope = &mut v8::HandleScope::new(isolate);
+  let context = v8::Context::new(scope);
+  let scope = &mut v8::ContextScope::new(scope, context);
+
+  let global = context.global(scope);
+  for (name, template) in [
+    ("Counter", v8::object_wrap::template::<Counter>(scope)),
+    ("OpaqueHandle", v8::object_wrap::template::<Opaque>(scope)),
+    ("Renamed", v8::object_wrap::template::<Renamed>(scope)),
+  ] {
+    let name = v8::String::new(scope, name).unwrap();
+    let function = template.get_function(scope).unwrap();
+    global.set(scope, name.into(), function.into());
+  }
+
+  let counter = eval(scope, "var c = new Counter(1); c").unwrap();
+  let counter = v8::Local::<v8::Object>::try_from(counter).unwrap();
+  fn check(scope: &mut v8::HandleScope, code: &str, expected: &str) {
+    let result = eval(scope, code).unwrap();
+    assert_eq!(result.to_rust_string_lossy(scope), expected, "{}", code);
+  }
+  check(scope, "c.incrementBy(2)", "3");
+  check(scope, "c.count", "3");
+  check(scope, "c.label = 'clicks'; c.label", "clicks");
+  check(scope, "`${c}`", "clicks: 3");
+  check(scope, "typeof c.reset", "undefined");
+  check(scope, "Renamed.name", "Renamed");
+  check(
+    scope,
+    "Object.prototype.toString.call(c)",
+    "[object Counter]",
+  );
+
+  let unwrapped = v8::object_wrap::unwrap::<Counter>(scope, counter).unwrap();
+  unwrapped.reset();
+  check(scope, "c.count", "0");
+  assert!(v8::object_wrap::unwrap::<Opaque>(scope, counter).is_none());
+
+  {
+    let scope = &mut v8::TryCatch::new(scope);
+    assert!(eval(scope, "Counter.prototype.incrementBy.call({}, 1)").is_none());
+    let exception = scope.exception().unwrap();
+    assert_eq!(
+      exception.to_rust_string_lossy(scope),
+      "TypeError: Illegal invocation"
+    );
+    scope.reset();
+    assert!(eval(scope, "new OpaqueHandle()").is_none());
+    let exception = scope.exception().unwrap();
+    assert_eq!(
+      exception.to_rust_string_lossy(scope),
+      "TypeError: Illegal constructor"
+    );
+  }
+
+  let opaque = v8::object_wrap::wrap(scope, Opaque(42)).unwrap();
+  let unwrapped = v8::object_wrap::unwrap::<Opaque>(scope, opaque).unwrap();
+  assert_eq!(unwrapped.0, 42);
+}
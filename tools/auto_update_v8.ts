@@ -57,8 +57,17 @@ readme = readme.replace(
 );
 Deno.writeTextFileSync("README.md", readme);
 
+// Update the version constant
+const toTriple = (version: string) => version.split(".").slice(0, 3).join(", ");
+let v8Rs = Deno.readTextFileSync("src/V8.rs");
+v8Rs = v8Rs.replace(
+  `pub const VERSION: (u32, u32, u32) = (${toTriple(currentVersion)});`,
+  `pub const VERSION: (u32, u32, u32) = (${toTriple(newVersion)});`,
+);
+Deno.writeTextFileSync("src/V8.rs", v8Rs);
+
 // Stage the changes
-await run(["git", "add", "v8", "README.md"]);
+await run(["git", "add", "v8", "README.md", "src/V8.rs"]);
 
 // Commit the changes
 await run(["git", "commit", "-m", `Rolling to V8 ${newVersion}`]);